@@ -0,0 +1,114 @@
+//! 语句复杂度的简单指标：长度、JOIN 个数、是否排序/分组、IN 列表大小，
+//! 用于在指纹统计中挑选优先调优的语句。
+
+use serde::{Deserialize, Serialize};
+
+/// 一条 SQL 文本（通常是指纹）的复杂度指标
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Complexity {
+    /// 文本长度（字节）
+    pub sql_len: usize,
+    /// JOIN 关键字个数
+    pub joins: usize,
+    pub order_by: bool,
+    pub group_by: bool,
+    /// 最长的 `IN (...)` 值列表的元素个数，`IN (SELECT ...)` 不计
+    pub max_in_list: usize,
+}
+
+impl Complexity {
+    /// 计算指标；字符串与注释中的关键字不会被计入
+    pub fn of(sql: &str) -> Self {
+        let bytes = sql.as_bytes();
+        let n = bytes.len();
+        let mut c = Complexity {
+            sql_len: n,
+            ..Default::default()
+        };
+        let mut prev = "";
+        let mut i = 0;
+        while i < n {
+            let b = bytes[i];
+            if b == b'\'' {
+                i = sql[i + 1..].find('\'').map_or(n, |p| i + 1 + p + 1);
+                prev = "";
+            } else if sql[i..].starts_with("--") {
+                i = sql[i..].find('\n').map_or(n, |p| i + p + 1);
+            } else if sql[i..].starts_with("/*") {
+                i = sql[i + 2..].find("*/").map_or(n, |p| i + 2 + p + 2);
+            } else if b.is_ascii_alphabetic() || b == b'_' {
+                let start = i;
+                while i < n
+                    && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'$' | b'#'))
+                {
+                    i += 1;
+                }
+                let word = &sql[start..i];
+                if word.eq_ignore_ascii_case("join") {
+                    c.joins += 1;
+                } else if word.eq_ignore_ascii_case("by") {
+                    c.order_by |= prev.eq_ignore_ascii_case("order");
+                    c.group_by |= prev.eq_ignore_ascii_case("group");
+                } else if word.eq_ignore_ascii_case("in")
+                    && let Some(len) = in_list_len(&sql[i..])
+                {
+                    c.max_in_list = c.max_in_list.max(len);
+                }
+                prev = word;
+            } else {
+                if !b.is_ascii_whitespace() {
+                    prev = "";
+                }
+                i += sql[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        c
+    }
+}
+
+/// `rest` 以 `(v1, v2, ...)` 开头时返回值的个数；子查询或不是括号时返回 None
+fn in_list_len(rest: &str) -> Option<usize> {
+    let list = rest.trim_start().strip_prefix('(')?;
+    let first = list.trim_start();
+    if first.len() >= 6 && first[..6].eq_ignore_ascii_case("select") {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut items = 1;
+    let mut in_str = false;
+    for b in list.bytes() {
+        match b {
+            b'\'' => in_str = !in_str,
+            _ if in_str => {}
+            b'(' => depth += 1,
+            b')' if depth == 0 => return Some(items),
+            b')' => depth -= 1,
+            b',' if depth == 0 => items += 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_joins_clauses_and_in_lists() {
+        let sql = "select a.k, count(*) from a join b on a.id = b.id left join c on c.id = b.id \
+                   where a.k in (?, ?, ?) and a.s in (select s from d) and a.t in ('x,y', f(1, 2)) \
+                   group by a.k order by 2 -- join";
+        let c = Complexity::of(sql);
+        assert_eq!(c.sql_len, sql.len());
+        assert_eq!(c.joins, 2);
+        assert!(c.order_by);
+        assert!(c.group_by);
+        assert_eq!(c.max_in_list, 3);
+
+        let c = Complexity::of("select 'order by' from t where ord in (1)");
+        assert_eq!(c.joins, 0);
+        assert!(!c.order_by);
+        assert_eq!(c.max_in_list, 1);
+    }
+}
@@ -5,6 +5,13 @@ use std::num::{ParseFloatError, ParseIntError};
 #[derive(Debug)]
 pub enum ParseError {
     MissingFields(usize),
+    /// 缺少必需字段（字段名）
+    MissingField(&'static str),
+    /// 字段存在但格式不合法
+    InvalidField {
+        field: &'static str,
+        reason: &'static str,
+    },
     Int(ParseIntError),
     Float(ParseFloatError),
     InvalidFormat,
@@ -14,6 +21,10 @@ impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParseError::MissingFields(n) => write!(f, "missing fields: expected {} fields", n),
+            ParseError::MissingField(field) => write!(f, "missing field: {}", field),
+            ParseError::InvalidField { field, reason } => {
+                write!(f, "invalid field {}: {}", field, reason)
+            }
             ParseError::Int(e) => write!(f, "int parse error: {}", e),
             ParseError::Float(e) => write!(f, "float parse error: {}", e),
             ParseError::InvalidFormat => write!(f, "invalid format"),
@@ -0,0 +1,120 @@
+//! 按 EXEC_ID 定位记录：监控视图中看到的执行号可以直接找到日志中的对应记录。
+//!
+//! DM 有时把执行指标（`EXECTIME ... EXEC_ID`）单独记为一条记录，此时语句文本在
+//! 同一会话、同一语句句柄的前一条记录中，[`ExecIndex::statement`] 会一并找出。
+
+use crate::parser::{ParsedRecord, RecordSplitter, parse_record};
+use crate::sql::{self, RecordCategory};
+
+/// 向前查找语句文本时最多回看的记录数
+const MAX_LOOKBACK: usize = 4096;
+
+/// 一段日志文本的 EXEC_ID 索引：按 EXEC_ID 排序的 (EXEC_ID, 记录序号)，查找时二分
+pub struct ExecIndex<'a> {
+    text: &'a str,
+    /// 各条记录在 `text` 中的起止偏移
+    records: Vec<(usize, usize)>,
+    by_id: Vec<(u64, usize)>,
+}
+
+impl<'a> ExecIndex<'a> {
+    pub fn build(text: &'a str) -> Self {
+        let base = text.as_ptr() as usize;
+        let mut records = Vec::new();
+        let mut by_id = Vec::new();
+        for rec in RecordSplitter::new(text) {
+            let start = rec.as_ptr() as usize - base;
+            if let Some(id) = parse_record(rec).execute_id {
+                by_id.push((id, records.len()));
+            }
+            records.push((start, start + rec.len()));
+        }
+        by_id.sort_unstable();
+        Self {
+            text,
+            records,
+            by_id,
+        }
+    }
+
+    /// 带 EXEC_ID 的记录数
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// 返回 EXEC_ID 为 `exec_id` 的各条记录的 (在文本中的字节偏移, 记录文本)，按原文顺序排列
+    pub fn find(&self, exec_id: u64) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        let start = self.by_id.partition_point(|&(id, _)| id < exec_id);
+        self.by_id[start..]
+            .iter()
+            .take_while(move |&&(id, _)| id == exec_id)
+            .map(|&(_, i)| self.record(i))
+    }
+
+    /// 返回 EXEC_ID 为 `exec_id` 的执行所对应的语句记录：记录本身带有语句文本时即为该记录，
+    /// 否则为同一会话、同一语句句柄的前一条语句记录
+    pub fn statement(&self, exec_id: u64) -> Option<(usize, &'a str)> {
+        let start = self.by_id.partition_point(|&(id, _)| id < exec_id);
+        let &(id, i) = self.by_id.get(start)?;
+        if id != exec_id {
+            return None;
+        }
+        let (offset, text) = self.record(i);
+        let rec = parse_record(text);
+        if has_statement(&rec) {
+            return Some((offset, text));
+        }
+        (i.saturating_sub(MAX_LOOKBACK)..i)
+            .rev()
+            .map(|j| self.record(j))
+            .find(|&(_, prev)| same_statement(&rec, &parse_record(prev)))
+    }
+
+    fn record(&self, i: usize) -> (usize, &'a str) {
+        let (s, e) = self.records[i];
+        (s, &self.text[s..e])
+    }
+}
+
+/// 记录是否带有语句文本（而不只是执行指标）
+pub fn has_statement(rec: &ParsedRecord<'_>) -> bool {
+    sql::categorize(&rec.body) == RecordCategory::Statement && !sql::sql_text(&rec.body).is_empty()
+}
+
+/// `prev` 是否为 `rec` 这次执行的语句记录：同一会话、同一语句句柄且带有语句文本
+pub fn same_statement(rec: &ParsedRecord<'_>, prev: &ParsedRecord<'_>) -> bool {
+    prev.sess == rec.sess && prev.stmt == rec.stmt && has_statement(prev)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 30.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x3 appname:app) [UPD] update t set a = 1
+2025-08-12 10:57:09.563 (EP[0] sess:0x9 thrd:2 user:B trxid:1 stmt:0x3 appname:app) [SEL] select 2
+2025-08-12 10:57:09.564 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x3 appname:app) EXECTIME: 5(ms) ROWCOUNT: 3(rows) EXEC_ID: 10.
+";
+
+    #[test]
+    fn finds_records_and_their_statements() {
+        let index = ExecIndex::build(LOG);
+        assert_eq!(index.len(), 2);
+
+        let hits: Vec<_> = index.find(10).collect();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(&LOG[hits[0].0..hits[0].0 + 23], "2025-08-12 10:57:09.564");
+        assert_eq!(index.find(11).count(), 0);
+
+        let (_, stmt) = index.statement(10).unwrap();
+        assert!(stmt.contains("update t set a = 1"));
+        let (offset, stmt) = index.statement(30).unwrap();
+        assert_eq!(offset, 0);
+        assert!(stmt.contains("select 1"));
+        assert!(index.statement(11).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+
+use crate::parser::{for_each_record, parse_record};
+use crate::sqllog::Sqllog;
+
+/// 将日志文本中的记录以 JSON Lines 格式（每行一个 [`Sqllog`] 对象）流式写入 `writer`，
+/// 返回写入的记录数。
+///
+/// 记录由 [`for_each_record`] 逐条产生并立即序列化，序列化缓冲区在记录之间复用，
+/// 因此内存占用与记录总数无关。前导错误文本会被忽略。
+pub fn write_jsonl<W: Write>(text: &str, mut writer: W) -> io::Result<u64> {
+    let mut buf: Vec<u8> = Vec::with_capacity(1024);
+    let mut count = 0u64;
+    let mut result: io::Result<()> = Ok(());
+    for_each_record(text, |rec| {
+        // 写入失败后跳过剩余记录
+        if result.is_err() {
+            return;
+        }
+        buf.clear();
+        let log = Sqllog::from_record(&parse_record(rec));
+        result = serde_json::to_writer(&mut buf, &log)
+            .map_err(io::Error::from)
+            .and_then(|_| {
+                buf.push(b'\n');
+                writer.write_all(&buf)
+            });
+        if result.is_ok() {
+            count += 1;
+        }
+    });
+    result?;
+    writer.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_jsonl_writes_one_object_per_record() {
+        let log_text = "garbage\n2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 2(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\n2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:B trxid:1 stmt:0x2 appname:app) [DEL] delete from t\n";
+        let mut out = Vec::new();
+        assert_eq!(write_jsonl(log_text, &mut out).unwrap(), 2);
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: Sqllog = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first.username, "A");
+        assert_eq!(first.execute_id, 7);
+        let second: Sqllog = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second.sql_type, "DEL");
+        assert_eq!(second.description, "delete from t");
+    }
+}
@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+
+/// SQL 指纹：把字面量替换为 `?`、统一大小写与空白后的语句模板。
+///
+/// 同一模板、不同参数的语句会得到相同的 `text`，可用作聚合统计的键。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// 归一化后的语句文本
+    pub text: String,
+    /// 被替换掉的字面量（字符串、数字）个数
+    pub literals: usize,
+    /// 语句中原有的绑定占位符（`?` 或 `:name`）个数
+    pub placeholders: usize,
+}
+
+/// 指纹归一化的可选规则，对应配置中的 `[analysis.fingerprint]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FingerprintOptions {
+    /// 保留注释（如优化器提示 `/*+ INDEX(t idx) */`）；行注释改写为块注释，使指纹保持单行
+    pub keep_comments: bool,
+    /// 把只含 `?` 的 `IN (?, ?, ...)` 列表折叠为 `IN (?+)`，不同长度的列表得到相同的指纹
+    pub collapse_in_lists: bool,
+    /// 统一转换为小写（引号标识符保持原样）
+    pub lowercase: bool,
+    /// 去掉 FROM / JOIN / INTO / UPDATE 等之后表名的模式前缀，`sch.orders` 与 `orders` 视为同一表
+    pub strip_schema: bool,
+}
+
+impl FingerprintOptions {
+    /// 默认规则：去掉注释、不折叠 IN 列表、转换为小写、保留模式前缀
+    pub const DEFAULT: Self = Self {
+        keep_comments: false,
+        collapse_in_lists: false,
+        lowercase: true,
+        strip_schema: false,
+    };
+}
+
+impl Default for FingerprintOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// 按默认规则（[`FingerprintOptions::DEFAULT`]）计算 SQL 文本的指纹。
+///
+/// 归一化规则见 [`fingerprint_with`]。
+pub fn fingerprint(sql: &str) -> Fingerprint {
+    fingerprint_with(sql, &FingerprintOptions::DEFAULT)
+}
+
+/// 按指定规则计算 SQL 文本的指纹。
+///
+/// 归一化规则：
+/// - 去掉 `--` 行注释与 `/* */` 块注释（`keep_comments` 时保留）；
+/// - 连续空白压缩为一个空格，首尾空白去除；
+/// - 单引号字符串与独立的数字替换为 `?`，并计入 `literals`；
+/// - 其余字符统一转换为小写（`lowercase`，引号标识符保持原样）；
+/// - 按需折叠 IN 列表（`collapse_in_lists`）、去掉表名的模式前缀（`strip_schema`）。
+pub fn fingerprint_with(sql: &str, options: &FingerprintOptions) -> Fingerprint {
+    let bytes = sql.as_bytes();
+    let n = bytes.len();
+    let mut out = String::with_capacity(n);
+    let mut literals = 0usize;
+    let mut placeholders = 0usize;
+    let mut i = 0usize;
+    // 上一个输出字符是否为标识符字符，用于区分 `t1` 中的数字与独立数字
+    let mut prev_ident = false;
+    let mut pending_space = false;
+
+    while i < n {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            pending_space = !out.is_empty();
+            prev_ident = false;
+            i += 1;
+            continue;
+        }
+        if b == b'-' && i + 1 < n && bytes[i + 1] == b'-' {
+            let end = sql[i..].find('\n').map_or(n, |p| i + p);
+            if options.keep_comments {
+                if pending_space {
+                    out.push(' ');
+                }
+                out.push_str("/* ");
+                out.push_str(sql[i + 2..end].trim());
+                out.push_str(" */");
+            }
+            i = (end + 1).min(n);
+            pending_space = !out.is_empty();
+            prev_ident = false;
+            continue;
+        }
+        if b == b'/' && i + 1 < n && bytes[i + 1] == b'*' {
+            let end = sql[i + 2..].find("*/").map_or(n, |p| i + 2 + p + 2);
+            if options.keep_comments {
+                if pending_space {
+                    out.push(' ');
+                }
+                out.push_str(&sql[i..end]);
+            }
+            i = end;
+            pending_space = !out.is_empty();
+            prev_ident = false;
+            continue;
+        }
+        if pending_space {
+            out.push(' ');
+            pending_space = false;
+        }
+
+        if b == b'\'' {
+            // 单引号字符串，'' 为转义的单引号
+            i += 1;
+            while i < n {
+                if bytes[i] == b'\'' {
+                    if i + 1 < n && bytes[i + 1] == b'\'' {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            out.push('?');
+            literals += 1;
+            prev_ident = false;
+        } else if b == b'"' {
+            // 引号标识符原样保留
+            let end = sql[i + 1..].find('"').map_or(n, |p| i + 1 + p + 1);
+            out.push_str(&sql[i..end]);
+            i = end;
+            prev_ident = true;
+        } else if b.is_ascii_digit() && !prev_ident {
+            while i < n && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            out.push('?');
+            literals += 1;
+            prev_ident = false;
+        } else if b == b'?' {
+            out.push('?');
+            placeholders += 1;
+            i += 1;
+            prev_ident = false;
+        } else if b == b':' && i + 1 < n && bytes[i + 1].is_ascii_alphabetic() && !prev_ident {
+            // 命名绑定参数 :name
+            i += 1;
+            while i < n && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            out.push('?');
+            placeholders += 1;
+            prev_ident = false;
+        } else {
+            let ch = sql[i..].chars().next().unwrap_or(' ');
+            if options.lowercase {
+                out.extend(ch.to_lowercase());
+            } else {
+                out.push(ch);
+            }
+            prev_ident = ch.is_alphanumeric() || ch == '_' || ch == '$' || ch == '#';
+            i += ch.len_utf8();
+        }
+    }
+
+    if options.collapse_in_lists {
+        out = collapse_in_lists(&out);
+    }
+    if options.strip_schema {
+        out = strip_schema(&out);
+    }
+    Fingerprint {
+        text: out,
+        literals,
+        placeholders,
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '#')
+}
+
+/// 返回 `text[i..]` 开头的注释或引号标识符的结束位置，不是时返回 None
+fn skip_opaque(text: &str, i: usize) -> Option<usize> {
+    let rest = &text[i..];
+    if let Some(body) = rest.strip_prefix("/*") {
+        Some(body.find("*/").map_or(text.len(), |p| i + 2 + p + 2))
+    } else {
+        let body = rest.strip_prefix('"')?;
+        Some(body.find('"').map_or(text.len(), |p| i + 1 + p + 1))
+    }
+}
+
+/// 把已归一化文本中只含 `?` 的 `in (...)` 列表改写为 `in (?+)`
+fn collapse_in_lists(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    let mut prev = None;
+    while i < text.len() {
+        if let Some(end) = skip_opaque(text, i) {
+            out.push_str(&text[i..end]);
+            i = end;
+            prev = Some('"');
+            continue;
+        }
+        let rest = &text[i..];
+        let ch = rest.chars().next().unwrap_or(' ');
+        if !prev.is_some_and(is_ident_char)
+            && rest.len() > 2
+            && rest[..2].eq_ignore_ascii_case("in")
+            && !rest[2..].starts_with(is_ident_char)
+        {
+            let after = rest[2..].trim_start();
+            if let Some(list) = after.strip_prefix('(')
+                && let Some(close) = list.find(')')
+                && list[..close].contains('?')
+                && list[..close].chars().all(|c| matches!(c, '?' | ',' | ' '))
+            {
+                out.push_str(&rest[..rest.len() - after.len()]);
+                out.push_str("(?+)");
+                i += rest.len() - list.len() + close + 1;
+                prev = Some(')');
+                continue;
+            }
+        }
+        out.push(ch);
+        prev = Some(ch);
+        i += ch.len_utf8();
+    }
+    out
+}
+
+/// 这些关键字之后是表名
+const TABLE_KEYWORDS: &[&str] = &["from", "join", "into", "update", "table", "using"];
+
+/// 这些关键字结束 FROM 子句中逗号分隔的表列表
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "where",
+    "on",
+    "group",
+    "order",
+    "having",
+    "union",
+    "minus",
+    "intersect",
+    "except",
+    "set",
+    "values",
+    "select",
+    "connect",
+    "start",
+    "for",
+    "limit",
+    "fetch",
+    "returning",
+    "window",
+];
+
+/// 去掉已归一化文本中表名的模式前缀：`from sch.orders o, sch.items` → `from orders o, items`
+fn strip_schema(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // 下一个标识符是表名
+    let mut expect_table = false;
+    // 当前括号层是否处于 FROM 子句中（逗号后仍是表名）
+    let mut in_from = false;
+    let mut levels: Vec<bool> = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let ch = rest.chars().next().unwrap_or(' ');
+        if rest.starts_with("/*") {
+            let end = skip_opaque(text, i).unwrap_or(text.len());
+            out.push_str(&text[i..end]);
+            i = end;
+            continue;
+        }
+        let is_quoted = ch == '"';
+        if is_quoted || (is_ident_char(ch) && !ch.is_ascii_digit()) {
+            let end = if is_quoted {
+                skip_opaque(text, i).unwrap_or(text.len())
+            } else {
+                rest.find(|c| !is_ident_char(c))
+                    .map_or(text.len(), |p| i + p)
+            };
+            let word = &text[i..end];
+            let next = text[end..].chars().next();
+            let qualified = next == Some('.')
+                && text[end + 1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c == '"' || is_ident_char(c));
+            if expect_table && qualified {
+                // 跳过前缀和点号，下一轮处理余下的名称
+                i = end + 1;
+                continue;
+            }
+            let lower = word.to_ascii_lowercase();
+            if TABLE_KEYWORDS.contains(&lower.as_str()) {
+                expect_table = true;
+                in_from = lower == "from" || (in_from && lower == "join");
+            } else {
+                expect_table = false;
+                if CLAUSE_KEYWORDS.contains(&lower.as_str()) {
+                    in_from = false;
+                }
+            }
+            out.push_str(word);
+            i = end;
+            continue;
+        }
+        match ch {
+            ' ' => {}
+            '(' => {
+                levels.push(in_from);
+                in_from = false;
+                expect_table = false;
+            }
+            ')' => {
+                in_from = levels.pop().unwrap_or(false);
+                expect_table = false;
+            }
+            ',' => expect_table = in_from,
+            _ => expect_table = false,
+        }
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_replaces_literals() {
+        let fp = fingerprint("SELECT *  FROM t1 WHERE id = 42 AND name = 'O''Brien'");
+        assert_eq!(fp.text, "select * from t1 where id = ? and name = ?");
+        assert_eq!(fp.literals, 2);
+        assert_eq!(fp.placeholders, 0);
+    }
+
+    #[test]
+    fn test_fingerprint_same_template() {
+        let a = fingerprint("select name from users where id=1");
+        let b = fingerprint("SELECT name\n FROM users -- comment\n WHERE id=20");
+        assert_eq!(a.text, b.text);
+        assert_eq!(fingerprint("select 1 from dual").text, "select ? from dual");
+    }
+
+    #[test]
+    fn test_fingerprint_literal_after_whitespace() {
+        let fp = fingerprint("select 1 from t where a between 1 and 5 limit 10");
+        assert_eq!(fp.text, "select ? from t where a between ? and ? limit ?");
+        assert_eq!(fp.literals, 4);
+        assert_eq!(
+            fingerprint("select c1\n\t2 from t").text,
+            "select c1 ? from t"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_counts_placeholders() {
+        let fp = fingerprint("update t set a = ?, b = :b where \"Id\" = ?");
+        assert_eq!(fp.text, "update t set a = ?, b = ? where \"Id\" = ?");
+        assert_eq!(fp.placeholders, 3);
+        assert_eq!(fp.literals, 0);
+    }
+
+    #[test]
+    fn test_fingerprint_options() {
+        let sql = "SELECT /*+ INDEX(o idx) */ o.ID FROM SCH.Orders o, sch.\"Items\" i -- hot\n\
+                   WHERE o.id IN (1, 2, 3) AND o.k in ( ?,? ) AND o.s IN (select id from app.t) \
+                   AND o.v in (1, o.w)";
+        assert_eq!(
+            fingerprint_with(sql, &FingerprintOptions::DEFAULT).text,
+            fingerprint(sql).text
+        );
+        let options = FingerprintOptions {
+            keep_comments: true,
+            collapse_in_lists: true,
+            lowercase: false,
+            strip_schema: true,
+        };
+        assert_eq!(
+            fingerprint_with(sql, &options).text,
+            "SELECT /*+ INDEX(o idx) */ o.ID FROM Orders o, \"Items\" i /* hot */ \
+             WHERE o.id IN (?+) AND o.k in (?+) AND o.s IN (select id from t) AND o.v in (?, o.w)"
+        );
+        let collapsed = FingerprintOptions {
+            collapse_in_lists: true,
+            ..FingerprintOptions::DEFAULT
+        };
+        assert_eq!(
+            fingerprint_with("select * from t where id in (1)", &collapsed).text,
+            fingerprint_with("select * from t where id in (1,2,3,4)", &collapsed).text
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// 从 sqllog 文件名中解析出的实例信息。
+///
+/// DM 的 sqllog 文件名形如 `dmsql_DMSERVER_20250812_105700.log`，
+/// 其中 `DMSERVER` 为实例名，`20250812_105700` 为日志切换时间。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InstanceInfo {
+    /// 实例名
+    pub instance: String,
+    /// 日志切换时间，格式 `YYYY-MM-DD HH:MM:SS`；文件名中没有时间时为 None
+    pub rotated_at: Option<String>,
+}
+
+impl InstanceInfo {
+    /// 从文件名（可带 `.log` 扩展名）解析实例信息。
+    ///
+    /// 文件名必须以 `dmsql_` 开头，否则返回 None。
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let stem = name.strip_suffix(".log").unwrap_or(name);
+        let rest = stem.strip_prefix("dmsql_")?;
+        if rest.is_empty() {
+            return None;
+        }
+
+        // 从右往左取 日期_时间 两段，剩余部分为实例名（实例名本身可能包含下划线）
+        let mut parts = rest.rsplitn(3, '_');
+        let time = parts.next();
+        let date = parts.next();
+        let instance = parts.next();
+        if let (Some(instance), Some(date), Some(time)) = (instance, date, time)
+            && !instance.is_empty()
+            && date.len() == 8
+            && time.len() == 6
+            && date.bytes().chain(time.bytes()).all(|b| b.is_ascii_digit())
+        {
+            let rotated_at = format!(
+                "{}-{}-{} {}:{}:{}",
+                &date[0..4],
+                &date[4..6],
+                &date[6..8],
+                &time[0..2],
+                &time[2..4],
+                &time[4..6]
+            );
+            return Some(Self {
+                instance: instance.to_string(),
+                rotated_at: Some(rotated_at),
+            });
+        }
+
+        Some(Self {
+            instance: rest.to_string(),
+            rotated_at: None,
+        })
+    }
+
+    /// 从文件路径的文件名部分解析实例信息。
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?;
+        Self::from_file_name(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file_name() {
+        let info = InstanceInfo::from_file_name("dmsql_DMSERVER_20250812_105700.log").unwrap();
+        assert_eq!(info.instance, "DMSERVER");
+        assert_eq!(info.rotated_at.as_deref(), Some("2025-08-12 10:57:00"));
+    }
+
+    #[test]
+    fn test_instance_name_with_underscore() {
+        let info =
+            InstanceInfo::from_path("/data/log/dmsql_DM_PROD_01_20250101_000000.log").unwrap();
+        assert_eq!(info.instance, "DM_PROD_01");
+        assert_eq!(info.rotated_at.as_deref(), Some("2025-01-01 00:00:00"));
+    }
+
+    #[test]
+    fn test_without_rotation_time() {
+        let info = InstanceInfo::from_file_name("dmsql_DMSERVER.log").unwrap();
+        assert_eq!(info.instance, "DMSERVER");
+        assert_eq!(info.rotated_at, None);
+        assert_eq!(InstanceInfo::from_file_name("server.log"), None);
+    }
+}
@@ -1,12 +1,35 @@
+pub mod complexity;
 pub mod error;
+pub mod exec_index;
+pub mod export;
+pub mod fingerprint;
+pub mod instance;
+pub mod metrics;
+pub mod objects;
 pub mod parser;
+pub mod search;
+pub mod sql;
 pub mod sqllog;
 mod tools;
 
+pub use complexity::Complexity;
 pub use error::ParseError;
+pub use exec_index::ExecIndex;
+pub use fingerprint::{Fingerprint, FingerprintOptions, fingerprint, fingerprint_with};
+pub use instance::InstanceInfo;
+pub use metrics::RecordMetrics;
 pub use parser::split_by_ts_records_with_errors;
-pub use parser::{for_each_record, parse_records_with, split_into};
+pub use parser::{
+    ParseMode, RecordOrError, for_each_record, parse_record_strict, parse_records_with, split_into,
+    try_parse_record,
+};
+pub use search::KeywordMatcher;
+pub use sql::{RecordCategory, StatementKind};
 pub use sqllog::Sqllog;
+pub use tools::civil_from_days;
+pub use tools::epoch_millis_to_ts;
+pub use tools::find_next_record_start;
 pub use tools::is_record_start;
 pub use tools::is_ts_millis;
 pub use tools::prewarm;
+pub use tools::ts_to_epoch_millis;
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fingerprint::{FingerprintOptions, fingerprint_with};
+use crate::parser::ParsedRecord;
+use crate::sql::{self, RecordCategory};
+
+/// 丢弃 SQL 正文后的记录摘要：只保留时间戳、元数据、执行指标与 SQL 指纹。
+///
+/// 只需要聚合报表时，用它代替原始记录可以让 SQL 正文在计算指纹后立即释放，
+/// 长语句较多的日志内存占用可降低一个数量级。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordMetrics {
+    pub ts: String,
+    pub ep: Option<String>,
+    pub sess: Option<String>,
+    pub thrd: Option<String>,
+    pub user: Option<String>,
+    pub trxid: Option<String>,
+    pub stmt: Option<String>,
+    pub appname: Option<String>,
+    pub ip: Option<String>,
+    /// 语句标记，如 `SEL`
+    pub tag: Option<String>,
+    /// SQL 指纹，见 [`fingerprint_with`]
+    pub fingerprint: String,
+    pub execute_time_ms: Option<u64>,
+    pub row_count: Option<u64>,
+    pub execute_id: Option<u64>,
+    /// 记录类别，见 [`sql::categorize`]
+    pub category: RecordCategory,
+    /// 命中的配置规则的标签，逗号分隔；由调用方按规则填写，`from_record` 中为空
+    #[serde(default)]
+    pub tags: String,
+}
+
+impl RecordMetrics {
+    /// 按默认规则计算指纹
+    pub fn from_record(rec: &ParsedRecord<'_>) -> Self {
+        Self::from_record_with(rec, &FingerprintOptions::DEFAULT)
+    }
+
+    /// 按指定规则计算指纹
+    pub fn from_record_with(rec: &ParsedRecord<'_>, fingerprint: &FingerprintOptions) -> Self {
+        let owned = |v: Option<&str>| v.map(str::to_string);
+        Self {
+            ts: rec.ts.to_string(),
+            ep: owned(rec.ep),
+            sess: owned(rec.sess),
+            thrd: owned(rec.thrd),
+            user: owned(rec.user),
+            trxid: owned(rec.trxid),
+            stmt: owned(rec.stmt),
+            appname: owned(rec.appname),
+            ip: owned(rec.ip),
+            tag: owned(sql::split_tag(&rec.body).0),
+            fingerprint: fingerprint_with(sql::sql_text(&rec.body), fingerprint).text,
+            execute_time_ms: rec.execute_time_ms,
+            row_count: rec.row_count,
+            execute_id: rec.execute_id,
+            category: sql::categorize(&rec.body),
+            tags: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_record;
+
+    #[test]
+    fn from_record_keeps_fingerprint_not_body() {
+        let rec = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = 42 EXECTIME: 3(ms) ROWCOUNT: 1(rows) EXEC_ID: 9.";
+        let m = RecordMetrics::from_record(&parse_record(rec));
+        assert_eq!(m.ep.as_deref(), Some("EP[0]"));
+        assert_eq!(m.tag.as_deref(), Some("SEL"));
+        assert_eq!(m.fingerprint, "select * from t where id = ?");
+        assert_eq!(m.execute_time_ms, Some(3));
+        assert_eq!(m.execute_id, Some(9));
+    }
+}
@@ -0,0 +1,378 @@
+//! 从 SQL 文本中粗略提取引用的表/视图名与调用的存储过程名，用于统计热点表与脱敏。
+//!
+//! 不做完整的 SQL 解析：只在词法层面识别 `FROM`、`JOIN`、`INTO`、`UPDATE`、`USING`、
+//! `TABLE` 之后的（可带模式前缀的）名称，子查询、表函数与 WITH 子句定义的名称会被跳过；
+//! 过程调用见 [`called_procedures`]。名称统一转为小写（引号标识符去掉引号后同样处理）。
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Dot,
+    Comma,
+    Semicolon,
+    LParen,
+    RParen,
+    /// 其余符号、字面量与绑定参数
+    Other,
+}
+
+/// 紧跟在表名后、不能视为别名的关键字
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "where",
+    "join",
+    "inner",
+    "left",
+    "right",
+    "full",
+    "outer",
+    "cross",
+    "natural",
+    "on",
+    "using",
+    "group",
+    "order",
+    "having",
+    "union",
+    "minus",
+    "intersect",
+    "except",
+    "set",
+    "values",
+    "select",
+    "limit",
+    "offset",
+    "fetch",
+    "connect",
+    "start",
+    "for",
+    "with",
+    "when",
+    "partition",
+    "returning",
+    "into",
+    "window",
+    "default",
+];
+
+/// 这些函数的参数中出现的 FROM 不引入表名，如 `extract(day from ts)`
+const FROM_FUNCTIONS: &[&str] = &["extract", "trim", "substring", "overlay", "position"];
+
+/// 提取 `sql` 中引用的表/视图名，按首次出现的顺序去重
+pub fn referenced_tables(sql: &str) -> Vec<String> {
+    let tokens = tokenize(sql);
+    let mut tables: Vec<String> = Vec::new();
+    let mut ctes: Vec<String> = Vec::new();
+    // 每层括号前的标识符（函数名），用于识别 extract(... from ...)
+    let mut parens: Vec<Option<&str>> = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let kw = match &tokens[i] {
+            Token::Ident(s) => s.as_str(),
+            Token::LParen => {
+                let func = match i.checked_sub(1).map(|p| &tokens[p]) {
+                    Some(Token::Ident(s)) => Some(s.as_str()),
+                    _ => None,
+                };
+                parens.push(func);
+                i += 1;
+                continue;
+            }
+            Token::RParen => {
+                parens.pop();
+                i += 1;
+                continue;
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        i += 1;
+        match kw {
+            "from" | "join" => {
+                if kw == "from"
+                    && parens
+                        .last()
+                        .is_some_and(|f| f.is_some_and(is_from_function))
+                {
+                    continue;
+                }
+                // FROM 之后可以是逗号分隔的多个表
+                while let Some((name, next)) = qualified_name(&tokens, i) {
+                    i = next;
+                    // 名称后紧跟括号的是表函数
+                    if tokens.get(i) != Some(&Token::LParen) {
+                        push_unique(&mut tables, name);
+                    }
+                    i = skip_alias(&tokens, i);
+                    if kw == "from" && tokens.get(i) == Some(&Token::Comma) {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            "into" | "update" | "using" | "table" => {
+                if let Some((name, next)) = qualified_name(&tokens, i) {
+                    push_unique(&mut tables, name);
+                    i = next;
+                }
+            }
+            "as" => {
+                // WITH 子句：name AS ( ... )，name 不是实际的表
+                if tokens.get(i) == Some(&Token::LParen)
+                    && let Some(Token::Ident(name)) = i.checked_sub(2).map(|p| &tokens[p])
+                {
+                    push_unique(&mut ctes, name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    tables.retain(|t| t != "dual" && !ctes.contains(t));
+    tables
+}
+
+/// PL/SQL 块中以这些关键字开头的语句不是过程调用
+const BLOCK_KEYWORDS: &[&str] = &[
+    "begin",
+    "declare",
+    "end",
+    "if",
+    "elsif",
+    "else",
+    "then",
+    "case",
+    "when",
+    "loop",
+    "while",
+    "for",
+    "exit",
+    "return",
+    "goto",
+    "raise",
+    "null",
+    "open",
+    "close",
+    "fetch",
+    "commit",
+    "rollback",
+    "savepoint",
+    "select",
+    "insert",
+    "update",
+    "delete",
+    "merge",
+    "with",
+    "call",
+    "exec",
+    "execute",
+];
+
+/// 这些关键字之后开始 PL/SQL 块中的一条新语句
+const STATEMENT_STARTS: &[&str] = &["begin", "then", "else", "loop"];
+
+/// 提取 `sql` 中调用的存储过程/函数名（可带模式或包前缀），按首次出现的顺序去重。
+///
+/// 识别语句开头的 `CALL` / `EXEC` / `EXECUTE` 之后的名称（`EXECUTE IMMEDIATE` 除外），
+/// 以及 PL/SQL 块（`BEGIN ... END`）中作为单独语句出现的调用：带括号的 `p(...)`，
+/// 或不带参数的 `pkg.p;`。
+pub fn called_procedures(sql: &str) -> Vec<String> {
+    let tokens = tokenize(sql);
+    let mut procedures = Vec::new();
+    let mut in_block = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let Token::Ident(word) = &tokens[i] else {
+            i += 1;
+            continue;
+        };
+        let statement_start = match i.checked_sub(1).map(|p| &tokens[p]) {
+            None | Some(Token::Semicolon) => true,
+            Some(Token::Ident(prev)) => STATEMENT_STARTS.contains(&prev.as_str()),
+            _ => false,
+        };
+        if word == "begin" {
+            in_block = true;
+        }
+        if statement_start && matches!(word.as_str(), "call" | "exec" | "execute") {
+            if let Some((name, next)) = qualified_name(&tokens, i + 1)
+                && name != "immediate"
+            {
+                push_unique(&mut procedures, name);
+                i = next;
+                continue;
+            }
+        } else if in_block
+            && statement_start
+            && !BLOCK_KEYWORDS.contains(&word.as_str())
+            && let Some((name, next)) = qualified_name(&tokens, i)
+        {
+            let is_call = match tokens.get(next) {
+                Some(Token::LParen) => true,
+                Some(Token::Semicolon) => name.contains('.'),
+                _ => false,
+            };
+            if is_call {
+                push_unique(&mut procedures, name);
+            }
+            i = next;
+            continue;
+        }
+        i += 1;
+    }
+    procedures
+}
+
+fn is_from_function(name: &str) -> bool {
+    FROM_FUNCTIONS.contains(&name)
+}
+
+fn push_unique(list: &mut Vec<String>, name: String) {
+    if !list.contains(&name) {
+        list.push(name);
+    }
+}
+
+/// 从 `i` 开始读取 `a.b.c` 形式的名称，返回名称与其后的位置
+fn qualified_name(tokens: &[Token], mut i: usize) -> Option<(String, usize)> {
+    let Some(Token::Ident(first)) = tokens.get(i) else {
+        return None;
+    };
+    if CLAUSE_KEYWORDS.contains(&first.as_str()) {
+        return None;
+    }
+    let mut name = first.clone();
+    i += 1;
+    while tokens.get(i) == Some(&Token::Dot) {
+        let Some(Token::Ident(part)) = tokens.get(i + 1) else {
+            break;
+        };
+        name.push('.');
+        name.push_str(part);
+        i += 2;
+    }
+    Some((name, i))
+}
+
+/// 跳过表名后的别名（`t a` 或 `t as a`）
+fn skip_alias(tokens: &[Token], i: usize) -> usize {
+    match tokens.get(i) {
+        Some(Token::Ident(s)) if s == "as" => i + 2,
+        Some(Token::Ident(s)) if !CLAUSE_KEYWORDS.contains(&s.as_str()) => i + 1,
+        _ => i,
+    }
+}
+
+/// 切分为词法单元，跳过注释、字符串与数字字面量
+fn tokenize(sql: &str) -> Vec<Token> {
+    let bytes = sql.as_bytes();
+    let n = bytes.len();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < n {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            i += 1;
+        } else if sql[i..].starts_with("--") {
+            i = sql[i..].find('\n').map_or(n, |p| i + p + 1);
+        } else if sql[i..].starts_with("/*") {
+            i = sql[i + 2..].find("*/").map_or(n, |p| i + 2 + p + 2);
+        } else if b == b'\'' {
+            // '' 为转义的单引号，相当于两个相邻的字符串
+            i = sql[i + 1..].find('\'').map_or(n, |p| i + 1 + p + 1);
+            tokens.push(Token::Other);
+        } else if b == b'"' {
+            let end = sql[i + 1..].find('"').map_or(n, |p| i + 1 + p);
+            tokens.push(Token::Ident(sql[i + 1..end].to_lowercase()));
+            i = (end + 1).min(n);
+        } else if is_ident_start(b) {
+            let start = i;
+            while i < n && is_ident_byte(bytes[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Ident(sql[start..i].to_lowercase()));
+        } else if b.is_ascii_digit() {
+            while i < n && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                i += 1;
+            }
+            tokens.push(Token::Other);
+        } else {
+            tokens.push(match b {
+                b'.' => Token::Dot,
+                b',' => Token::Comma,
+                b';' => Token::Semicolon,
+                b'(' => Token::LParen,
+                b')' => Token::RParen,
+                _ => Token::Other,
+            });
+            i += sql[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    tokens
+}
+
+fn is_ident_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || !b.is_ascii()
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'$' | b'#') || !b.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_tables_from_common_statements() {
+        assert_eq!(
+            referenced_tables(
+                "SELECT a.id FROM sch.Orders a, customers c LEFT JOIN \"Items\" i ON i.id = a.id \
+                 WHERE a.ts > extract(year from sysdate) AND a.id IN (SELECT id FROM hist) -- from x"
+            ),
+            ["sch.orders", "customers", "items", "hist"]
+        );
+        assert_eq!(
+            referenced_tables("insert into t1(a, b) select a, b from t2 where s = 'from t3'"),
+            ["t1", "t2"]
+        );
+        assert_eq!(referenced_tables("update t set a = 1"), ["t"]);
+        assert_eq!(referenced_tables("delete from t where a = 1"), ["t"]);
+        assert_eq!(
+            referenced_tables(
+                "merge into t using s on (t.id = s.id) when matched then update set t.a = s.a"
+            ),
+            ["t", "s"]
+        );
+        assert_eq!(
+            referenced_tables("with r as (select * from base) select * from r, table(f(1)) x"),
+            ["base"]
+        );
+        assert_eq!(referenced_tables("truncate table big"), ["big"]);
+        assert!(referenced_tables("select 1 from dual").is_empty());
+        assert!(referenced_tables("commit").is_empty());
+    }
+
+    #[test]
+    fn extracts_called_procedures() {
+        assert_eq!(
+            called_procedures("call sales.p_login('alice', 'hunter2')"),
+            ["sales.p_login"]
+        );
+        assert_eq!(
+            called_procedures("EXEC \"HR\".Refresh_Stats"),
+            ["hr.refresh_stats"]
+        );
+        assert_eq!(
+            called_procedures(
+                "declare v int; begin v := 1; sales.p_log(v); if v > 0 then pkg.flush; end if; \
+                 execute immediate 'call x.y()'; commit; end;"
+            ),
+            ["sales.p_log", "pkg.flush"]
+        );
+        assert!(called_procedures("select f(1) from dual").is_empty());
+    }
+}
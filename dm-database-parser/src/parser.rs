@@ -1,3 +1,9 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use crate::error::ParseError;
+use crate::tools::{MetaOrderError, check_meta_order, find_next_record_start, is_ts_millis};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedRecord<'a> {
     pub ts: &'a str,
@@ -10,10 +16,13 @@ pub struct ParsedRecord<'a> {
     pub stmt: Option<&'a str>,
     pub appname: Option<&'a str>,
     pub ip: Option<&'a str>,
-    pub body: &'a str,
+    /// 记录正文；多行正文中的 `\r\n` 统一为 `\n`，只有这种情况才会复制
+    pub body: Cow<'a, str>,
     pub execute_time_ms: Option<u64>,
     pub row_count: Option<u64>,
     pub execute_id: Option<u64>,
+    /// 记录在所属文件中的起始字节偏移；解析单条记录时为 0，由按块读取文件的调用方换算填写
+    pub offset: u64,
 }
 
 /// 迭代器，从输入日志文本中产生记录切片(&str)，不进行额外分配。
@@ -35,20 +44,7 @@ impl<'a> RecordSplitter<'a> {
     pub fn new(text: &'a str) -> Self {
         let bytes = text.as_bytes();
         let n = text.len();
-        let mut first_start = None;
-        if n >= 23 {
-            let limit = n.saturating_sub(23);
-            let mut pos = 0usize;
-            while pos <= limit {
-                if (pos == 0 || bytes[pos - 1] == b'\n')
-                    && crate::tools::is_ts_millis_bytes(&bytes[pos..pos + 23])
-                {
-                    first_start = Some(pos);
-                    break;
-                }
-                pos += 1;
-            }
-        }
+        let first_start = find_next_record_start(bytes, 0);
         let scan_pos = first_start.unwrap_or(0).saturating_add(1);
         RecordSplitter {
             text,
@@ -61,9 +57,94 @@ impl<'a> RecordSplitter<'a> {
         }
     }
 
-    /// 返回完整的前导错误文本切片（第一条记录之前的所有内容）
+    /// 返回完整的前导错误文本切片（第一条记录之前的所有内容，不含开头的 BOM）
     pub fn leading_errors_slice(&self) -> Option<&'a str> {
-        self.first_start.map(|s| &self.text[..s])
+        self.first_start.map(|s| {
+            let prefix = &self.text[..s];
+            prefix.strip_prefix('\u{feff}').unwrap_or(prefix)
+        })
+    }
+
+    /// 预读下一条将要返回的记录的时间戳，不推进迭代器；没有更多记录时返回 None
+    pub fn peek_next_start_ts(&self) -> Option<&'a str> {
+        if self.finished {
+            return None;
+        }
+        let s = self.next_start?;
+        Some(&self.text[s..s + 23])
+    }
+
+    /// 转换为同时产生前导错误文本的迭代器，见 [`RecordsWithErrors`]
+    pub fn with_errors(self) -> RecordsWithErrors<'a> {
+        // 整段文本都不含记录起始时，全部文本都是无法识别的内容
+        let garbage = match self.first_start {
+            Some(_) => self.leading_errors_slice(),
+            None => Some(self.text.strip_prefix('\u{feff}').unwrap_or(self.text)),
+        };
+        RecordsWithErrors {
+            garbage: garbage.filter(|g| !g.trim().is_empty()),
+            inner: self,
+        }
+    }
+
+    /// 转换为按 `size` 条记录滑动的窗口迭代器，见 [`RecordWindows`]
+    pub fn windows(self, size: usize) -> RecordWindows<'a> {
+        RecordWindows {
+            inner: self,
+            size: size.max(1),
+            window: VecDeque::with_capacity(size.max(1)),
+        }
+    }
+}
+
+/// 相邻记录的滑动窗口迭代器，由 [`RecordSplitter::windows`] 创建。
+///
+/// 与 `slice::windows` 语义一致：每次前进一条记录，产生恰好 `size` 条相邻记录（按原文顺序）；
+/// 记录总数不足 `size` 时不产生任何窗口。
+pub struct RecordWindows<'a> {
+    inner: RecordSplitter<'a>,
+    size: usize,
+    window: VecDeque<&'a str>,
+}
+
+impl<'a> Iterator for RecordWindows<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.inner.next()?);
+        }
+        Some(self.window.iter().copied().collect())
+    }
+}
+
+/// [`RecordsWithErrors`] 产生的一项：一条记录，或第一条记录之前无法识别的文本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordOrError<'a> {
+    Record(&'a str),
+    Garbage(&'a str),
+}
+
+/// 记录迭代器，由 [`RecordSplitter::with_errors`] 创建。
+///
+/// 先以 [`RecordOrError::Garbage`] 产生前导错误文本（不含 BOM，只含空白时省略），再依次产生各条记录，
+/// 流式处理时无需再单独检查前缀即可把无法识别的文本交给错误处理。
+pub struct RecordsWithErrors<'a> {
+    garbage: Option<&'a str>,
+    inner: RecordSplitter<'a>,
+}
+
+impl<'a> Iterator for RecordsWithErrors<'a> {
+    type Item = RecordOrError<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(g) = self.garbage.take() {
+            return Some(RecordOrError::Garbage(g));
+        }
+        self.inner.next().map(RecordOrError::Record)
     }
 }
 
@@ -83,25 +164,11 @@ impl<'a> Iterator for RecordSplitter<'a> {
         };
 
         // 扫描下一个记录的起始位置
-        if self.scan_pos > self.n {
-            // 没有足够空间容纳另一个时间戳，返回剩余内容
-            self.finished = true;
-            return Some(&self.text[start..self.n]);
-        }
-        let limit = self.n.saturating_sub(23);
-        let mut pos = self.scan_pos;
-        while pos <= limit {
-            if (pos == 0 || self.bytes[pos - 1] == b'\n')
-                && crate::tools::is_ts_millis_bytes(&self.bytes[pos..pos + 23])
-            {
-                // 找到下一个起始位置
-                let end = pos;
-                // 为下一次调用做准备
-                self.next_start = Some(pos);
-                self.scan_pos = pos + 1;
-                return Some(&self.text[start..end]);
-            }
-            pos += 1;
+        if let Some(pos) = find_next_record_start(self.bytes, self.scan_pos) {
+            // 为下一次调用做准备
+            self.next_start = Some(pos);
+            self.scan_pos = pos + 1;
+            return Some(&self.text[start..pos]);
         }
 
         // 没有下一个起始位置 => 返回最后一条记录
@@ -154,7 +221,7 @@ where
     F: FnMut(&str),
 {
     let splitter = RecordSplitter::new(text);
-    // 对流式 API 忽略前导错误；如果需要，调用者可以通过 RecordSplitter::leading_errors_slice 检查它们。
+    // 对流式 API 忽略前导错误；如果需要，调用者可以通过 RecordSplitter::with_errors 一并处理它们。
     if let Some(_prefix) = splitter.leading_errors_slice() {
         // 在迭代之前释放前缀借用
     }
@@ -211,7 +278,10 @@ fn parse_digits_forward(s: &str, mut i: usize) -> Option<(u64, usize)> {
 
 /// 解析单条记录（由 split_by_ts_records_with_errors 生成）。
 /// 返回一个从输入 `rec` 借用的 ParsedRecord。
+///
+/// 记录末尾的换行（包括 Windows 的 `\r\n`）不计入 body，body 内部的 `\r\n` 统一为 `\n`。
 pub fn parse_record<'a>(rec: &'a str) -> ParsedRecord<'a> {
+    let rec = rec.trim_end_matches(['\r', '\n']);
     let ts: &'a str = if rec.len() >= 23 { &rec[..23] } else { "" };
 
     // 在时间戳之后查找第一个 '('，然后查找对应的 ')'
@@ -250,16 +320,16 @@ pub fn parse_record<'a>(rec: &'a str) -> ParsedRecord<'a> {
     while let Some(tok) = iter.next() {
         if tok.starts_with("EP[") {
             ep = Some(tok);
-        } else if tok.starts_with("sess:") {
-            sess = Some(&tok[5..]);
-        } else if tok.starts_with("thrd:") {
-            thrd = Some(&tok[5..]);
-        } else if tok.starts_with("user:") {
-            user = Some(&tok[5..]);
-        } else if tok.starts_with("trxid:") {
-            trxid = Some(&tok[6..]);
-        } else if tok.starts_with("stmt:") {
-            stmt = Some(&tok[5..]);
+        } else if let Some(v) = tok.strip_prefix("sess:") {
+            sess = Some(v);
+        } else if let Some(v) = tok.strip_prefix("thrd:") {
+            thrd = Some(v);
+        } else if let Some(v) = tok.strip_prefix("user:") {
+            user = Some(v);
+        } else if let Some(v) = tok.strip_prefix("trxid:") {
+            trxid = Some(v);
+        } else if let Some(v) = tok.strip_prefix("stmt:") {
+            stmt = Some(v);
         } else if tok == "appname:" {
             // 下一个标记可能是 ip:::... 或 appname 的值
             if let Some(next) = iter.peek() {
@@ -278,8 +348,10 @@ pub fn parse_record<'a>(rec: &'a str) -> ParsedRecord<'a> {
             } else {
                 appname = Some("");
             }
-        } else if tok.starts_with("appname:") {
-            let val = &tok[8..];
+        } else if let Some(v) = tok.strip_prefix("ip:::") {
+            // appname 有值时 ip 作为独立标记出现
+            ip = Some(v.trim_start_matches("ffff:"));
+        } else if let Some(val) = tok.strip_prefix("appname:") {
             if val.starts_with("ip:::") {
                 let ippart = val.trim_start_matches("ip:::");
                 let ipclean = ippart.trim_start_matches("ffff:");
@@ -333,10 +405,88 @@ pub fn parse_record<'a>(rec: &'a str) -> ParsedRecord<'a> {
         stmt,
         appname,
         ip,
-        body,
+        body: if body.contains("\r\n") {
+            Cow::Owned(body.replace("\r\n", "\n"))
+        } else {
+            Cow::Borrowed(body)
+        },
         execute_time_ms,
         row_count,
         execute_id,
+        offset: 0,
+    }
+}
+
+/// 校验必需字段后解析单条记录，失败时报告出错的字段及原因。
+///
+/// 与尽力而为的 [`parse_record`] 不同，要求记录以合法时间戳开头、紧随其后是闭合的
+/// 元数据括号，且元数据中包含 EP、sess、thrd、user、trxid、stmt。
+pub fn try_parse_record<'a>(rec: &'a str) -> Result<ParsedRecord<'a>, ParseError> {
+    let ts = rec.get(..23).ok_or(ParseError::MissingField("timestamp"))?;
+    if !is_ts_millis(ts) {
+        return Err(ParseError::InvalidField {
+            field: "timestamp",
+            reason: "expected YYYY-MM-DD HH:MM:SS.mmm",
+        });
+    }
+    let after_ts = rec[23..].trim_start_matches(' ');
+    if !after_ts.starts_with('(') {
+        return Err(ParseError::MissingField("meta"));
+    }
+    if !after_ts.contains(')') {
+        return Err(ParseError::InvalidField {
+            field: "meta",
+            reason: "unclosed parenthesis",
+        });
+    }
+
+    let parsed = parse_record(rec);
+    let required = [
+        ("ep", parsed.ep),
+        ("sess", parsed.sess),
+        ("thrd", parsed.thrd),
+        ("user", parsed.user),
+        ("trxid", parsed.trxid),
+        ("stmt", parsed.stmt),
+    ];
+    if let Some((field, _)) = required.iter().find(|(_, v)| v.is_none()) {
+        return Err(ParseError::MissingField(field));
+    }
+    Ok(parsed)
+}
+
+/// 严格解析单条记录：在 [`try_parse_record`] 的基础上，要求元数据中的关键短语
+/// 与 [`is_record_start`](crate::is_record_start) 一致，全部出现且顺序为
+/// EP -> sess -> thrd -> user -> trxid -> stmt -> appname，用于排除格式相似的非 DM 日志。
+pub fn parse_record_strict<'a>(rec: &'a str) -> Result<ParsedRecord<'a>, ParseError> {
+    let parsed = try_parse_record(rec)?;
+    match check_meta_order(parsed.meta_raw) {
+        Ok(()) => Ok(parsed),
+        Err(MetaOrderError::Missing(field)) => Err(ParseError::MissingField(field)),
+        Err(MetaOrderError::OutOfOrder(field)) => Err(ParseError::InvalidField {
+            field,
+            reason: "meta keys out of order",
+        }),
+    }
+}
+
+/// 记录解析模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// 尽力而为，任何输入都会得到一条记录，见 [`parse_record`]
+    #[default]
+    Lenient,
+    /// 严格校验元数据字段及其顺序，见 [`parse_record_strict`]
+    Strict,
+}
+
+impl ParseMode {
+    /// 按当前模式解析单条记录；宽松模式永远不会失败
+    pub fn parse<'a>(self, rec: &'a str) -> Result<ParsedRecord<'a>, ParseError> {
+        match self {
+            ParseMode::Lenient => Ok(parse_record(rec)),
+            ParseMode::Strict => parse_record_strict(rec),
+        }
     }
 }
 
@@ -374,6 +524,76 @@ mod tests {
         assert_eq!(v.len(), 2);
     }
 
+    #[test]
+    fn test_with_errors_yields_garbage_first() {
+        let log_text = "\u{feff}garbage\n2023-10-05 14:23:45.123 (EP[1]) foo\n2023-10-05 14:23:46.456 (EP[2]) bar\n";
+        let items: Vec<RecordOrError> = RecordSplitter::new(log_text).with_errors().collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0], RecordOrError::Garbage("garbage\n"));
+        assert!(matches!(items[2], RecordOrError::Record(r) if r.ends_with("bar\n")));
+
+        // 只有空白的前缀不产生错误；完全不含记录时整段文本都是错误
+        let clean = "\n2023-10-05 14:23:45.123 (EP[1]) foo\n";
+        assert!(matches!(
+            RecordSplitter::new(clean).with_errors().next(),
+            Some(RecordOrError::Record(_))
+        ));
+        let items: Vec<RecordOrError> = RecordSplitter::new("no records\n").with_errors().collect();
+        assert_eq!(items, vec![RecordOrError::Garbage("no records\n")]);
+    }
+
+    #[test]
+    fn test_peek_and_windows() {
+        let log_text = "2023-10-05 14:23:45.123 (EP[1]) a\n2023-10-05 14:23:46.456 (EP[2]) b\n2023-10-05 14:23:47.789 (EP[3]) c\n";
+        let mut it = RecordSplitter::new(log_text);
+        assert_eq!(it.peek_next_start_ts(), Some("2023-10-05 14:23:45.123"));
+        it.next();
+        assert_eq!(it.peek_next_start_ts(), Some("2023-10-05 14:23:46.456"));
+        it.next();
+        it.next();
+        assert_eq!(it.peek_next_start_ts(), None);
+
+        let windows: Vec<Vec<&str>> = RecordSplitter::new(log_text).windows(2).collect();
+        assert_eq!(windows.len(), 2);
+        assert!(windows[0][0].ends_with("a\n") && windows[0][1].ends_with("b\n"));
+        assert!(windows[1][0].ends_with("b\n") && windows[1][1].ends_with("c\n"));
+        assert_eq!(RecordSplitter::new(log_text).windows(4).count(), 0);
+    }
+
+    #[test]
+    fn test_crlf_and_bom_tolerance() {
+        let log_text = "\u{feff}2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:app) TRX: START\r\n2025-08-12 10:57:09.563 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:app) [SEL] select 1\r\n";
+        let (records, errors) = split_by_ts_records_with_errors(log_text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            RecordSplitter::new(log_text).leading_errors_slice(),
+            Some("")
+        );
+
+        let r0 = parse_record(records[0]);
+        assert_eq!(r0.ts, "2025-08-12 10:57:09.562");
+        assert_eq!(r0.body, "TRX: START");
+        assert_eq!(parse_record(records[1]).body, "[SEL] select 1");
+    }
+
+    #[test]
+    fn test_crlf_multiline_body_normalized() {
+        let rec = "2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:app) [SEL] select a,\r\n  b\r\nfrom t\r\nwhere id = 1 EXECTIME: 3(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\r\n";
+        let r = parse_record(rec);
+        assert_eq!(
+            r.body,
+            "[SEL] select a,\n  b\nfrom t\nwhere id = 1 EXECTIME: 3(ms) ROWCOUNT: 1(rows) EXEC_ID: 7."
+        );
+        assert!(!crate::sql::sql_text(&r.body).contains('\r'));
+        assert_eq!(r.execute_time_ms, Some(3));
+        assert_eq!(r.execute_id, Some(7));
+        assert!(matches!(
+            parse_record("2025-08-12 10:57:09.562 (EP[0]) x\r\n").body,
+            Cow::Borrowed(_)
+        ));
+    }
+
     #[test]
     fn test_parse_simple_log_sample() {
         let log_text = "2025-08-12 10:57:09.562 (EP[0] sess:0x7fb24f392a30 thrd:757794 user:HBTCOMS_V3_PROD trxid:688489653 stmt:0x7fb236077b70 appname: ip:::ffff:10.3.100.68) EXECTIME: 0ms ROWCOUNT: 1 EXEC_ID: 289655185\n2025-08-12 10:57:09.562 (EP[0] sess:0x7fb24f392a30 thrd:757794 user:HBTCOMS_V3_PROD trxid:0 stmt:NULL appname:) TRX: START\n";
@@ -392,4 +612,60 @@ mod tests {
         let r1 = parse_record(records[1]);
         assert!(r1.body.contains("TRX: START"));
     }
+
+    #[test]
+    fn test_try_parse_record_reports_failed_field() {
+        let ok = "2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:) [SEL] select 1";
+        assert_eq!(try_parse_record(ok).unwrap(), parse_record(ok));
+
+        assert!(matches!(
+            try_parse_record("2025-08-12"),
+            Err(ParseError::MissingField("timestamp"))
+        ));
+        assert!(matches!(
+            try_parse_record("2025/08/12 10:57:09.562 (EP[0])"),
+            Err(ParseError::InvalidField {
+                field: "timestamp",
+                ..
+            })
+        ));
+        assert!(matches!(
+            try_parse_record("2025-08-12 10:57:09.562 select (1)"),
+            Err(ParseError::MissingField("meta"))
+        ));
+        assert!(matches!(
+            try_parse_record("2025-08-12 10:57:09.562 (EP[0] sess:0x1"),
+            Err(ParseError::InvalidField { field: "meta", .. })
+        ));
+        let err =
+            try_parse_record("2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1) x").unwrap_err();
+        assert_eq!(err.to_string(), "missing field: user");
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_reordered_meta() {
+        let ok = "2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:) [SEL] select 1";
+        assert!(ParseMode::Strict.parse(ok).is_ok());
+
+        let reordered = "2025-08-12 10:57:09.562 (EP[0] thrd:1 sess:0x1 user:U trxid:0 stmt:0x2 appname:) [SEL] select 1";
+        assert!(ParseMode::Lenient.parse(reordered).is_ok());
+        assert!(matches!(
+            ParseMode::Strict.parse(reordered),
+            Err(ParseError::InvalidField { field: "thrd", .. })
+        ));
+
+        let no_appname = "2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2) [SEL] select 1";
+        assert!(matches!(
+            parse_record_strict(no_appname),
+            Err(ParseError::MissingField("appname"))
+        ));
+    }
+
+    #[test]
+    fn test_parse_ip_after_named_appname() {
+        let rec = "2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:0 stmt:0x2 appname:disql ip:::ffff:10.0.0.1) [SEL] select 1";
+        let r = parse_record(rec);
+        assert_eq!(r.appname, Some("disql"));
+        assert_eq!(r.ip, Some("10.0.0.1"));
+    }
 }
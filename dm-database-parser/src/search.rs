@@ -0,0 +1,101 @@
+//! 多关键字搜索：用 Aho-Corasick 自动机（daachorse）一次扫描记录正文，
+//! 同时匹配几十个表名、过程名，代替逐个运行正则表达式。
+
+use std::{fmt, str::FromStr};
+
+use daachorse::DoubleArrayAhoCorasick;
+
+/// 一组关键字的匹配器，不区分 ASCII 大小写，只匹配完整的标识符
+/// （`t1` 不会匹配 `t10` 或 `my_t1`）
+#[derive(Clone)]
+pub struct KeywordMatcher {
+    ac: DoubleArrayAhoCorasick<u32>,
+    keywords: Vec<String>,
+}
+
+impl KeywordMatcher {
+    /// 由关键字构造；去掉空白与重复（不区分大小写）后没有关键字时返回 None
+    pub fn new<I, S>(keywords: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut list: Vec<String> = Vec::new();
+        for kw in keywords {
+            let kw = kw.as_ref().trim().to_ascii_lowercase();
+            if !kw.is_empty() && !list.contains(&kw) {
+                list.push(kw);
+            }
+        }
+        let ac = DoubleArrayAhoCorasick::new(&list).ok()?;
+        Some(Self { ac, keywords: list })
+    }
+
+    /// 关键字（已转为小写），下标即 [`matches`](Self::matches) 返回的编号
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// `text` 是否包含任一关键字
+    pub fn is_match(&self, text: &str) -> bool {
+        self.matches(text).next().is_some()
+    }
+
+    /// 依次返回 `text` 中出现的关键字编号（同一关键字出现多次时返回多次）
+    pub fn matches<'t>(&'t self, text: &'t str) -> impl Iterator<Item = usize> + 't {
+        let bytes = text.as_bytes();
+        self.ac
+            .find_overlapping_iter_from_iter(bytes.iter().map(u8::to_ascii_lowercase))
+            .filter(move |m| {
+                let before = m.start().checked_sub(1).map(|i| bytes[i]);
+                let after = bytes.get(m.end()).copied();
+                !before.is_some_and(is_ident_byte) && !after.is_some_and(is_ident_byte)
+            })
+            .map(|m| m.value() as usize)
+    }
+}
+
+/// 标识符中可出现的字节；非 ASCII 字节视为标识符的一部分（中文表名等）
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'$' | b'#') || !b.is_ascii()
+}
+
+impl fmt::Debug for KeywordMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("KeywordMatcher")
+            .field(&self.keywords)
+            .finish()
+    }
+}
+
+/// 由逗号分隔的关键字解析，如 `orders,customers,proc_x`
+impl FromStr for KeywordMatcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KeywordMatcher::new(s.split(',')).ok_or_else(|| "至少需要一个关键字".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_whole_identifiers_ignoring_case() {
+        let m: KeywordMatcher = "orders, T1,proc_x,orders".parse().unwrap();
+        assert_eq!(m.keywords(), ["orders", "t1", "proc_x"]);
+
+        assert!(m.is_match("select * from ORDERS o where o.id = 1"));
+        assert!(m.is_match("call PROC_X(1)"));
+        assert!(m.is_match("select * from sch.t1"));
+        assert!(!m.is_match("select * from t10 join my_t1 on 1 = 1"));
+        assert!(!m.is_match("select * from orders_hist"));
+
+        let hits: Vec<usize> = m
+            .matches("insert into t1 select * from orders, t1")
+            .collect();
+        assert_eq!(hits, [1, 0, 1]);
+        assert!(" , ".parse::<KeywordMatcher>().is_err());
+    }
+}
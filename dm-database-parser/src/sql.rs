@@ -0,0 +1,261 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// 语句类别，按 SQL 文本的首个关键字粗略划分。
+///
+/// 该划分不依赖完整的 SQL 解析器，只识别语句开头的关键字，
+/// 对于审计、过滤等场景足够使用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatementKind {
+    /// SELECT / WITH 查询
+    Query,
+    /// INSERT / UPDATE / DELETE / MERGE
+    Dml,
+    /// CREATE / ALTER / DROP / TRUNCATE
+    Ddl,
+    /// GRANT / REVOKE
+    Dcl,
+    /// COMMIT / ROLLBACK / SAVEPOINT 等事务控制
+    Transaction,
+    /// 无法识别的语句或非 SQL 消息
+    Other,
+}
+
+impl StatementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementKind::Query => "QUERY",
+            StatementKind::Dml => "DML",
+            StatementKind::Ddl => "DDL",
+            StatementKind::Dcl => "DCL",
+            StatementKind::Transaction => "TRANSACTION",
+            StatementKind::Other => "OTHER",
+        }
+    }
+}
+
+/// 记录类别，按 body 的语句标记与开头的关键字划分 DM 输出的各类消息，
+/// 用于在过滤与统计中排除非语句记录。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordCategory {
+    /// 语句执行（含只有执行指标的记录）
+    #[default]
+    Statement,
+    /// 事务控制，如 `TRX: START`、COMMIT、ROLLBACK
+    Transaction,
+    /// 登录与登出
+    Login,
+    /// 错误消息
+    Error,
+    /// 检查点、PURGE、REDO 等系统消息
+    System,
+    /// 无法识别的消息
+    Other,
+}
+
+impl RecordCategory {
+    pub const ALL: [RecordCategory; 6] = [
+        RecordCategory::Statement,
+        RecordCategory::Transaction,
+        RecordCategory::Login,
+        RecordCategory::Error,
+        RecordCategory::System,
+        RecordCategory::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordCategory::Statement => "statement",
+            RecordCategory::Transaction => "transaction",
+            RecordCategory::Login => "login",
+            RecordCategory::Error => "error",
+            RecordCategory::System => "system",
+            RecordCategory::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for RecordCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RecordCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RecordCategory::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = RecordCategory::ALL.iter().map(|c| c.as_str()).collect();
+                format!("未知的记录类别: {s}，可选: {}", names.join(", "))
+            })
+    }
+}
+
+/// 对记录 body 分类：先看语句标记，没有标记时看开头的关键字。
+pub fn categorize(body: &str) -> RecordCategory {
+    let (tag, rest) = split_tag(body);
+    let kw = tag.unwrap_or_else(|| first_keyword(rest));
+    let is = |k: &str| kw.eq_ignore_ascii_case(k);
+    if is("LOGIN") || is("LOGOUT") {
+        RecordCategory::Login
+    } else if is("ERR") || is("ERROR") {
+        RecordCategory::Error
+    } else if is("CHECKPOINT") || is("CKPT") || is("PURGE") || is("REDO") {
+        RecordCategory::System
+    } else if is("TRX") || classify(sql_text(body)) == StatementKind::Transaction {
+        RecordCategory::Transaction
+    } else if tag.is_some() || is("EXECTIME") || classify(rest) != StatementKind::Other {
+        RecordCategory::Statement
+    } else {
+        RecordCategory::Other
+    }
+}
+
+/// 拆分 body 开头的 DM 语句标记（如 `[SEL]`、`[INS]`、`[DDL]`）。
+///
+/// 返回 (标记, 剩余文本)；标记不包含方括号，剩余文本已去除前导空白。
+/// 若 body 不以 `[XXX]` 开头，则标记为 None，剩余文本为原 body。
+pub fn split_tag(body: &str) -> (Option<&str>, &str) {
+    let trimmed = body.trim_start();
+    if let Some(rest) = trimmed.strip_prefix('[')
+        && let Some(close) = rest.find(']')
+    {
+        let tag = &rest[..close];
+        // 标记只由大写字母组成，避免把 SQL 中的数组下标等误判为标记
+        if !tag.is_empty() && tag.bytes().all(|b| b.is_ascii_uppercase()) {
+            return (Some(tag), rest[close + 1..].trim_start());
+        }
+    }
+    (None, trimmed)
+}
+
+/// 去掉开头的语句标记与末尾的执行指标，返回 SQL 文本与可能存在的参数块。
+fn strip_tag_and_metrics(body: &str) -> &str {
+    let (_, rest) = split_tag(body);
+    let end = rest.rfind("EXECTIME:").unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// 提取 body 中的 SQL 文本：去掉开头的语句标记、绑定参数块 `PARAMS(...)={...}`
+/// 以及末尾的 `EXECTIME: ... ROWCOUNT: ... EXEC_ID: ...` 执行指标。
+pub fn sql_text(body: &str) -> &str {
+    let rest = strip_tag_and_metrics(body);
+    let end = rest.rfind("PARAMS(").unwrap_or(rest.len());
+    rest[..end].trim_end()
+}
+
+/// 提取 body 中预编译语句的绑定参数块 `PARAMS(...)={...}`，不存在时返回 None。
+pub fn params_text(body: &str) -> Option<&str> {
+    let rest = strip_tag_and_metrics(body);
+    rest.rfind("PARAMS(").map(|p| rest[p..].trim_end())
+}
+
+/// 返回 SQL 文本的首个关键字（跳过前导空白、注释与左括号）。
+fn first_keyword(sql: &str) -> &str {
+    let mut s = sql;
+    loop {
+        s = s.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+        if let Some(rest) = s.strip_prefix("--") {
+            s = rest.find('\n').map_or("", |p| &rest[p + 1..]);
+        } else if let Some(rest) = s.strip_prefix("/*") {
+            s = rest.find("*/").map_or("", |p| &rest[p + 2..]);
+        } else {
+            break;
+        }
+    }
+    let end = s
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    &s[..end]
+}
+
+/// 根据首个关键字对 SQL 文本分类（大小写不敏感）。
+///
+/// 传入的应当是 `sql_text` 返回的纯 SQL 文本。
+pub fn classify(sql: &str) -> StatementKind {
+    let kw = first_keyword(sql);
+    let is = |k: &str| kw.eq_ignore_ascii_case(k);
+    if is("SELECT") || is("WITH") {
+        StatementKind::Query
+    } else if is("INSERT") || is("UPDATE") || is("DELETE") || is("MERGE") {
+        StatementKind::Dml
+    } else if is("CREATE") || is("ALTER") || is("DROP") || is("TRUNCATE") {
+        StatementKind::Ddl
+    } else if is("GRANT") || is("REVOKE") {
+        StatementKind::Dcl
+    } else if is("COMMIT") || is("ROLLBACK") || is("SAVEPOINT") {
+        StatementKind::Transaction
+    } else {
+        StatementKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_tag() {
+        assert_eq!(
+            split_tag("[SEL] select 1 from dual"),
+            (Some("SEL"), "select 1 from dual")
+        );
+        assert_eq!(split_tag("TRX: START"), (None, "TRX: START"));
+        assert_eq!(split_tag("[a] foo"), (None, "[a] foo"));
+    }
+
+    #[test]
+    fn test_sql_text_strips_metrics() {
+        let body = "[DDL] create table t(id int); EXECTIME: 3(ms) ROWCOUNT: 0(rows) EXEC_ID: 42.";
+        assert_eq!(sql_text(body), "create table t(id int);");
+    }
+
+    #[test]
+    fn test_params_text() {
+        let body = "[INS] insert into t values(?, ?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1), (1, VARCHAR, 'a')} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.";
+        assert_eq!(sql_text(body), "insert into t values(?, ?)");
+        assert_eq!(
+            params_text(body),
+            Some("PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1), (1, VARCHAR, 'a')}")
+        );
+        assert_eq!(params_text("[SEL] select 1"), None);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("select 1"), StatementKind::Query);
+        assert_eq!(
+            classify("  /* hint */ INSERT into t values(1)"),
+            StatementKind::Dml
+        );
+        assert_eq!(classify("-- c\nDrop TABLE t"), StatementKind::Ddl);
+        assert_eq!(classify("grant select on t to u"), StatementKind::Dcl);
+        assert_eq!(classify("REVOKE dba FROM u"), StatementKind::Dcl);
+        assert_eq!(classify("commit"), StatementKind::Transaction);
+        assert_eq!(classify("TRX: START"), StatementKind::Other);
+    }
+
+    #[test]
+    fn test_categorize() {
+        assert_eq!(categorize("[SEL] select 1"), RecordCategory::Statement);
+        assert_eq!(
+            categorize("EXECTIME: 0ms ROWCOUNT: 1 EXEC_ID: 2"),
+            RecordCategory::Statement
+        );
+        assert_eq!(categorize("TRX: START"), RecordCategory::Transaction);
+        assert_eq!(categorize("[ORA] commit"), RecordCategory::Transaction);
+        assert_eq!(categorize("login success"), RecordCategory::Login);
+        assert_eq!(categorize("[ERR] -2106: 无效的表"), RecordCategory::Error);
+        assert_eq!(categorize("checkpoint begin"), RecordCategory::System);
+        assert_eq!(categorize("redo log switch"), RecordCategory::System);
+        assert_eq!(categorize("hello"), RecordCategory::Other);
+        assert_eq!("Login".parse(), Ok(RecordCategory::Login));
+        assert!("nope".parse::<RecordCategory>().is_err());
+    }
+}
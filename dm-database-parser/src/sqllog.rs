@@ -1,4 +1,13 @@
-#[derive(Debug, PartialEq)]
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::instance::InstanceInfo;
+use crate::parser::ParsedRecord;
+use crate::sql::{self, RecordCategory};
+
+/// 一条 sqllog 记录的完整（拥有所有权的）表示，适合序列化或跨线程传递。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Sqllog {
     pub sqllog_datetime: String,
     pub ep: u8,
@@ -8,11 +17,34 @@ pub struct Sqllog {
     pub statement: String,
     pub appname: String,
     pub client_ip: String,
+    /// 语句标记，如 `SEL`、`INS`、`ORA`；body 不以 `[XXX]` 开头时为空
     pub sql_type: String,
+    /// 去掉语句标记、绑定参数与执行指标后的语句文本
     pub description: String,
     pub execute_time: f32,
-    pub row_count: u32,
+    pub row_count: u64,
     pub execute_id: i64,
+    /// 记录所属实例（由 sqllog 文件名解析得到）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<InstanceInfo>,
+    /// 稳定的记录标识（文件标识与记录在文件中的偏移的哈希），重复导入时用于去重；未知时为空
+    #[serde(default)]
+    pub record_id: String,
+    /// 记录类别，见 [`sql::categorize`]
+    #[serde(default)]
+    pub category: RecordCategory,
+    /// 命中的配置规则的标签，逗号分隔；没有命中时为空
+    #[serde(default)]
+    pub tags: String,
+    /// `description` 是否因超出长度上限被截断（截断后以 `...` 结尾）
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl Default for Sqllog {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Sqllog {
@@ -31,6 +63,105 @@ impl Sqllog {
             execute_time: 0.0,
             row_count: 0,
             execute_id: 0,
+            instance: None,
+            record_id: String::new(),
+            category: RecordCategory::Statement,
+            tags: String::new(),
+            truncated: false,
+        }
+    }
+
+    /// 从解析结果构造记录；无法解析为数字的字段取 0，`instance` 为 None，`record_id` 与 `tags` 为空
+    pub fn from_record(rec: &ParsedRecord<'_>) -> Self {
+        let num = |v: Option<&str>| v.and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+        let (tag, _) = sql::split_tag(&rec.body);
+        Self {
+            sqllog_datetime: rec.ts.to_string(),
+            ep: rec
+                .ep
+                .and_then(|s| s.strip_prefix("EP[")?.strip_suffix(']')?.parse().ok())
+                .unwrap_or(0),
+            thread_id: num(rec.thrd),
+            username: rec.user.unwrap_or_default().to_string(),
+            trxid: num(rec.trxid),
+            statement: rec.stmt.unwrap_or_default().to_string(),
+            appname: rec.appname.unwrap_or_default().to_string(),
+            client_ip: rec.ip.unwrap_or_default().to_string(),
+            sql_type: tag.unwrap_or_default().to_string(),
+            description: sql::sql_text(&rec.body).to_string(),
+            execute_time: rec.execute_time_ms.unwrap_or(0) as f32,
+            row_count: rec.row_count.unwrap_or(0),
+            execute_id: rec.execute_id.unwrap_or(0) as i64,
+            instance: None,
+            record_id: String::new(),
+            category: sql::categorize(&rec.body),
+            tags: String::new(),
+            truncated: false,
+        }
+    }
+}
+
+/// 以接近 sqllog 原始格式的单行文本输出
+impl fmt::Display for Sqllog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (EP[{}] thrd:{} user:{} trxid:{} stmt:{} appname:{}",
+            self.sqllog_datetime,
+            self.ep,
+            self.thread_id,
+            self.username,
+            self.trxid,
+            self.statement,
+            self.appname
+        )?;
+        if !self.client_ip.is_empty() {
+            write!(f, " ip:::ffff:{}", self.client_ip)?;
         }
+        write!(f, ")")?;
+        if !self.sql_type.is_empty() {
+            write!(f, " [{}]", self.sql_type)?;
+        }
+        write!(
+            f,
+            " {} EXECTIME: {}(ms) ROWCOUNT: {}(rows) EXEC_ID: {}.",
+            self.description, self.execute_time, self.row_count, self.execute_id
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_record;
+
+    const REC: &str = "2025-08-12 10:57:09.562 (EP[1] sess:0x1 thrd:757794 user:SYSDBA trxid:688489653 stmt:0x7fb236077b70 appname:disql ip:::ffff:10.3.100.68) [SEL] select * from t EXECTIME: 12(ms) ROWCOUNT: 3(rows) EXEC_ID: 289655185.";
+
+    #[test]
+    fn from_record_fills_all_fields() {
+        let log = Sqllog::from_record(&parse_record(REC));
+        assert_eq!(log.sqllog_datetime, "2025-08-12 10:57:09.562");
+        assert_eq!(log.ep, 1);
+        assert_eq!(log.thread_id, 757794);
+        assert_eq!(log.username, "SYSDBA");
+        assert_eq!(log.trxid, 688489653);
+        assert_eq!(log.client_ip, "10.3.100.68");
+        assert_eq!(log.sql_type, "SEL");
+        assert_eq!(log.description, "select * from t");
+        assert_eq!(log.execute_time, 12.0);
+        assert_eq!(log.row_count, 3);
+        assert_eq!(log.execute_id, 289655185);
+        assert_eq!(log.category, RecordCategory::Statement);
+    }
+
+    #[test]
+    fn display_round_trips_through_parser() {
+        let log = Sqllog::from_record(&parse_record(REC));
+        let text = log.to_string();
+        let again = Sqllog::from_record(&parse_record(&text));
+        assert_eq!(again.description, log.description);
+        assert_eq!(again.sql_type, "SEL");
+        assert_eq!(again.client_ip, "10.3.100.68");
+        assert_eq!(again.row_count, 3);
     }
 }
@@ -7,6 +7,19 @@ static PATTERNS: &[&str] = &[
     "EP[", "sess:", "thrd:", "user:", "trxid:", "stmt:", "appname:",
 ];
 
+/// UTF-8 字节序标记，Windows 上复制出的日志文件可能以此开头
+pub(crate) const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 与 PATTERNS 一一对应的字段名，用于错误报告
+static FIELD_NAMES: &[&str] = &["ep", "sess", "thrd", "user", "trxid", "stmt", "appname"];
+
+/// 元信息关键短语校验失败的原因，携带出错的字段名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MetaOrderError {
+    Missing(&'static str),
+    OutOfOrder(&'static str),
+}
+
 #[allow(dead_code)]
 static AC: Lazy<DoubleArrayAhoCorasick<usize>> = Lazy::new(|| {
     // 从字节模式构建自动机
@@ -68,6 +81,86 @@ pub fn is_ts_millis_bytes(bytes: &[u8]) -> bool {
     true
 }
 
+/// 将 `YYYY-MM-DD HH:MM:SS.mmm` 时间戳转换为自 1970-01-01 起的毫秒数。
+///
+/// 只做整数运算（不依赖日期库），按 UTC 计算、不做时区换算；格式不合法时返回 None。
+/// 月、日、时分秒的取值范围不做校验。
+pub fn ts_to_epoch_millis(ts: &str) -> Option<i64> {
+    if !is_ts_millis(ts) {
+        return None;
+    }
+    let b = ts.as_bytes();
+    let num = |r: std::ops::Range<usize>| {
+        b[r].iter()
+            .fold(0i64, |acc, &d| acc * 10 + (d - b'0') as i64)
+    };
+    let (y, m, d) = (num(0..4), num(5..7), num(8..10));
+    // 公历日期转换为自 1970-01-01 起的天数
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let secs = days * 86400 + num(11..13) * 3600 + num(14..16) * 60 + num(17..19);
+    Some(secs * 1000 + num(20..23))
+}
+
+/// [`ts_to_epoch_millis`] 的逆运算：把自 1970-01-01 起的毫秒数格式化为 `YYYY-MM-DD HH:MM:SS.mmm`
+pub fn epoch_millis_to_ts(ms: i64) -> String {
+    let (days, rem) = (ms.div_euclid(86_400_000), ms.rem_euclid(86_400_000));
+    let (y, m, d) = civil_from_days(days);
+    format!(
+        "{y:04}-{m:02}-{d:02} {:02}:{:02}:{:02}.{:03}",
+        rem / 3_600_000,
+        rem % 3_600_000 / 60_000,
+        rem % 60_000 / 1000,
+        rem % 1000
+    )
+}
+
+/// 自 1970-01-01 起的天数转换为公历日期 `(年, 月, 日)`，只做整数运算，负数表示 1970 年之前
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+/// 从 `from` 开始查找下一个记录起始位置：位于缓冲区开头或紧随 `\n` 之后，
+/// 且其后 23 字节为 `YYYY-MM-DD HH:MM:SS.mmm` 时间戳。
+///
+/// 与 `RecordSplitter` 使用完全相同的边界判定（只看时间戳，不校验元信息），
+/// 便于自定义读取器按记录边界切分缓冲区。缓冲区以 UTF-8 BOM 开头时，紧随 BOM
+/// 的时间戳同样视为记录起始。找不到时返回 None。
+pub fn find_next_record_start(bytes: &[u8], from: usize) -> Option<usize> {
+    let limit = bytes.len().checked_sub(23)?;
+    let bom = UTF8_BOM.len();
+    if from <= bom
+        && bytes.starts_with(UTF8_BOM)
+        && bytes.len() >= bom + 23
+        && is_ts_millis_bytes(&bytes[bom..bom + 23])
+    {
+        return Some(bom);
+    }
+    let mut pos = from;
+    while pos <= limit {
+        if (pos == 0 || bytes[pos - 1] == b'\n') && is_ts_millis_bytes(&bytes[pos..pos + 23]) {
+            return Some(pos);
+        }
+        // 直接跳到下一个换行符之后
+        pos += bytes[pos..limit].iter().position(|&b| b == b'\n')? + 1;
+    }
+    None
+}
+
 /// 判断一行是否为 sqllog 的“记录起始行”。
 ///
 /// 判定规则（严格匹配当前实现）：
@@ -118,40 +211,45 @@ pub fn is_record_start(line: &str) -> bool {
     // 元信息字符串（不包含括号）
     let meta = &rest[open + 1..close];
 
-    // 4) 使用 Double-Array Aho-Corasick (daachorse) 在 meta 中一次扫描所有模式，记录每个模式的首次出现位置
-    //    patterns 的定义顺序就是我们要求的出现顺序（见静态 PATTERNS 定义）
+    // 4) 关键短语必须全部出现且顺序正确
+    check_meta_order(meta).is_ok()
+}
+
+/// 校验元信息中 7 个关键短语是否全部出现，且首次出现的顺序为
+/// EP[ -> sess: -> thrd: -> user: -> trxid: -> stmt: -> appname:
+pub(crate) fn check_meta_order(meta: &str) -> Result<(), MetaOrderError> {
+    // 使用 Double-Array Aho-Corasick (daachorse) 在 meta 中一次扫描所有模式，记录每个模式的首次出现位置
+    // patterns 的定义顺序就是我们要求的出现顺序（见静态 PATTERNS 定义）
     let mut first_pos: [Option<usize>; 7] = [None, None, None, None, None, None, None];
     for m in AC.find_iter(meta.as_bytes()) {
         // daachorse 返回匹配的字节范围（start,end）以及关联的模式 id
         let start = m.start();
         // value() 返回模式对应的 id（在构造时按 PATTERNS 的顺序分配）
         let id = m.value();
-        if id < first_pos.len() {
-            if first_pos[id].is_none() {
-                first_pos[id] = Some(start);
-            }
+        if id < first_pos.len() && first_pos[id].is_none() {
+            first_pos[id] = Some(start);
         }
     }
 
     // 要求全部 7 个模式均出现
-    if first_pos.iter().any(|p| p.is_none()) {
-        return false;
+    if let Some(i) = first_pos.iter().position(|p| p.is_none()) {
+        return Err(MetaOrderError::Missing(FIELD_NAMES[i]));
     }
 
     // 验证首次出现位置严格递增，保证顺序为 EP -> sess -> thrd -> user -> trxid -> stmt -> appname
     let mut prev: Option<usize> = None;
-    for p in &first_pos {
+    for (i, p) in first_pos.iter().enumerate() {
         let cur = p.unwrap();
         if let Some(prev_pos) = prev {
             // 若当前位置小于等于前一个位置，说明顺序错误或重叠
             if cur <= prev_pos {
-                return false;
+                return Err(MetaOrderError::OutOfOrder(FIELD_NAMES[i]));
             }
         }
         prev = Some(cur);
     }
 
-    true
+    Ok(())
 }
 
 /// 预热内部自动机和相关静态结构，以便第一次计时调用不包含延迟初始化分配。
@@ -180,6 +278,49 @@ mod tests {
         assert!(!is_ts_millis(invalid_ts_4));
     }
 
+    #[test]
+    fn test_ts_to_epoch_millis() {
+        assert_eq!(ts_to_epoch_millis("1970-01-01 00:00:00.000"), Some(0));
+        assert_eq!(
+            ts_to_epoch_millis("2025-08-12 10:57:09.561"),
+            Some(1754996229561)
+        );
+        assert_eq!(
+            ts_to_epoch_millis("2024-02-29 23:59:59.999"),
+            Some(1709251199999)
+        );
+        assert_eq!(ts_to_epoch_millis("1969-12-31 23:59:59.000"), Some(-1000));
+        assert_eq!(ts_to_epoch_millis("2025-08-12T10:57:09.561"), None);
+        for ts in [
+            "1970-01-01 00:00:00.000",
+            "2024-02-29 23:59:59.999",
+            "1969-12-31 23:59:59.000",
+            "2025-08-12 10:57:09.561",
+        ] {
+            assert_eq!(epoch_millis_to_ts(ts_to_epoch_millis(ts).unwrap()), ts);
+        }
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_find_next_record_start() {
+        let text = b"junk\n2025-08-12 10:57:09.561 a\nx 2025-08-12 10:57:09.562\n2025-08-12 10:57:09.563 b";
+        assert_eq!(find_next_record_start(text, 0), Some(5));
+        assert_eq!(find_next_record_start(text, 6), Some(57));
+        assert_eq!(find_next_record_start(text, 58), None);
+        assert_eq!(
+            find_next_record_start(b"2025-08-12 10:57:09.561", 0),
+            Some(0)
+        );
+        assert_eq!(find_next_record_start(b"short", 0), None);
+        assert_eq!(
+            find_next_record_start(b"\xEF\xBB\xBF2025-08-12 10:57:09.561 a", 0),
+            Some(3)
+        );
+    }
+
     #[test]
     fn test_is_record_start_basic() {
         let line = "2025-08-12 10:57:09.561 (EP[0] sess:abc thrd:1 user:joe trxid:123 stmt:0x1 appname:my)";
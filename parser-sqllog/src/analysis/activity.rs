@@ -0,0 +1,219 @@
+//! 会话与时间序列汇总：每个会话的起止时间、执行次数与耗时，以及按固定时间桶统计的执行量与耗时，
+//! 与指纹统计一起构成面向管理人员的概览报告
+
+use std::collections::{BTreeMap, HashMap};
+
+use dm_database_parser::{RecordMetrics, epoch_millis_to_ts, ts_to_epoch_millis};
+use serde::Serialize;
+
+/// 从记录中提取的样本
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivitySample {
+    pub instance: String,
+    pub sess: String,
+    pub user: String,
+    pub appname: String,
+    pub ip: String,
+    pub ts: String,
+    pub ts_ms: i64,
+    pub exec_ms: Option<u64>,
+    pub rows: Option<u64>,
+}
+
+impl ActivitySample {
+    /// 时间戳无法识别的记录返回 None
+    pub fn from_metrics(m: &RecordMetrics, instance: &str) -> Option<Self> {
+        let owned = |v: &Option<String>| v.clone().unwrap_or_default();
+        Some(Self {
+            instance: instance.to_string(),
+            sess: owned(&m.sess),
+            user: owned(&m.user),
+            appname: owned(&m.appname),
+            ip: owned(&m.ip),
+            ts_ms: ts_to_epoch_millis(&m.ts)?,
+            ts: m.ts.clone(),
+            exec_ms: m.execute_time_ms,
+            rows: m.row_count,
+        })
+    }
+}
+
+/// 一个会话的汇总
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionRow {
+    pub instance: String,
+    pub sess: String,
+    pub user: String,
+    pub appname: String,
+    pub client_ip: String,
+    pub first_ts: String,
+    pub last_ts: String,
+    /// 最早与最晚记录之间的时长（毫秒）
+    pub duration_ms: i64,
+    pub records: u64,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub total_rows: u64,
+    #[serde(skip)]
+    first_ms: i64,
+    #[serde(skip)]
+    last_ms: i64,
+}
+
+/// 一个时间桶的汇总
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimeSeriesRow {
+    pub bucket_start: String,
+    pub records: u64,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub avg_ms: f64,
+    pub max_ms: u64,
+    pub total_rows: u64,
+}
+
+/// 按会话与时间桶汇总记录
+#[derive(Debug)]
+pub struct ActivityTracker {
+    bucket_ms: i64,
+    sessions: HashMap<(String, String), SessionRow>,
+    buckets: BTreeMap<i64, TimeSeriesRow>,
+}
+
+impl ActivityTracker {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            sessions: HashMap::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, s: ActivitySample) {
+        let bucket = self
+            .buckets
+            .entry(s.ts_ms.div_euclid(self.bucket_ms))
+            .or_default();
+        bucket.records += 1;
+        if let Some(exec_ms) = s.exec_ms {
+            bucket.executions += 1;
+            bucket.total_ms += exec_ms;
+            bucket.max_ms = bucket.max_ms.max(exec_ms);
+            bucket.total_rows += s.rows.unwrap_or(0);
+        }
+
+        if s.sess.is_empty() {
+            return;
+        }
+        let row = self
+            .sessions
+            .entry((s.instance.clone(), s.sess.clone()))
+            .or_insert_with(|| SessionRow {
+                instance: s.instance,
+                sess: s.sess,
+                first_ts: s.ts.clone(),
+                last_ts: s.ts.clone(),
+                first_ms: s.ts_ms,
+                last_ms: s.ts_ms,
+                ..Default::default()
+            });
+        // 登录记录之后的语句记录可能不带客户端信息，取第一个非空值
+        for (field, value) in [
+            (&mut row.user, s.user),
+            (&mut row.appname, s.appname),
+            (&mut row.client_ip, s.ip),
+        ] {
+            if field.is_empty() {
+                *field = value;
+            }
+        }
+        if s.ts_ms < row.first_ms {
+            (row.first_ms, row.first_ts) = (s.ts_ms, s.ts.clone());
+        }
+        if s.ts_ms > row.last_ms {
+            (row.last_ms, row.last_ts) = (s.ts_ms, s.ts);
+        }
+        row.records += 1;
+        if let Some(exec_ms) = s.exec_ms {
+            row.executions += 1;
+            row.total_ms += exec_ms;
+            row.max_ms = row.max_ms.max(exec_ms);
+            row.total_rows += s.rows.unwrap_or(0);
+        }
+    }
+
+    /// 按总耗时从高到低排列的会话，`top` 限制返回的个数
+    pub fn sessions(&self, top: Option<usize>) -> Vec<SessionRow> {
+        let mut rows: Vec<SessionRow> = self
+            .sessions
+            .values()
+            .map(|r| SessionRow {
+                duration_ms: r.last_ms - r.first_ms,
+                ..r.clone()
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.total_ms
+                .cmp(&a.total_ms)
+                .then_with(|| a.first_ts.cmp(&b.first_ts))
+                .then_with(|| a.sess.cmp(&b.sess))
+        });
+        if let Some(top) = top {
+            rows.truncate(top);
+        }
+        rows
+    }
+
+    /// 按时间顺序返回各时间桶；没有记录的时间桶不输出
+    pub fn time_series(&self) -> Vec<TimeSeriesRow> {
+        self.buckets
+            .iter()
+            .map(|(&idx, r)| TimeSeriesRow {
+                bucket_start: epoch_millis_to_ts(idx * self.bucket_ms),
+                avg_ms: if r.executions > 0 {
+                    r.total_ms as f64 / r.executions as f64
+                } else {
+                    0.0
+                },
+                ..r.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    #[test]
+    fn aggregates_sessions_and_buckets() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:0 stmt:NULL appname: ip:::ffff:10.0.0.1) login success
+2025-08-12 10:00:10.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 30(ms) ROWCOUNT: 2(rows) EXEC_ID: 1.
+2025-08-12 10:01:05.000 (EP[0] sess:0x2 thrd:2 user:B trxid:2 stmt:0x3 appname:app) [SEL] select 2 EXECTIME: 50(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:01:30.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 3 EXECTIME: 10(ms) ROWCOUNT: 0(rows) EXEC_ID: 3.
+";
+        let mut t = ActivityTracker::new(60_000);
+        parse_records_with(log, |rec| {
+            let m = RecordMetrics::from_record(&rec);
+            t.add(ActivitySample::from_metrics(&m, "DM1").unwrap())
+        });
+
+        let sessions = t.sessions(None);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].sess, "0x2");
+        let s1 = &sessions[1];
+        assert_eq!((s1.records, s1.executions, s1.total_ms), (3, 2, 40));
+        assert_eq!(s1.appname, "app");
+        assert_eq!(s1.duration_ms, 90_000);
+        assert_eq!(t.sessions(Some(1)).len(), 1);
+
+        let series = t.time_series();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].bucket_start, "2025-08-12 10:00:00.000");
+        assert_eq!((series[0].records, series[0].executions), (2, 1));
+        assert_eq!(series[1].avg_ms, 30.0);
+        assert_eq!(series[1].total_rows, 1);
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+/// 一条语句在某线程上的执行区间 `[start, end)`（毫秒）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecInterval {
+    pub thread: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// 从带 `thrd:` 与 EXECTIME 的记录中提取执行区间
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<ExecInterval> {
+    let thread = rec.thrd?;
+    let exec = rec.execute_time_ms?;
+    let start = ts_to_epoch_millis(rec.ts)?;
+    Some(ExecInterval {
+        thread: thread.to_string(),
+        start,
+        end: start + exec as i64,
+    })
+}
+
+/// 时间序列中的一个时间桶
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConcurrencyBucket {
+    /// 时间桶起点（毫秒时间戳）
+    pub bucket_start_ms: i64,
+    /// 该时间桶内有语句执行的线程数，近似活跃会话数
+    pub active_threads: usize,
+    /// 平均并发度：所有语句在该时间桶内的执行时长之和 / 时间桶长度
+    pub avg_concurrency: f64,
+}
+
+/// 单个线程的繁忙程度
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThreadBusy {
+    pub thread: String,
+    pub statements: u64,
+    /// 语句执行时长总和（毫秒）
+    pub busy_ms: i64,
+    /// 第一条语句开始到最后一条语句结束的时长（毫秒）
+    pub span_ms: i64,
+    pub busy_ratio: f64,
+}
+
+#[derive(Debug, Default)]
+struct ThreadState {
+    statements: u64,
+    busy_ms: i64,
+    first_start: i64,
+    last_end: i64,
+}
+
+/// 根据 `thrd:` 与时间戳、EXECTIME 计算并发时间序列
+///
+/// 每条带 EXECTIME 的记录视为区间 `[ts, ts + EXECTIME)`。
+#[derive(Debug)]
+pub struct ConcurrencyTimeline {
+    bucket_ms: i64,
+    buckets: BTreeMap<i64, (HashSet<String>, i64)>,
+    threads: HashMap<String, ThreadState>,
+}
+
+impl ConcurrencyTimeline {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            buckets: BTreeMap::new(),
+            threads: HashMap::new(),
+        }
+    }
+
+    /// 解析日志文本并累加
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一个执行区间
+    pub fn add_sample(&mut self, s: ExecInterval) {
+        self.add_interval(&s.thread, s.start, s.end);
+    }
+
+    fn add_interval(&mut self, thread: &str, start: i64, end: i64) {
+        let state = self
+            .threads
+            .entry(thread.to_string())
+            .or_insert_with(|| ThreadState {
+                first_start: start,
+                last_end: end,
+                ..Default::default()
+            });
+        state.statements += 1;
+        state.busy_ms += end - start;
+        state.first_start = state.first_start.min(start);
+        state.last_end = state.last_end.max(end);
+
+        // 将区间切分到其覆盖的每个时间桶上
+        let first = start.div_euclid(self.bucket_ms);
+        let last = (end - 1).max(start).div_euclid(self.bucket_ms);
+        for idx in first..=last {
+            let b_start = idx * self.bucket_ms;
+            let overlap = end.min(b_start + self.bucket_ms) - start.max(b_start);
+            let entry = self.buckets.entry(idx).or_default();
+            entry.0.insert(thread.to_string());
+            entry.1 += overlap.max(0);
+        }
+    }
+
+    /// 按时间顺序返回时间序列
+    pub fn timeline(&self) -> Vec<ConcurrencyBucket> {
+        self.buckets
+            .iter()
+            .map(|(idx, (threads, busy))| ConcurrencyBucket {
+                bucket_start_ms: idx * self.bucket_ms,
+                active_threads: threads.len(),
+                avg_concurrency: *busy as f64 / self.bucket_ms as f64,
+            })
+            .collect()
+    }
+
+    /// 返回按繁忙比例降序排列的线程统计
+    pub fn thread_busy(&self) -> Vec<ThreadBusy> {
+        let mut rows: Vec<ThreadBusy> = self
+            .threads
+            .iter()
+            .map(|(thread, s)| {
+                let span_ms = s.last_end - s.first_start;
+                ThreadBusy {
+                    thread: thread.clone(),
+                    statements: s.statements,
+                    busy_ms: s.busy_ms,
+                    span_ms,
+                    busy_ratio: if span_ms > 0 {
+                        s.busy_ms as f64 / span_ms as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.busy_ratio
+                .total_cmp(&a.busy_ratio)
+                .then(a.thread.cmp(&b.thread))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_splits_intervals_across_buckets() {
+        let log = "2025-08-12 10:57:09.500 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 1000(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:57:09.800 (EP[0] sess:0x3 thrd:2 user:B trxid:1 stmt:0x4 appname:app) [SEL] select 2 EXECTIME: 100(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+";
+        let mut t = ConcurrencyTimeline::new(1000);
+        t.add_text(log);
+        let series = t.timeline();
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].active_threads, 2);
+        assert!((series[0].avg_concurrency - 0.6).abs() < 1e-9);
+        assert_eq!(series[1].active_threads, 1);
+        assert!((series[1].avg_concurrency - 0.5).abs() < 1e-9);
+
+        let busy = t.thread_busy();
+        assert_eq!(busy.len(), 2);
+        assert_eq!(busy[0].busy_ratio, 1.0);
+    }
+}
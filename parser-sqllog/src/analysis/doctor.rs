@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashMap};
+
+use dm_database_parser::is_ts_millis;
+use dm_database_parser::parser::{RecordSplitter, parse_record_strict, try_parse_record};
+
+/// 列出的元数据键顺序变体与错误原因的最大数量
+const TOP_N: usize = 5;
+
+/// 对输入文件样本的诊断结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnosis {
+    pub sample_bytes: usize,
+    pub file_bytes: u64,
+    /// 文件以 UTF-8 BOM 开头
+    pub bom: bool,
+    /// 样本是合法的 UTF-8
+    pub utf8: bool,
+    pub crlf_lines: usize,
+    pub lf_lines: usize,
+    /// 以 `YYYY-MM-DD HH:MM:SS.mmm` 开头的行数
+    pub ts_millis_lines: usize,
+    /// 以不带毫秒的 `YYYY-MM-DD HH:MM:SS` 开头的行数（不受支持）
+    pub ts_seconds_lines: usize,
+    pub records: usize,
+    /// 能被严格模式 / 宽松校验（[`try_parse_record`]）解析的记录数
+    pub strict_ok: usize,
+    pub lenient_ok: usize,
+    /// 元数据键顺序变体（如 `EP sess thrd user trxid stmt appname ip`）及出现次数
+    pub meta_variants: BTreeMap<String, usize>,
+    /// 严格模式失败的原因及次数
+    pub strict_errors: BTreeMap<String, usize>,
+}
+
+impl Diagnosis {
+    /// 按样本中的记录密度估算整个文件的记录数
+    pub fn estimated_records(&self) -> u64 {
+        if self.sample_bytes == 0 {
+            return 0;
+        }
+        (self.records as f64 * self.file_bytes as f64 / self.sample_bytes as f64).round() as u64
+    }
+
+    /// 编码描述
+    pub fn encoding(&self) -> &'static str {
+        match (self.utf8, self.bom) {
+            (true, true) => "UTF-8（带 BOM）",
+            (true, false) => "UTF-8",
+            (false, _) => {
+                "非 UTF-8（可能为 GBK/GB18030，可设置 sqllog.input.encoding = \"gb18030\"）"
+            }
+        }
+    }
+
+    pub fn line_ending(&self) -> &'static str {
+        match (self.crlf_lines, self.lf_lines) {
+            (0, 0) => "无换行",
+            (0, _) => "LF",
+            (_, 0) => "CRLF",
+            _ => "LF/CRLF 混合",
+        }
+    }
+
+    pub fn timestamp_format(&self) -> &'static str {
+        match (self.ts_millis_lines, self.ts_seconds_lines) {
+            (0, 0) => "未识别",
+            (_, 0) => "YYYY-MM-DD HH:MM:SS.mmm",
+            (0, _) => "YYYY-MM-DD HH:MM:SS（缺少毫秒，不受支持）",
+            _ => "混合（部分行缺少毫秒）",
+        }
+    }
+
+    /// 出现次数最多的元数据键顺序变体
+    pub fn top_meta_variants(&self) -> Vec<(&str, usize)> {
+        top(&self.meta_variants)
+    }
+
+    pub fn top_strict_errors(&self) -> Vec<(&str, usize)> {
+        top(&self.strict_errors)
+    }
+
+    /// 建议的解析模式
+    pub fn recommendation(&self) -> &'static str {
+        if self.records == 0 {
+            "样本中没有识别到记录，请确认文件为 DM sqllog 且时间戳带毫秒"
+        } else if self.strict_ok == self.records {
+            "严格模式与宽松模式均可解析（推荐 strict 以排除格式相似的非 DM 日志）"
+        } else if self.lenient_ok == self.records {
+            "需要宽松模式（lenient）：部分记录的元数据键缺失或顺序不标准"
+        } else {
+            "部分记录缺少必需字段，宽松模式下会作为错误记录处理，建议 on_error = \"collect\" 后检查"
+        }
+    }
+}
+
+fn top(map: &BTreeMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut v: Vec<(&str, usize)> = map.iter().map(|(k, &n)| (k.as_str(), n)).collect();
+    v.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    v.truncate(TOP_N);
+    v
+}
+
+/// 诊断文件开头的样本字节，`file_bytes` 为文件总大小
+pub fn diagnose(sample: &[u8], file_bytes: u64) -> Diagnosis {
+    let mut d = Diagnosis {
+        sample_bytes: sample.len(),
+        file_bytes,
+        bom: sample.starts_with(b"\xEF\xBB\xBF"),
+        // 样本可能在多字节字符中间截断，末尾的不完整字符不算非法
+        utf8: match std::str::from_utf8(sample) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        },
+        ..Default::default()
+    };
+    let text = String::from_utf8_lossy(sample);
+
+    for line in text.split_inclusive('\n') {
+        if line.ends_with("\r\n") {
+            d.crlf_lines += 1;
+        } else if line.ends_with('\n') {
+            d.lf_lines += 1;
+        }
+        let line = line.trim_start_matches('\u{feff}');
+        if line.get(..23).is_some_and(is_ts_millis) {
+            d.ts_millis_lines += 1;
+        } else if line.get(..19).is_some_and(is_ts_seconds) {
+            d.ts_seconds_lines += 1;
+        }
+    }
+
+    let mut variants: HashMap<String, usize> = HashMap::new();
+    for rec in RecordSplitter::new(&text) {
+        d.records += 1;
+        if let Ok(parsed) = try_parse_record(rec) {
+            d.lenient_ok += 1;
+            *variants.entry(meta_keys(parsed.meta_raw)).or_default() += 1;
+        }
+        match parse_record_strict(rec) {
+            Ok(_) => d.strict_ok += 1,
+            Err(e) => *d.strict_errors.entry(e.to_string()).or_default() += 1,
+        }
+    }
+    d.meta_variants = variants.into_iter().collect();
+    d
+}
+
+/// 元数据中键的出现顺序，如 `EP sess thrd user trxid stmt appname ip`
+fn meta_keys(meta: &str) -> String {
+    meta.split_whitespace()
+        .filter_map(|tok| {
+            if tok.starts_with("EP[") {
+                Some("EP")
+            } else {
+                tok.split_once(':')
+                    .map(|(k, _)| k)
+                    .filter(|k| !k.is_empty())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `YYYY-MM-DD HH:MM:SS`
+fn is_ts_seconds(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 19
+        && b.iter().enumerate().all(|(i, &c)| match i {
+            4 | 7 => c == b'-',
+            10 => c == b' ',
+            13 | 16 => c == b':',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnose_reports_format_and_mode() {
+        let text = "\u{feff}2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app ip:::ffff:1.2.3.4) [SEL] select 1 EXECTIME: 2(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\r\n\
+2025-08-12 10:57:09.562 (EP[0] user:B sess:0x1 thrd:1 trxid:1 stmt:0x2 appname:app) [SEL] select 2\r\n\
+2025-08-12 10:57:10 not a record\r\n";
+        let d = diagnose(text.as_bytes(), text.len() as u64 * 10);
+        assert!(d.bom && d.utf8);
+        assert_eq!(d.line_ending(), "CRLF");
+        assert_eq!(d.timestamp_format(), "混合（部分行缺少毫秒）");
+        assert_eq!((d.records, d.lenient_ok, d.strict_ok), (2, 2, 1));
+        assert_eq!(d.estimated_records(), 20);
+        assert_eq!(
+            d.top_meta_variants()[0].0,
+            "EP sess thrd user trxid stmt appname ip"
+        );
+        assert!(d.recommendation().starts_with("需要宽松模式"));
+    }
+
+    #[test]
+    fn diagnose_detects_non_utf8() {
+        // "达梦" 的 GBK 编码
+        let d = diagnose(b"\xb4\xef\xc3\xce\n", 5);
+        assert!(!d.utf8);
+        assert_eq!(d.records, 0);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::{RecordCategory, sql, ts_to_epoch_millis};
+use serde::Serialize;
+
+use crate::analysis::truncate_body;
+
+/// 系统消息的种类，按消息开头的关键字划分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemEventKind {
+    Checkpoint,
+    Purge,
+    Redo,
+    Other,
+}
+
+impl SystemEventKind {
+    fn of(body: &str) -> Self {
+        let (tag, rest) = sql::split_tag(body);
+        let kw = tag.unwrap_or_else(|| {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            &rest[..end]
+        });
+        let is = |k: &str| kw.eq_ignore_ascii_case(k);
+        if is("CHECKPOINT") || is("CKPT") {
+            SystemEventKind::Checkpoint
+        } else if is("PURGE") {
+            SystemEventKind::Purge
+        } else if is("REDO") {
+            SystemEventKind::Redo
+        } else {
+            SystemEventKind::Other
+        }
+    }
+}
+
+/// 一条系统消息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SystemEventRow {
+    pub ts: String,
+    pub instance: String,
+    pub kind: SystemEventKind,
+    /// 消息带有 EXECTIME 时的耗时
+    pub duration_ms: Option<u64>,
+    pub message: String,
+}
+
+/// 从记录中提取的样本：系统消息，或用于对照的语句执行耗时
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventSample {
+    Event {
+        ts_ms: i64,
+        row: SystemEventRow,
+    },
+    Exec {
+        instance: String,
+        ts_ms: i64,
+        exec_ms: u64,
+    },
+}
+
+/// 系统消息与带 EXECTIME 的语句记录产生样本，其余记录返回 None；
+/// `max_body_len` 限制消息长度，0 表示不截断
+pub fn sample(instance: &str, rec: &ParsedRecord<'_>, max_body_len: usize) -> Option<EventSample> {
+    let ts_ms = ts_to_epoch_millis(rec.ts)?;
+    match sql::categorize(&rec.body) {
+        RecordCategory::System => {
+            let mut message = rec.body.trim().to_string();
+            truncate_body(&mut message, max_body_len);
+            Some(EventSample::Event {
+                ts_ms,
+                row: SystemEventRow {
+                    ts: rec.ts.to_string(),
+                    instance: instance.to_string(),
+                    kind: SystemEventKind::of(&rec.body),
+                    duration_ms: rec.execute_time_ms,
+                    message,
+                },
+            })
+        }
+        RecordCategory::Statement => Some(EventSample::Exec {
+            instance: instance.to_string(),
+            ts_ms,
+            exec_ms: rec.execute_time_ms?,
+        }),
+        _ => None,
+    }
+}
+
+/// 一个实例在一个时间桶内的系统消息数与语句耗时
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EventBucket {
+    pub instance: String,
+    /// 时间桶起点（毫秒时间戳）
+    pub bucket_start_ms: i64,
+    pub checkpoints: u64,
+    pub purges: u64,
+    pub redo: u64,
+    pub other_events: u64,
+    pub executions: u64,
+    pub avg_exec_ms: f64,
+    pub max_exec_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct BucketAcc {
+    events: [u64; 4],
+    executions: u64,
+    total_ms: u64,
+    max_ms: u64,
+}
+
+/// 收集系统消息，并按实例与时间桶统计消息数与同期语句耗时，用于把检查点、PURGE 等
+/// 与延迟尖刺对照
+#[derive(Debug)]
+pub struct EventTimeline {
+    bucket_ms: i64,
+    events: Vec<(i64, SystemEventRow)>,
+    buckets: BTreeMap<(String, i64), BucketAcc>,
+}
+
+impl EventTimeline {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            events: Vec::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// 解析日志文本并累加
+    #[cfg(test)]
+    fn add_text(&mut self, instance: &str, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(instance, &rec, 0) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    pub fn add_sample(&mut self, s: EventSample) {
+        match s {
+            EventSample::Event { ts_ms, row } => {
+                let idx = ts_ms.div_euclid(self.bucket_ms);
+                let acc = self.buckets.entry((row.instance.clone(), idx)).or_default();
+                acc.events[row.kind as usize] += 1;
+                self.events.push((ts_ms, row));
+            }
+            EventSample::Exec {
+                instance,
+                ts_ms,
+                exec_ms,
+            } => {
+                let idx = ts_ms.div_euclid(self.bucket_ms);
+                let acc = self.buckets.entry((instance, idx)).or_default();
+                acc.executions += 1;
+                acc.total_ms += exec_ms;
+                acc.max_ms = acc.max_ms.max(exec_ms);
+            }
+        }
+    }
+
+    /// 按时间顺序返回系统消息
+    pub fn events(&mut self) -> Vec<SystemEventRow> {
+        self.events.sort_by_key(|(ts_ms, _)| *ts_ms);
+        self.events.iter().map(|(_, row)| row.clone()).collect()
+    }
+
+    /// 按实例、时间顺序返回各时间桶的消息数与语句耗时
+    pub fn timeline(&self) -> Vec<EventBucket> {
+        self.buckets
+            .iter()
+            .map(|((instance, idx), acc)| EventBucket {
+                instance: instance.clone(),
+                bucket_start_ms: idx * self.bucket_ms,
+                checkpoints: acc.events[SystemEventKind::Checkpoint as usize],
+                purges: acc.events[SystemEventKind::Purge as usize],
+                redo: acc.events[SystemEventKind::Redo as usize],
+                other_events: acc.events[SystemEventKind::Other as usize],
+                executions: acc.executions,
+                avg_exec_ms: if acc.executions > 0 {
+                    acc.total_ms as f64 / acc.executions as f64
+                } else {
+                    0.0
+                },
+                max_exec_ms: acc.max_ms,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_system_events_and_bucket_latency() {
+        let log = "2025-08-12 10:00:05.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 10(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:01:00.000 (EP[0] sess:NULL thrd:9 user:NULL trxid:NULL stmt:NULL) CHECKPOINT begin
+2025-08-12 10:01:02.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 900(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:01:03.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 100(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:00:30.000 (EP[0] sess:NULL thrd:9 user:NULL trxid:NULL stmt:NULL) PURGE 12 pages
+2025-08-12 10:01:10.000 (EP[0] sess:NULL thrd:9 user:NULL trxid:NULL stmt:NULL) REDO log switch
+2025-08-12 10:01:20.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) login success
+";
+        let mut t = EventTimeline::new(60_000);
+        t.add_text("DM1", log);
+
+        let events = t.events();
+        let kinds: Vec<SystemEventKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SystemEventKind::Purge,
+                SystemEventKind::Checkpoint,
+                SystemEventKind::Redo
+            ]
+        );
+        assert_eq!(events[1].message, "CHECKPOINT begin");
+
+        let timeline = t.timeline();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].purges, 1);
+        assert_eq!(timeline[0].executions, 1);
+        assert_eq!(timeline[1].checkpoints, 1);
+        assert_eq!(timeline[1].redo, 1);
+        assert_eq!(timeline[1].executions, 2);
+        assert_eq!(timeline[1].avg_exec_ms, 500.0);
+        assert_eq!(timeline[1].max_exec_ms, 900);
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+
+use dm_database_parser::exec_index::has_statement;
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql;
+use serde::Serialize;
+
+/// 按 EXEC_ID 找到的一次执行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecHit {
+    pub exec_id: u64,
+    pub path: String,
+    /// 记录在文件中的字节偏移
+    pub offset: u64,
+    pub ts: String,
+    pub user: String,
+    pub sess: String,
+    pub stmt: String,
+    pub exec_time_ms: Option<u64>,
+    pub row_count: Option<u64>,
+    /// 语句文本；执行指标单独成行时取自同一会话、同一语句句柄的前一条语句记录
+    pub sql: String,
+}
+
+/// 顺序扫描记录，查找指定 EXEC_ID 的执行。
+///
+/// 只为不带 EXEC_ID 的语句记录保留每个 (会话, 语句句柄) 的最近一条语句文本，
+/// 用来补全单独记录的执行指标；换文件时清空。
+#[derive(Debug, Default)]
+pub struct ExecLookup {
+    ids: HashSet<u64>,
+    found: HashSet<u64>,
+    path: String,
+    pending: HashMap<(String, String), String>,
+}
+
+impl ExecLookup {
+    pub fn new(ids: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            ids: ids.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// 是否已找到全部 EXEC_ID
+    pub fn is_done(&self) -> bool {
+        self.found.len() == self.ids.len()
+    }
+
+    /// 处理属于文件 `path` 的一条记录，是要查找的执行时返回结果
+    pub fn add(&mut self, path: &str, rec: &ParsedRecord<'_>) -> Option<ExecHit> {
+        if self.path != path {
+            self.path = path.to_string();
+            self.pending.clear();
+        }
+        let key = || {
+            (
+                rec.sess.unwrap_or_default().to_string(),
+                rec.stmt.unwrap_or_default().to_string(),
+            )
+        };
+        let Some(exec_id) = rec.execute_id else {
+            if has_statement(rec) {
+                self.pending
+                    .insert(key(), sql::sql_text(&rec.body).to_string());
+            }
+            return None;
+        };
+        let pending = self.pending.remove(&key());
+        if !self.ids.contains(&exec_id) {
+            return None;
+        }
+        self.found.insert(exec_id);
+        let sql = match sql::sql_text(&rec.body) {
+            "" => pending.unwrap_or_default(),
+            text => text.to_string(),
+        };
+        Some(ExecHit {
+            exec_id,
+            path: path.to_string(),
+            offset: rec.offset,
+            ts: rec.ts.to_string(),
+            user: rec.user.unwrap_or_default().to_string(),
+            sess: rec.sess.unwrap_or_default().to_string(),
+            stmt: rec.stmt.unwrap_or_default().to_string(),
+            exec_time_ms: rec.execute_time_ms,
+            row_count: rec.row_count,
+            sql,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    const LOG: &str = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 30.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x3 appname:app) [UPD] update t set a = 1
+2025-08-12 10:57:09.564 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x3 appname:app) EXECTIME: 5(ms) ROWCOUNT: 3(rows) EXEC_ID: 10.
+";
+
+    #[test]
+    fn finds_executions_and_fills_separate_statement_text() {
+        let mut lookup = ExecLookup::new([10, 30]);
+        let mut hits = Vec::new();
+        parse_records_with(LOG, |rec| hits.extend(lookup.add("a.log", &rec)));
+        assert!(lookup.is_done());
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].exec_id, 30);
+        assert_eq!(hits[0].sql, "select 1");
+        assert_eq!(hits[1].exec_id, 10);
+        assert_eq!(hits[1].sql, "update t set a = 1");
+        assert_eq!(hits[1].row_count, Some(3));
+    }
+}
@@ -0,0 +1,259 @@
+//! 交互式浏览的数据模型：保留每次执行的精简样本，按用户/时间过滤后重新汇总指纹
+
+use std::collections::HashMap;
+
+use dm_database_parser::fingerprint::{FingerprintOptions, fingerprint_with};
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::{sql, ts_to_epoch_millis};
+
+/// 每个指纹最多保留的示例语句数
+pub const MAX_EXAMPLES: usize = 20;
+
+/// 从记录中提取的浏览样本
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExploreSample {
+    pub ts: String,
+    pub user: String,
+    pub fingerprint: String,
+    pub exec_ms: u64,
+    pub sql: String,
+}
+
+/// 提取带 EXECTIME 的记录作为浏览样本，指纹按 `fingerprint` 规则计算
+pub fn sample(rec: &ParsedRecord<'_>, fingerprint: &FingerprintOptions) -> Option<ExploreSample> {
+    let exec_ms = rec.execute_time_ms?;
+    let text = sql::sql_text(&rec.body);
+    Some(ExploreSample {
+        ts: rec.ts.to_string(),
+        user: rec.user.unwrap_or_default().to_string(),
+        fingerprint: fingerprint_with(text, fingerprint).text,
+        exec_ms,
+        sql: text.to_string(),
+    })
+}
+
+/// 示例语句
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub ts: String,
+    pub user: String,
+    pub exec_ms: u64,
+    pub sql: String,
+}
+
+/// 过滤条件：用户名精确匹配，时间为闭区间（毫秒时间戳）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub user: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl Filter {
+    /// 解析 `起..止` 形式的时间范围，两端均可省略；时间可省略秒或毫秒部分，
+    /// 如 `2025-08-12 10:00..2025-08-12 10:30:00`。格式错误时返回 None
+    pub fn parse_time_range(s: &str) -> Option<(Option<i64>, Option<i64>)> {
+        let (from, to) = s.split_once("..")?;
+        let bound = |t: &str| -> Option<Option<i64>> {
+            let t = t.trim();
+            if t.is_empty() {
+                return Some(None);
+            }
+            let full = match t.len() {
+                16 => format!("{t}:00.000"),
+                19 => format!("{t}.000"),
+                _ => t.to_string(),
+            };
+            ts_to_epoch_millis(&full).map(Some)
+        };
+        Some((bound(from)?, bound(to)?))
+    }
+
+    fn matches(&self, user: &str, ts_ms: Option<i64>) -> bool {
+        if self.user.as_deref().is_some_and(|u| u != user) {
+            return false;
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        let Some(ts) = ts_ms else {
+            return false;
+        };
+        self.from.is_none_or(|f| ts >= f) && self.to.is_none_or(|t| ts <= t)
+    }
+}
+
+/// 过滤后单个指纹的汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct FingerprintSummary {
+    /// 指纹在 [`Explorer`] 中的序号，用于查询示例
+    pub id: usize,
+    pub fingerprint: String,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl FingerprintSummary {
+    pub fn avg_ms(&self) -> f64 {
+        self.total_ms as f64 / self.executions.max(1) as f64
+    }
+}
+
+/// 一次执行，用户和指纹以序号保存以节省内存
+#[derive(Debug, Clone, Copy)]
+struct Execution {
+    ts_ms: Option<i64>,
+    user: u32,
+    fingerprint: u32,
+    exec_ms: u64,
+}
+
+/// 保存所有执行样本，支持按条件重新汇总
+#[derive(Debug, Default)]
+pub struct Explorer {
+    fingerprints: Vec<String>,
+    fingerprint_ids: HashMap<String, u32>,
+    users: Vec<String>,
+    user_ids: HashMap<String, u32>,
+    executions: Vec<Execution>,
+    examples: Vec<Vec<Example>>,
+}
+
+impl Explorer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, s: ExploreSample) {
+        let fingerprint = match self.fingerprint_ids.get(&s.fingerprint) {
+            Some(&id) => id,
+            None => {
+                let id = self.fingerprints.len() as u32;
+                self.fingerprint_ids.insert(s.fingerprint.clone(), id);
+                self.fingerprints.push(s.fingerprint);
+                self.examples.push(Vec::new());
+                id
+            }
+        };
+        let user = match self.user_ids.get(&s.user) {
+            Some(&id) => id,
+            None => {
+                let id = self.users.len() as u32;
+                self.user_ids.insert(s.user.clone(), id);
+                self.users.push(s.user.clone());
+                id
+            }
+        };
+        self.executions.push(Execution {
+            ts_ms: ts_to_epoch_millis(&s.ts),
+            user,
+            fingerprint,
+            exec_ms: s.exec_ms,
+        });
+        let examples = &mut self.examples[fingerprint as usize];
+        if examples.len() < MAX_EXAMPLES {
+            examples.push(Example {
+                ts: s.ts,
+                user: s.user,
+                exec_ms: s.exec_ms,
+                sql: s.sql,
+            });
+        }
+    }
+
+    /// 执行样本总数
+    pub fn len(&self) -> usize {
+        self.executions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.executions.is_empty()
+    }
+
+    /// 按过滤条件汇总各指纹，按总耗时降序排列
+    pub fn summarize(&self, filter: &Filter) -> Vec<FingerprintSummary> {
+        let mut rows: Vec<Option<FingerprintSummary>> = vec![None; self.fingerprints.len()];
+        for e in &self.executions {
+            if !filter.matches(&self.users[e.user as usize], e.ts_ms) {
+                continue;
+            }
+            let id = e.fingerprint as usize;
+            let row = rows[id].get_or_insert_with(|| FingerprintSummary {
+                id,
+                fingerprint: self.fingerprints[id].clone(),
+                executions: 0,
+                total_ms: 0,
+                max_ms: 0,
+            });
+            row.executions += 1;
+            row.total_ms += e.exec_ms;
+            row.max_ms = row.max_ms.max(e.exec_ms);
+        }
+        let mut rows: Vec<_> = rows.into_iter().flatten().collect();
+        rows.sort_by(|a, b| {
+            b.total_ms
+                .cmp(&a.total_ms)
+                .then(a.fingerprint.cmp(&b.fingerprint))
+        });
+        rows
+    }
+
+    /// 指纹 `id` 下符合过滤条件的示例语句
+    pub fn examples(&self, id: usize, filter: &Filter) -> Vec<&Example> {
+        self.examples.get(id).map_or_else(Vec::new, |list| {
+            list.iter()
+                .filter(|e| filter.matches(&e.user, ts_to_epoch_millis(&e.ts)))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    const LOG: &str = "\
+2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = 1 EXECTIME: 10(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:10:00.000 (EP[0] sess:0x1 thrd:1 user:B trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = 2 EXECTIME: 30(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:20:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [UPD] update t set v = 1 EXECTIME: 25(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+";
+
+    fn explorer() -> Explorer {
+        let mut ex = Explorer::new();
+        parse_records_with(LOG, |rec| {
+            ex.add(sample(&rec, &FingerprintOptions::DEFAULT).unwrap())
+        });
+        ex
+    }
+
+    #[test]
+    fn summarize_orders_by_total_time() {
+        let ex = explorer();
+        assert_eq!(ex.len(), 3);
+        let rows = ex.summarize(&Filter::default());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].fingerprint, "select * from t where id = ?");
+        assert_eq!((rows[0].executions, rows[0].total_ms), (2, 40));
+        assert_eq!(ex.examples(rows[0].id, &Filter::default()).len(), 2);
+    }
+
+    #[test]
+    fn summarize_applies_user_and_time_filters() {
+        let ex = explorer();
+        let (from, to) = Filter::parse_time_range("2025-08-12 10:05..").unwrap();
+        let filter = Filter {
+            user: Some("A".into()),
+            from,
+            to,
+        };
+        let rows = ex.summarize(&filter);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].fingerprint, "update t set v = ?");
+        assert!(ex.examples(0, &filter).is_empty());
+
+        assert!(Filter::parse_time_range("10:00").is_none());
+        assert!(Filter::parse_time_range("bad..").is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+const DAY_MS: i64 = 86_400_000;
+
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// 一次执行落在的星期（0 = 周一）、小时与耗时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeatmapSample {
+    pub weekday: usize,
+    pub hour: usize,
+    pub exec_ms: u64,
+}
+
+/// 从带 EXECTIME 的记录中提取样本；时间戳按日志中的本地时间计算，不做时区换算
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<HeatmapSample> {
+    let exec_ms = rec.execute_time_ms?;
+    let ms = ts_to_epoch_millis(rec.ts)?;
+    // 1970-01-01 为周四
+    let weekday = (ms.div_euclid(DAY_MS) + 3).rem_euclid(7) as usize;
+    let hour = (ms.rem_euclid(DAY_MS) / 3_600_000) as usize;
+    Some(HeatmapSample {
+        weekday,
+        hour,
+        exec_ms,
+    })
+}
+
+/// 矩阵中的一格：某个星期几的某个小时
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct HeatmapCell {
+    /// 1 = 周一 … 7 = 周日（ISO 8601）
+    pub weekday: usize,
+    pub weekday_name: &'static str,
+    pub hour: usize,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub avg_ms: f64,
+    pub max_ms: u64,
+}
+
+/// 按星期 × 小时汇总执行次数与耗时，用于绘制热力图
+#[derive(Debug)]
+pub struct Heatmap {
+    cells: Vec<HeatmapCell>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        let cells = (0..7 * 24)
+            .map(|i| HeatmapCell {
+                weekday: i / 24 + 1,
+                weekday_name: WEEKDAY_NAMES[i / 24],
+                hour: i % 24,
+                ..Default::default()
+            })
+            .collect();
+        Self { cells }
+    }
+
+    /// 解析日志文本并累加统计；只统计带 EXECTIME 的记录
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一次执行
+    pub fn add_sample(&mut self, s: HeatmapSample) {
+        let cell = &mut self.cells[s.weekday * 24 + s.hour];
+        cell.executions += 1;
+        cell.total_ms += s.exec_ms;
+        cell.max_ms = cell.max_ms.max(s.exec_ms);
+    }
+
+    /// 返回全部 7 × 24 格（没有执行的格子为 0），按星期、小时排列
+    pub fn cells(&self) -> Vec<HeatmapCell> {
+        let mut cells = self.cells.clone();
+        for cell in &mut cells {
+            cell.avg_ms = cell.total_ms as f64 / cell.executions.max(1) as f64;
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_by_weekday_and_hour() {
+        // 2025-08-12 为周二，2025-08-17 为周日
+        let log = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 10(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:59:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 30(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-17 23:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 5(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-17 23:00:01.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1
+";
+        let mut heatmap = Heatmap::new();
+        heatmap.add_text(log);
+        let cells = heatmap.cells();
+
+        assert_eq!(cells.len(), 168);
+        let tue = &cells[24 + 10];
+        assert_eq!((tue.weekday, tue.weekday_name, tue.hour), (2, "Tue", 10));
+        assert_eq!(tue.executions, 2);
+        assert!((tue.avg_ms - 20.0).abs() < 1e-9);
+        assert_eq!(tue.max_ms, 30);
+        let sun = &cells[6 * 24 + 23];
+        assert_eq!((sun.weekday, sun.executions), (7, 1));
+        assert_eq!(cells.iter().map(|c| c.executions).sum::<u64>(), 3);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql::{self, RecordCategory, StatementKind};
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+/// 记录对会话事务状态的影响
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrxEffect {
+    /// DML：开启（或继续）一个未提交的事务
+    Open,
+    /// COMMIT / ROLLBACK、DDL（隐式提交）或登出：结束事务
+    Close,
+    None,
+}
+
+/// 会话中的一条记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionEvent {
+    pub sess: String,
+    pub user: String,
+    pub appname: String,
+    pub trxid: String,
+    pub ts: String,
+    pub start_ms: i64,
+    /// 语句结束时间：开始时间加 EXECTIME，没有 EXECTIME 时等于开始时间
+    pub end_ms: i64,
+    pub effect: TrxEffect,
+    /// 登出后会话结束，状态不再保留
+    pub logout: bool,
+}
+
+/// 从带 `sess:` 与合法时间戳的记录中提取会话事件
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<SessionEvent> {
+    let sess = rec.sess?;
+    let start_ms = ts_to_epoch_millis(rec.ts)?;
+    let category = sql::categorize(&rec.body);
+    let logout = category == RecordCategory::Login
+        && sql::split_tag(&rec.body)
+            .1
+            .trim_start()
+            .get(..6)
+            .is_some_and(|k| k.eq_ignore_ascii_case("logout"));
+    let effect = match category {
+        RecordCategory::Transaction => TrxEffect::Close,
+        _ if logout => TrxEffect::Close,
+        RecordCategory::Statement => match sql::classify(sql::sql_text(&rec.body)) {
+            StatementKind::Dml => TrxEffect::Open,
+            StatementKind::Ddl => TrxEffect::Close,
+            _ => TrxEffect::None,
+        },
+        _ => TrxEffect::None,
+    };
+    Some(SessionEvent {
+        sess: sess.to_string(),
+        user: rec.user.unwrap_or_default().to_string(),
+        appname: rec.appname.unwrap_or_default().to_string(),
+        trxid: rec.trxid.unwrap_or_default().to_string(),
+        ts: rec.ts.to_string(),
+        start_ms,
+        end_ms: start_ms + rec.execute_time_ms.unwrap_or(0) as i64,
+        effect,
+        logout,
+    })
+}
+
+/// 单个会话的空闲（思考时间）统计
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct SessionIdleRow {
+    pub sess: String,
+    pub user: String,
+    pub appname: String,
+    pub statements: u64,
+    /// 相邻两条记录之间的间隔（上一条结束到下一条开始）的平均值与最大值
+    pub avg_gap_ms: f64,
+    pub max_gap_ms: i64,
+    /// 事务未提交时空闲超过阈值的次数
+    pub idle_in_trx: u64,
+    /// 事务未提交时的最长空闲时长
+    pub max_idle_in_trx_ms: i64,
+    /// 最长一次空闲之前的最后一条记录的时间与事务号
+    pub idle_after_ts: String,
+    pub idle_trxid: String,
+    #[serde(skip)]
+    total_gap_ms: i64,
+    #[serde(skip)]
+    gaps: u64,
+}
+
+#[derive(Debug)]
+struct SessionState {
+    row: SessionIdleRow,
+    last_ts: String,
+    last_end_ms: i64,
+    /// 未提交事务的事务号，没有未提交事务时为 None
+    open_trx: Option<String>,
+}
+
+/// 按会话计算相邻语句的间隔，找出持有未提交事务却长时间空闲的会话。
+///
+/// 需要按时间顺序接收同一会话的记录。
+#[derive(Debug)]
+pub struct IdleTracker {
+    threshold_ms: i64,
+    sessions: HashMap<String, SessionState>,
+    finished: Vec<SessionIdleRow>,
+}
+
+impl IdleTracker {
+    pub fn new(threshold_ms: u64) -> Self {
+        Self {
+            threshold_ms: threshold_ms as i64,
+            sessions: HashMap::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// 解析日志文本并累加统计
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(e) = sample(&rec) {
+                self.add_event(e);
+            }
+        });
+    }
+
+    /// 处理会话中的下一条记录
+    pub fn add_event(&mut self, e: SessionEvent) {
+        let state = self
+            .sessions
+            .entry(e.sess.clone())
+            .or_insert_with(|| SessionState {
+                row: SessionIdleRow {
+                    sess: e.sess.clone(),
+                    user: e.user.clone(),
+                    appname: e.appname.clone(),
+                    ..Default::default()
+                },
+                last_ts: e.ts.clone(),
+                last_end_ms: e.start_ms,
+                open_trx: None,
+            });
+        let row = &mut state.row;
+        if row.statements > 0 {
+            let gap = (e.start_ms - state.last_end_ms).max(0);
+            row.gaps += 1;
+            row.total_gap_ms += gap;
+            row.max_gap_ms = row.max_gap_ms.max(gap);
+            // 事务号变化说明之前的事务已经结束
+            let open = state.open_trx.as_ref().filter(|t| **t == e.trxid);
+            if let Some(trxid) = open
+                && gap > self.threshold_ms
+            {
+                row.idle_in_trx += 1;
+                if gap > row.max_idle_in_trx_ms {
+                    row.max_idle_in_trx_ms = gap;
+                    row.idle_after_ts = state.last_ts.clone();
+                    row.idle_trxid = trxid.clone();
+                }
+            }
+        }
+        row.statements += 1;
+        if row.user.is_empty() {
+            row.user = e.user;
+        }
+        if row.appname.is_empty() {
+            row.appname = e.appname;
+        }
+        state.last_ts = e.ts;
+        state.last_end_ms = state.last_end_ms.max(e.end_ms);
+        state.open_trx = match e.effect {
+            TrxEffect::Open => Some(e.trxid),
+            TrxEffect::Close => None,
+            TrxEffect::None => state.open_trx.take().filter(|t| *t == e.trxid),
+        };
+        if e.logout
+            && let Some(state) = self.sessions.remove(&e.sess)
+        {
+            self.finished.push(state.row);
+        }
+    }
+
+    /// 返回会话统计行，按事务内最长空闲时长降序；`all` 为 false 时只返回出现过事务内长时间空闲的会话
+    pub fn rows(&self, all: bool) -> Vec<SessionIdleRow> {
+        let mut rows: Vec<SessionIdleRow> = self
+            .finished
+            .iter()
+            .chain(self.sessions.values().map(|s| &s.row))
+            .filter(|r| all || r.idle_in_trx > 0)
+            .cloned()
+            .collect();
+        for row in &mut rows {
+            row.avg_gap_ms = row.total_gap_ms as f64 / row.gaps.max(1) as f64;
+        }
+        rows.sort_by(|a, b| {
+            b.max_idle_in_trx_ms
+                .cmp(&a.max_idle_in_trx_ms)
+                .then(b.max_gap_ms.cmp(&a.max_gap_ms))
+                .then(a.sess.cmp(&b.sess))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_idle_sessions_holding_open_transactions() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:7 stmt:0x2 appname:pool) [UPD] update t set a = 1 EXECTIME: 100(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:00.000 (EP[0] sess:0x2 thrd:2 user:B trxid:9 stmt:0x3 appname:web) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:01:00.100 (EP[0] sess:0x1 thrd:1 user:A trxid:7 stmt:0x2 appname:pool) [SEL] select 2 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:01:00.200 (EP[0] sess:0x1 thrd:1 user:A trxid:7 stmt:0x2 appname:pool) [ORA] commit EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 4.
+2025-08-12 10:05:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:8 stmt:0x2 appname:pool) [SEL] select 3 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 5.
+2025-08-12 10:10:00.000 (EP[0] sess:0x2 thrd:2 user:B trxid:9 stmt:0x3 appname:web) [SEL] select 4 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 6.
+";
+        let mut tracker = IdleTracker::new(30_000);
+        tracker.add_text(log);
+
+        let rows = tracker.rows(false);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].sess, "0x1");
+        assert_eq!(rows[0].statements, 4);
+        assert_eq!(rows[0].idle_in_trx, 1);
+        assert_eq!(rows[0].max_idle_in_trx_ms, 60_000);
+        assert_eq!(rows[0].idle_after_ts, "2025-08-12 10:00:00.000");
+        assert_eq!(rows[0].idle_trxid, "7");
+        // 提交后的空闲不计入
+        assert_eq!(rows[0].max_gap_ms, 239_799);
+
+        let all = tracker.rows(true);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].sess, "0x2");
+        assert_eq!(all[1].max_gap_ms, 599_999);
+        assert_eq!(all[1].idle_in_trx, 0);
+    }
+}
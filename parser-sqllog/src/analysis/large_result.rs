@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use dm_database_parser::fingerprint::{FingerprintOptions, fingerprint_with};
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql;
+use serde::Serialize;
+
+/// 同一指纹、同一用户下的大结果集统计
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct LargeResultRow {
+    pub fingerprint: String,
+    pub user: String,
+    /// 超过阈值的执行次数
+    pub executions: u64,
+    pub total_rows: u64,
+    pub max_rows: u64,
+    /// 返回行数最多的一次执行的原始 SQL
+    pub sample: String,
+    /// `sample` 是否因超过 `max_body_len` 被截断
+    pub truncated: bool,
+}
+
+/// 单条超过阈值的语句
+#[derive(Debug, Clone, PartialEq)]
+pub struct LargeResultSample {
+    pub fingerprint: String,
+    pub user: String,
+    pub rows: u64,
+    pub sql: String,
+}
+
+/// 若记录的 ROWCOUNT 超过 `threshold` 则提取样本，否则返回 None；指纹按 `fingerprint` 规则计算
+pub fn sample(
+    rec: &ParsedRecord<'_>,
+    threshold: u64,
+    fingerprint: &FingerprintOptions,
+) -> Option<LargeResultSample> {
+    let rows = rec.row_count.filter(|&r| r > threshold)?;
+    let sql_text = sql::sql_text(&rec.body);
+    Some(LargeResultSample {
+        fingerprint: fingerprint_with(sql_text, fingerprint).text,
+        user: rec.user.unwrap_or_default().to_string(),
+        rows,
+        sql: sql_text.to_string(),
+    })
+}
+
+/// 按 (指纹, 用户) 汇总 ROWCOUNT 超过阈值的语句
+/// （阈值在 [`sample`] 中判断）
+#[derive(Debug, Default)]
+pub struct LargeResultDetector {
+    groups: HashMap<(String, String), LargeResultRow>,
+}
+
+impl LargeResultDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析日志文本并累加 ROWCOUNT 超过 `threshold` 的语句
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str, threshold: u64) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec, threshold, &FingerprintOptions::DEFAULT) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一条超过阈值的语句
+    pub fn add_sample(&mut self, s: LargeResultSample) {
+        let row = self
+            .groups
+            .entry((s.fingerprint.clone(), s.user.clone()))
+            .or_insert_with(|| LargeResultRow {
+                fingerprint: s.fingerprint,
+                user: s.user,
+                ..Default::default()
+            });
+        row.executions += 1;
+        row.total_rows += s.rows;
+        if s.rows > row.max_rows {
+            row.max_rows = s.rows;
+            row.sample = s.sql;
+        }
+    }
+
+    /// 返回按最大行数降序排列的统计行
+    pub fn rows(&self) -> Vec<LargeResultRow> {
+        let mut rows: Vec<LargeResultRow> = self.groups.values().cloned().collect();
+        rows.sort_by(|a, b| {
+            b.max_rows
+                .cmp(&a.max_rows)
+                .then(a.fingerprint.cmp(&b.fingerprint))
+                .then(a.user.cmp(&b.user))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_by_fingerprint_and_user_above_threshold() {
+        let log = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from big where k = 1 EXECTIME: 900(ms) ROWCOUNT: 50000(rows) EXEC_ID: 1.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from big where k = 2 EXECTIME: 950(ms) ROWCOUNT: 80000(rows) EXEC_ID: 2.
+2025-08-12 10:57:09.563 (EP[0] sess:0x3 thrd:2 user:B trxid:1 stmt:0x4 appname:app) [SEL] select * from big where k = 3 EXECTIME: 10(ms) ROWCOUNT: 20000(rows) EXEC_ID: 3.
+2025-08-12 10:57:09.564 (EP[0] sess:0x3 thrd:2 user:B trxid:1 stmt:0x4 appname:app) [SEL] select * from small EXECTIME: 1(ms) ROWCOUNT: 10(rows) EXEC_ID: 4.
+";
+        let mut detector = LargeResultDetector::new();
+        detector.add_text(log, 10000);
+        let rows = detector.rows();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].user, "A");
+        assert_eq!(rows[0].fingerprint, "select * from big where k = ?");
+        assert_eq!(rows[0].executions, 2);
+        assert_eq!(rows[0].total_rows, 130000);
+        assert_eq!(rows[0].max_rows, 80000);
+        assert_eq!(rows[0].sample, "select * from big where k = 2");
+        assert_eq!(rows[1].user, "B");
+    }
+}
@@ -0,0 +1,249 @@
+//! 死锁与锁等待消息的提取：识别错误、系统消息中与锁相关的记录，关联所在会话此前执行的语句，
+//! 以及消息中提到的其他事务所属的会话与最近语句
+
+use std::collections::HashMap;
+
+use dm_database_parser::{RecordCategory, exec_index::has_statement, parser::ParsedRecord, sql};
+use serde::Serialize;
+
+use crate::analysis::truncate_body;
+
+/// 锁相关消息的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockEventKind {
+    Deadlock,
+    LockTimeout,
+    LockWait,
+}
+
+/// 各种类的关键字（小写），按顺序匹配，先匹配到的种类优先
+const PATTERNS: &[(LockEventKind, &[&str])] = &[
+    (LockEventKind::Deadlock, &["deadlock", "死锁"]),
+    (
+        LockEventKind::LockTimeout,
+        &["lock timeout", "lock wait timeout", "锁超时", "等待锁超时"],
+    ),
+    (
+        LockEventKind::LockWait,
+        &[
+            "lock wait",
+            "waiting for lock",
+            "wait for lock",
+            "锁等待",
+            "等待锁",
+        ],
+    ),
+];
+
+/// 判断记录 body 是否为锁相关消息；语句与事务控制记录中的文本（如查询 `v$deadlock_history`）不计
+pub fn lock_kind(body: &str) -> Option<LockEventKind> {
+    if matches!(
+        sql::categorize(body),
+        RecordCategory::Statement | RecordCategory::Transaction
+    ) {
+        return None;
+    }
+    let lower = body.to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|(_, words)| words.iter().any(|w| lower.contains(w)))
+        .map(|(kind, _)| *kind)
+}
+
+/// 提取消息中提到的事务号，如 `trx 123`、`trxid:123`、`TRX[123]`、`事务123`，按出现顺序去重
+pub fn mentioned_trxids(body: &str) -> Vec<String> {
+    let lower = body.to_lowercase();
+    let mut ids: Vec<String> = Vec::new();
+    for marker in ["trx", "事务"] {
+        for (pos, _) in lower.match_indices(marker) {
+            let rest = &lower[pos + marker.len()..];
+            let digits_at = rest
+                .char_indices()
+                .take(8)
+                .find(|(_, c)| {
+                    !matches!(
+                        c,
+                        '_' | 'i' | 'd' | ' ' | ':' | '=' | '[' | '(' | '#' | '号'
+                    )
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            let digits: String = rest[digits_at..]
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            if !digits.is_empty() && !ids.contains(&digits) {
+                ids.push(digits);
+            }
+        }
+    }
+    ids
+}
+
+/// 一条锁相关消息及其关联信息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LockEventRow {
+    pub ts: String,
+    pub instance: String,
+    pub path: String,
+    pub offset: u64,
+    pub kind: LockEventKind,
+    pub sess: String,
+    pub trxid: String,
+    pub user: String,
+    pub appname: String,
+    /// 所在会话此前执行的最后一条语句
+    pub last_sql: String,
+    /// 消息中提到的其他事务号，逗号分隔
+    pub other_trxids: String,
+    /// 其他事务所属的会话（未见过的事务为空），与 `other_trxids` 一一对应
+    pub other_sessions: String,
+    /// 其他事务所属会话最近执行的语句，以 ` | ` 分隔，与 `other_trxids` 一一对应
+    pub other_sql: String,
+    pub message: String,
+}
+
+/// 会话最近的事务号与语句
+#[derive(Debug, Default)]
+struct SessionState {
+    trxid: String,
+    last_sql: String,
+}
+
+/// 顺序处理记录，为锁相关消息关联会话与事务；换实例时清空已记住的会话
+#[derive(Debug, Default)]
+pub struct LockTracker {
+    instance: String,
+    max_body_len: usize,
+    sessions: HashMap<String, SessionState>,
+    trx_sessions: HashMap<String, String>,
+}
+
+impl LockTracker {
+    /// `max_body_len` 限制输出的语句与消息长度，0 表示不截断
+    pub fn new(max_body_len: usize) -> Self {
+        Self {
+            max_body_len,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        path: &str,
+        instance: &str,
+        rec: &ParsedRecord<'_>,
+    ) -> Option<LockEventRow> {
+        if self.instance != instance {
+            self.instance = instance.to_string();
+            self.sessions.clear();
+            self.trx_sessions.clear();
+        }
+        let sess = rec.sess.unwrap_or_default();
+        let trxid = rec.trxid.unwrap_or_default();
+        let Some(kind) = lock_kind(&rec.body) else {
+            self.remember(sess, trxid, rec);
+            return None;
+        };
+
+        let others: Vec<String> = mentioned_trxids(&rec.body)
+            .into_iter()
+            .filter(|t| t != trxid)
+            .collect();
+        let other_sessions: Vec<&str> = others
+            .iter()
+            .map(|t| self.trx_sessions.get(t).map_or("", String::as_str))
+            .collect();
+        let other_sql: Vec<&str> = other_sessions
+            .iter()
+            .map(|s| self.sessions.get(*s).map_or("", |st| st.last_sql.as_str()))
+            .collect();
+        let mut message = rec.body.trim().to_string();
+        truncate_body(&mut message, self.max_body_len);
+        Some(LockEventRow {
+            ts: rec.ts.to_string(),
+            instance: instance.to_string(),
+            path: path.to_string(),
+            offset: rec.offset,
+            kind,
+            sess: sess.to_string(),
+            trxid: trxid.to_string(),
+            user: rec.user.unwrap_or_default().to_string(),
+            appname: rec.appname.unwrap_or_default().to_string(),
+            last_sql: self
+                .sessions
+                .get(sess)
+                .map(|s| s.last_sql.clone())
+                .unwrap_or_default(),
+            other_trxids: others.join(","),
+            other_sessions: other_sessions.join(","),
+            other_sql: other_sql.join(" | "),
+            message,
+        })
+    }
+
+    /// 记住会话当前的事务号与最后一条语句；登出时忘记该会话
+    fn remember(&mut self, sess: &str, trxid: &str, rec: &ParsedRecord<'_>) {
+        if sess.is_empty() {
+            return;
+        }
+        if sql::categorize(&rec.body) == RecordCategory::Login
+            && sql::split_tag(&rec.body)
+                .1
+                .get(..6)
+                .is_some_and(|kw| kw.eq_ignore_ascii_case("logout"))
+        {
+            if let Some(state) = self.sessions.remove(sess) {
+                self.trx_sessions.remove(&state.trxid);
+            }
+            return;
+        }
+        let state = self.sessions.entry(sess.to_string()).or_default();
+        if !trxid.is_empty() && state.trxid != trxid {
+            self.trx_sessions.remove(&state.trxid);
+            self.trx_sessions
+                .insert(trxid.to_string(), sess.to_string());
+            state.trxid = trxid.to_string();
+        }
+        if has_statement(rec) {
+            state.last_sql = sql::sql_text(&rec.body).to_string();
+            truncate_body(&mut state.last_sql, self.max_body_len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    const LOG: &str = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:10 stmt:0x2 appname:app) [UPD] update t set a = 1 where id = 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:01.000 (EP[0] sess:0x2 thrd:2 user:B trxid:20 stmt:0x3 appname:etl) [UPD] update t set a = 2 where id = 2 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:02.000 (EP[0] sess:0x2 thrd:2 user:B trxid:20 stmt:0x3 appname:etl) [SEL] select * from v$deadlock_history EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 3.
+2025-08-12 10:00:05.000 (EP[0] sess:0x2 thrd:2 user:B trxid:20 stmt:0x3 appname:etl) [ERR] 检测到死锁: trx 20 waits for trx 10
+2025-08-12 10:00:06.000 (EP[0] sess:0x3 thrd:3 user:C trxid:30 stmt:0x4 appname:app) [ERR] lock timeout, waiting for TRXID:10
+";
+
+    #[test]
+    fn extracts_lock_events_and_correlates_sessions() {
+        let mut tracker = LockTracker::new(0);
+        let mut rows = Vec::new();
+        parse_records_with(LOG, |rec| rows.extend(tracker.add("a.log", "DM1", &rec)));
+        assert_eq!(rows.len(), 2);
+
+        let dl = &rows[0];
+        assert_eq!(dl.kind, LockEventKind::Deadlock);
+        assert_eq!(dl.sess, "0x2");
+        assert_eq!(dl.last_sql, "select * from v$deadlock_history");
+        assert_eq!(dl.other_trxids, "10");
+        assert_eq!(dl.other_sessions, "0x1");
+        assert_eq!(dl.other_sql, "update t set a = 1 where id = 1");
+
+        assert_eq!(rows[1].kind, LockEventKind::LockTimeout);
+        assert_eq!(rows[1].last_sql, "");
+        assert_eq!(rows[1].other_sessions, "0x1");
+
+        assert_eq!(mentioned_trxids("TRX[5] 事务号 6, trxid=5"), ["5", "6"]);
+        assert_eq!(lock_kind("[SEL] select * from v$deadlock_history"), None);
+    }
+}
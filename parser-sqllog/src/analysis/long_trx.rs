@@ -0,0 +1,182 @@
+//! 实时跟踪未提交的事务：事务的划分与 `rollback` 报告相同（从第一条 DML 到 COMMIT / ROLLBACK /
+//! DDL / 事务号变化 / 登出），开启时长超过阈值时产生一次告警
+
+use std::collections::HashMap;
+
+use dm_database_parser::{exec_index::has_statement, parser::ParsedRecord, sql};
+use serde::Serialize;
+
+use crate::analysis::{
+    rollback::{self, TrxAction},
+    truncate_body,
+};
+
+/// 一个开启时长超过阈值的未提交事务
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LongTrxAlert {
+    pub instance: String,
+    pub sess: String,
+    pub trxid: String,
+    pub user: String,
+    pub appname: String,
+    /// 事务中第一条 DML 的时间
+    pub started_at: String,
+    /// 告警时事务已开启的时长
+    pub open_ms: i64,
+    /// 事务中已执行的语句数
+    pub statement_count: u64,
+    /// 事务中已执行的语句，最多保留前若干条
+    pub statements: Vec<String>,
+}
+
+#[derive(Debug)]
+struct OpenTrx {
+    started_at: String,
+    start_ms: i64,
+    trxid: String,
+    user: String,
+    appname: String,
+    statement_count: u64,
+    statements: Vec<String>,
+    alerted: bool,
+}
+
+/// 按实例与会话跟踪未提交的事务，需要按时间顺序接收同一会话的记录
+#[derive(Debug)]
+pub struct LongTrxTracker {
+    threshold_ms: i64,
+    max_statements: usize,
+    max_body_len: usize,
+    latest_ms: Option<i64>,
+    open: HashMap<(String, String), OpenTrx>,
+}
+
+impl LongTrxTracker {
+    /// `max_statements` 为告警中最多保留的语句数，`max_body_len` 限制每条语句的长度（0 表示不截断）
+    pub fn new(threshold_ms: u64, max_statements: usize, max_body_len: usize) -> Self {
+        Self {
+            threshold_ms: i64::try_from(threshold_ms).unwrap_or(i64::MAX),
+            max_statements,
+            max_body_len,
+            latest_ms: None,
+            open: HashMap::new(),
+        }
+    }
+
+    /// 已见到的最新记录时间（毫秒时间戳）
+    pub fn latest_ms(&self) -> Option<i64> {
+        self.latest_ms
+    }
+
+    /// 当前未提交的事务数
+    pub fn open_count(&self) -> usize {
+        self.open.len()
+    }
+
+    pub fn add(&mut self, instance: &str, rec: &ParsedRecord<'_>) {
+        let Some(e) = rollback::sample(rec) else {
+            return;
+        };
+        self.latest_ms = Some(self.latest_ms.map_or(e.ts_ms, |t| t.max(e.ts_ms)));
+        let key = (instance.to_string(), e.sess);
+        // 事务号变化说明之前的事务已经结束
+        if self
+            .open
+            .get(&key)
+            .is_some_and(|t| !e.trxid.is_empty() && t.trxid != e.trxid)
+        {
+            self.open.remove(&key);
+        }
+        match e.action {
+            TrxAction::Dml | TrxAction::Statement => {
+                let trx = match self.open.get_mut(&key) {
+                    Some(trx) => trx,
+                    None if e.action == TrxAction::Dml => {
+                        self.open.entry(key).or_insert_with(|| OpenTrx {
+                            started_at: rec.ts.to_string(),
+                            start_ms: e.ts_ms,
+                            trxid: e.trxid,
+                            user: e.user,
+                            appname: e.appname,
+                            statement_count: 0,
+                            statements: Vec::new(),
+                            alerted: false,
+                        })
+                    }
+                    None => return,
+                };
+                if has_statement(rec) {
+                    trx.statement_count += 1;
+                    if trx.statements.len() < self.max_statements {
+                        let mut text = sql::sql_text(&rec.body).to_string();
+                        truncate_body(&mut text, self.max_body_len);
+                        trx.statements.push(text);
+                    }
+                }
+            }
+            TrxAction::Commit | TrxAction::Rollback | TrxAction::Ddl | TrxAction::Logout => {
+                self.open.remove(&key);
+            }
+        }
+    }
+
+    /// 返回到 `now_ms` 时开启时长首次超过阈值的事务，每个事务只告警一次；
+    /// 按开启时间排序
+    pub fn check(&mut self, now_ms: i64) -> Vec<LongTrxAlert> {
+        let mut alerts: Vec<LongTrxAlert> = self
+            .open
+            .iter_mut()
+            .filter(|(_, t)| !t.alerted && now_ms - t.start_ms >= self.threshold_ms)
+            .map(|((instance, sess), t)| {
+                t.alerted = true;
+                LongTrxAlert {
+                    instance: instance.clone(),
+                    sess: sess.clone(),
+                    trxid: t.trxid.clone(),
+                    user: t.user.clone(),
+                    appname: t.appname.clone(),
+                    started_at: t.started_at.clone(),
+                    open_ms: now_ms - t.start_ms,
+                    statement_count: t.statement_count,
+                    statements: t.statements.clone(),
+                }
+            })
+            .collect();
+        alerts.sort_by(|a, b| a.started_at.cmp(&b.started_at).then(a.sess.cmp(&b.sess)));
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::{parser::parse_records_with, ts_to_epoch_millis};
+
+    #[test]
+    fn alerts_once_for_transactions_open_past_threshold() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:01.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [UPD] update t set a = 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:02.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select a from t EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:00:03.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [INS] insert into t values(2) EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+2025-08-12 10:00:04.000 (EP[0] sess:0x2 thrd:2 user:B trxid:5 stmt:0x3 appname:etl) [DEL] delete from t EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 5.
+2025-08-12 10:00:05.000 (EP[0] sess:0x2 thrd:2 user:B trxid:5 stmt:0x3 appname:etl) [ORA] commit EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 6.
+";
+        let mut t = LongTrxTracker::new(60_000, 2, 0);
+        parse_records_with(log, |rec| t.add("DM1", &rec));
+        assert_eq!(t.open_count(), 1);
+        let latest = t.latest_ms().unwrap();
+        assert_eq!(Some(latest), ts_to_epoch_millis("2025-08-12 10:00:05.000"));
+
+        assert!(t.check(latest).is_empty());
+        let alerts = t.check(latest + 60_000);
+        assert_eq!(alerts.len(), 1);
+        let a = &alerts[0];
+        assert_eq!(a.sess, "0x1");
+        assert_eq!(a.user, "A");
+        assert_eq!(a.started_at, "2025-08-12 10:00:01.000");
+        assert_eq!(a.open_ms, 64_000);
+        assert_eq!(a.statement_count, 3);
+        assert_eq!(a.statements, ["update t set a = 1", "select a from t"]);
+        assert!(t.check(latest + 120_000).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+pub mod activity;
+pub mod audit;
+pub mod concurrency;
+pub mod doctor;
+pub mod events;
+pub mod exec;
+pub mod explore;
+pub mod heatmap;
+pub mod idle;
+pub mod large_result;
+pub mod locks;
+pub mod long_trx;
+pub mod pool;
+pub mod prepared;
+pub mod rollback;
+pub mod row_latency;
+pub mod saturation;
+pub mod show;
+pub mod sqlfmt;
+pub mod stats;
+pub mod summary;
+pub mod table;
+pub mod tables;
+pub mod verify;
+
+/// 将 `text` 截断到不超过 `max_len` 字节（按字符边界）并追加省略号，返回是否发生了截断。
+/// `max_len` 为 0 表示不截断。
+pub fn truncate_body(text: &mut String, max_len: usize) -> bool {
+    if max_len == 0 || text.len() <= max_len {
+        return false;
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str("...");
+    true
+}
+
+/// 以 CSV 格式写出报告行（包含表头）
+pub fn write_csv<W: Write, T: Serialize>(rows: &[T], writer: W) -> csv::Result<()> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_body_respects_char_boundaries() {
+        let mut s = "select '达梦' from dual".to_string();
+        assert!(!truncate_body(&mut s, 0));
+        assert!(truncate_body(&mut s, 10));
+        assert_eq!(s, "select '...");
+
+        let mut short = "select 1".to_string();
+        assert!(!truncate_body(&mut short, 100));
+        assert_eq!(short, "select 1");
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+/// 一个会话的一次活动：所属 appname 与执行区间 `[start, end)`（毫秒）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionActivity {
+    pub appname: String,
+    pub sess: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// 从带 `sess:` 的记录中提取活动区间；没有 EXECTIME 时视为瞬时活动
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<SessionActivity> {
+    let sess = rec.sess?;
+    let start = ts_to_epoch_millis(rec.ts)?;
+    Some(SessionActivity {
+        appname: rec.appname.unwrap_or_default().to_string(),
+        sess: sess.to_string(),
+        start,
+        end: start + rec.execute_time_ms.unwrap_or(0) as i64,
+    })
+}
+
+/// 某个 appname 在一个时间桶内的活跃会话数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolBucket {
+    pub appname: String,
+    /// 时间桶起点（毫秒时间戳）
+    pub bucket_start_ms: i64,
+    pub sessions: usize,
+}
+
+/// 单个 appname 的连接池使用概况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolUsageRow {
+    pub appname: String,
+    /// 出现过的不同会话总数
+    pub distinct_sessions: usize,
+    /// 有活动的时间桶数
+    pub active_buckets: usize,
+    /// 各活跃时间桶内活跃会话数的平均值、P95 与最大值
+    pub avg_sessions: f64,
+    pub p95_sessions: usize,
+    pub max_sessions: usize,
+    /// 活跃会话数最多的时间桶起点（毫秒时间戳）
+    pub peak_bucket_start_ms: i64,
+}
+
+/// 按 appname 和时间桶统计不同会话数，用于估算连接池的实际占用与峰值
+#[derive(Debug)]
+pub struct PoolUsage {
+    bucket_ms: i64,
+    apps: HashMap<String, BTreeMap<i64, HashSet<String>>>,
+    sessions: HashMap<String, HashSet<String>>,
+}
+
+impl PoolUsage {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            apps: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// 解析日志文本并累加
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一次会话活动：会话在区间覆盖的每个时间桶内都计为活跃
+    pub fn add_sample(&mut self, s: SessionActivity) {
+        let buckets = self.apps.entry(s.appname.clone()).or_default();
+        let first = s.start.div_euclid(self.bucket_ms);
+        let last = (s.end - 1).max(s.start).div_euclid(self.bucket_ms);
+        for idx in first..=last {
+            buckets.entry(idx).or_default().insert(s.sess.clone());
+        }
+        self.sessions.entry(s.appname).or_default().insert(s.sess);
+    }
+
+    /// 按 appname、时间顺序返回各时间桶的活跃会话数
+    pub fn timeline(&self) -> Vec<PoolBucket> {
+        let mut rows: Vec<PoolBucket> = self
+            .apps
+            .iter()
+            .flat_map(|(app, buckets)| {
+                buckets.iter().map(|(idx, sess)| PoolBucket {
+                    appname: app.clone(),
+                    bucket_start_ms: idx * self.bucket_ms,
+                    sessions: sess.len(),
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            a.appname
+                .cmp(&b.appname)
+                .then(a.bucket_start_ms.cmp(&b.bucket_start_ms))
+        });
+        rows
+    }
+
+    /// 返回按峰值会话数降序排列的各 appname 概况
+    pub fn summary(&self) -> Vec<PoolUsageRow> {
+        let mut rows: Vec<PoolUsageRow> = self
+            .apps
+            .iter()
+            .map(|(app, buckets)| {
+                let mut counts: Vec<usize> = buckets.values().map(HashSet::len).collect();
+                counts.sort_unstable();
+                // 同样多时取最早的时间桶
+                let (peak_idx, peak) = buckets
+                    .iter()
+                    .map(|(idx, s)| (*idx, s.len()))
+                    .fold((0, 0), |best, cur| if cur.1 > best.1 { cur } else { best });
+                let p95 = counts[(counts.len() * 95).div_ceil(100).max(1) - 1];
+                PoolUsageRow {
+                    appname: app.clone(),
+                    distinct_sessions: self.sessions.get(app).map_or(0, HashSet::len),
+                    active_buckets: counts.len(),
+                    avg_sessions: counts.iter().sum::<usize>() as f64 / counts.len().max(1) as f64,
+                    p95_sessions: p95,
+                    max_sessions: peak,
+                    peak_bucket_start_ms: peak_idx * self.bucket_ms,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.max_sessions
+                .cmp(&a.max_sessions)
+                .then(a.appname.cmp(&b.appname))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_distinct_sessions_per_app_and_bucket() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:10.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:20.000 (EP[0] sess:0x2 thrd:2 user:A trxid:1 stmt:0x3 appname:web) [SEL] select 1 EXECTIME: 50000(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:01:30.000 (EP[0] sess:0x3 thrd:3 user:A trxid:1 stmt:0x4 appname:web) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+2025-08-12 10:00:30.000 (EP[0] sess:0x9 thrd:4 user:B trxid:1 stmt:0x5 appname:etl) [INS] insert into t values(1) EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 5.
+";
+        let mut pool = PoolUsage::new(60_000);
+        pool.add_text(log);
+
+        let timeline = pool.timeline();
+        let web: Vec<usize> = timeline
+            .iter()
+            .filter(|b| b.appname == "web")
+            .map(|b| b.sessions)
+            .collect();
+        assert_eq!(web, [2, 2]);
+
+        let summary = pool.summary();
+        assert_eq!(summary[0].appname, "web");
+        assert_eq!(summary[0].distinct_sessions, 3);
+        assert_eq!(summary[0].active_buckets, 2);
+        assert_eq!(summary[0].max_sessions, 2);
+        assert_eq!(summary[0].p95_sessions, 2);
+        assert_eq!(summary[1].appname, "etl");
+        assert_eq!(summary[1].max_sessions, 1);
+    }
+}
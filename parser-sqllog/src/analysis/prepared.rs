@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use dm_database_parser::fingerprint::fingerprint;
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql::{self, StatementKind};
+use serde::Serialize;
+
+/// 一次语句执行的参数传递方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecStyle {
+    /// 带 PARAMS 参数块或绑定占位符的预编译执行
+    Prepared,
+    /// 参数以字面量拼接进 SQL 文本
+    Literal,
+    /// 既无字面量也无绑定参数（如 `select sysdate from dual`），不计入比例
+    Plain,
+}
+
+/// 判定一条记录 body 的执行方式；非查询/DML 语句返回 None。
+pub fn classify_exec(body: &str) -> Option<ExecStyle> {
+    let text = sql::sql_text(body);
+    if !matches!(
+        sql::classify(text),
+        StatementKind::Query | StatementKind::Dml
+    ) {
+        return None;
+    }
+    if sql::params_text(body).is_some() {
+        return Some(ExecStyle::Prepared);
+    }
+    let fp = fingerprint(text);
+    Some(if fp.placeholders > 0 {
+        ExecStyle::Prepared
+    } else if fp.literals > 0 {
+        ExecStyle::Literal
+    } else {
+        ExecStyle::Plain
+    })
+}
+
+/// 从记录中提取 (appname, 执行方式)；非查询/DML 语句返回 None
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<(String, ExecStyle)> {
+    let style = classify_exec(&rec.body)?;
+    Some((rec.appname.unwrap_or_default().to_string(), style))
+}
+
+/// 单个 appname 的绑定变量使用统计
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct AppBindUsage {
+    pub appname: String,
+    pub total: u64,
+    pub prepared: u64,
+    pub literal: u64,
+    pub plain: u64,
+    /// prepared / (prepared + literal)，两者都为 0 时为 0
+    pub prepared_ratio: f64,
+}
+
+/// 按 appname 汇总预编译语句使用情况
+#[derive(Debug, Default)]
+pub struct PreparedUsage {
+    by_app: HashMap<String, AppBindUsage>,
+}
+
+impl PreparedUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析日志文本并累加统计
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some((app, style)) = sample(&rec) {
+                self.add(&app, style);
+            }
+        });
+    }
+
+    /// 累加一次执行
+    pub fn add(&mut self, appname: &str, style: ExecStyle) {
+        let usage = self
+            .by_app
+            .entry(appname.to_string())
+            .or_insert_with(|| AppBindUsage {
+                appname: appname.to_string(),
+                ..Default::default()
+            });
+        usage.total += 1;
+        match style {
+            ExecStyle::Prepared => usage.prepared += 1,
+            ExecStyle::Literal => usage.literal += 1,
+            ExecStyle::Plain => usage.plain += 1,
+        }
+    }
+
+    /// 返回按执行次数降序排列的统计行
+    pub fn rows(&self) -> Vec<AppBindUsage> {
+        let mut rows: Vec<AppBindUsage> = self
+            .by_app
+            .values()
+            .map(|u| {
+                let denom = u.prepared + u.literal;
+                let mut row = u.clone();
+                row.prepared_ratio = if denom == 0 {
+                    0.0
+                } else {
+                    u.prepared as f64 / denom as f64
+                };
+                row
+            })
+            .collect();
+        rows.sort_by(|a, b| b.total.cmp(&a.total).then(a.appname.cmp(&b.appname)));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_exec_styles() {
+        assert_eq!(
+            classify_exec("[INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)}"),
+            Some(ExecStyle::Prepared)
+        );
+        assert_eq!(
+            classify_exec("[SEL] select * from t where id = 5"),
+            Some(ExecStyle::Literal)
+        );
+        assert_eq!(
+            classify_exec("[SEL] select sysdate from dual"),
+            Some(ExecStyle::Plain)
+        );
+        assert_eq!(classify_exec("[DDL] create table t(id int)"), None);
+    }
+
+    #[test]
+    fn usage_ratio_per_appname() {
+        let log = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app1) [SEL] select * from t where id = ? PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)}
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app1) [SEL] select * from t where id = 2
+2025-08-12 10:57:09.563 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app1) [SEL] select * from t where id = ?
+2025-08-12 10:57:09.564 (EP[0] sess:0x3 thrd:2 user:U trxid:1 stmt:0x4 appname:app2) [UPD] update t set a = 'x'
+";
+        let mut usage = PreparedUsage::new();
+        usage.add_text(log);
+        let rows = usage.rows();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].appname, "app1");
+        assert_eq!(rows[0].prepared, 2);
+        assert_eq!(rows[0].literal, 1);
+        assert!((rows[0].prepared_ratio - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(rows[1].appname, "app2");
+        assert_eq!(rows[1].prepared_ratio, 0.0);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashMap};
+
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql::{self, RecordCategory, StatementKind};
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+/// 记录对事务的作用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrxAction {
+    /// DML：开启（或继续）事务
+    Dml,
+    /// 其他语句：不开启事务，但事务号变化时说明之前的事务已隐式结束
+    Statement,
+    Commit,
+    Rollback,
+    /// DDL：隐式提交
+    Ddl,
+    Logout,
+}
+
+/// 会话中影响事务的一条记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrxEvent {
+    pub sess: String,
+    pub user: String,
+    pub appname: String,
+    pub trxid: String,
+    pub ts_ms: i64,
+    pub action: TrxAction,
+}
+
+/// 从带 `sess:` 与合法时间戳的语句、事务控制与登出记录中提取事务事件
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<TrxEvent> {
+    let sess = rec.sess?;
+    let ts_ms = ts_to_epoch_millis(rec.ts)?;
+    let text = sql::sql_text(&rec.body);
+    let first_word = |w: &str| {
+        text.trim_start()
+            .get(..w.len())
+            .is_some_and(|k| k.eq_ignore_ascii_case(w))
+    };
+    let action = match sql::categorize(&rec.body) {
+        RecordCategory::Transaction if first_word("rollback") => {
+            // ROLLBACK TO SAVEPOINT 不结束事务
+            if text.to_ascii_lowercase().contains(" to ") {
+                return None;
+            }
+            TrxAction::Rollback
+        }
+        RecordCategory::Transaction if first_word("commit") => TrxAction::Commit,
+        RecordCategory::Login if first_word("logout") => TrxAction::Logout,
+        RecordCategory::Statement if !text.is_empty() => match sql::classify(text) {
+            StatementKind::Dml => TrxAction::Dml,
+            StatementKind::Ddl => TrxAction::Ddl,
+            _ => TrxAction::Statement,
+        },
+        _ => return None,
+    };
+    Some(TrxEvent {
+        sess: sess.to_string(),
+        user: rec.user.unwrap_or_default().to_string(),
+        appname: rec.appname.unwrap_or_default().to_string(),
+        trxid: rec.trxid.unwrap_or_default().to_string(),
+        ts_ms,
+        action,
+    })
+}
+
+/// 事务的结束方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrxEnd {
+    Commit,
+    /// DDL 或事务号变化（如自动提交）导致的隐式结束
+    Implicit,
+    Rollback,
+    /// 会话登出时仍未提交
+    Aborted,
+}
+
+/// 某个用户与 appname 在一个时间桶内结束的事务
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RollbackRow {
+    /// 时间桶起点（毫秒时间戳），按事务结束时间归入
+    pub bucket_start_ms: i64,
+    pub user: String,
+    pub appname: String,
+    /// 含 DML 的事务数
+    pub transactions: u64,
+    pub commits: u64,
+    pub implicit_commits: u64,
+    pub rollbacks: u64,
+    /// 会话登出时仍未提交的事务
+    pub aborted: u64,
+    /// 没有 DML 的事务上的 ROLLBACK（如连接池归还连接时的清理），不计入回滚率
+    pub empty_rollbacks: u64,
+    /// (rollbacks + aborted) / transactions
+    pub rollback_rate: f64,
+    /// 回滚数突增：不少于该用户与 appname 各时间桶平均回滚数的 3 倍，且不少于最小回滚数
+    pub spike: bool,
+}
+
+/// 会话中未结束的含 DML 的事务
+#[derive(Debug)]
+struct OpenTrx {
+    trxid: String,
+    user: String,
+    appname: String,
+}
+
+/// 按会话重建事务（从第一条 DML 到 COMMIT / ROLLBACK / DDL / 事务号变化 / 登出），
+/// 按用户、appname 与时间桶统计回滚次数与回滚率。
+///
+/// 需要按时间顺序接收同一会话的记录。
+#[derive(Debug)]
+pub struct RollbackTracker {
+    bucket_ms: i64,
+    open: HashMap<String, OpenTrx>,
+    buckets: BTreeMap<(String, String, i64), RollbackRow>,
+}
+
+impl RollbackTracker {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            open: HashMap::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// 解析日志文本并累加统计
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(e) = sample(&rec) {
+                self.add_event(e);
+            }
+        });
+    }
+
+    /// 处理会话中的下一条事务事件
+    pub fn add_event(&mut self, e: TrxEvent) {
+        // 事务号变化说明之前的事务已经结束
+        if self
+            .open
+            .get(&e.sess)
+            .is_some_and(|t| !e.trxid.is_empty() && t.trxid != e.trxid)
+        {
+            self.close(&e.sess, e.ts_ms, TrxEnd::Implicit);
+        }
+        match e.action {
+            TrxAction::Dml => {
+                self.open.entry(e.sess).or_insert(OpenTrx {
+                    trxid: e.trxid,
+                    user: e.user,
+                    appname: e.appname,
+                });
+            }
+            TrxAction::Statement => {}
+            TrxAction::Commit => {
+                self.close(&e.sess, e.ts_ms, TrxEnd::Commit);
+            }
+            TrxAction::Ddl => {
+                self.close(&e.sess, e.ts_ms, TrxEnd::Implicit);
+            }
+            TrxAction::Logout => {
+                self.close(&e.sess, e.ts_ms, TrxEnd::Aborted);
+            }
+            TrxAction::Rollback => {
+                if !self.close(&e.sess, e.ts_ms, TrxEnd::Rollback) {
+                    self.bucket(e.user, e.appname, e.ts_ms).empty_rollbacks += 1;
+                }
+            }
+        }
+    }
+
+    /// 结束会话中未结束的事务，没有时返回 false
+    fn close(&mut self, sess: &str, ts_ms: i64, end: TrxEnd) -> bool {
+        let Some(trx) = self.open.remove(sess) else {
+            return false;
+        };
+        let row = self.bucket(trx.user, trx.appname, ts_ms);
+        row.transactions += 1;
+        match end {
+            TrxEnd::Commit => row.commits += 1,
+            TrxEnd::Implicit => row.implicit_commits += 1,
+            TrxEnd::Rollback => row.rollbacks += 1,
+            TrxEnd::Aborted => row.aborted += 1,
+        }
+        true
+    }
+
+    fn bucket(&mut self, user: String, appname: String, ts_ms: i64) -> &mut RollbackRow {
+        let start = ts_ms.div_euclid(self.bucket_ms) * self.bucket_ms;
+        self.buckets
+            .entry((user.clone(), appname.clone(), start))
+            .or_insert_with(|| RollbackRow {
+                bucket_start_ms: start,
+                user,
+                appname,
+                ..Default::default()
+            })
+    }
+
+    /// 按用户、appname、时间顺序返回各时间桶的统计；回滚数不少于 `min_rollbacks` 且
+    /// 达到平均值 3 倍的时间桶标记为突增
+    pub fn rows(&self, min_rollbacks: u64) -> Vec<RollbackRow> {
+        let mut rows: Vec<RollbackRow> = self.buckets.values().cloned().collect();
+        let mut start = 0;
+        while start < rows.len() {
+            let key = (&rows[start].user, &rows[start].appname);
+            let end = start
+                + rows[start..]
+                    .iter()
+                    .take_while(|r| (&r.user, &r.appname) == key)
+                    .count();
+            let group = &mut rows[start..end];
+            let total: u64 = group.iter().map(|r| r.rollbacks + r.aborted).sum();
+            let avg = total as f64 / group.len() as f64;
+            for row in group {
+                let rolled = row.rollbacks + row.aborted;
+                row.rollback_rate = rolled as f64 / row.transactions.max(1) as f64;
+                row.spike = rolled >= min_rollbacks && rolled as f64 >= 3.0 * avg;
+            }
+            start = end;
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconstructs_transactions_and_counts_rollbacks() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [INS] insert into t values(1) EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:01.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [ORA] rollback EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 2.
+2025-08-12 10:00:02.000 (EP[0] sess:0x1 thrd:1 user:A trxid:2 stmt:0x2 appname:web) [ORA] rollback EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 3.
+2025-08-12 10:00:03.000 (EP[0] sess:0x1 thrd:1 user:A trxid:3 stmt:0x2 appname:web) [UPD] update t set a = 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+2025-08-12 10:00:04.000 (EP[0] sess:0x1 thrd:1 user:A trxid:3 stmt:0x2 appname:web) [ORA] rollback to savepoint s1 EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 5.
+2025-08-12 10:00:05.000 (EP[0] sess:0x1 thrd:1 user:A trxid:3 stmt:0x2 appname:web) [ORA] commit EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 6.
+2025-08-12 10:00:06.000 (EP[0] sess:0x2 thrd:2 user:A trxid:7 stmt:0x3 appname:web) [DEL] delete from t EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.
+2025-08-12 10:00:07.000 (EP[0] sess:0x2 thrd:2 user:A trxid:8 stmt:0x3 appname:web) [INS] insert into t values(2) EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 8.
+2025-08-12 10:00:08.000 (EP[0] sess:0x2 thrd:2 user:A trxid:8 stmt:0x3 appname:web) logout
+";
+        let mut t = RollbackTracker::new(3_600_000);
+        t.add_text(log);
+        let rows = t.rows(1);
+        assert_eq!(rows.len(), 1);
+        let r = &rows[0];
+        assert_eq!(r.transactions, 4);
+        assert_eq!(r.commits, 1);
+        assert_eq!(r.implicit_commits, 1);
+        assert_eq!(r.rollbacks, 1);
+        assert_eq!(r.aborted, 1);
+        assert_eq!(r.empty_rollbacks, 1);
+        assert_eq!(r.rollback_rate, 0.5);
+        assert!(!r.spike);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use dm_database_parser::fingerprint::{FingerprintOptions, fingerprint_with};
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql;
+use serde::Serialize;
+
+/// ROWCOUNT 分桶
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum RowBucket {
+    #[serde(rename = "0")]
+    Zero,
+    #[serde(rename = "1-10")]
+    Few,
+    #[serde(rename = "11-1000")]
+    Some,
+    #[serde(rename = ">1000")]
+    Many,
+}
+
+impl RowBucket {
+    pub fn of(rows: u64) -> Self {
+        match rows {
+            0 => RowBucket::Zero,
+            1..=10 => RowBucket::Few,
+            11..=1000 => RowBucket::Some,
+            _ => RowBucket::Many,
+        }
+    }
+}
+
+/// 单次执行的指纹、行数分桶与耗时
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowLatencySample {
+    pub fingerprint: String,
+    pub bucket: RowBucket,
+    pub exec_ms: u64,
+}
+
+/// 从同时带 EXECTIME 与 ROWCOUNT 的记录中提取样本，指纹按 `fingerprint` 规则计算
+pub fn sample(
+    rec: &ParsedRecord<'_>,
+    fingerprint: &FingerprintOptions,
+) -> Option<RowLatencySample> {
+    let exec_ms = rec.execute_time_ms?;
+    let rows = rec.row_count?;
+    Some(RowLatencySample {
+        fingerprint: fingerprint_with(sql::sql_text(&rec.body), fingerprint).text,
+        bucket: RowBucket::of(rows),
+        exec_ms,
+    })
+}
+
+/// 某个指纹在某个行数区间内的耗时统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowLatencyRow {
+    pub fingerprint: String,
+    pub rows_bucket: RowBucket,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub avg_ms: f64,
+    pub max_ms: u64,
+}
+
+/// 按 (指纹, ROWCOUNT 分桶) 汇总耗时，区分“结果集大所以慢”与“结果集小仍然慢”
+#[derive(Debug, Default)]
+pub struct RowLatency {
+    groups: HashMap<(String, RowBucket), RowLatencyRow>,
+}
+
+impl RowLatency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析日志文本并累加统计
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec, &FingerprintOptions::DEFAULT) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一次执行
+    pub fn add_sample(&mut self, s: RowLatencySample) {
+        let row = self
+            .groups
+            .entry((s.fingerprint.clone(), s.bucket))
+            .or_insert_with(|| RowLatencyRow {
+                fingerprint: s.fingerprint,
+                rows_bucket: s.bucket,
+                executions: 0,
+                total_ms: 0,
+                avg_ms: 0.0,
+                max_ms: 0,
+            });
+        row.executions += 1;
+        row.total_ms += s.exec_ms;
+        row.max_ms = row.max_ms.max(s.exec_ms);
+    }
+
+    /// 返回统计行：指纹按总耗时降序，同一指纹的各分桶相邻并按行数升序；
+    /// `top` 限制输出的指纹个数
+    pub fn rows(&self, top: Option<usize>) -> Vec<RowLatencyRow> {
+        let mut totals: HashMap<&str, u64> = HashMap::new();
+        for row in self.groups.values() {
+            *totals.entry(&row.fingerprint).or_default() += row.total_ms;
+        }
+        let mut ranked: Vec<(&str, u64)> = totals.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        if let Some(top) = top {
+            ranked.truncate(top);
+        }
+        let order: HashMap<&str, usize> =
+            ranked.iter().zip(0..).map(|(&(f, _), i)| (f, i)).collect();
+
+        let mut rows: Vec<RowLatencyRow> = self
+            .groups
+            .values()
+            .filter(|r| order.contains_key(r.fingerprint.as_str()))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|r| (order[r.fingerprint.as_str()], r.rows_bucket));
+        for row in &mut rows {
+            row.avg_ms = row.total_ms as f64 / row.executions.max(1) as f64;
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_latency_by_rowcount_bucket() {
+        let log = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where k = 1 EXECTIME: 900(ms) ROWCOUNT: 50000(rows) EXEC_ID: 1.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where k = 2 EXECTIME: 300(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:57:09.563 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where k = 3 EXECTIME: 100(ms) ROWCOUNT: 0(rows) EXEC_ID: 3.
+2025-08-12 10:57:09.564 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where k = 4 EXECTIME: 200(ms) ROWCOUNT: 3(rows) EXEC_ID: 4.
+2025-08-12 10:57:09.565 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [UPD] update u set a = 1 EXECTIME: 5(ms) ROWCOUNT: 20(rows) EXEC_ID: 5.
+";
+        let mut agg = RowLatency::new();
+        agg.add_text(log);
+        let rows = agg.rows(None);
+
+        let buckets: Vec<RowBucket> = rows.iter().map(|r| r.rows_bucket).collect();
+        assert_eq!(
+            buckets,
+            [
+                RowBucket::Zero,
+                RowBucket::Few,
+                RowBucket::Many,
+                RowBucket::Some
+            ]
+        );
+        assert_eq!(rows[1].executions, 2);
+        assert!((rows[1].avg_ms - 250.0).abs() < 1e-9);
+        assert_eq!(rows[3].fingerprint, "update u set a = ?");
+        assert_eq!(agg.rows(Some(1)).len(), 3);
+    }
+}
@@ -0,0 +1,209 @@
+//! 同时执行的语句数：把语句记录与其后单独记录的执行指标拼接为执行区间，逐个实例扫描区间端点，
+//! 找出同时执行的语句数超过上限的时间桶，作为服务器工作线程饱和的依据
+
+use std::collections::{BTreeMap, HashMap};
+
+use dm_database_parser::{exec_index::has_statement, parser::ParsedRecord, ts_to_epoch_millis};
+use serde::Serialize;
+
+/// 从记录中提取的样本
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaturationSample {
+    /// 不带执行指标的语句记录：执行开始，等待同一会话、同一语句句柄的执行指标
+    Start {
+        instance: String,
+        sess: String,
+        stmt: String,
+        ts_ms: i64,
+    },
+    /// 带 EXECTIME 的记录
+    Exec {
+        instance: String,
+        sess: String,
+        stmt: String,
+        ts_ms: i64,
+        exec_ms: u64,
+        /// 记录本身带有语句文本，不需要与之前的语句记录拼接
+        has_statement: bool,
+    },
+}
+
+/// 不带执行指标的语句记录产生开始样本，带 EXECTIME 的记录产生执行样本，其余记录返回 None
+pub fn sample(instance: &str, rec: &ParsedRecord<'_>) -> Option<SaturationSample> {
+    let ts_ms = ts_to_epoch_millis(rec.ts)?;
+    let sess = rec.sess.unwrap_or_default().to_string();
+    let stmt = rec.stmt.unwrap_or_default().to_string();
+    match rec.execute_time_ms {
+        Some(exec_ms) => Some(SaturationSample::Exec {
+            instance: instance.to_string(),
+            sess,
+            stmt,
+            ts_ms,
+            exec_ms,
+            has_statement: has_statement(rec),
+        }),
+        None if has_statement(rec) => Some(SaturationSample::Start {
+            instance: instance.to_string(),
+            sess,
+            stmt,
+            ts_ms,
+        }),
+        None => None,
+    }
+}
+
+/// 一个实例在一个时间桶内的并发执行情况
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SaturationRow {
+    pub instance: String,
+    /// 时间桶起点（毫秒时间戳）
+    pub bucket_start_ms: i64,
+    /// 时间桶内开始执行的语句数
+    pub statements: u64,
+    /// 时间桶内同时执行的语句数的峰值
+    pub peak_concurrent: u64,
+    /// 时间桶内同时执行的语句数超过上限的总时长（毫秒）
+    pub over_limit_ms: i64,
+    pub limit: u64,
+}
+
+/// 按实例收集执行区间，计算各时间桶内同时执行的语句数；需要按时间顺序接收同一会话的记录
+#[derive(Debug)]
+pub struct SaturationTracker {
+    bucket_ms: i64,
+    /// (实例, 会话, 语句句柄) 上等待执行指标的语句开始时间
+    pending: HashMap<(String, String, String), i64>,
+    intervals: BTreeMap<String, Vec<(i64, i64)>>,
+}
+
+impl SaturationTracker {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms: bucket_ms.max(1),
+            pending: HashMap::new(),
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    pub fn add_sample(&mut self, s: SaturationSample) {
+        match s {
+            SaturationSample::Start {
+                instance,
+                sess,
+                stmt,
+                ts_ms,
+            } => {
+                self.pending.insert((instance, sess, stmt), ts_ms);
+            }
+            SaturationSample::Exec {
+                instance,
+                sess,
+                stmt,
+                ts_ms,
+                exec_ms,
+                has_statement,
+            } => {
+                let key = (instance, sess, stmt);
+                let pending = self.pending.remove(&key);
+                // 执行指标单独成行时，执行从语句记录的时间开始
+                let start = match pending {
+                    Some(start) if !has_statement => start,
+                    _ => ts_ms,
+                };
+                let end = start + exec_ms as i64;
+                self.intervals.entry(key.0).or_default().push((start, end));
+            }
+        }
+    }
+
+    /// 按实例、时间顺序返回各时间桶的峰值并发数；`all` 为 false 时只返回峰值超过 `limit` 的时间桶
+    pub fn rows(&self, limit: u64, all: bool) -> Vec<SaturationRow> {
+        let mut rows = Vec::new();
+        for (instance, intervals) in &self.intervals {
+            let mut buckets: BTreeMap<i64, SaturationRow> = BTreeMap::new();
+            // 区间端点按时间排序，同一时刻结束先于开始，首尾相接的语句不算同时执行
+            let mut points: Vec<(i64, i64)> = Vec::with_capacity(intervals.len() * 2);
+            for &(start, end) in intervals {
+                let idx = start.div_euclid(self.bucket_ms);
+                bucket_row(&mut buckets, instance, idx, self.bucket_ms, limit).statements += 1;
+                if end > start {
+                    points.push((start, 1));
+                    points.push((end, -1));
+                }
+            }
+            points.sort_unstable();
+            let mut current = 0i64;
+            for (i, &(t, delta)) in points.iter().enumerate() {
+                current += delta;
+                let Some(&(next, _)) = points.get(i + 1) else {
+                    break;
+                };
+                if current <= 0 || next <= t {
+                    continue;
+                }
+                // 把 [t, next) 这一段按时间桶切分
+                for idx in t.div_euclid(self.bucket_ms)..=(next - 1).div_euclid(self.bucket_ms) {
+                    let b_start = idx * self.bucket_ms;
+                    let overlap = next.min(b_start + self.bucket_ms) - t.max(b_start);
+                    let row = bucket_row(&mut buckets, instance, idx, self.bucket_ms, limit);
+                    row.peak_concurrent = row.peak_concurrent.max(current as u64);
+                    if current as u64 > limit {
+                        row.over_limit_ms += overlap;
+                    }
+                }
+            }
+            rows.extend(
+                buckets
+                    .into_values()
+                    .filter(|r| all || r.peak_concurrent > limit),
+            );
+        }
+        rows
+    }
+}
+
+fn bucket_row<'a>(
+    buckets: &'a mut BTreeMap<i64, SaturationRow>,
+    instance: &str,
+    idx: i64,
+    bucket_ms: i64,
+    limit: u64,
+) -> &'a mut SaturationRow {
+    buckets.entry(idx).or_insert_with(|| SaturationRow {
+        instance: instance.to_string(),
+        bucket_start_ms: idx * bucket_ms,
+        limit,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    #[test]
+    fn stitches_statements_and_reports_buckets_over_limit() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1
+2025-08-12 10:00:00.100 (EP[0] sess:0x2 thrd:2 user:A trxid:2 stmt:0x3 appname:app) [SEL] select 2 EXECTIME: 500(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:00.200 (EP[0] sess:0x3 thrd:3 user:A trxid:3 stmt:0x4 appname:app) [SEL] select 3 EXECTIME: 300(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:00.600 (EP[0] sess:0x4 thrd:4 user:A trxid:4 stmt:0x5 appname:app) [SEL] select 4 EXECTIME: 900(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:00:01.200 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) EXECTIME: 1200(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+";
+        let mut t = SaturationTracker::new(1000);
+        parse_records_with(log, |rec| t.add_sample(sample("DM1", &rec).unwrap()));
+
+        // 0x1 的语句在 [0, 1200) 内执行，与 0x2、0x3 在 [200, 500) 内同时执行
+        let rows = t.rows(2, false);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].statements, 4);
+        assert_eq!(rows[0].peak_concurrent, 3);
+        assert_eq!(rows[0].over_limit_ms, 300);
+
+        let all = t.rows(2, true);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].statements, 0);
+        assert_eq!(all[1].peak_concurrent, 2);
+        assert_eq!(all[1].over_limit_ms, 0);
+    }
+}
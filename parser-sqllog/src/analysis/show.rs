@@ -0,0 +1,209 @@
+//! 单条记录的人工阅读视图：字段逐行对齐、时间戳附带星期与毫秒时间戳、耗时换算为易读单位，
+//! SQL 文本按关键字、字符串、数字着色，可选格式化为多行（见 [`crate::analysis::sqlfmt`]）
+
+use std::fmt;
+
+use dm_database_parser::{RecordCategory, parser::ParsedRecord, sql, ts_to_epoch_millis};
+
+use crate::{
+    analysis::sqlfmt::{format_sql, highlight_sql},
+    pipeline::Source,
+};
+
+/// 字段名的对齐宽度
+const LABEL_WIDTH: usize = 10;
+
+const LABEL_STYLE: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// 一条记录的阅读视图，通过 [`fmt::Display`] 渲染为多行文本
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordView {
+    pub path: String,
+    pub offset: u64,
+    pub record_id: String,
+    pub ts: String,
+    pub ep: String,
+    pub sess: String,
+    pub thrd: String,
+    pub user: String,
+    pub trxid: String,
+    pub stmt: String,
+    pub appname: String,
+    pub ip: String,
+    pub tag: String,
+    pub category: RecordCategory,
+    pub exec_time_ms: Option<u64>,
+    pub row_count: Option<u64>,
+    pub exec_id: Option<u64>,
+    pub sql: String,
+    pub params: String,
+    /// 是否输出 ANSI 颜色
+    pub color: bool,
+    /// 是否把 SQL 格式化为多行缩进的形式
+    pub format_sql: bool,
+}
+
+impl RecordView {
+    pub fn new(src: &Source, rec: &ParsedRecord<'_>) -> Self {
+        let text = |v: Option<&str>| v.unwrap_or_default().to_string();
+        Self {
+            path: src.path.display().to_string(),
+            offset: rec.offset,
+            record_id: src.record_id(rec),
+            ts: rec.ts.to_string(),
+            ep: text(rec.ep),
+            sess: text(rec.sess),
+            thrd: text(rec.thrd),
+            user: text(rec.user),
+            trxid: text(rec.trxid),
+            stmt: text(rec.stmt),
+            appname: text(rec.appname),
+            ip: text(rec.ip),
+            tag: sql::split_tag(&rec.body).0.unwrap_or_default().to_string(),
+            category: sql::categorize(&rec.body),
+            exec_time_ms: rec.execute_time_ms,
+            row_count: rec.row_count,
+            exec_id: rec.execute_id,
+            sql: sql::sql_text(&rec.body).to_string(),
+            params: sql::params_text(&rec.body).unwrap_or_default().to_string(),
+            color: false,
+            format_sql: false,
+        }
+    }
+
+    pub fn set_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn set_format_sql(mut self, format_sql: bool) -> Self {
+        self.format_sql = format_sql;
+        self
+    }
+
+    fn label_style(&self) -> (&'static str, &'static str) {
+        if self.color {
+            (LABEL_STYLE, RESET)
+        } else {
+            ("", "")
+        }
+    }
+
+    /// 写出一行 `字段名 值`，值为空时省略该行
+    fn field(&self, f: &mut fmt::Formatter<'_>, label: &str, value: &str) -> fmt::Result {
+        if value.is_empty() {
+            return Ok(());
+        }
+        let (bold, reset) = self.label_style();
+        writeln!(f, "{bold}{label:<LABEL_WIDTH$}{reset}{value}")
+    }
+
+    /// 写出字段名，其后各行缩进写出多行的值
+    fn block(&self, f: &mut fmt::Formatter<'_>, label: &str, value: &str) -> fmt::Result {
+        let (bold, reset) = self.label_style();
+        writeln!(f, "{bold}{label}{reset}")?;
+        for line in value.lines() {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RecordView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "── {} @ {} ({}) ──",
+            self.path, self.offset, self.record_id
+        )?;
+        self.field(f, "ts", &decode_ts(&self.ts))?;
+        self.field(f, "ep", &self.ep)?;
+        self.field(f, "sess", &self.sess)?;
+        self.field(f, "thrd", &self.thrd)?;
+        self.field(f, "user", &self.user)?;
+        self.field(f, "trxid", &self.trxid)?;
+        self.field(f, "stmt", &self.stmt)?;
+        self.field(f, "appname", &self.appname)?;
+        self.field(f, "ip", &self.ip)?;
+        let category = match self.tag.as_str() {
+            "" => self.category.to_string(),
+            tag => format!("{} [{}]", self.category, tag),
+        };
+        self.field(f, "category", &category)?;
+        let exec_time = self.exec_time_ms.map(|ms| match ms {
+            0..1000 => format!("{ms} ms"),
+            _ => format!("{ms} ms ({})", humanize_ms(ms)),
+        });
+        self.field(f, "exec_time", exec_time.as_deref().unwrap_or_default())?;
+        let num = |v: Option<u64>| v.map(|n| n.to_string()).unwrap_or_default();
+        self.field(f, "row_count", &num(self.row_count))?;
+        self.field(f, "exec_id", &num(self.exec_id))?;
+        let sql = if self.format_sql {
+            format_sql(&self.sql)
+        } else {
+            self.sql.clone()
+        };
+        if self.color {
+            self.block(f, "sql", &highlight_sql(&sql))?;
+        } else {
+            self.block(f, "sql", &sql)?;
+        }
+        if !self.params.is_empty() {
+            self.block(f, "params", &self.params)?;
+        }
+        Ok(())
+    }
+}
+
+/// 时间戳附带星期与毫秒时间戳，格式不合法时原样返回
+fn decode_ts(ts: &str) -> String {
+    const WEEKDAYS: [&str; 7] = ["周日", "周一", "周二", "周三", "周四", "周五", "周六"];
+    match ts_to_epoch_millis(ts) {
+        Some(ms) => {
+            // 1970-01-01 为周四
+            let weekday = (ms.div_euclid(86_400_000) + 4).rem_euclid(7) as usize;
+            format!("{ts}  {}  (epoch_ms {ms})", WEEKDAYS[weekday])
+        }
+        None => ts.to_string(),
+    }
+}
+
+/// 把毫秒数换算为易读的时长，如 `2.35 s`、`3 min 12 s`、`1 h 05 min`
+pub fn humanize_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    match ms {
+        0..1000 => format!("{ms} ms"),
+        1000..60_000 => format!("{:.2} s", ms as f64 / 1000.0),
+        60_000..3_600_000 => format!("{} min {} s", secs / 60, secs % 60),
+        _ => format!("{} h {:02} min", secs / 3600, secs / 60 % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_record;
+    use std::path::PathBuf;
+
+    #[test]
+    fn renders_aligned_fields_and_highlighted_sql() {
+        let rec = parse_record(
+            "2025-08-12 10:57:09.562 (EP[1] sess:0x1 thrd:7 user:SYSDBA trxid:9 stmt:0x2 appname:disql) \
+             [SEL] select * from t where a = 'x' and b = 12 EXECTIME: 83456(ms) ROWCOUNT: 3(rows) EXEC_ID: 5.",
+        );
+        let src = Source::new(PathBuf::from("a.log"), "");
+        let text = RecordView::new(&src, &rec).to_string();
+        assert!(
+            text.contains("ts        2025-08-12 10:57:09.562  周二  (epoch_ms 1754996229562)\n")
+        );
+        assert!(text.contains("category  statement [SEL]\n"));
+        assert!(text.contains("exec_time 83456 ms (1 min 23 s)\n"));
+        assert!(text.contains("sql\n  select * from t where a = 'x' and b = 12\n"));
+        assert!(!text.contains("ip "));
+        assert!(!text.contains('\x1b'));
+
+        assert_eq!(humanize_ms(2345), "2.35 s");
+        assert_eq!(humanize_ms(3_900_000), "1 h 05 min");
+    }
+}
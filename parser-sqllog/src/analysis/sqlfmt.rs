@@ -0,0 +1,183 @@
+//! SQL 文本的终端展示：按关键字、字符串、数字、绑定参数与注释着色；启用 `sql-format` 特性时
+//! 还可以把单行的长语句格式化为多行缩进的形式，便于排查时阅读
+
+/// 当前构建是否支持 [`format_sql`]（启用了 `sql-format` 特性）
+pub const FORMAT_AVAILABLE: bool = cfg!(feature = "sql-format");
+
+/// 着色时视为关键字的单词（小写）
+const KEYWORDS: &[&str] = &[
+    "all",
+    "alter",
+    "and",
+    "as",
+    "asc",
+    "begin",
+    "between",
+    "by",
+    "call",
+    "case",
+    "commit",
+    "connect",
+    "create",
+    "cross",
+    "delete",
+    "desc",
+    "distinct",
+    "drop",
+    "else",
+    "end",
+    "exists",
+    "fetch",
+    "for",
+    "from",
+    "full",
+    "group",
+    "having",
+    "in",
+    "inner",
+    "insert",
+    "intersect",
+    "into",
+    "is",
+    "join",
+    "left",
+    "like",
+    "limit",
+    "merge",
+    "minus",
+    "not",
+    "null",
+    "offset",
+    "on",
+    "or",
+    "order",
+    "outer",
+    "over",
+    "partition",
+    "right",
+    "rollback",
+    "rownum",
+    "select",
+    "set",
+    "start",
+    "table",
+    "then",
+    "top",
+    "truncate",
+    "union",
+    "update",
+    "using",
+    "values",
+    "when",
+    "where",
+    "with",
+];
+
+const KEYWORD_STYLE: &str = "\x1b[1;34m";
+const STRING_STYLE: &str = "\x1b[32m";
+const NUMBER_STYLE: &str = "\x1b[36m";
+const PARAM_STYLE: &str = "\x1b[33m";
+const COMMENT_STYLE: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// 把 SQL 格式化为多行缩进的形式，关键字大小写与绑定参数保持原样；
+/// 未启用 `sql-format` 特性时原样返回
+#[cfg(feature = "sql-format")]
+pub fn format_sql(sql: &str) -> String {
+    use sqlformat::{FormatOptions, Indent, QueryParams};
+
+    let opts = FormatOptions {
+        indent: Indent::Spaces(2),
+        uppercase: false,
+        lines_between_queries: 1,
+    };
+    sqlformat::format(sql, &QueryParams::None, opts)
+}
+
+#[cfg(not(feature = "sql-format"))]
+pub fn format_sql(sql: &str) -> String {
+    sql.to_string()
+}
+
+/// 用 ANSI 颜色标出 SQL 文本中的关键字、字符串、数字、绑定参数与注释
+pub fn highlight_sql(sql: &str) -> String {
+    let bytes = sql.as_bytes();
+    let n = bytes.len();
+    let mut out = String::with_capacity(n + n / 4);
+    let styled = |out: &mut String, style: &str, text: &str| {
+        out.push_str(style);
+        out.push_str(text);
+        out.push_str(RESET);
+    };
+    let mut i = 0;
+    while i < n {
+        let b = bytes[i];
+        let start = i;
+        if b == b'\'' {
+            i = sql[i + 1..].find('\'').map_or(n, |p| i + 1 + p + 1);
+            styled(&mut out, STRING_STYLE, &sql[start..i]);
+        } else if sql[i..].starts_with("--") {
+            i = sql[i..].find('\n').map_or(n, |p| i + p);
+            styled(&mut out, COMMENT_STYLE, &sql[start..i]);
+        } else if sql[i..].starts_with("/*") {
+            i = sql[i + 2..].find("*/").map_or(n, |p| i + 2 + p + 2);
+            styled(&mut out, COMMENT_STYLE, &sql[start..i]);
+        } else if b.is_ascii_alphabetic() || b == b'_' {
+            while i < n
+                && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'$' | b'#'))
+            {
+                i += 1;
+            }
+            let word = &sql[start..i];
+            if KEYWORDS.contains(&word.to_ascii_lowercase().as_str()) {
+                styled(&mut out, KEYWORD_STYLE, word);
+            } else {
+                out.push_str(word);
+            }
+        } else if b.is_ascii_digit() {
+            while i < n && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            styled(&mut out, NUMBER_STYLE, &sql[start..i]);
+        } else if b == b'?'
+            || (b == b':' && bytes.get(i + 1).is_some_and(u8::is_ascii_alphanumeric))
+        {
+            i += 1;
+            while i < n && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            styled(&mut out, PARAM_STYLE, &sql[start..i]);
+        } else {
+            i += sql[i..].chars().next().map_or(1, char::len_utf8);
+            out.push_str(&sql[start..i]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_and_formats_sql() {
+        assert_eq!(
+            highlight_sql("select 'a' from t -- x"),
+            "\x1b[1;34mselect\x1b[0m \x1b[32m'a'\x1b[0m \x1b[1;34mfrom\x1b[0m t \x1b[2m-- x\x1b[0m"
+        );
+        assert_eq!(
+            highlight_sql("where id = ? and n > 1.5"),
+            "\x1b[1;34mwhere\x1b[0m id = \x1b[33m?\x1b[0m \x1b[1;34mand\x1b[0m n > \x1b[36m1.5\x1b[0m"
+        );
+
+        let sql = "select a, b from t where a = ? and b in (1, 2)";
+        if FORMAT_AVAILABLE {
+            let formatted = format_sql(sql);
+            assert!(formatted.lines().count() > 1);
+            assert!(formatted.contains("?"));
+            assert_eq!(formatted.split_whitespace().next(), Some("select"));
+        } else {
+            assert_eq!(format_sql(sql), sql);
+        }
+    }
+}
@@ -0,0 +1,756 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::{Complexity, RecordMetrics, sql};
+use serde::{Deserialize, Serialize};
+
+use crate::sort::ExternalSorter;
+
+/// 统计结果的分组维度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// 不分组，所有输入合并统计
+    #[default]
+    None,
+    /// 按实例（sqllog 文件名中的实例名）分组
+    Instance,
+    /// 按 EP 节点分组（DSC 集群）
+    Ep,
+    /// 按命中的标签规则（`[[rules.rule]]`）分组，多个标签以逗号连接
+    Tags,
+}
+
+/// 单次执行的统计样本
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StatsSample {
+    pub group: String,
+    pub fingerprint: String,
+    pub exec_ms: u64,
+    pub rows: u64,
+    /// 该次执行的完整语句，需要示例语句时由调用方填写
+    #[serde(default)]
+    pub example: Option<StatementExample>,
+}
+
+/// 某个指纹的一条示例语句：完整的 SQL 文本与绑定参数
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StatementExample {
+    pub ts: String,
+    pub exec_ms: u64,
+    pub sql: String,
+    /// 绑定参数块 `PARAMS(...)={...}`，没有参数时为空
+    pub params: String,
+}
+
+impl StatementExample {
+    pub fn from_record(rec: &ParsedRecord<'_>) -> Self {
+        Self {
+            ts: rec.ts.to_string(),
+            exec_ms: rec.execute_time_ms.unwrap_or(0),
+            sql: sql::sql_text(&rec.body).to_string(),
+            params: sql::params_text(&rec.body).unwrap_or_default().to_string(),
+        }
+    }
+
+    /// 单行展示：SQL 文本后接参数块
+    fn display(&self) -> String {
+        if self.params.is_empty() {
+            self.sql.clone()
+        } else {
+            format!("{} {}", self.sql, self.params)
+        }
+    }
+}
+
+/// 从带 EXECTIME 的记录摘要中提取统计样本，`instance` 为记录所属实例
+pub fn sample(m: RecordMetrics, group_by: GroupBy, instance: &str) -> Option<StatsSample> {
+    let exec_ms = m.execute_time_ms?;
+    let group = match group_by {
+        GroupBy::None => String::new(),
+        GroupBy::Instance => instance.to_string(),
+        GroupBy::Ep => m.ep.unwrap_or_default(),
+        GroupBy::Tags => m.tags,
+    };
+    Some(StatsSample {
+        group,
+        fingerprint: m.fingerprint,
+        exec_ms,
+        rows: m.row_count.unwrap_or(0),
+        example: None,
+    })
+}
+
+/// 某一分组下单个指纹的统计
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StatsRow {
+    /// 分组标签；不分组时为空
+    pub group: String,
+    pub fingerprint: String,
+    pub executions: u64,
+    pub total_ms: u64,
+    pub avg_ms: f64,
+    pub max_ms: u64,
+    pub total_rows: u64,
+    /// 被 `--dedup-window` 折叠、未计入上述统计的重复执行次数
+    pub duplicates: u64,
+    /// 指纹的复杂度指标，见 [`Complexity`]；在输出前计算
+    pub sql_len: usize,
+    pub joins: usize,
+    pub order_by: bool,
+    pub group_by: bool,
+    pub max_in_list: usize,
+    /// 最慢一次执行的时间、完整 SQL 与绑定参数；未要求示例语句时为空
+    pub slowest_ts: String,
+    pub slowest_sql: String,
+    pub slowest_params: String,
+    /// 其余示例语句（SQL 后接参数块），每行一条
+    pub examples: String,
+}
+
+impl StatsRow {
+    /// 表格输出仅在 `--wide` 时显示的列：复杂度指标与示例语句
+    pub const WIDE_ONLY: &'static [&'static str] = &[
+        "sql_len",
+        "joins",
+        "order_by",
+        "group_by",
+        "max_in_list",
+        "slowest_ts",
+        "slowest_sql",
+        "slowest_params",
+        "examples",
+    ];
+
+    /// 合并同一 (分组, 指纹) 的另一份部分统计
+    fn merge(&mut self, other: &StatsRow) {
+        self.executions += other.executions;
+        self.total_ms += other.total_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+        self.total_rows += other.total_rows;
+        self.duplicates += other.duplicates;
+    }
+
+    fn set_complexity(&mut self) {
+        let c = Complexity::of(&self.fingerprint);
+        self.sql_len = c.sql_len;
+        self.joins = c.joins;
+        self.order_by = c.order_by;
+        self.group_by = c.group_by;
+        self.max_in_list = c.max_in_list;
+    }
+}
+
+/// 一个 (分组, 指纹) 的部分统计与保留的示例语句，溢写时整体写入临时文件
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+struct Group {
+    row: StatsRow,
+    /// 最慢的一条在最前，其余按首次出现的顺序排列
+    examples: Vec<StatementExample>,
+}
+
+impl Group {
+    fn merge(&mut self, other: Group, keep: usize) {
+        self.row.merge(&other.row);
+        for ex in other.examples {
+            self.add_example(ex, keep);
+        }
+    }
+
+    /// 保留最多 `keep` 条示例：始终保留最慢的一条，其余取最先出现的不同语句
+    fn add_example(&mut self, ex: StatementExample, keep: usize) {
+        if keep == 0 {
+            return;
+        }
+        if self.examples.first().is_none_or(|s| ex.exec_ms > s.exec_ms) {
+            self.examples.insert(0, ex);
+        } else if self.examples.len() < keep
+            && !self
+                .examples
+                .iter()
+                .any(|e| e.sql == ex.sql && e.params == ex.params)
+        {
+            self.examples.push(ex);
+        }
+        self.examples.truncate(keep);
+    }
+
+    /// 把示例语句写入统计行的输出列
+    fn into_row(mut self) -> StatsRow {
+        let mut examples = self.examples.into_iter();
+        if let Some(slowest) = examples.next() {
+            self.row.slowest_ts = slowest.ts;
+            self.row.slowest_sql = slowest.sql;
+            self.row.slowest_params = slowest.params;
+        }
+        self.row.examples = examples.map(|e| e.display()).collect::<Vec<_>>().join("\n");
+        self.row
+    }
+}
+
+/// 按 (分组, 指纹) 汇总执行次数与耗时
+///
+/// 设置了分组数上限时，超出上限的部分聚合结果排好序溢写到临时文件，结束时归并，
+/// 内存占用不随输入中不同指纹的数量增长。
+#[derive(Debug, Default)]
+pub struct StatsAggregator {
+    group_by: GroupBy,
+    groups: HashMap<(String, String), Group>,
+    examples: usize,
+    max_groups: usize,
+    spill_dir: PathBuf,
+    spill: Option<ExternalSorter<Group>>,
+    save_state: Option<PathBuf>,
+}
+
+impl StatsAggregator {
+    pub fn new(group_by: GroupBy) -> Self {
+        Self {
+            group_by,
+            groups: HashMap::new(),
+            examples: 0,
+            max_groups: 0,
+            spill_dir: PathBuf::new(),
+            spill: None,
+            save_state: None,
+        }
+    }
+
+    /// 内存中最多保留 `max_groups` 个 (分组, 指纹)，超出后溢写到 `dir` 下的临时文件；0 表示不限制
+    pub fn set_spill(mut self, max_groups: usize, dir: impl Into<PathBuf>) -> Self {
+        self.max_groups = max_groups;
+        self.spill_dir = dir.into();
+        self
+    }
+
+    /// 每个指纹保留最多 `examples` 条示例语句（包括最慢的一条）；0 表示不保留
+    pub fn set_examples(mut self, examples: usize) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    /// 已溢写的临时文件数
+    pub fn spilled(&self) -> usize {
+        self.spill.as_ref().map_or(0, ExternalSorter::spilled)
+    }
+
+    /// 解析属于实例 `instance` 的日志文本并累加统计；只统计带 EXECTIME 的记录
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str, instance: &str) -> io::Result<()> {
+        let mut result = Ok(());
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if result.is_ok()
+                && let Some(s) = sample(RecordMetrics::from_record(&rec), self.group_by, instance)
+            {
+                result = self.add_sample(s);
+            }
+        });
+        result
+    }
+
+    /// 累加一次执行；分组数达到上限时溢写
+    pub fn add_sample(&mut self, s: StatsSample) -> io::Result<()> {
+        let keep = self.examples;
+        let group = self.group(&s);
+        let row = &mut group.row;
+        row.executions += 1;
+        row.total_ms += s.exec_ms;
+        row.max_ms = row.max_ms.max(s.exec_ms);
+        row.total_rows += s.rows;
+        if let Some(ex) = s.example {
+            group.add_example(ex, keep);
+        }
+        self.maybe_spill()
+    }
+
+    /// 记录一次被去重折叠的重复执行：只计入 `duplicates`，不影响耗时统计
+    pub fn add_duplicate(&mut self, s: StatsSample) -> io::Result<()> {
+        self.group(&s).row.duplicates += 1;
+        self.maybe_spill()
+    }
+
+    fn group(&mut self, s: &StatsSample) -> &mut Group {
+        self.groups
+            .entry((s.group.clone(), s.fingerprint.clone()))
+            .or_insert_with(|| Group {
+                row: StatsRow {
+                    group: s.group.clone(),
+                    fingerprint: s.fingerprint.clone(),
+                    ..Default::default()
+                },
+                examples: Vec::new(),
+            })
+    }
+
+    /// 分组数达到上限时溢写
+    fn maybe_spill(&mut self) -> io::Result<()> {
+        if self.max_groups > 0 && self.groups.len() >= self.max_groups {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// 把内存中的部分聚合结果全部交给外部排序器，按 (分组, 指纹) 排序后写出为一个临时文件
+    fn spill(&mut self) -> io::Result<()> {
+        let sorter = self
+            .spill
+            .get_or_insert_with(|| ExternalSorter::new(self.max_groups, &self.spill_dir));
+        for (_, group) in self.groups.drain() {
+            sorter.push(spill_key(&group.row), group)?;
+        }
+        Ok(())
+    }
+
+    /// 结束统计时把合并后的完整聚合状态写入 `path`，供之后用 `stats merge` 与其他状态合并。
+    ///
+    /// 扩展名为 `.json` / `.jsonl` 时每行一个 JSON 对象，否则为 MessagePack。
+    pub fn set_save_state(mut self, path: Option<PathBuf>) -> Self {
+        self.save_state = path;
+        self
+    }
+
+    /// 合并一份之前保存的聚合状态；分组维度必须一致，示例语句数取两者中较大的
+    pub fn add_state(&mut self, mut state: StatsState) -> io::Result<()> {
+        if state.header.group_by != self.group_by {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "状态文件 {} 的分组维度为 {:?}，与 {:?} 不一致",
+                    state.path.display(),
+                    state.header.group_by,
+                    self.group_by
+                ),
+            ));
+        }
+        self.examples = self.examples.max(state.header.examples);
+        while let Some(group) = state.next_group()? {
+            let keep = self.examples;
+            match self
+                .groups
+                .entry((group.row.group.clone(), group.row.fingerprint.clone()))
+            {
+                Entry::Occupied(e) => e.into_mut().merge(group, keep),
+                Entry::Vacant(e) => {
+                    e.insert(group);
+                }
+            }
+            self.maybe_spill()?;
+        }
+        Ok(())
+    }
+
+    /// 结束统计并返回统计行：按分组排序，组内按总耗时降序；`top` 限制每组保留的行数。
+    ///
+    /// 发生过溢写时按 (分组, 指纹) 归并各临时文件，合并同一指纹的部分统计。
+    /// 设置了 [`set_save_state`](Self::set_save_state) 时同时写出裁剪前的完整聚合状态。
+    pub fn finish(mut self, top: Option<usize>) -> io::Result<Vec<StatsRow>> {
+        let mut state = match self.save_state.take() {
+            Some(path) => Some(StateWriter::create(
+                &path,
+                &StateHeader {
+                    version: STATE_VERSION,
+                    group_by: self.group_by,
+                    examples: self.examples,
+                },
+            )?),
+            None => None,
+        };
+        let mut rows = Vec::new();
+        let mut prune_at = 1024;
+        self.for_each_group(|group| {
+            if let Some(state) = state.as_mut() {
+                state.write(&group)?;
+            }
+            rows.push(group.into_row());
+            // 已合并完整的行可以提前按 top 裁剪，避免保留全部指纹
+            if top.is_some() && rows.len() >= prune_at {
+                rows = rank(std::mem::take(&mut rows), top);
+                prune_at = (rows.len() * 2).max(1024);
+            }
+            Ok(())
+        })?;
+        if let Some(state) = state {
+            state.finish()?;
+        }
+        let mut rows = rank(rows, top);
+        for row in &mut rows {
+            row.set_complexity();
+        }
+        Ok(rows)
+    }
+
+    /// 依次交出合并完整的各 (分组, 指纹) 部分统计
+    fn for_each_group(self, mut f: impl FnMut(Group) -> io::Result<()>) -> io::Result<()> {
+        let Some(mut sorter) = self.spill else {
+            return self.groups.into_values().try_for_each(f);
+        };
+        for (_, group) in self.groups {
+            sorter.push(spill_key(&group.row), group)?;
+        }
+        let mut current: Option<Group> = None;
+        for group in sorter.finish()? {
+            let group = group?;
+            if let Some(cur) = current.as_mut()
+                && cur.row.group == group.row.group
+                && cur.row.fingerprint == group.row.fingerprint
+            {
+                cur.merge(group, self.examples);
+                continue;
+            }
+            if let Some(done) = current.replace(group) {
+                f(done)?;
+            }
+        }
+        current.map_or(Ok(()), f)
+    }
+}
+
+/// 聚合状态文件的格式版本，不兼容的改动时递增
+const STATE_VERSION: u32 = 1;
+
+/// 聚合状态文件的首项
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct StateHeader {
+    version: u32,
+    group_by: GroupBy,
+    examples: usize,
+}
+
+/// 聚合状态文件的编码，按扩展名选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StateFormat {
+    Json,
+    MsgPack,
+}
+
+impl StateFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json" | "jsonl") => Self::Json,
+            _ => Self::MsgPack,
+        }
+    }
+}
+
+/// 顺序写出聚合状态：首项为 [`StateHeader`]，其后每项为一个 (分组, 指纹) 的部分统计
+struct StateWriter {
+    format: StateFormat,
+    out: BufWriter<File>,
+}
+
+impl StateWriter {
+    fn create(path: &Path, header: &StateHeader) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut writer = Self {
+            format: StateFormat::from_path(path),
+            out: BufWriter::new(File::create(path)?),
+        };
+        writer.write(header)?;
+        Ok(writer)
+    }
+
+    fn write<T: Serialize>(&mut self, item: &T) -> io::Result<()> {
+        match self.format {
+            StateFormat::Json => {
+                serde_json::to_writer(&mut self.out, item)?;
+                self.out.write_all(b"\n")
+            }
+            StateFormat::MsgPack => {
+                rmp_serde::encode::write_named(&mut self.out, item).map_err(io::Error::other)
+            }
+        }
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// 一份已保存的聚合状态，用 [`StatsAggregator::add_state`] 合并
+#[derive(Debug)]
+pub struct StatsState {
+    path: PathBuf,
+    format: StateFormat,
+    header: StateHeader,
+    reader: BufReader<File>,
+}
+
+impl StatsState {
+    /// 打开状态文件并读取首项，版本不兼容时返回错误
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut state = Self {
+            format: StateFormat::from_path(&path),
+            reader: BufReader::new(File::open(&path).map_err(|e| read_error(&path, e))?),
+            header: StateHeader {
+                version: STATE_VERSION,
+                group_by: GroupBy::None,
+                examples: 0,
+            },
+            path,
+        };
+        let header: StateHeader = state.next()?.ok_or_else(|| state.invalid("文件为空"))?;
+        if header.version != STATE_VERSION {
+            return Err(state.invalid(&format!("不支持的版本 {}", header.version)));
+        }
+        state.header = header;
+        Ok(state)
+    }
+
+    /// 保存状态时的分组维度
+    pub fn group_by(&self) -> GroupBy {
+        self.header.group_by
+    }
+
+    fn next_group(&mut self) -> io::Result<Option<Group>> {
+        self.next()
+    }
+
+    fn next<T: serde::de::DeserializeOwned>(&mut self) -> io::Result<Option<T>> {
+        match self.format {
+            StateFormat::Json => {
+                let mut line = String::new();
+                while line.trim().is_empty() {
+                    line.clear();
+                    if self
+                        .reader
+                        .read_line(&mut line)
+                        .map_err(|e| read_error(&self.path, e))?
+                        == 0
+                    {
+                        return Ok(None);
+                    }
+                }
+                serde_json::from_str(&line)
+                    .map(Some)
+                    .map_err(|e| self.invalid(&e.to_string()))
+            }
+            StateFormat::MsgPack => {
+                if self
+                    .reader
+                    .fill_buf()
+                    .map_err(|e| read_error(&self.path, e))?
+                    .is_empty()
+                {
+                    return Ok(None);
+                }
+                rmp_serde::decode::from_read(&mut self.reader)
+                    .map(Some)
+                    .map_err(|e| self.invalid(&e.to_string()))
+            }
+        }
+    }
+
+    fn invalid(&self, msg: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("无效的状态文件 {}: {}", self.path.display(), msg),
+        )
+    }
+}
+
+/// 为读取状态文件时的 I/O 错误附上文件名，错误类型不变
+fn read_error(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!("无法读取状态文件 {}: {e}", path.display()),
+    )
+}
+
+/// 溢写时的排序键，使同一 (分组, 指纹) 的部分统计在归并时相邻
+fn spill_key(row: &StatsRow) -> String {
+    format!("{}\0{}", row.group, row.fingerprint)
+}
+
+/// 计算平均耗时后排序：按分组排序，组内按总耗时降序；`top` 限制每组保留的行数
+fn rank(mut rows: Vec<StatsRow>, top: Option<usize>) -> Vec<StatsRow> {
+    for row in &mut rows {
+        row.avg_ms = row.total_ms as f64 / row.executions.max(1) as f64;
+    }
+    rows.sort_by(|a, b| {
+        a.group
+            .cmp(&b.group)
+            .then(b.total_ms.cmp(&a.total_ms))
+            .then(a.fingerprint.cmp(&b.fingerprint))
+    });
+    if let Some(top) = top {
+        let mut kept: HashMap<String, usize> = HashMap::new();
+        rows.retain(|r| {
+            let n = kept.entry(r.group.clone()).or_default();
+            *n += 1;
+            *n <= top
+        });
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const LOG_EP0: &str = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = 1 EXECTIME: 10(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = 2 EXECTIME: 30(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+";
+    const LOG_EP1: &str = "2025-08-12 10:57:09.563 (EP[1] sess:0x3 thrd:2 user:B trxid:1 stmt:0x4 appname:app) [SEL] select * from t where id = 3 EXECTIME: 100(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:57:09.564 (EP[1] sess:0x3 thrd:2 user:B trxid:1 stmt:0x4 appname:app) [UPD] update t set a = 1 EXECTIME: 5(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+";
+
+    #[test]
+    fn aggregates_without_grouping() {
+        let mut agg = StatsAggregator::new(GroupBy::None);
+        agg.add_text(LOG_EP0, "DM1").unwrap();
+        agg.add_text(LOG_EP1, "DM2").unwrap();
+        let rows = agg.finish(None).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].fingerprint, "select * from t where id = ?");
+        assert_eq!(rows[0].executions, 3);
+        assert_eq!(rows[0].total_ms, 140);
+        assert_eq!(rows[0].max_ms, 100);
+        assert_eq!(rows[0].sql_len, rows[0].fingerprint.len());
+        assert_eq!(rows[0].joins, 0);
+        assert!(!rows[0].order_by);
+    }
+
+    #[test]
+    fn groups_by_instance_and_ep() {
+        let mut by_instance = StatsAggregator::new(GroupBy::Instance);
+        by_instance.add_text(LOG_EP0, "DM1").unwrap();
+        by_instance.add_text(LOG_EP1, "DM2").unwrap();
+        let rows = by_instance.finish(None).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].group, "DM1");
+        assert!((rows[0].avg_ms - 20.0).abs() < 1e-9);
+
+        let mut by_ep = StatsAggregator::new(GroupBy::Ep);
+        by_ep.add_text(LOG_EP0, "DM1").unwrap();
+        by_ep.add_text(LOG_EP1, "DM1").unwrap();
+        let rows = by_ep.finish(Some(1)).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].group, "EP[0]");
+        assert_eq!(rows[1].group, "EP[1]");
+        assert_eq!(rows[1].total_ms, 100);
+    }
+
+    #[test]
+    fn spills_and_merges_partial_aggregates() {
+        let dir = tempdir().unwrap();
+        let samples: Vec<StatsSample> = (0..300u64)
+            .map(|i| StatsSample {
+                group: format!("EP[{}]", i % 2),
+                fingerprint: format!("select {}", i % 37),
+                exec_ms: i,
+                rows: 1,
+                example: None,
+            })
+            .collect();
+
+        let mut in_memory = StatsAggregator::new(GroupBy::Ep);
+        let mut spilled = StatsAggregator::new(GroupBy::Ep).set_spill(5, dir.path());
+        for s in &samples {
+            in_memory.add_sample(s.clone()).unwrap();
+            spilled.add_sample(s.clone()).unwrap();
+        }
+        assert!(spilled.spilled() > 1);
+        assert_eq!(
+            spilled.finish(Some(3)).unwrap(),
+            in_memory.finish(Some(3)).unwrap()
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn saves_and_merges_states() {
+        let dir = tempdir().unwrap();
+        let mut whole = StatsAggregator::new(GroupBy::Ep);
+        whole.add_text(LOG_EP0, "DM1").unwrap();
+        whole.add_text(LOG_EP1, "DM1").unwrap();
+        let expected = whole.finish(None).unwrap();
+
+        let day1 = dir.path().join("day1.state");
+        let day2 = dir.path().join("day2.json");
+        for (log, path) in [(LOG_EP0, &day1), (LOG_EP1, &day2)] {
+            let mut agg = StatsAggregator::new(GroupBy::Ep).set_save_state(Some(path.clone()));
+            agg.add_text(log, "DM1").unwrap();
+            agg.add_text(LOG_EP0, "DM1").unwrap();
+            agg.finish(Some(1)).unwrap();
+        }
+
+        // 两份状态共包含三份 LOG_EP0，EP[1] 只来自 day2
+        let mut merged = StatsAggregator::new(GroupBy::Ep).set_spill(1, dir.path());
+        for path in [&day1, &day2] {
+            let state = StatsState::open(path).unwrap();
+            assert_eq!(state.group_by(), GroupBy::Ep);
+            merged.add_state(state).unwrap();
+        }
+        let rows = merged.finish(None).unwrap();
+        assert_eq!(rows.len(), expected.len());
+        assert_eq!(rows[0].group, "EP[0]");
+        assert_eq!(rows[0].executions, 3 * expected[0].executions);
+        assert_eq!(rows[0].total_ms, 3 * expected[0].total_ms);
+        assert_eq!(rows[1..], expected[1..]);
+
+        let mut other = StatsAggregator::new(GroupBy::None);
+        assert!(other.add_state(StatsState::open(&day1).unwrap()).is_err());
+
+        let missing = dir.path().join("missing.state");
+        let err = StatsState::open(&missing).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("missing.state"));
+    }
+
+    #[test]
+    fn keeps_slowest_and_distinct_examples() {
+        let dir = tempdir().unwrap();
+        let samples: Vec<StatsSample> = [(5, "1"), (5, "1"), (40, "2"), (7, "3"), (9, "4")]
+            .into_iter()
+            .map(|(ms, id)| StatsSample {
+                group: String::new(),
+                fingerprint: "select * from t where id = ?".to_string(),
+                exec_ms: ms,
+                rows: 1,
+                example: Some(StatementExample {
+                    ts: format!("2025-08-12 10:57:09.00{id}"),
+                    exec_ms: ms,
+                    sql: "select * from t where id = ?".to_string(),
+                    params: format!("PARAMS(SEQNO, TYPE, DATA)={{(0, INT, {id})}}"),
+                }),
+            })
+            .collect();
+
+        let mut in_memory = StatsAggregator::new(GroupBy::None).set_examples(3);
+        let mut spilled = StatsAggregator::new(GroupBy::None)
+            .set_examples(3)
+            .set_spill(1, dir.path());
+        for s in &samples {
+            in_memory.add_sample(s.clone()).unwrap();
+            spilled.add_sample(s.clone()).unwrap();
+        }
+        let rows = in_memory.finish(None).unwrap();
+        assert_eq!(rows[0].slowest_ts, "2025-08-12 10:57:09.002");
+        assert_eq!(
+            rows[0].slowest_params,
+            "PARAMS(SEQNO, TYPE, DATA)={(0, INT, 2)}"
+        );
+        assert_eq!(
+            rows[0].examples,
+            "select * from t where id = ? PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)}\n\
+             select * from t where id = ? PARAMS(SEQNO, TYPE, DATA)={(0, INT, 3)}"
+        );
+        assert_eq!(spilled.finish(None).unwrap(), rows);
+
+        let mut none = StatsAggregator::new(GroupBy::None);
+        none.add_sample(samples[0].clone()).unwrap();
+        assert!(none.finish(None).unwrap()[0].slowest_sql.is_empty());
+    }
+}
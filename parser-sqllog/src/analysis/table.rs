@@ -0,0 +1,292 @@
+//! 终端表格输出：列对齐、数值右对齐，可选表头着色，按终端宽度截断过宽的列
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+/// 未设置 `COLUMNS` 时假定的终端宽度
+const DEFAULT_TERM_WIDTH: usize = 120;
+
+/// 列被压缩时保留的最小宽度
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// 列之间的分隔符
+const SEPARATOR: &str = "  ";
+
+/// 表格渲染选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableOptions {
+    /// 表头是否使用 ANSI 粗体
+    pub color: bool,
+    /// 表格总宽度上限，None 表示不截断（`--wide`）
+    pub max_width: Option<usize>,
+    /// 仅在不截断（`--wide`）时输出的列，多为长文本或次要指标
+    pub wide_only: &'static [&'static str],
+}
+
+impl TableOptions {
+    /// 根据环境推断：设置了 `NO_COLOR` 时不着色；宽度取 `COLUMNS`，未设置时为 120
+    pub fn from_env(color: bool, wide: bool) -> Self {
+        let color = color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+        let max_width = (!wide).then(|| {
+            std::env::var("COLUMNS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&w: &usize| w > 0)
+                .unwrap_or(DEFAULT_TERM_WIDTH)
+        });
+        Self {
+            color,
+            max_width,
+            wide_only: &[],
+        }
+    }
+
+    /// 指定仅在 `--wide` 时输出的列
+    pub fn set_wide_only(mut self, columns: &'static [&'static str]) -> Self {
+        self.wide_only = columns;
+        self
+    }
+}
+
+/// 以对齐的表格形式写出报告行（包含表头），列名与 CSV 输出一致
+pub fn write_table<W: Write, T: Serialize>(
+    rows: &[T],
+    writer: W,
+    opts: &TableOptions,
+) -> csv::Result<()> {
+    // 借助 CSV 序列化得到与 CSV 输出相同的表头和单元格文本
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    let data = wtr
+        .into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let mut rdr = csv::Reader::from_reader(data.as_slice());
+    let header: Vec<String> = rdr.headers()?.iter().map(str::to_string).collect();
+    let mut cells: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+    for rec in rdr.records() {
+        cells.push(rec?.iter().map(str::to_string).collect());
+    }
+
+    write_table_records(&header, &cells, writer, opts)?;
+    Ok(())
+}
+
+/// 以对齐的表格形式写出已转换为文本的表头和单元格，单元格中的换行和制表符显示为空格
+pub fn write_table_records<W: Write>(
+    header: &[String],
+    cells: &[Vec<String>],
+    mut writer: W,
+    opts: &TableOptions,
+) -> io::Result<()> {
+    // 截断宽度时先去掉次要列，让其余列（如指纹）保留足够的宽度
+    let keep: Vec<usize> = (0..header.len())
+        .filter(|&i| opts.max_width.is_none() || !opts.wide_only.contains(&header[i].as_str()))
+        .collect();
+    let header: Vec<String> = keep.iter().map(|&i| header[i].clone()).collect();
+    let cells: Vec<Vec<String>> = cells
+        .iter()
+        .map(|r| {
+            keep.iter()
+                .map(|&i| r[i].replace(['\n', '\r', '\t'], " "))
+                .collect()
+        })
+        .collect();
+    let numeric: Vec<bool> = (0..header.len())
+        .map(|i| !cells.is_empty() && cells.iter().all(|r| is_number(&r[i])))
+        .collect();
+    let mut widths: Vec<usize> = header.iter().map(|h| display_width(h)).collect();
+    for row in &cells {
+        for (w, c) in widths.iter_mut().zip(row) {
+            *w = (*w).max(display_width(c));
+        }
+    }
+    if let Some(max) = opts.max_width {
+        shrink_to_fit(&mut widths, max);
+    }
+
+    let (bold, reset) = if opts.color {
+        ("\x1b[1m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    write!(writer, "{bold}")?;
+    write_line(&mut writer, &header, &widths, &numeric)?;
+    writeln!(writer, "{reset}")?;
+    let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+    write_line(&mut writer, &rule, &widths, &numeric)?;
+    writeln!(writer)?;
+    for row in &cells {
+        write_line(&mut writer, row, &widths, &numeric)?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// 写出一行（不含换行），行尾不留多余空格
+fn write_line<W: Write>(
+    writer: &mut W,
+    row: &[String],
+    widths: &[usize],
+    numeric: &[bool],
+) -> io::Result<()> {
+    let last = row.len().saturating_sub(1);
+    for (i, cell) in row.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(SEPARATOR.as_bytes())?;
+        }
+        let text = fit(cell, widths[i]);
+        let pad = " ".repeat(widths[i] - display_width(&text));
+        if numeric[i] {
+            write!(writer, "{pad}{text}")?;
+        } else if i == last {
+            write!(writer, "{text}")?;
+        } else {
+            write!(writer, "{text}{pad}")?;
+        }
+    }
+    Ok(())
+}
+
+/// 反复压缩最宽的列，直到总宽度不超过 `max`（或所有列都已到达最小宽度）
+fn shrink_to_fit(widths: &mut [usize], max: usize) {
+    let total = |w: &[usize]| w.iter().sum::<usize>() + SEPARATOR.len() * w.len().saturating_sub(1);
+    while total(widths) > max {
+        let Some((i, &w)) = widths.iter().enumerate().max_by_key(|&(_, w)| *w) else {
+            return;
+        };
+        if w <= MIN_COLUMN_WIDTH {
+            return;
+        }
+        let excess = total(widths) - max;
+        widths[i] = w.saturating_sub(excess).max(MIN_COLUMN_WIDTH);
+    }
+}
+
+/// 截断到 `width` 显示宽度以内，被截断时以 `…` 结尾
+fn fit(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let w = char_width(ch);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(ch);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.parse::<f64>().is_ok()
+}
+
+/// 终端显示宽度：中日韩文字与全角符号按 2 列计算
+fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        name: &'static str,
+        count: u64,
+    }
+
+    fn render(rows: &[Row], opts: TableOptions) -> String {
+        let mut out = Vec::new();
+        write_table(rows, &mut out, &opts).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn aligns_columns_and_right_aligns_numbers() {
+        let rows = [
+            Row {
+                name: "select",
+                count: 5,
+            },
+            Row {
+                name: "达梦",
+                count: 120,
+            },
+        ];
+        let out = render(&rows, TableOptions::default());
+        assert_eq!(
+            out,
+            "name    count\n------  -----\nselect      5\n达梦      120\n"
+        );
+    }
+
+    #[test]
+    fn truncates_to_width_and_colors_header() {
+        let rows = [Row {
+            name: "select * from a_very_long_table_name where id = 1",
+            count: 1,
+        }];
+        let opts = TableOptions {
+            color: true,
+            max_width: Some(20),
+            ..Default::default()
+        };
+        let out = render(&rows, opts);
+        assert!(out.starts_with("\x1b[1mname"));
+        let last = out.lines().last().unwrap();
+        assert_eq!(display_width(last), 20);
+        assert!(last.contains('…'));
+    }
+
+    #[test]
+    fn drops_wide_only_columns_when_truncating() {
+        let rows = [Row {
+            name: "select",
+            count: 5,
+        }];
+        let opts = TableOptions {
+            max_width: Some(80),
+            ..Default::default()
+        }
+        .set_wide_only(&["count"]);
+        assert_eq!(render(&rows, opts), "name\n------\nselect\n");
+
+        let wide = TableOptions {
+            max_width: None,
+            ..opts
+        };
+        assert!(render(&rows, wide).starts_with("name    count\n"));
+    }
+
+    #[test]
+    fn shrink_stops_at_min_width_when_columns_exceed_max() {
+        let mut widths = vec![10, 12, 9, 11, 10, 12, 9, 10];
+        shrink_to_fit(&mut widths, 20);
+        assert!(widths.iter().all(|&w| w >= MIN_COLUMN_WIDTH));
+    }
+}
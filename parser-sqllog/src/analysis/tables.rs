@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use dm_database_parser::objects::referenced_tables;
+use dm_database_parser::parser::ParsedRecord;
+use dm_database_parser::sql::{self, StatementKind};
+use serde::Serialize;
+
+/// 单条语句引用的表及其执行信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSample {
+    pub tables: Vec<String>,
+    pub kind: StatementKind,
+    pub exec_time_ms: u64,
+}
+
+/// 从记录中提取引用的表；不引用任何表的记录返回 None
+pub fn sample(rec: &ParsedRecord<'_>) -> Option<TableSample> {
+    let text = sql::sql_text(&rec.body);
+    let tables = referenced_tables(text);
+    if tables.is_empty() {
+        return None;
+    }
+    Some(TableSample {
+        tables,
+        kind: sql::classify(text),
+        exec_time_ms: rec.execute_time_ms.unwrap_or(0),
+    })
+}
+
+/// 单个表/视图的访问统计（近似值，基于词法提取）
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TableUsageRow {
+    pub object: String,
+    /// 引用该对象的语句执行次数
+    pub executions: u64,
+    pub queries: u64,
+    pub dml: u64,
+    pub ddl: u64,
+    pub other: u64,
+    /// 引用该对象的语句总耗时；一条语句引用多个表时每个表都计入全部耗时
+    pub total_exec_time_ms: u64,
+}
+
+/// 按表/视图名汇总访问次数
+#[derive(Debug, Default)]
+pub struct TableUsage {
+    objects: HashMap<String, TableUsageRow>,
+}
+
+impl TableUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析日志文本并累加统计
+    #[cfg(test)]
+    fn add_text(&mut self, text: &str) {
+        dm_database_parser::parser::parse_records_with(text, |rec| {
+            if let Some(s) = sample(&rec) {
+                self.add_sample(s);
+            }
+        });
+    }
+
+    /// 累加一条语句
+    pub fn add_sample(&mut self, s: TableSample) {
+        for table in s.tables {
+            let row = self
+                .objects
+                .entry(table)
+                .or_insert_with_key(|k| TableUsageRow {
+                    object: k.clone(),
+                    ..Default::default()
+                });
+            row.executions += 1;
+            match s.kind {
+                StatementKind::Query => row.queries += 1,
+                StatementKind::Dml => row.dml += 1,
+                StatementKind::Ddl => row.ddl += 1,
+                _ => row.other += 1,
+            }
+            row.total_exec_time_ms += s.exec_time_ms;
+        }
+    }
+
+    /// 返回按执行次数降序排列的统计行，`top` 限制行数
+    pub fn rows(&self, top: Option<usize>) -> Vec<TableUsageRow> {
+        let mut rows: Vec<TableUsageRow> = self.objects.values().cloned().collect();
+        rows.sort_by(|a, b| {
+            b.executions
+                .cmp(&a.executions)
+                .then(b.total_exec_time_ms.cmp(&a.total_exec_time_ms))
+                .then(a.object.cmp(&b.object))
+        });
+        if let Some(top) = top {
+            rows.truncate(top);
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_accesses_per_object() {
+        let log = "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from orders o join customers c on c.id = o.cid EXECTIME: 10(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:57:09.562 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [UPD] update ORDERS set s = 1 EXECTIME: 5(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:57:09.563 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1 from dual EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+";
+        let mut usage = TableUsage::new();
+        usage.add_text(log);
+        let rows = usage.rows(None);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].object, "orders");
+        assert_eq!(rows[0].executions, 2);
+        assert_eq!(rows[0].queries, 1);
+        assert_eq!(rows[0].dml, 1);
+        assert_eq!(rows[0].total_exec_time_ms, 15);
+        assert_eq!(rows[1].object, "customers");
+        assert_eq!(usage.rows(Some(1)).len(), 1);
+    }
+}
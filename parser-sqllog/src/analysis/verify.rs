@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use dm_database_parser::ts_to_epoch_millis;
+use serde::Serialize;
+
+/// 单个文件中记录覆盖的时间范围
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSpan {
+    pub instance: String,
+    pub path: PathBuf,
+    pub records: u64,
+    /// 最早 / 最晚记录的时间戳；文件中没有记录时为 None
+    pub first: Option<(i64, String)>,
+    pub last: Option<(i64, String)>,
+}
+
+impl FileSpan {
+    pub fn new(path: &Path, instance: &str) -> Self {
+        Self {
+            instance: instance.to_string(),
+            path: path.to_path_buf(),
+            records: 0,
+            first: None,
+            last: None,
+        }
+    }
+
+    /// 记录一条记录的时间戳
+    pub fn add(&mut self, ts: &str) {
+        self.records += 1;
+        let Some(ms) = ts_to_epoch_millis(ts) else {
+            return;
+        };
+        if self.first.as_ref().is_none_or(|(f, _)| ms < *f) {
+            self.first = Some((ms, ts.to_string()));
+        }
+        if self.last.as_ref().is_none_or(|(l, _)| ms > *l) {
+            self.last = Some((ms, ts.to_string()));
+        }
+    }
+}
+
+/// 与同一实例上一个文件相比的衔接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Continuity {
+    /// 首个文件，或与上一个文件首尾相接
+    Ok,
+    /// 起始时间早于上一个文件的结束时间，可能存在重复记录
+    Overlap,
+    /// 与上一个文件之间的间隔超过阈值，可能缺少文件
+    Gap,
+    /// 文件中没有可识别时间戳的记录
+    Empty,
+}
+
+/// 连续性检查结果的一行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContinuityRow {
+    pub instance: String,
+    pub file: String,
+    pub records: u64,
+    pub first_ts: String,
+    pub last_ts: String,
+    pub status: Continuity,
+    /// 本文件最早记录与上一个文件最晚记录的时间差（毫秒），为负表示重叠
+    pub delta_ms: Option<i64>,
+}
+
+/// 按实例分组、按起始时间排序后，逐个比较相邻文件的首尾时间。
+///
+/// 时间差超过 `max_gap_ms` 视为缺口，小于 0 视为重叠。
+pub fn check_continuity(mut spans: Vec<FileSpan>, max_gap_ms: i64) -> Vec<ContinuityRow> {
+    spans.sort_by(|a, b| {
+        a.instance
+            .cmp(&b.instance)
+            .then(
+                a.first
+                    .as_ref()
+                    .map(|f| f.0)
+                    .cmp(&b.first.as_ref().map(|f| f.0)),
+            )
+            .then(a.path.cmp(&b.path))
+    });
+    let mut rows = Vec::with_capacity(spans.len());
+    let mut prev: Option<(&str, i64)> = None;
+    for span in &spans {
+        let ts = |v: &Option<(i64, String)>| v.as_ref().map(|t| t.1.clone()).unwrap_or_default();
+        let mut row = ContinuityRow {
+            instance: span.instance.clone(),
+            file: span.path.display().to_string(),
+            records: span.records,
+            first_ts: ts(&span.first),
+            last_ts: ts(&span.last),
+            status: Continuity::Ok,
+            delta_ms: None,
+        };
+        match (&span.first, &span.last) {
+            (Some((first, _)), Some((last, _))) => {
+                if let Some((inst, prev_last)) = prev
+                    && inst == span.instance
+                {
+                    let delta = first - prev_last;
+                    row.delta_ms = Some(delta);
+                    if delta < 0 {
+                        row.status = Continuity::Overlap;
+                    } else if delta > max_gap_ms {
+                        row.status = Continuity::Gap;
+                    }
+                }
+                prev = Some((&span.instance, *last));
+            }
+            _ => row.status = Continuity::Empty,
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(name: &str, instance: &str, ts: &[&str]) -> FileSpan {
+        let mut s = FileSpan::new(Path::new(name), instance);
+        for t in ts {
+            s.add(t);
+        }
+        s
+    }
+
+    #[test]
+    fn reports_overlaps_and_gaps_per_instance() {
+        let spans = vec![
+            span(
+                "b.log",
+                "DM1",
+                &["2025-08-12 10:09:00.000", "2025-08-12 10:20:00.000"],
+            ),
+            span(
+                "a.log",
+                "DM1",
+                &["2025-08-12 10:00:00.000", "2025-08-12 10:10:00.000"],
+            ),
+            span("c.log", "DM1", &["2025-08-12 11:00:00.000"]),
+            span("d.log", "DM2", &["2025-08-12 08:00:00.000"]),
+            span("e.log", "DM2", &[]),
+        ];
+        let rows = check_continuity(spans, 60_000);
+        let status: Vec<_> = rows.iter().map(|r| (r.file.as_str(), r.status)).collect();
+        assert_eq!(
+            status,
+            [
+                ("a.log", Continuity::Ok),
+                ("b.log", Continuity::Overlap),
+                ("c.log", Continuity::Gap),
+                ("e.log", Continuity::Empty),
+                ("d.log", Continuity::Ok),
+            ]
+        );
+        assert_eq!(rows[1].delta_ms, Some(-60_000));
+        assert_eq!(rows[2].delta_ms, Some(40 * 60_000));
+    }
+}
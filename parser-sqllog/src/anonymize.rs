@@ -0,0 +1,268 @@
+//! 导出记录的脱敏：用户、模式、表等名称按带密钥的哈希（HMAC-SHA256）替换为假名，字面量替换为 `?`，
+//! 保留语句结构与执行指标，便于把客户的负载交给厂商支持或用于基准测试。
+//!
+//! 假名只取决于密钥与小写的名称，与名称出现的位置无关：模式与同名用户得到相同的哈希部分，
+//! 只是前缀不同（`S_` / `U_`）。SQL 中的表与模式按 [`referenced_tables`] 识别，存储过程（`P_`）
+//! 与其模式或包前缀按 [`called_procedures`] 识别；其他位置上与之同名的标识符、以及语句中任意位置
+//! 与已识别模式同名的 `名称.` 前缀同样被替换。未出现在这些位置的用户名（如 `GRANT ... TO u`）不做替换。
+
+use std::collections::HashSet;
+
+use dm_database_parser::{
+    FingerprintOptions, RecordMetrics, Sqllog, fingerprint_with,
+    objects::{called_procedures, referenced_tables},
+};
+
+use crate::{
+    config::export::AnonymizeConfig,
+    error::ConfigParseError,
+    hmac::{hex, hmac_sha256},
+};
+
+/// 字面量替换规则：去掉注释，保留大小写与模式前缀
+const LITERAL_OPTIONS: FingerprintOptions = FingerprintOptions {
+    keep_comments: false,
+    collapse_in_lists: false,
+    lowercase: false,
+    strip_schema: false,
+};
+
+/// 按 `[export.anonymize]` 的规则替换记录中的名称与字面量
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    key: Vec<u8>,
+    cfg: AnonymizeConfig,
+}
+
+impl Anonymizer {
+    /// 未设置密钥时返回错误
+    pub fn new(cfg: &AnonymizeConfig) -> Result<Self, ConfigParseError> {
+        if cfg.key.is_empty() {
+            return Err(ConfigParseError::MissingField(
+                "export.anonymize.key".to_string(),
+            ));
+        }
+        Ok(Self {
+            key: cfg.key.as_bytes().to_vec(),
+            cfg: cfg.clone(),
+        })
+    }
+
+    /// 名称的假名：`prefix` 加上小写名称的 HMAC 的前 8 位十六进制
+    pub fn pseudonym(&self, prefix: &str, name: &str) -> String {
+        let mac = hmac_sha256(&self.key, name.to_lowercase().as_bytes());
+        format!("{prefix}_{}", hex(&mac[..4]))
+    }
+
+    /// IP 地址的假名：`10.x.x.x` 形式，同一地址总是得到相同的结果
+    pub fn ip(&self, ip: &str) -> String {
+        let mac = hmac_sha256(&self.key, ip.as_bytes());
+        format!("10.{}.{}.{}", mac[0], mac[1], mac[2])
+    }
+
+    /// 替换 SQL 中的字面量与表名、模式名、过程名
+    pub fn sql(&self, sql: &str) -> String {
+        if self.cfg.literals {
+            self.identifiers(&fingerprint_with(sql, &LITERAL_OPTIONS).text)
+        } else {
+            self.identifiers(sql)
+        }
+    }
+
+    /// 把 SQL 中的表名、模式名、过程名替换为假名；字符串字面量中的内容不变
+    fn identifiers(&self, sql: &str) -> String {
+        if !self.cfg.tables && !self.cfg.schemas && !self.cfg.procedures {
+            return sql.to_string();
+        }
+        let mut tables = HashSet::new();
+        let mut procedures = HashSet::new();
+        let mut schemas = HashSet::new();
+        let mut add = |names: Vec<String>, objects: &mut HashSet<String>| {
+            for name in names {
+                let mut parts: Vec<&str> = name.split('.').collect();
+                if let Some(object) = parts.pop() {
+                    objects.insert(object.to_string());
+                }
+                schemas.extend(parts.into_iter().map(str::to_string));
+            }
+        };
+        add(referenced_tables(sql), &mut tables);
+        add(called_procedures(sql), &mut procedures);
+        if tables.is_empty() && procedures.is_empty() {
+            return sql.to_string();
+        }
+
+        let mut out = String::with_capacity(sql.len());
+        let mut rest = sql;
+        while let Some(c) = rest.chars().next() {
+            // 字符串字面量原样保留（引号内的 '' 为转义）
+            if c == '\'' {
+                let end = skip_escaped_quotes(rest).unwrap_or(rest.len());
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+            let (ident, quoted, len) = if c == '"' {
+                match rest[1..].find('"') {
+                    Some(i) => (&rest[1..=i], true, i + 2),
+                    None => (&rest[1..], true, rest.len()),
+                }
+            } else if c.is_alphabetic() || c == '_' {
+                let len = rest
+                    .find(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '_' | '$' | '#')))
+                    .unwrap_or(rest.len());
+                (&rest[..len], false, len)
+            } else {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            };
+            let lower = ident.to_lowercase();
+            let before_dot = rest[len..].trim_start().starts_with('.');
+            let replacement = if before_dot && self.cfg.schemas && schemas.contains(&lower) {
+                Some(self.pseudonym("S", ident))
+            } else if self.cfg.tables && tables.contains(&lower) {
+                Some(self.pseudonym("T", ident))
+            } else if self.cfg.procedures && procedures.contains(&lower) {
+                Some(self.pseudonym("P", ident))
+            } else {
+                None
+            };
+            match (replacement, quoted) {
+                (Some(p), true) => out.push_str(&format!("\"{p}\"")),
+                (Some(p), false) => out.push_str(&p),
+                (None, _) => out.push_str(&rest[..len]),
+            }
+            rest = &rest[len..];
+        }
+        out
+    }
+
+    /// 脱敏导出记录
+    pub fn apply(&self, log: &mut Sqllog) {
+        if self.cfg.users && !log.username.is_empty() {
+            log.username = self.pseudonym("U", &log.username);
+        }
+        if self.cfg.appnames && !log.appname.is_empty() {
+            log.appname = self.pseudonym("A", &log.appname);
+        }
+        if self.cfg.client_ips && !log.client_ip.is_empty() {
+            log.client_ip = self.ip(&log.client_ip);
+        }
+        log.description = self.sql(&log.description);
+    }
+
+    /// 脱敏统计样本用的记录指标；指纹中已没有字面量，只替换名称
+    pub fn apply_metrics(&self, m: &mut RecordMetrics) {
+        if self.cfg.users
+            && let Some(user) = &mut m.user
+        {
+            *user = self.pseudonym("U", user);
+        }
+        if self.cfg.appnames
+            && let Some(appname) = &mut m.appname
+        {
+            *appname = self.pseudonym("A", appname);
+        }
+        if self.cfg.client_ips
+            && let Some(ip) = &mut m.ip
+        {
+            *ip = self.ip(ip);
+        }
+        m.fingerprint = self.identifiers(&m.fingerprint);
+    }
+}
+
+/// 以单引号开头的字符串字面量的长度（含两端引号），引号内的 `''` 为转义；未闭合时返回 None
+fn skip_escaped_quotes(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return Some(i + 1);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudonymizes_names_consistently_and_keeps_structure() {
+        let anon = Anonymizer::new(&AnonymizeConfig::new().set_key("secret")).unwrap();
+        let t = anon.pseudonym("T", "orders");
+        let s = anon.pseudonym("S", "SALES");
+        assert_eq!(t.len(), 10);
+        assert_eq!(anon.pseudonym("T", "ORDERS"), t);
+        // 同名的用户与模式只有前缀不同
+        assert_eq!(anon.pseudonym("U", "sales")[2..], s[2..]);
+
+        let sql = "SELECT o.id FROM sales.orders o JOIN \"SALES\".\"ORDERS\" x ON o.id = x.id WHERE o.note = 'orders' AND o.id = 42";
+        assert_eq!(
+            anon.sql(sql),
+            format!(
+                "SELECT o.id FROM {s}.{t} o JOIN \"{s}\".\"{t}\" x ON o.id = x.id WHERE o.note = ? AND o.id = ?"
+            )
+        );
+        let keep_literals =
+            Anonymizer::new(&AnonymizeConfig::new().set_key("secret").set_literals(false)).unwrap();
+        assert_eq!(
+            keep_literals.sql("update orders set note = 'it''s orders'"),
+            format!("update {t} set note = 'it''s orders'")
+        );
+        let other = Anonymizer::new(&AnonymizeConfig::new().set_key("other")).unwrap();
+        assert_ne!(other.pseudonym("T", "orders"), t);
+
+        let mut log = Sqllog::new();
+        log.username = "SYSDBA".to_string();
+        log.client_ip = "192.168.1.7".to_string();
+        log.appname = "disql".to_string();
+        log.description = "insert into orders values(1, 'x')".to_string();
+        anon.apply(&mut log);
+        assert_eq!(log.username, anon.pseudonym("U", "sysdba"));
+        assert!(log.client_ip.starts_with("10."));
+        assert_eq!(log.appname, "disql");
+        assert_eq!(log.description, format!("insert into {t} values(?, ?)"));
+
+        assert!(Anonymizer::new(&AnonymizeConfig::new()).is_err());
+    }
+
+    #[test]
+    fn pseudonymizes_procedure_calls() {
+        let anon = Anonymizer::new(&AnonymizeConfig::new().set_key("secret")).unwrap();
+        let s = anon.pseudonym("S", "sales");
+        let p = anon.pseudonym("P", "p_login");
+        // 与查询语句中的模式假名一致，不能通过对照两条语句还原模式名
+        assert_eq!(
+            anon.sql("select * from sales.orders"),
+            format!("select * from {s}.{}", anon.pseudonym("T", "orders"))
+        );
+        assert_eq!(
+            anon.sql("call sales.p_login('alice','hunter2')"),
+            format!("call {s}.{p}(?,?)")
+        );
+        assert_eq!(
+            anon.sql("begin sales.p_login(:1, :2); sales.pkg_audit.flush; end;"),
+            format!(
+                "begin {s}.{p}(:?, :?); {s}.{}.{}; end;",
+                anon.pseudonym("S", "pkg_audit"),
+                anon.pseudonym("P", "flush")
+            )
+        );
+
+        let keep = Anonymizer::new(
+            &AnonymizeConfig::new()
+                .set_key("secret")
+                .set_procedures(false),
+        )
+        .unwrap();
+        assert_eq!(keep.sql("exec sales.p_login"), format!("exec {s}.p_login"));
+    }
+}
@@ -0,0 +1,56 @@
+use clap::Args;
+use dm_database_parser::parser::ParsedRecord;
+use tracing::info;
+
+use crate::{
+    analysis::{audit::AuditEntry, truncate_body, write_csv},
+    command::{WindowArgs, open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+    pipeline::Source,
+};
+
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    /// CSV 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+}
+
+/// 提取所有 DDL/DCL 语句并导出为 CSV
+pub fn run(
+    args: &AuditArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let max_body_len = export_cfg.max_body_len;
+    let map = |src: &Source, rec: ParsedRecord<'_>| {
+        let mut entry = AuditEntry::from_record(&rec, &src.instance)?;
+        entry.truncated = truncate_body(&mut entry.statement, max_body_len);
+        Some(entry)
+    };
+    let pipeline = pipeline(cfg, err_cfg);
+    let (entries, summary) = if args.window.is_set() {
+        // 顺序扫描，按文件顺序取窗口内的记录，取满即停
+        args.window.collect(&pipeline, files, map)?
+    } else {
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        let summary = pipeline.run(files, map, |entry| entries.push(entry))?;
+        // 各批次到达顺序不确定，按时间排序后输出
+        entries.sort_by(|a, b| a.ts.cmp(&b.ts).then(a.instance.cmp(&b.instance)));
+        (entries, summary)
+    };
+    write_csv(&entries, open_output(args.output.as_deref())?)?;
+    info!(
+        "审计完成: 共 {} 个文件, {} 条记录",
+        summary.files,
+        entries.len()
+    );
+    Ok(())
+}
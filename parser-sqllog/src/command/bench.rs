@@ -0,0 +1,136 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use dm_database_parser::{
+    Sqllog,
+    parser::{for_each_record, parse_record},
+    prewarm, split_by_ts_records_with_errors,
+};
+use serde::Serialize;
+use tracing::info;
+
+use crate::{
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// 用于测试的 sqllog 文件
+    pub file: PathBuf,
+
+    /// 每个阶段计时的轮数，取最快的一轮
+    #[arg(long, default_value_t = 3)]
+    pub iterations: usize,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 一个阶段的吞吐
+#[derive(Debug, Serialize)]
+struct BenchRow {
+    stage: &'static str,
+    records: u64,
+    ms: f64,
+    mb_per_s: f64,
+    records_per_s: f64,
+}
+
+impl BenchRow {
+    fn new(stage: &'static str, bytes: usize, records: u64, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        Self {
+            stage,
+            records,
+            ms: (secs * 1e6).round() / 1e3,
+            mb_per_s: round1(bytes as f64 / 1024.0 / 1024.0 / secs),
+            records_per_s: round1(records as f64 / secs),
+        }
+    }
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// 运行 `iterations` 轮（另加一轮不计时的预热），返回最快一轮的耗时与该轮的记录数
+fn measure<F: FnMut() -> CommandResult<u64>>(
+    iterations: usize,
+    mut f: F,
+) -> CommandResult<(Duration, u64)> {
+    f()?;
+    let mut best = (Duration::MAX, 0);
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let records = f()?;
+        best = best.min((start.elapsed(), records));
+    }
+    Ok(best)
+}
+
+/// 分别测量仅拆分、仅解析与完整流水线（读取、拆分、解析、转换为记录）的吞吐，
+/// 用于在现场验证硬件并调整线程数等设置
+pub fn run(
+    args: &BenchArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    prewarm();
+    let text = input::read_text(&args.file)?;
+    let bytes = text.len();
+    let (records, _) = split_by_ts_records_with_errors(&text);
+    info!(
+        "测试文件: {}, {} 字节, {} 条记录",
+        args.file.display(),
+        bytes,
+        records.len()
+    );
+
+    let mut rows = Vec::new();
+    let (elapsed, n) = measure(args.iterations, || {
+        let mut n = 0;
+        for_each_record(&text, |_| n += 1);
+        Ok(n)
+    })?;
+    rows.push(BenchRow::new("split", bytes, n, elapsed));
+
+    let (elapsed, n) = measure(args.iterations, || {
+        let mut n = 0;
+        for rec in &records {
+            std::hint::black_box(parse_record(rec));
+            n += 1;
+        }
+        Ok(n)
+    })?;
+    rows.push(BenchRow::new("parse", bytes, n, elapsed));
+
+    let pipeline = pipeline(cfg, err_cfg);
+    let (elapsed, n) = measure(args.iterations, || {
+        let summary = pipeline.run(
+            vec![args.file.clone()],
+            |_, rec| Some(Sqllog::from_record(&rec)),
+            |log| {
+                std::hint::black_box(log);
+            },
+        )?;
+        Ok(summary.outputs)
+    })?;
+    rows.push(BenchRow::new("pipeline", bytes, n, elapsed));
+
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "流水线设置: thread_num={}, batch_size={}, chunk_size_kb={}, read_ahead={}",
+        cfg.thread_num, cfg.batch_size, cfg.chunk_size_kb, cfg.read_ahead
+    );
+    Ok(())
+}
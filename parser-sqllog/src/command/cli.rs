@@ -1,4 +1,13 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use crate::command::{
+    audit, bench, concurrency, daemon, doctor, events, exec, export, heatmap, idle, large_result,
+    locks, merge, otlp, pool, prepared, replay, rollback, row_latency, saturation, schema, show,
+    slice, stats, summary, tables, tail, verify,
+};
+use crate::config::effective::{Origin, Override};
+use crate::config::sqllog::{OnError, ProgressMode};
 
 #[derive(Parser)]
 #[command(name = crate::NAME)]
@@ -8,4 +17,224 @@ pub struct Cli {
     /// 配置文件路径
     #[arg(short, long, default_value = "config.toml")]
     pub config_path: String,
+
+    /// 输出合并配置文件、环境变量与命令行参数后的生效配置（TOML，注明每项来源）后退出
+    #[arg(long, global = true)]
+    pub print_config: bool,
+
+    /// 严格解析配置：配置文件必须存在，未知的节或键、类型错误直接报错，而不是回退到默认值
+    #[arg(long, global = true)]
+    pub strict_config: bool,
+
+    /// 选用配置文件中的 `[profile.<名称>]` 配置档，覆盖基础配置（环境变量与命令行参数仍优先）
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// 提高日志详细程度，覆盖配置中的 logging.level：-v 为 debug，-vv 为 trace，
+    /// -vvv 同时忽略 [logging.targets] 中按模块设置的级别
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// 安静模式：只输出错误日志，不输出进度
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// 临时覆盖任意配置项，如 `--set sqllog.thread_num=8`，可重复；在其他来源之后按出现顺序应用
+    #[arg(long = "set", global = true)]
+    pub set: Vec<Override>,
+
+    /// 流式读取的块大小（KB），覆盖配置中的 sqllog.chunk_size_kb
+    #[arg(long, global = true)]
+    pub chunk_size_kb: Option<usize>,
+
+    /// 预读深度（块数），覆盖配置中的 sqllog.read_ahead
+    #[arg(long, global = true)]
+    pub read_ahead: Option<usize>,
+
+    /// 错误记录处理策略，覆盖配置中的 sqllog.on_error
+    #[arg(long, global = true, value_enum)]
+    pub on_error: Option<OnError>,
+
+    /// 启用严格解析模式，等同于配置 sqllog.strict = true
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// 遇到无法读取的文件时立即中止，等同于配置 sqllog.fail_fast = true
+    #[arg(long, global = true)]
+    pub fail_fast: bool,
+
+    /// 错误记录占比上限（0~1），超过时作业失败，覆盖配置中的 sqllog.max_error_rate
+    #[arg(long, global = true, value_parser = parse_rate)]
+    pub max_error_rate: Option<f64>,
+
+    /// 进度输出方式，覆盖配置中的 sqllog.progress；json 时向标准错误输出 NDJSON 进度事件
+    #[arg(long, global = true, value_enum)]
+    pub progress: Option<ProgressMode>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+impl Cli {
+    /// 命令行参数对配置的覆盖项，在配置文件与环境变量之后应用
+    pub fn config_overrides(&self) -> Vec<Override> {
+        let mut o = Vec::new();
+        push(
+            &mut o,
+            "sqllog.chunk_size_kb",
+            "--chunk-size-kb",
+            self.chunk_size_kb,
+        );
+        push(&mut o, "sqllog.read_ahead", "--read-ahead", self.read_ahead);
+        push(&mut o, "sqllog.on_error", "--on-error", self.on_error);
+        push(
+            &mut o,
+            "sqllog.strict",
+            "--strict",
+            self.strict.then_some(true),
+        );
+        push(
+            &mut o,
+            "sqllog.fail_fast",
+            "--fail-fast",
+            self.fail_fast.then_some(true),
+        );
+        push(
+            &mut o,
+            "sqllog.max_error_rate",
+            "--max-error-rate",
+            self.max_error_rate,
+        );
+        push(&mut o, "sqllog.progress", "--progress", self.progress);
+        match &self.command {
+            Some(Commands::Export(args)) => {
+                push(
+                    &mut o,
+                    "export.max_body_len",
+                    "--max-body-len",
+                    args.max_body_len,
+                );
+                push(&mut o, "export.compress", "--compress", args.compress);
+                push(
+                    &mut o,
+                    "export.max_output_size",
+                    "--max-output-size",
+                    args.max_output_size,
+                );
+                push(&mut o, "export.roll_every", "--roll-every", args.roll_every);
+            }
+            Some(Commands::Exec(args)) => push(
+                &mut o,
+                "export.max_body_len",
+                "--max-body-len",
+                args.max_body_len,
+            ),
+            _ => {}
+        }
+        if self.quiet {
+            push(&mut o, "logging.level", "-q", Some("error"));
+            push(&mut o, "sqllog.progress", "-q", Some(ProgressMode::None));
+        } else if self.verbose > 0 {
+            let flag = format!("-{}", "v".repeat(self.verbose as usize));
+            let level = if self.verbose == 1 { "debug" } else { "trace" };
+            push(&mut o, "logging.level", &flag, Some(level));
+        }
+        o.extend(self.set.iter().cloned());
+        o
+    }
+}
+
+/// 解析 0~1 之间的比例
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!("应为 0~1 之间的小数: {s}")),
+    }
+}
+
+/// 命令行给出了 `value` 时记录一项覆盖
+fn push<T: Serialize>(overrides: &mut Vec<Override>, key: &str, flag: &str, value: Option<T>) {
+    if let Some(v) = value.and_then(|v| toml::Value::try_from(v).ok()) {
+        overrides.push(Override::new(key, v, Origin::Cli(flag.to_string())));
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// 提取 DDL/DCL 语句（时间、用户、客户端 IP），导出为 CSV
+    Audit(audit::AuditArgs),
+    /// 按 appname 统计绑定变量（预编译）与字面量 SQL 的执行比例
+    Prepared(prepared::PreparedArgs),
+    /// 列出 ROWCOUNT 超过阈值的语句，按指纹和用户分组
+    LargeResults(large_result::LargeResultArgs),
+    /// 按指纹与 ROWCOUNT 区间统计耗时，区分结果集大与结果集小的慢语句
+    RowLatency(row_latency::RowLatencyArgs),
+    /// 按时间桶统计并发执行的语句数与线程繁忙比例
+    Concurrency(concurrency::ConcurrencyArgs),
+    /// 拼接语句与执行指标，报告同时执行的语句数超过上限的时间桶
+    Saturation(saturation::SaturationArgs),
+    /// 按星期 × 小时统计执行次数与耗时，输出热力图数据
+    Heatmap(heatmap::HeatmapArgs),
+    /// 按会话统计语句间隔，报告持有未提交事务却长时间空闲的会话（连接池泄漏）
+    Idle(idle::IdleArgs),
+    /// 按会话重建事务，按用户、appname 与时间统计回滚次数与回滚率，标记回滚突增
+    Rollback(rollback::RollbackArgs),
+    /// 提取检查点、PURGE、REDO 等系统消息，可按时间桶与同期语句耗时对照
+    Events(events::EventsArgs),
+    /// 提取死锁、锁超时与锁等待消息，关联涉及的会话、事务及其最近执行的语句
+    Locks(locks::LocksArgs),
+    /// 合并多次导出的 JSON Lines 结果与清单，按时间戳排序并按记录标识去重
+    Merge(merge::MergeArgs),
+    /// 把语句执行转换为 OpenTelemetry span（按会话归入 trace），以 OTLP/JSON 写出供 Jaeger / Tempo 浏览
+    Otlp(otlp::OtlpArgs),
+    /// 按 appname 统计各时间段的不同会话数，估算连接池占用与峰值
+    Pool(pool::PoolArgs),
+    /// 按时间范围截取原始日志记录（或只取首尾 N 条），原样写出到新文件
+    Slice(slice::SliceArgs),
+    /// 逐条显示选中的记录：字段对齐、易读的时间与耗时、SQL 着色
+    Show(show::ShowArgs),
+    /// 按指纹统计执行次数与耗时，可按实例或 EP 节点分组对比
+    Stats(stats::StatsArgs),
+    /// 从语句中提取引用的表/视图名，按对象统计访问次数
+    Tables(tables::TablesArgs),
+    /// 按 EXEC_ID 定位执行记录，输出所在文件、偏移与语句文本
+    #[command(name = "exec-id")]
+    Exec(exec::ExecArgs),
+    /// 以 JSON Lines 格式导出记录，可按 EP 节点拆分为多个文件
+    Export(export::ExportArgs),
+    /// 把执行过的语句生成为回放脚本，可选保留原始的到达节奏
+    Replay(replay::ReplayArgs),
+    /// 按文件汇总记录数、时间范围、会话与用户数、错误行数以及解析吞吐
+    Summary(summary::SummaryArgs),
+    /// 检查轮转出的多个日志文件在时间上是否连续，报告重叠与缺口
+    Verify(verify::VerifyArgs),
+    /// 诊断单个 sqllog 文件：编码、时间戳格式、元数据键顺序及建议的解析模式
+    Doctor(doctor::DoctorArgs),
+    /// 测量单个文件仅拆分、仅解析与完整流水线的吞吐（MB/s、记录/s），用于验证硬件与调整线程设置
+    Bench(bench::BenchArgs),
+    /// 监听日志目录，持续导出新轮转出的文件并标记完成
+    Daemon(daemon::DaemonArgs),
+    /// 持续跟踪正在写入的日志文件，事务开启超过阈值仍未提交时告警
+    Tail(tail::TailArgs),
+    /// 输出导出记录的 JSON Schema、Arrow / Avro schema、protobuf 定义或数据库建表语句
+    Schema(schema::SchemaArgs),
+    /// 在解析后的记录（`records` 表）上执行 SQL 查询
+    #[cfg(feature = "query")]
+    Query(crate::command::query::QueryArgs),
+    /// 交互式浏览指纹汇总与示例语句，可按用户和时间过滤
+    #[cfg(feature = "tui")]
+    Tui(crate::command::tui::TuiArgs),
+    /// 把指纹统计、会话与时间序列写成 Excel 工作簿，每项一个工作表
+    #[cfg(feature = "xlsx")]
+    Xlsx(crate::command::xlsx::XlsxArgs),
+    /// 用自定义的 Tera 模板渲染指纹统计、会话与时间序列的概览报告
+    #[cfg(feature = "template")]
+    Render(crate::command::render::RenderArgs),
+}
+
+impl Commands {
+    /// 是否读取 `[sqllog]` 配置的输入；`merge` 读取导出结果，`doctor` 读取命令行给出的文件，`schema` 不读取文件
+    pub fn reads_input(&self) -> bool {
+        !matches!(self, Self::Merge(_) | Self::Doctor(_) | Self::Schema(_))
+    }
 }
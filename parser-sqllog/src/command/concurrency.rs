@@ -0,0 +1,56 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::{
+        concurrency::{self, ConcurrencyTimeline},
+        write_csv,
+    },
+    command::{open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct ConcurrencyArgs {
+    /// 时间桶长度（毫秒）
+    #[arg(short, long, default_value_t = 1000)]
+    pub bucket_ms: i64,
+
+    /// 输出每个线程的繁忙比例，而不是并发时间序列
+    #[arg(long)]
+    pub per_thread: bool,
+
+    /// CSV 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+/// 根据 thrd 与 EXECTIME 计算并发时间序列或线程繁忙比例
+pub fn run(
+    args: &ConcurrencyArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut timeline = ConcurrencyTimeline::new(args.bucket_ms);
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| concurrency::sample(&rec),
+        |s| timeline.add_sample(s),
+    )?;
+
+    let out = open_output(args.output.as_deref())?;
+    let rows = if args.per_thread {
+        let rows = timeline.thread_busy();
+        write_csv(&rows, out)?;
+        rows.len()
+    } else {
+        let rows = timeline.timeline();
+        write_csv(&rows, out)?;
+        rows.len()
+    };
+    info!("并发分析完成: 共 {} 个文件, {} 行输出", summary.files, rows);
+    Ok(())
+}
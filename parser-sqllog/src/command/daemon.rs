@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use clap::Args;
+use dm_database_parser::{InstanceInfo, Sqllog};
+use notify::{RecursiveMode, Watcher};
+use tracing::{debug, info, warn};
+
+use crate::{
+    analysis::truncate_body,
+    command::pipeline,
+    config::{error_exporter::ErrorExporterConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    exporter::{compress::Encoder, jsonl::JsonlWriter},
+    input,
+    lock::LockFile,
+    pipeline::Pipeline,
+    rules::RuleSet,
+};
+
+/// 处理完成标记文件的扩展名
+const DONE_SUFFIX: &str = ".done";
+
+#[derive(Debug, Args)]
+pub struct DaemonArgs {
+    /// 输出目录，每个处理完成的日志文件导出为 `<output>/<文件名主干>.jsonl`
+    #[arg(short, long)]
+    pub output: String,
+
+    /// 处理完成的日志文件移动到该目录；缺省时在原位置写入 `<文件名>.done` 标记文件
+    #[arg(long)]
+    pub done_dir: Option<String>,
+
+    /// 未收到文件变化通知时重新扫描目录的间隔（秒）
+    #[arg(long, default_value_t = 60)]
+    pub rescan_secs: u64,
+
+    /// 处理完当前已轮转的文件后退出，不持续监听
+    #[arg(long)]
+    pub once: bool,
+
+    /// 目录锁被其他实例持有时仍强制接管（仅在确认该实例已卡死时使用）
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// 监听 sqllog 目录，持续处理新轮转出的 `dmsql_*.log` 文件。
+///
+/// 同一实例中最新的文件视为仍在写入，待出现更新的文件后才处理；
+/// 处理结果以 JSON Lines 格式写入输出目录，完成后移动源文件或写入标记文件。
+/// 运行期间在日志目录中持有锁文件，防止多个实例重复处理同一目录。
+pub fn run(
+    args: &DaemonArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    export_cfg: &ExportConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    let dir = PathBuf::from(&cfg.sqllog_path);
+    let lock = LockFile::acquire(&dir, args.force)?;
+    debug!("已获取目录锁: {}", lock.path().display());
+    fs::create_dir_all(&args.output)?;
+    if let Some(done) = &args.done_dir {
+        fs::create_dir_all(done)?;
+    }
+    let pipeline = pipeline(cfg, err_cfg).set_ordered(true);
+    let process_pending = || -> CommandResult<usize> {
+        let files = input::collect_files(&dir)?;
+        let pending: Vec<PathBuf> = input::rotated_files(&files)
+            .into_iter()
+            .filter(|p| !done_marker(p).exists())
+            .collect();
+        for file in &pending {
+            process_file(&pipeline, file, args, export_cfg, rules)?;
+        }
+        Ok(pending.len())
+    };
+
+    let processed = process_pending()?;
+    info!("已处理 {} 个已轮转的文件", processed);
+    if args.once {
+        return Ok(());
+    }
+
+    // 监听线程只负责通知，文件处理在当前线程中进行
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|e| e.kind.is_create() || e.kind.is_modify()) {
+            let _ = tx.send(());
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    info!("开始监听目录: {}", dir.display());
+
+    let rescan = Duration::from_secs(args.rescan_secs.max(1));
+    loop {
+        match rx.recv_timeout(rescan) {
+            // 合并短时间内的多次通知
+            Ok(()) => while rx.try_recv().is_ok() {},
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if let Err(e) = process_pending() {
+            warn!("处理新文件失败: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// 导出单个文件并标记完成；先写入临时文件，完成后再重命名，避免下游读到不完整的结果
+fn process_file(
+    pipeline: &Pipeline,
+    file: &Path,
+    args: &DaemonArgs,
+    export_cfg: &ExportConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let name = export_cfg.compress.apply_to(&format!("{stem}.jsonl"));
+    let target = Path::new(&args.output).join(&name);
+    let partial = Path::new(&args.output).join(format!("{name}.part"));
+
+    let out = BufWriter::new(fs::File::create(&partial)?);
+    let mut writer = JsonlWriter::new(Encoder::new(out, export_cfg.compress)?);
+    let mut result = Ok(());
+    let instance = InstanceInfo::from_path(file);
+    let max_body_len = export_cfg.max_body_len;
+    let summary = pipeline.run(
+        vec![file.to_path_buf()],
+        |src, rec| {
+            let mut log = Sqllog::from_record(&rec);
+            log.instance = instance.clone();
+            log.record_id = src.record_id(&rec);
+            log.tags = rules.tags(&rec);
+            log.truncated = truncate_body(&mut log.description, max_body_len);
+            Some(log)
+        },
+        |log| {
+            if result.is_ok() {
+                result = writer.write(&log);
+            }
+        },
+    )?;
+    result?;
+    let count = writer.count();
+    writer.into_inner().finish()?;
+    if !summary.failed_files.is_empty() {
+        // 读取失败的文件不标记完成，下次扫描时重试
+        fs::remove_file(&partial)?;
+        return Ok(());
+    }
+    fs::rename(&partial, &target)?;
+
+    match &args.done_dir {
+        Some(done) => {
+            let name = file.file_name().unwrap_or_default();
+            fs::rename(file, Path::new(done).join(name))?;
+        }
+        None => {
+            fs::write(done_marker(file), format!("{count}\n"))?;
+        }
+    }
+    info!(
+        "已处理 {}: {} 条记录 -> {}",
+        file.display(),
+        count,
+        target.display()
+    );
+    Ok(())
+}
+
+/// 文件处理完成后写入的标记文件路径：`<文件名>.done`
+fn done_marker(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(DONE_SUFFIX);
+    PathBuf::from(name)
+}
@@ -0,0 +1,69 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+
+use crate::{analysis::doctor::diagnose, command::open_output, error::CommandResult};
+
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// 要检查的 sqllog 文件
+    pub file: PathBuf,
+
+    /// 读取文件开头多少 MB 作为样本
+    #[arg(long, default_value_t = 4)]
+    pub sample_mb: u64,
+
+    /// 报告输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+/// 检查输入文件的编码、时间戳格式、元数据键顺序等，给出解析模式建议
+pub fn run(args: &DoctorArgs) -> CommandResult<()> {
+    let file = File::open(&args.file)?;
+    let file_bytes = file.metadata()?.len();
+    let mut sample = Vec::new();
+    file.take(args.sample_mb.max(1) * 1024 * 1024)
+        .read_to_end(&mut sample)?;
+    let d = diagnose(&sample, file_bytes);
+
+    let mut out = open_output(args.output.as_deref())?;
+    writeln!(out, "文件: {}", args.file.display())?;
+    writeln!(
+        out,
+        "大小: {} 字节（样本 {} 字节）",
+        d.file_bytes, d.sample_bytes
+    )?;
+    writeln!(out, "编码: {}", d.encoding())?;
+    writeln!(out, "换行符: {}", d.line_ending())?;
+    writeln!(out, "时间戳格式: {}", d.timestamp_format())?;
+    writeln!(
+        out,
+        "记录数: 样本中 {} 条，估计全文件约 {} 条",
+        d.records,
+        d.estimated_records()
+    )?;
+    writeln!(
+        out,
+        "可解析: 严格模式 {} 条，宽松校验 {} 条",
+        d.strict_ok, d.lenient_ok
+    )?;
+    writeln!(out, "元数据键顺序:")?;
+    for (keys, n) in d.top_meta_variants() {
+        writeln!(out, "  {n:>8}  {keys}")?;
+    }
+    let errors = d.top_strict_errors();
+    if !errors.is_empty() {
+        writeln!(out, "严格模式失败原因:")?;
+        for (reason, n) in errors {
+            writeln!(out, "  {n:>8}  {reason}")?;
+        }
+    }
+    writeln!(out, "建议: {}", d.recommendation())?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::events::{self, EventTimeline},
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct EventsArgs {
+    /// 时间桶长度（毫秒）
+    #[arg(short, long, default_value_t = 60_000)]
+    pub bucket_ms: i64,
+
+    /// 输出各时间桶的系统消息数与同期语句耗时，而不是消息列表
+    #[arg(long)]
+    pub timeline: bool,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 提取检查点、PURGE、REDO 等系统消息，按时间输出，或与同期语句耗时一起按时间桶汇总
+pub fn run(
+    args: &EventsArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut timeline = EventTimeline::new(args.bucket_ms);
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |src, rec| events::sample(&src.instance, &rec, export_cfg.max_body_len),
+        |s| timeline.add_sample(s),
+    )?;
+
+    let rows = if args.timeline {
+        let rows = timeline.timeline();
+        args.report.write(&rows, args.output.as_deref())?;
+        rows.len()
+    } else {
+        let rows = timeline.events();
+        args.report.write(&rows, args.output.as_deref())?;
+        rows.len()
+    };
+    info!(
+        "系统消息提取完成: 共 {} 个文件, {} 条记录, {} 行输出",
+        summary.files, summary.records, rows
+    );
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::ops::ControlFlow;
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::{exec::ExecLookup, truncate_body, write_csv},
+    command::{open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct ExecArgs {
+    /// 要查找的 EXEC_ID，可指定多个
+    #[arg(required = true)]
+    pub ids: Vec<u64>,
+
+    /// 找到全部 EXEC_ID 后继续扫描剩余文件（同一 EXEC_ID 可能出现在多个实例的日志中）
+    #[arg(long)]
+    pub all: bool,
+
+    /// CSV 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 输出的 SQL 正文最大长度（字节），覆盖配置中的 export.max_body_len
+    #[arg(long)]
+    pub max_body_len: Option<usize>,
+}
+
+/// 按 EXEC_ID 定位执行记录，输出所在文件、偏移、执行指标与语句文本
+pub fn run(
+    args: &ExecArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut lookup = ExecLookup::new(args.ids.iter().copied());
+    let mut hits = Vec::new();
+    // 顺序扫描：补全单独记录的执行指标需要按原文顺序看到前面的语句记录
+    let summary = pipeline(cfg, err_cfg).scan(files, |src, rec| {
+        if let Some(mut hit) = lookup.add(&src.path.to_string_lossy(), &rec) {
+            truncate_body(&mut hit.sql, export_cfg.max_body_len);
+            hits.push(hit);
+            if lookup.is_done() && !args.all {
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    })?;
+    write_csv(&hits, open_output(args.output.as_deref())?)?;
+    info!(
+        "EXEC_ID 查找完成: 查找 {} 个, 找到 {} 条记录, 扫描 {} 条记录",
+        args.ids.len(),
+        hits.len(),
+        summary.records
+    );
+    Ok(())
+}
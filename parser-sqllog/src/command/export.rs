@@ -0,0 +1,328 @@
+use std::{collections::HashMap, io, path::PathBuf};
+
+use clap::Args;
+use dm_database_parser::{
+    InstanceInfo, KeywordMatcher, RecordMetrics, Sqllog, parser::ParsedRecord, sql,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    analysis::{stats, truncate_body},
+    anonymize::Anonymizer,
+    command::{CategoryArgs, DedupArgs, WindowArgs, open_compressed_output, pipeline},
+    config::{
+        analysis::AnalysisConfig,
+        error_exporter::ErrorExporterConfig,
+        export::{ExportConfig, SinkKind},
+        sqllog::SqllogConfig,
+    },
+    dedup::DedupKey,
+    error::CommandResult,
+    exporter::{
+        compress::Compression,
+        manifest::{Manifest, ManifestEntry},
+        record::{RecordFormat, RecordWriter},
+        rolling::RollPolicy,
+        schema::Projection,
+        sink::{Sink, SinkRecord, SinkWriters},
+    },
+    input,
+    pipeline::{Pipeline, PipelineSummary, Source},
+    rules::RuleSet,
+    sort::ExternalSorter,
+};
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// 输出路径；缺省且未配置 `[[export.sink]]` 时输出到标准输出。指定 `--split-by-ep` 时为输出目录
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 按 EP 节点拆分输出：EP N 的记录写入 `<output>/epN/records.jsonl`
+    #[arg(long, requires = "output")]
+    pub split_by_ep: bool,
+
+    /// 写出已处理文件清单（路径、大小、SHA-256、记录数、起止时间）的 JSON 文件
+    #[arg(long, conflicts_with_all = ["offset", "limit"])]
+    pub manifest: Option<String>,
+
+    /// 跳过大小与 SHA-256 都与已有清单一致的文件，清单中保留其原条目
+    #[arg(long, requires = "manifest")]
+    pub skip_unchanged: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = RecordFormat::Jsonl)]
+    pub format: RecordFormat,
+
+    /// 只输出选中的字段，逗号分隔，如 `ts,user,exec_time_ms,fingerprint`
+    #[arg(long)]
+    pub fields: Option<Projection>,
+
+    /// 不按输入顺序输出：各批次按完成顺序写出，吞吐最高但记录顺序不确定
+    #[arg(long, conflicts_with_all = ["sort", "dedup_window"])]
+    pub unordered: bool,
+
+    /// 跨文件按时间戳全局排序后输出；记录数超过 `export.sort_run_size` 时溢写到临时文件再归并
+    #[arg(long)]
+    pub sort: bool,
+
+    /// 只输出正文中出现任一关键字（表名、过程名等完整标识符，不区分大小写）的记录，逗号分隔，
+    /// 如 `orders,customers,proc_x`
+    #[arg(long)]
+    pub match_any: Option<KeywordMatcher>,
+
+    /// 按 `[export.anonymize]` 脱敏：用户、模式、表名替换为带密钥的假名，字面量替换为 `?`
+    #[arg(long)]
+    pub anonymize: bool,
+
+    /// 导出的 SQL 正文最大长度（字节），覆盖配置中的 export.max_body_len
+    #[arg(long)]
+    pub max_body_len: Option<usize>,
+
+    /// 导出文件的压缩格式，覆盖配置中的 export.compress
+    #[arg(long, value_enum)]
+    pub compress: Option<Compression>,
+
+    /// 单个导出文件的最大字节数，覆盖配置中的 export.max_output_size
+    #[arg(long)]
+    pub max_output_size: Option<u64>,
+
+    /// 每隔多少秒滚动导出文件，覆盖配置中的 export.roll_every
+    #[arg(long)]
+    pub roll_every: Option<u64>,
+
+    #[command(flatten)]
+    pub categories: CategoryArgs,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    #[command(flatten)]
+    pub dedup: DedupArgs,
+}
+
+/// 一条导出记录（含按各统计目标的分组维度提取的样本）及其所属文件在输入列表中的下标
+#[derive(Serialize, Deserialize)]
+struct Item {
+    file: usize,
+    record: SinkRecord,
+    /// 去重用的判重键，未指定 `--dedup-window` 时为 None
+    dedup: Option<DedupKey>,
+}
+
+/// 导出所有记录：写到 `--output`（或标准输出）以及配置的各个 `[[export.sink]]`，只解析一遍
+pub fn run(
+    args: &ExportArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    export_cfg: &ExportConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    let mut files = input::collect_inputs(cfg)?;
+    // (未变化而沿用的旧条目, 本次处理的文件条目)，与 `files` 一一对应
+    let mut manifest: Option<(Vec<ManifestEntry>, Vec<ManifestEntry>)> = None;
+    if let Some(path) = &args.manifest {
+        let previous = Manifest::load(path)?;
+        let (mut carried, mut entries, mut kept) = (Vec::new(), Vec::new(), Vec::new());
+        for file in files {
+            let entry = match ManifestEntry::for_file(&file) {
+                Ok(entry) => entry,
+                Err(e) if !cfg.fail_fast => {
+                    warn!("无法读取文件 {}: {}", file.display(), e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            match previous.get(&entry.path) {
+                Some(old) if args.skip_unchanged && previous.is_unchanged(&entry) => {
+                    carried.push(old.clone())
+                }
+                _ => {
+                    entries.push(entry);
+                    kept.push(file);
+                }
+            }
+        }
+        if !carried.is_empty() {
+            info!("跳过 {} 个未变化的文件", carried.len());
+        }
+        files = kept;
+        manifest = Some((carried, entries));
+    }
+
+    let fingerprint = analysis_cfg.fingerprint;
+    let fields = args.fields.clone().map(|f| f.set_fingerprint(fingerprint));
+    let mut sinks = Vec::new();
+    if let Some(output) = &args.output {
+        let fields = fields.clone();
+        sinks.push(Sink::file(
+            output,
+            args.format,
+            fields,
+            args.split_by_ep,
+            export_cfg,
+        ));
+    } else if export_cfg.sinks.is_empty() {
+        if RollPolicy::from_config(export_cfg).is_set() {
+            warn!("输出到标准输出时不滚动文件，忽略 max_output_size / roll_every");
+        }
+        let out = open_compressed_output(None, export_cfg.compress)?;
+        sinks.push(Sink::Stream(RecordWriter::new(
+            out,
+            args.format,
+            fields.as_ref(),
+        )?));
+    }
+    let offset = sinks.len();
+    for (i, sink_cfg) in export_cfg.sinks.iter().enumerate() {
+        sinks.push(Sink::open(i, sink_cfg, export_cfg, fingerprint)?);
+    }
+    // 与 `sinks` 一一对应：统计目标的分组维度
+    let group_by: Vec<_> = (0..offset)
+        .map(|_| None)
+        .chain(
+            export_cfg
+                .sinks
+                .iter()
+                .map(|s| (s.kind == SinkKind::Stats).then_some(s.group_by)),
+        )
+        .collect();
+    let wants_samples = group_by.iter().any(Option::is_some);
+
+    let index: HashMap<PathBuf, usize> = files.iter().cloned().zip(0..).collect();
+    let max_body_len = export_cfg.max_body_len;
+    let anonymizer = if args.anonymize {
+        Some(Anonymizer::new(&export_cfg.anonymize)?)
+    } else {
+        None
+    };
+    let mut dedup = args.dedup.dedup();
+    let wants_dedup = dedup.is_some();
+    let map = |src: &Source, rec: ParsedRecord<'_>| {
+        if !args.categories.matches(sql::categorize(&rec.body)) {
+            return None;
+        }
+        if args
+            .match_any
+            .as_ref()
+            .is_some_and(|m| !m.is_match(&rec.body))
+        {
+            return None;
+        }
+        let tags = rules.tags(&rec);
+        // 统计样本在截断正文之前提取，指纹基于完整的 SQL
+        let samples = if wants_samples {
+            let mut metrics = RecordMetrics {
+                tags: tags.clone(),
+                ..RecordMetrics::from_record_with(&rec, &fingerprint)
+            };
+            if let Some(anonymizer) = &anonymizer {
+                anonymizer.apply_metrics(&mut metrics);
+            }
+            group_by
+                .iter()
+                .map(|g| g.and_then(|g| stats::sample(metrics.clone(), g, &src.instance)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let dedup = if wants_dedup {
+            DedupKey::of(&rec)
+        } else {
+            None
+        };
+        let mut log = Sqllog::from_record(&rec);
+        log.instance = InstanceInfo::from_path(&src.path);
+        log.record_id = src.record_id(&rec);
+        log.tags = tags;
+        if let Some(anonymizer) = &anonymizer {
+            anonymizer.apply(&mut log);
+        }
+        log.truncated = truncate_body(&mut log.description, max_body_len);
+        Some(Item {
+            file: index[&src.path],
+            record: SinkRecord { log, samples },
+            dedup,
+        })
+    };
+    let pipeline = pipeline(cfg, err_cfg).set_ordered(!args.unordered);
+    let mut entries = manifest.as_mut().map(|(_, e)| e);
+    let mut writers = SinkWriters::new(sinks, export_cfg.writer_threads);
+    let mut write = |item: Item| {
+        if let Some(dedup) = dedup.as_mut()
+            && dedup.is_duplicate(item.dedup)
+        {
+            return Ok(());
+        }
+        if let Some(entries) = entries.as_deref_mut() {
+            entries[item.file].add(&item.record.log.sqllog_datetime);
+        }
+        writers.write(item.record)
+    };
+    let summary = if args.sort {
+        let mut sorter = ExternalSorter::new(export_cfg.sort_run_size, export_cfg.sort_dir());
+        let summary = write_records(&pipeline, &args.window, files, map, &mut |item| {
+            sorter.push(item.record.log.sqllog_datetime.clone(), item)
+        })?;
+        if sorter.spilled() > 0 {
+            info!("排序溢写了 {} 个临时文件，开始归并", sorter.spilled());
+        }
+        for item in sorter.finish()? {
+            write(item?)?;
+        }
+        summary
+    } else {
+        write_records(&pipeline, &args.window, files, map, &mut write)?
+    };
+
+    let mut records = 0;
+    for report in writers.finish()? {
+        info!(
+            "已写出 {}: {} 条记录, {} 个文件",
+            report.target, report.records, report.files
+        );
+        records = records.max(report.records);
+    }
+    if let Some(dedup) = &dedup {
+        info!("去重折叠了 {} 条重复记录", dedup.folded());
+    }
+    info!("导出完成: 共 {} 个文件, {} 条记录", summary.files, records);
+
+    if let (Some(path), Some((carried, entries))) = (&args.manifest, manifest) {
+        let mut files: Vec<ManifestEntry> = carried.into_iter().chain(entries).collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Manifest::new(files).save(path)?;
+        info!("已写出清单: {}", path);
+    }
+    Ok(())
+}
+
+/// 运行流水线并把每条记录交给 `write`，遇到第一个写入错误后丢弃剩余记录并返回该错误。
+///
+/// 指定了窗口时顺序扫描；否则按流水线的设置，按输入顺序或按各批次的到达顺序写出。
+fn write_records<M>(
+    pipeline: &Pipeline,
+    window: &WindowArgs,
+    files: Vec<PathBuf>,
+    map: M,
+    write: &mut dyn FnMut(Item) -> io::Result<()>,
+) -> io::Result<PipelineSummary>
+where
+    M: Fn(&Source, ParsedRecord<'_>) -> Option<Item> + Sync,
+{
+    let mut result = Ok(());
+    let summary = if window.is_set() {
+        let (logs, summary) = window.collect(pipeline, files, map)?;
+        result = logs.into_iter().try_for_each(&mut *write);
+        summary
+    } else {
+        pipeline.run(files, map, |item| {
+            if result.is_ok() {
+                result = write(item);
+            }
+        })?
+    };
+    result.map(|_| summary)
+}
@@ -0,0 +1,42 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::heatmap::{self, Heatmap},
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct HeatmapArgs {
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按星期 × 小时统计语句执行次数与耗时，输出 7 × 24 行供绘制热力图
+pub fn run(
+    args: &HeatmapArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut map = Heatmap::new();
+    let summary =
+        pipeline(cfg, err_cfg).run(files, |_, rec| heatmap::sample(&rec), |s| map.add_sample(s))?;
+
+    let cells = map.cells();
+    args.report.write(&cells, args.output.as_deref())?;
+    info!(
+        "热力图统计完成: 共 {} 个文件, {} 条记录, {} 次执行",
+        summary.files,
+        summary.records,
+        cells.iter().map(|c| c.executions).sum::<u64>()
+    );
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::idle::{self, IdleTracker},
+    command::{ReportArgs, pipeline},
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct IdleArgs {
+    /// 事务内空闲阈值（毫秒），覆盖配置文件中的 analysis.idle_in_trx_threshold_ms
+    #[arg(short, long)]
+    pub threshold_ms: Option<u64>,
+
+    /// 输出全部会话的间隔统计，而不只是事务内长时间空闲的会话
+    #[arg(long)]
+    pub all: bool,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按会话计算相邻语句的间隔，报告持有未提交事务却空闲超过阈值的会话
+pub fn run(
+    args: &IdleArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+) -> CommandResult<()> {
+    let threshold = args
+        .threshold_ms
+        .unwrap_or(analysis_cfg.idle_in_trx_threshold_ms);
+    let files = input::collect_inputs(cfg)?;
+    let mut tracker = IdleTracker::new(threshold);
+    // 间隔依赖同一会话内记录的先后顺序
+    let summary = pipeline(cfg, err_cfg).set_ordered(true).run(
+        files,
+        |_, rec| idle::sample(&rec),
+        |e| tracker.add_event(e),
+    )?;
+
+    let rows = tracker.rows(args.all);
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "会话空闲分析完成: 阈值 {} ms, 共 {} 个文件, {} 条记录, {} 个会话",
+        threshold,
+        summary.files,
+        summary.records,
+        rows.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::{
+        large_result::{self, LargeResultDetector},
+        truncate_body, write_csv,
+    },
+    command::{open_output, pipeline},
+    config::{
+        analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, export::ExportConfig,
+        sqllog::SqllogConfig,
+    },
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct LargeResultArgs {
+    /// ROWCOUNT 阈值，覆盖配置文件中的 analysis.large_rowcount_threshold
+    #[arg(short, long)]
+    pub threshold: Option<u64>,
+
+    /// CSV 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+/// 列出返回/影响行数超过阈值的语句，按指纹和用户分组
+pub fn run(
+    args: &LargeResultArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let threshold = args
+        .threshold
+        .unwrap_or(analysis_cfg.large_rowcount_threshold);
+    let files = input::collect_inputs(cfg)?;
+    let mut detector = LargeResultDetector::new();
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| large_result::sample(&rec, threshold, &analysis_cfg.fingerprint),
+        |s| detector.add_sample(s),
+    )?;
+
+    let mut rows = detector.rows();
+    for row in &mut rows {
+        row.truncated = truncate_body(&mut row.sample, export_cfg.max_body_len);
+    }
+    write_csv(&rows, open_output(args.output.as_deref())?)?;
+    info!(
+        "大结果集分析完成: 阈值 {}, 共 {} 个文件, {} 组语句",
+        threshold,
+        summary.files,
+        rows.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::ops::ControlFlow;
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::locks::LockTracker,
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct LocksArgs {
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 列出死锁、锁超时与锁等待消息，关联所在会话此前的语句以及消息中提到的其他事务与会话
+pub fn run(
+    args: &LocksArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut tracker = LockTracker::new(export_cfg.max_body_len);
+    let mut rows = Vec::new();
+    // 顺序扫描：关联会话与事务需要按原文顺序看到之前的语句
+    let summary = pipeline(cfg, err_cfg).scan(files, |src, rec| {
+        rows.extend(tracker.add(&src.path.to_string_lossy(), &src.instance, &rec));
+        ControlFlow::Continue(())
+    })?;
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "锁消息提取完成: 共 {} 个文件, {} 条记录, {} 条锁相关消息",
+        summary.files,
+        summary.records,
+        rows.len()
+    );
+    Ok(())
+}
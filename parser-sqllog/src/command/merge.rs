@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    command::open_compressed_output,
+    config::{analysis::AnalysisConfig, export::ExportConfig},
+    error::CommandResult,
+    exporter::{
+        manifest::Manifest,
+        merge,
+        record::{RecordFormat, RecordWriter},
+        schema::Projection,
+        sink::Sink,
+    },
+};
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    /// 之前导出的 JSON Lines 文件（导出时未指定 `--fields`），可为 `.gz` / `.zst` 压缩文件
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = RecordFormat::Jsonl)]
+    pub format: RecordFormat,
+
+    /// 只输出选中的字段，逗号分隔
+    #[arg(long)]
+    pub fields: Option<Projection>,
+
+    /// 各次导出写出的清单文件，逗号分隔；合并后写入 `--manifest`
+    #[arg(long, value_delimiter = ',', requires = "manifest")]
+    pub manifests: Vec<String>,
+
+    /// 合并后的清单文件路径
+    #[arg(long, requires = "manifests")]
+    pub manifest: Option<String>,
+}
+
+/// 合并多次导出的结果：按时间戳全局排序，按记录标识去重后写出为一个数据集
+pub fn run(
+    args: &MergeArgs,
+    analysis_cfg: &AnalysisConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let fields = args
+        .fields
+        .clone()
+        .map(|f| f.set_fingerprint(analysis_cfg.fingerprint));
+    let mut sink = match &args.output {
+        Some(output) => Sink::file(output, args.format, fields, false, export_cfg),
+        None => {
+            let out = open_compressed_output(None, export_cfg.compress)?;
+            Sink::Stream(RecordWriter::new(out, args.format, fields.as_ref())?)
+        }
+    };
+    let summary = merge::merge_records(
+        &args.inputs,
+        export_cfg.sort_run_size,
+        &export_cfg.sort_dir(),
+        |log| sink.write(&log, None),
+    )?;
+    let report = sink.finish()?;
+    info!(
+        "已写出 {}: {} 条记录, {} 个文件",
+        report.target, report.records, report.files
+    );
+
+    if let Some(path) = &args.manifest {
+        let manifests = args
+            .manifests
+            .iter()
+            .map(Manifest::load)
+            .collect::<Result<Vec<_>, _>>()?;
+        Manifest::merge(manifests).save(path)?;
+        info!("已写出清单: {}", path);
+    }
+    info!(
+        "合并完成: 共 {} 个文件, 读入 {} 条记录, 写出 {} 条, 去除重复 {} 条",
+        args.inputs.len(),
+        summary.read,
+        summary.written,
+        summary.duplicates
+    );
+    Ok(())
+}
@@ -1 +1,401 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, ValueEnum};
+use dm_database_parser::{RecordCategory, parser::ParsedRecord};
+use serde::Serialize;
+
+#[cfg(feature = "query")]
+use crate::analysis::table::write_table_records;
+#[cfg(any(feature = "xlsx", feature = "template"))]
+use crate::{
+    analysis::{
+        activity::{ActivitySample, ActivityTracker, SessionRow, TimeSeriesRow},
+        stats::{GroupBy, StatsAggregator, StatsRow, sample as stats_sample},
+    },
+    config::analysis::AnalysisConfig,
+    input,
+    rules::RuleSet,
+};
+use crate::{
+    analysis::{
+        table::{TableOptions, write_table},
+        write_csv,
+    },
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    dedup::Dedup,
+    error::CommandResult,
+    exporter::compress::{Compression, Encoder},
+    pipeline::{Pipeline, PipelineSummary, Source},
+};
+#[cfg(any(feature = "xlsx", feature = "template"))]
+use dm_database_parser::RecordMetrics;
+
+pub mod audit;
+pub mod bench;
 pub mod cli;
+pub mod concurrency;
+pub mod daemon;
+pub mod doctor;
+pub mod events;
+pub mod exec;
+pub mod export;
+pub mod heatmap;
+pub mod idle;
+pub mod large_result;
+pub mod locks;
+pub mod merge;
+pub mod otlp;
+pub mod pool;
+pub mod prepared;
+#[cfg(feature = "query")]
+pub mod query;
+#[cfg(feature = "template")]
+pub mod render;
+pub mod replay;
+pub mod rollback;
+pub mod row_latency;
+pub mod saturation;
+pub mod schema;
+pub mod show;
+pub mod slice;
+pub mod stats;
+pub mod summary;
+pub mod tables;
+pub mod tail;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod verify;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+/// 根据 `[sqllog]` 与 `[error_exporter]` 配置创建处理流水线
+pub(crate) fn pipeline(cfg: &SqllogConfig, err_cfg: &ErrorExporterConfig) -> Pipeline {
+    Pipeline::from_config(cfg).set_error_exporter(err_cfg.clone())
+}
+
+/// 打开子命令的输出目标：给定路径时写入文件，否则写到标准输出。
+///
+/// 路径以 `.gz` / `.zst` 结尾时按对应格式流式压缩。
+pub(crate) fn open_output(path: Option<&str>) -> io::Result<Box<dyn Write>> {
+    open_compressed_output(path, Compression::None).map(|w| w as Box<dyn Write>)
+}
+
+/// 按指定格式压缩输出，写入文件时路径自动补上压缩扩展名；`compression` 为 `None` 时按路径扩展名推断。
+///
+/// 返回的写入器可移交给导出的写入线程。
+pub(crate) fn open_compressed_output(
+    path: Option<&str>,
+    compression: Compression,
+) -> io::Result<Box<dyn Write + Send>> {
+    Ok(match path {
+        Some(p) => {
+            let c = match compression {
+                Compression::None => Compression::from_path(Path::new(p)),
+                c => c,
+            };
+            let file = BufWriter::new(File::create(c.apply_to(p))?);
+            match c {
+                Compression::None => Box::new(file),
+                c => Box::new(Encoder::new(file, c)?),
+            }
+        }
+        None => match compression {
+            Compression::None => Box::new(BufWriter::new(io::stdout())),
+            c => Box::new(Encoder::new(BufWriter::new(io::stdout()), c)?),
+        },
+    })
+}
+
+/// 报告的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    /// 对齐的终端表格
+    Table,
+    /// JSON 数组，每行一个对象
+    Json,
+}
+
+/// 以 JSON 数组写出报告行，末尾换行
+fn write_json<W: Write, T: Serialize>(rows: &[T], mut writer: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut writer, rows)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+/// 表格输出是否着色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// 仅在输出到终端且未设置 `NO_COLOR` 时着色
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// 是否着色：`Auto` 时仅在输出到终端时着色；设置了 `NO_COLOR` 时均不着色
+    pub(crate) fn enabled(self, output: Option<&str>) -> bool {
+        let color = match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => output.is_none() && io::stdout().is_terminal(),
+        };
+        color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+    }
+}
+
+/// 报告输出选项
+#[derive(Debug, Clone, Default, Args)]
+pub struct ReportArgs {
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub format: OutputFormat,
+
+    /// 表格输出时不按终端宽度截断列，并输出全部列
+    #[arg(long)]
+    pub wide: bool,
+
+    /// 表格输出时是否着色
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+impl ReportArgs {
+    /// 按所选格式把报告行写到 `output`（缺省为标准输出）
+    pub(crate) fn write<T: Serialize>(
+        &self,
+        rows: &[T],
+        output: Option<&str>,
+    ) -> CommandResult<()> {
+        self.write_with_wide_only(rows, output, &[])
+    }
+
+    /// 同 [`ReportArgs::write`]，但表格输出未指定 `--wide` 时省略 `wide_only` 中的列
+    pub(crate) fn write_with_wide_only<T: Serialize>(
+        &self,
+        rows: &[T],
+        output: Option<&str>,
+        wide_only: &'static [&'static str],
+    ) -> CommandResult<()> {
+        let writer = open_output(output)?;
+        match self.format {
+            OutputFormat::Csv => write_csv(rows, writer)?,
+            OutputFormat::Table => write_table(
+                rows,
+                writer,
+                &self.table_options(output).set_wide_only(wide_only),
+            )?,
+            OutputFormat::Json => write_json(rows, writer)?,
+        }
+        Ok(())
+    }
+
+    /// 按所选格式写出已转换为文本的表头和各行
+    #[cfg(feature = "query")]
+    pub(crate) fn write_records(
+        &self,
+        header: &[String],
+        rows: &[Vec<String>],
+        output: Option<&str>,
+    ) -> CommandResult<()> {
+        let writer = open_output(output)?;
+        match self.format {
+            OutputFormat::Csv => {
+                let mut wtr = csv::Writer::from_writer(writer);
+                wtr.write_record(header)?;
+                for row in rows {
+                    wtr.write_record(row)?;
+                }
+                wtr.flush()?;
+            }
+            OutputFormat::Table => {
+                write_table_records(header, rows, writer, &self.table_options(output))?
+            }
+            OutputFormat::Json => {
+                let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        header
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().cloned().map(serde_json::Value::String))
+                            .collect()
+                    })
+                    .collect();
+                write_json(&objects, writer)?
+            }
+        }
+        Ok(())
+    }
+
+    fn table_options(&self, output: Option<&str>) -> TableOptions {
+        TableOptions::from_env(self.color.enabled(output), self.wide)
+    }
+}
+
+/// 按记录类别过滤，用于排除事务控制、登录等非语句记录
+#[derive(Debug, Clone, Default, Args)]
+pub struct CategoryArgs {
+    /// 只处理这些类别的记录，逗号分隔（statement / transaction / login / error / system / other），缺省时处理全部记录
+    #[arg(long, value_delimiter = ',')]
+    pub category: Vec<RecordCategory>,
+}
+
+impl CategoryArgs {
+    pub fn matches(&self, category: RecordCategory) -> bool {
+        self.category.is_empty() || self.category.contains(&category)
+    }
+}
+
+/// 折叠客户端重试风暴：同一会话在窗口内重复提交的相同语句只保留第一条，见 [`crate::dedup`]
+#[derive(Debug, Clone, Default, Args)]
+pub struct DedupArgs {
+    /// 去重窗口（秒）；指定后按记录顺序处理
+    #[arg(long, value_name = "SECONDS")]
+    pub dedup_window: Option<u64>,
+}
+
+impl DedupArgs {
+    /// 未指定窗口时返回 None
+    pub fn dedup(&self) -> Option<Dedup> {
+        self.dedup_window
+            .map(|secs| Dedup::new(secs.saturating_mul(1000) as i64))
+    }
+}
+
+/// 记录窗口：跳过前 `offset` 条匹配记录后最多输出 `limit` 条
+#[derive(Debug, Clone, Default, Args)]
+pub struct WindowArgs {
+    /// 跳过前 M 条匹配记录
+    #[arg(long, default_value_t = 0)]
+    pub offset: usize,
+
+    /// 最多输出 N 条匹配记录，取满后立即停止读取
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+impl WindowArgs {
+    /// 是否指定了窗口；未指定时子命令走多线程流水线
+    pub fn is_set(&self) -> bool {
+        self.offset > 0 || self.limit.is_some()
+    }
+
+    /// 按文件顺序顺序扫描，收集窗口内 `map` 返回的结果，取满 `limit` 条后提前结束
+    pub(crate) fn collect<T, M>(
+        &self,
+        pipeline: &Pipeline,
+        files: Vec<PathBuf>,
+        map: M,
+    ) -> io::Result<(Vec<T>, PipelineSummary)>
+    where
+        M: Fn(&Source, ParsedRecord<'_>) -> Option<T>,
+    {
+        let mut items = Vec::new();
+        if self.limit == Some(0) {
+            return Ok((items, PipelineSummary::default()));
+        }
+        let mut skipped = 0;
+        let summary = pipeline.scan(files, |src, rec| {
+            if let Some(item) = map(src, rec) {
+                if skipped < self.offset {
+                    skipped += 1;
+                } else {
+                    items.push(item);
+                }
+            }
+            match self.limit {
+                Some(limit) if items.len() >= limit => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        })?;
+        Ok((items, summary))
+    }
+}
+
+/// 概览报告的内容选项：指纹统计、会话与时间序列，供 Excel 与模板报告共用
+#[cfg(any(feature = "xlsx", feature = "template"))]
+#[derive(Debug, Clone, Args)]
+pub struct OverviewArgs {
+    /// 指纹统计只保留总耗时最高的前 N 个指纹
+    #[arg(short, long, default_value_t = 100)]
+    pub top: usize,
+
+    /// 会话列表只保留总耗时最高的前 N 个会话
+    #[arg(long, default_value_t = 1000)]
+    pub top_sessions: usize,
+
+    /// 时间序列的时间桶大小（毫秒）
+    #[arg(short, long, default_value_t = 60_000)]
+    pub bucket_ms: i64,
+
+    #[command(flatten)]
+    pub categories: CategoryArgs,
+}
+
+/// 概览报告的数据：只解析一遍得到的指纹统计（Top SQL）、会话与时间序列
+#[cfg(any(feature = "xlsx", feature = "template"))]
+#[derive(Debug, Clone)]
+pub struct Overview {
+    pub summary: PipelineSummary,
+    pub top_sql: Vec<StatsRow>,
+    pub sessions: Vec<SessionRow>,
+    pub time_series: Vec<TimeSeriesRow>,
+}
+
+#[cfg(any(feature = "xlsx", feature = "template"))]
+impl OverviewArgs {
+    /// 解析所有输入文件并汇总概览数据
+    pub(crate) fn collect(
+        &self,
+        cfg: &SqllogConfig,
+        err_cfg: &ErrorExporterConfig,
+        analysis_cfg: &AnalysisConfig,
+        rules: &RuleSet,
+    ) -> CommandResult<Overview> {
+        let files = input::collect_inputs(cfg)?;
+        let mut agg = StatsAggregator::new(GroupBy::None)
+            .set_spill(analysis_cfg.stats_max_groups, analysis_cfg.spill_dir());
+        let mut activity = ActivityTracker::new(self.bucket_ms);
+        let mut result = Ok(());
+        let summary = pipeline(cfg, err_cfg).run(
+            files,
+            |src, rec| {
+                let m = RecordMetrics {
+                    tags: rules.tags(&rec),
+                    ..RecordMetrics::from_record_with(&rec, &analysis_cfg.fingerprint)
+                };
+                if !self.categories.matches(m.category) {
+                    return None;
+                }
+                let a = ActivitySample::from_metrics(&m, &src.instance);
+                let s = stats_sample(m, GroupBy::None, &src.instance);
+                Some((s, a))
+            },
+            |(s, a)| {
+                if let Some(a) = a {
+                    activity.add(a);
+                }
+                if let Some(s) = s
+                    && result.is_ok()
+                {
+                    result = agg.add_sample(s);
+                }
+            },
+        )?;
+        result?;
+        Ok(Overview {
+            summary,
+            top_sql: agg.finish(Some(self.top))?,
+            sessions: activity.sessions(Some(self.top_sessions)),
+            time_series: activity.time_series(),
+        })
+    }
+}
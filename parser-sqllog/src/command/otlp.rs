@@ -0,0 +1,72 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    command::{open_output, pipeline},
+    config::{
+        analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, export::ExportConfig,
+        sqllog::SqllogConfig,
+    },
+    error::CommandResult,
+    exporter::otlp::{OtlpWriter, Span, parse_utc_offset},
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct OtlpArgs {
+    /// 输出路径，缺省时输出到标准输出；以 `.gz` / `.zst` 结尾时压缩
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 资源属性 `service.name`，即追踪界面中的服务名
+    #[arg(long, default_value = "dameng")]
+    pub service_name: String,
+
+    /// 日志时间相对 UTC 的偏移（日志记录的是服务器本地时间）
+    #[arg(long, value_parser = parse_utc_offset, default_value = "+08:00", allow_hyphen_values = true)]
+    pub utc_offset: i64,
+
+    /// 每行请求包含的 span 数
+    #[arg(long, default_value_t = 1000)]
+    pub batch_size: usize,
+}
+
+/// 把每次语句执行转换为 OpenTelemetry span（开始时间为记录时间，时长为 EXECTIME），
+/// 按会话归入 trace，以 OTLP/JSON 写出
+pub fn run(
+    args: &OtlpArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut writer = OtlpWriter::new(open_output(args.output.as_deref())?, &args.service_name)
+        .set_batch_size(args.batch_size);
+    let mut result = Ok(());
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |src, rec| {
+            Span::from_record(
+                src,
+                &rec,
+                args.utc_offset,
+                export_cfg.max_body_len,
+                &analysis_cfg.fingerprint,
+            )
+        },
+        |span| {
+            if result.is_ok() {
+                result = writer.write(span);
+            }
+        },
+    )?;
+    result?;
+    let spans = writer.count();
+    writer.finish()?;
+    info!(
+        "OTLP 导出完成: 共 {} 个文件, {} 条记录, {} 个 span",
+        summary.files, summary.records, spans
+    );
+    Ok(())
+}
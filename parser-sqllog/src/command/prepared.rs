@@ -0,0 +1,44 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::{
+        prepared::{self, PreparedUsage},
+        write_csv,
+    },
+    command::{open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct PreparedArgs {
+    /// CSV 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+/// 按 appname 统计预编译（绑定变量）与字面量 SQL 的执行比例
+pub fn run(
+    args: &PreparedArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut usage = PreparedUsage::new();
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| prepared::sample(&rec),
+        |(app, style)| usage.add(&app, style),
+    )?;
+
+    let rows = usage.rows();
+    write_csv(&rows, open_output(args.output.as_deref())?)?;
+    info!(
+        "预编译语句分析完成: 共 {} 个文件, {} 个应用",
+        summary.files,
+        rows.len()
+    );
+    Ok(())
+}
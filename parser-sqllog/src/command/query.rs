@@ -0,0 +1,162 @@
+use clap::Args;
+use dm_database_parser::{parser::ParsedRecord, sql};
+use duckdb::{Connection, params};
+use tracing::info;
+
+use crate::{
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+    pipeline::Source,
+};
+
+/// 查询中可用的 `records` 表结构
+const CREATE_RECORDS: &str = "CREATE TABLE records (
+    ts TIMESTAMP,
+    instance VARCHAR,
+    ep VARCHAR,
+    sess VARCHAR,
+    thrd VARCHAR,
+    \"user\" VARCHAR,
+    trxid VARCHAR,
+    stmt VARCHAR,
+    appname VARCHAR,
+    ip VARCHAR,
+    tag VARCHAR,
+    sql VARCHAR,
+    exec_time_ms BIGINT,
+    row_count BIGINT,
+    exec_id BIGINT
+)";
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+    /// 在 `records` 表上执行的 SQL，例如
+    /// `SELECT "user", count(*) FROM records WHERE exec_time_ms > 100 GROUP BY "user"`
+    pub sql: String,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 装入 `records` 表的一行
+struct RecordRow {
+    ts: String,
+    instance: String,
+    ep: Option<String>,
+    sess: Option<String>,
+    thrd: Option<String>,
+    user: Option<String>,
+    trxid: Option<String>,
+    stmt: Option<String>,
+    appname: Option<String>,
+    ip: Option<String>,
+    tag: Option<String>,
+    sql: String,
+    exec_time_ms: Option<i64>,
+    row_count: Option<i64>,
+    exec_id: Option<i64>,
+}
+
+impl RecordRow {
+    fn new(src: &Source, rec: &ParsedRecord<'_>) -> Self {
+        let owned = |v: Option<&str>| v.map(str::to_string);
+        let (tag, _) = sql::split_tag(&rec.body);
+        Self {
+            ts: rec.ts.to_string(),
+            instance: src.instance.clone(),
+            ep: owned(rec.ep),
+            sess: owned(rec.sess),
+            thrd: owned(rec.thrd),
+            user: owned(rec.user),
+            trxid: owned(rec.trxid),
+            stmt: owned(rec.stmt),
+            appname: owned(rec.appname),
+            ip: owned(rec.ip),
+            tag: owned(tag),
+            sql: sql::sql_text(&rec.body).to_string(),
+            exec_time_ms: rec.execute_time_ms.map(|v| v as i64),
+            row_count: rec.row_count.map(|v| v as i64),
+            exec_id: rec.execute_id.map(|v| v as i64),
+        }
+    }
+}
+
+/// 把所有记录装入内存中的 DuckDB `records` 表后执行 SQL
+pub fn run(
+    args: &QueryArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(CREATE_RECORDS)?;
+
+    let mut appender = conn.appender("records")?;
+    let mut append_err = None;
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |src, rec| Some(RecordRow::new(src, &rec)),
+        |r| {
+            if append_err.is_some() {
+                return;
+            }
+            if let Err(e) = appender.append_row(params![
+                r.ts,
+                r.instance,
+                r.ep,
+                r.sess,
+                r.thrd,
+                r.user,
+                r.trxid,
+                r.stmt,
+                r.appname,
+                r.ip,
+                r.tag,
+                r.sql,
+                r.exec_time_ms,
+                r.row_count,
+                r.exec_id,
+            ]) {
+                append_err = Some(e);
+            }
+        },
+    )?;
+    if let Some(e) = append_err {
+        return Err(e.into());
+    }
+    appender.flush()?;
+    drop(appender);
+
+    // 结果统一转换为文本，便于按 CSV 或表格输出
+    let query = format!(
+        "SELECT COLUMNS(*)::VARCHAR FROM ({})",
+        args.sql.trim().trim_end_matches(';')
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let mut rows = stmt.query([])?;
+    let header = rows.as_ref().map(|s| s.column_names()).unwrap_or_default();
+    let mut cells = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut line = Vec::with_capacity(header.len());
+        for i in 0..header.len() {
+            line.push(row.get::<_, Option<String>>(i)?.unwrap_or_default());
+        }
+        cells.push(line);
+    }
+
+    args.report
+        .write_records(&header, &cells, args.output.as_deref())?;
+    info!(
+        "查询完成: 共 {} 个文件, {} 条记录, {} 行结果",
+        summary.files,
+        summary.outputs,
+        cells.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    command::{OverviewArgs, open_output},
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    exporter::template::{self, ReportContext},
+    rules::RuleSet,
+};
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    /// Tera 模板文件；扩展名为 `.html` / `.htm` / `.xml` 时自动转义输出
+    #[arg(short = 'T', long, required_unless_present = "dump_context")]
+    pub template: Option<PathBuf>,
+
+    /// 不渲染模板，以 JSON 输出模板可用的全部数据，便于编写模板
+    #[arg(long, conflicts_with = "template")]
+    pub dump_context: bool,
+
+    /// 传给模板的自定义变量（模板中为 `vars.键`），可重复，如 `--var company=ACME`
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub overview: OverviewArgs,
+}
+
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("应为 键=值 的形式: {s}")),
+    }
+}
+
+/// 用自定义模板渲染指纹统计、会话与时间序列的概览报告
+pub fn run(
+    args: &RenderArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    let overview = args.overview.collect(cfg, err_cfg, analysis_cfg, rules)?;
+    let vars: BTreeMap<String, String> = args.vars.iter().cloned().collect();
+    let ctx = ReportContext::new(&overview, &vars);
+    // 先渲染再打开输出，模板出错时不留下空文件
+    let text = match &args.template {
+        Some(path) => template::render(path, &ctx)?,
+        None => serde_json::to_string_pretty(&ctx).map_err(io::Error::from)? + "\n",
+    };
+    let mut out = open_output(args.output.as_deref())?;
+    out.write_all(text.as_bytes())?;
+    out.flush()?;
+    if let Some(path) = &args.template {
+        info!(
+            "报告已按模板 {} 渲染, 共 {} 个文件, {} 条记录",
+            path.display(),
+            overview.summary.files,
+            overview.summary.records
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    io::{self, BufWriter},
+};
+
+use clap::{Args, ValueEnum};
+use tracing::info;
+
+use crate::{
+    command::{open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    exporter::replay::{ReplayStatement, ReplayWriter},
+    input,
+};
+
+/// 回放节奏
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Pacing {
+    /// 语句依次连续执行
+    #[default]
+    None,
+    /// 在语句之间插入 `CALL DBMS_LOCK.SLEEP(...)`，保留原始间隔
+    Sleep,
+    /// 脚本中不插入等待，另外输出调度文件（JSON）
+    Schedule,
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// 脚本输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 回放节奏
+    #[arg(long, value_enum, default_value_t = Pacing::None)]
+    pub pacing: Pacing,
+
+    /// 调度文件路径，缺省为 `<脚本路径>.schedule.json`；`--pacing schedule` 且输出到标准输出时必须指定
+    #[arg(long)]
+    pub schedule: Option<String>,
+
+    /// 回放速度倍数，2 表示以两倍速回放
+    #[arg(long, default_value_t = 1.0)]
+    pub speed: f64,
+
+    /// 短于该时长（毫秒）的间隔不插入等待
+    #[arg(long, default_value_t = 10)]
+    pub min_sleep_ms: u64,
+}
+
+/// 按日志顺序把执行过的语句生成为回放脚本，可选保留原始的到达节奏
+pub fn run(
+    args: &ReplayArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let schedule_path = match (args.pacing, &args.schedule, &args.output) {
+        (Pacing::Schedule, Some(path), _) => Some(path.clone()),
+        (Pacing::Schedule, None, Some(output)) => Some(format!("{output}.schedule.json")),
+        (Pacing::Schedule, None, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--pacing schedule 输出到标准输出时需要用 --schedule 指定调度文件路径",
+            )
+            .into());
+        }
+        _ => None,
+    };
+    let files = input::collect_inputs(cfg)?;
+    let mut writer = ReplayWriter::new(open_output(args.output.as_deref())?)
+        .set_sleep(args.pacing == Pacing::Sleep)
+        .set_speed(args.speed)
+        .set_min_sleep_ms(args.min_sleep_ms);
+    let mut result = Ok(());
+    // 脚本按日志中的先后顺序回放
+    let summary = pipeline(cfg, err_cfg).set_ordered(true).run(
+        files,
+        |_, rec| ReplayStatement::from_record(&rec),
+        |stmt| {
+            if result.is_ok() {
+                result = writer.write(&stmt);
+            }
+        },
+    )?;
+    result?;
+    let statements = writer.count();
+    if let Some(path) = &schedule_path {
+        let out = BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer_pretty(out, writer.schedule()).map_err(io::Error::from)?;
+    }
+    writer.finish()?;
+    info!(
+        "回放脚本生成完成: 共 {} 个文件, {} 条记录, {} 条语句{}",
+        summary.files,
+        summary.records,
+        statements,
+        schedule_path
+            .map(|p| format!(", 调度文件 {p}"))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::rollback::{self, RollbackTracker},
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct RollbackArgs {
+    /// 时间桶宽度（毫秒）
+    #[arg(short, long, default_value_t = 3_600_000)]
+    pub bucket_ms: i64,
+
+    /// 标记为突增所需的最小回滚数（含登出时未提交的事务）
+    #[arg(long, default_value_t = 5)]
+    pub min_rollbacks: u64,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按会话重建事务，按用户、appname 与时间桶报告回滚次数与回滚率，并标记回滚突增的时间桶
+pub fn run(
+    args: &RollbackArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut tracker = RollbackTracker::new(args.bucket_ms);
+    // 事务重建依赖同一会话内记录的先后顺序
+    let summary = pipeline(cfg, err_cfg).set_ordered(true).run(
+        files,
+        |_, rec| rollback::sample(&rec),
+        |e| tracker.add_event(e),
+    )?;
+
+    let rows = tracker.rows(args.min_rollbacks);
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "回滚分析完成: 共 {} 个文件, {} 条记录, {} 个时间桶, {} 个突增",
+        summary.files,
+        summary.records,
+        rows.len(),
+        rows.iter().filter(|r| r.spike).count()
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::row_latency::{self, RowLatency},
+    command::{ReportArgs, pipeline},
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct RowLatencyArgs {
+    /// 只输出总耗时最高的前 N 个指纹
+    #[arg(short, long)]
+    pub top: Option<usize>,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按指纹和 ROWCOUNT 区间（0、1-10、11-1000、>1000）统计耗时
+pub fn run(
+    args: &RowLatencyArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut agg = RowLatency::new();
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| row_latency::sample(&rec, &analysis_cfg.fingerprint),
+        |s| agg.add_sample(s),
+    )?;
+
+    let rows = agg.rows(args.top);
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "行数分桶耗时统计完成: 共 {} 个文件, {} 条记录, {} 行输出",
+        summary.files,
+        summary.records,
+        rows.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::saturation::{self, SaturationTracker},
+    command::{ReportArgs, pipeline},
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct SaturationArgs {
+    /// 同时执行的语句数上限，覆盖配置文件中的 analysis.concurrent_statement_limit
+    #[arg(short, long)]
+    pub limit: Option<u64>,
+
+    /// 时间桶长度（毫秒）
+    #[arg(short, long, default_value_t = 1000)]
+    pub bucket_ms: i64,
+
+    /// 输出全部时间桶，而不只是峰值超过上限的时间桶
+    #[arg(long)]
+    pub all: bool,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按实例与时间桶计算同时执行的语句数峰值，报告超过上限的时间桶
+pub fn run(
+    args: &SaturationArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+) -> CommandResult<()> {
+    let limit = args
+        .limit
+        .unwrap_or(analysis_cfg.concurrent_statement_limit);
+    let files = input::collect_inputs(cfg)?;
+    let mut tracker = SaturationTracker::new(args.bucket_ms);
+    // 语句记录与其后单独记录的执行指标按先后顺序拼接
+    let summary = pipeline(cfg, err_cfg).set_ordered(true).run(
+        files,
+        |src, rec| saturation::sample(&src.instance, &rec),
+        |s| tracker.add_sample(s),
+    )?;
+
+    let rows = tracker.rows(limit, args.all);
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "并发上限分析完成: 上限 {}, 共 {} 个文件, {} 条记录, {} 个时间桶超过上限",
+        limit,
+        summary.files,
+        summary.records,
+        rows.iter().filter(|r| r.peak_concurrent > limit).count()
+    );
+    Ok(())
+}
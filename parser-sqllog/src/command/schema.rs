@@ -0,0 +1,78 @@
+use std::io::Write;
+
+use clap::{Args, ValueEnum};
+
+use crate::{
+    command::open_output,
+    error::CommandResult,
+    exporter::{
+        avro::avro_schema,
+        ddl::{Dialect, create_table},
+        protobuf::PROTO,
+        schema::{Projection, arrow_schema, json_schema},
+    },
+};
+
+/// schema 的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SchemaFormat {
+    /// JSON Schema（draft 2020-12）
+    #[default]
+    JsonSchema,
+    /// Arrow schema（JSON 格式）
+    Arrow,
+    /// `export --format protobuf` 使用的 .proto 定义
+    Proto,
+    /// Avro schema，可直接注册到 Kafka Schema Registry
+    Avro,
+    /// ClickHouse 建表语句（ReplacingMergeTree，按 record_id 去重）
+    Clickhouse,
+    /// PostgreSQL 建表语句（record_id 为主键）及幂等导入语句
+    Postgres,
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = SchemaFormat::JsonSchema)]
+    pub format: SchemaFormat,
+
+    /// 描述 `export --fields` 选中字段的输出，缺省时描述完整记录
+    #[arg(long)]
+    pub fields: Option<Projection>,
+
+    /// 建表语句（clickhouse / postgres）中的表名
+    #[arg(long, default_value = "sqllog")]
+    pub table: String,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+/// 输出导出记录的 schema，其中带有记录结构的版本号
+pub fn run(args: &SchemaArgs) -> CommandResult<()> {
+    let mut out = open_output(args.output.as_deref())?;
+    let schema = match args.format {
+        SchemaFormat::JsonSchema => json_schema(args.fields.as_ref()),
+        SchemaFormat::Arrow => arrow_schema(args.fields.as_ref()),
+        SchemaFormat::Avro => avro_schema(args.fields.as_ref()),
+        // .proto 中包含全部字段，未选中的字段不出现在消息中即可
+        SchemaFormat::Proto => {
+            out.write_all(PROTO.as_bytes())?;
+            return Ok(());
+        }
+        SchemaFormat::Clickhouse | SchemaFormat::Postgres => {
+            let dialect = match args.format {
+                SchemaFormat::Clickhouse => Dialect::Clickhouse,
+                _ => Dialect::Postgres,
+            };
+            let sql = create_table(dialect, &args.table, args.fields.as_ref())?;
+            out.write_all(sql.as_bytes())?;
+            return Ok(());
+        }
+    };
+    serde_json::to_writer_pretty(&mut out, &schema).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::{io::Write, ops::ControlFlow};
+
+use clap::Args;
+use tracing::{info, warn};
+
+use crate::{
+    analysis::{show::RecordView, sqlfmt},
+    command::{ColorChoice, open_output, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input::{self, seek::parse_ts_prefix},
+};
+
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// 按 EXEC_ID 选取记录，可指定多个
+    #[arg(long, value_delimiter = ',', required_unless_present_any = ["record_id", "at"])]
+    pub exec_id: Vec<u64>,
+
+    /// 按记录标识（导出记录的 `record_id` 字段）选取记录，可指定多个
+    #[arg(long, value_delimiter = ',')]
+    pub record_id: Vec<String>,
+
+    /// 选取时间戳以此为前缀的记录，如 `2025-08-12 10:57:09.562`
+    #[arg(long, value_parser = parse_ts_prefix)]
+    pub at: Option<String>,
+
+    /// 最多显示的记录数，0 表示不限制
+    #[arg(short = 'n', long, default_value_t = 1)]
+    pub limit: usize,
+
+    /// 把 SQL 格式化为多行缩进的形式（需要启用 `sql-format` 特性）
+    #[arg(long)]
+    pub format_sql: bool,
+
+    /// 是否着色
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+impl ShowArgs {
+    /// 记录是否满足任一选取条件
+    fn matches(&self, exec_id: Option<u64>, record_id: impl FnOnce() -> String, ts: &str) -> bool {
+        exec_id.is_some_and(|id| self.exec_id.contains(&id))
+            || self.at.as_deref().is_some_and(|at| ts.starts_with(at))
+            || (!self.record_id.is_empty() && self.record_id.contains(&record_id()))
+    }
+}
+
+/// 逐条显示选中的记录：字段对齐、时间戳与耗时换算为易读形式、SQL 着色，用于人工排查单条语句
+pub fn run(
+    args: &ShowArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    if args.format_sql && !sqlfmt::FORMAT_AVAILABLE {
+        warn!("当前构建未启用 sql-format 特性，--format-sql 不生效");
+    }
+    let color = args.color.enabled(args.output.as_deref());
+    let mut out = open_output(args.output.as_deref())?;
+    let mut shown = 0;
+    let mut result = Ok(());
+    let summary = pipeline(cfg, err_cfg).scan(files, |src, rec| {
+        if !args.matches(rec.execute_id, || src.record_id(&rec), rec.ts) {
+            return ControlFlow::Continue(());
+        }
+        let view = RecordView::new(src, &rec)
+            .set_color(color)
+            .set_format_sql(args.format_sql);
+        if let Err(e) = writeln!(out, "{view}") {
+            result = Err(e);
+            return ControlFlow::Break(());
+        }
+        shown += 1;
+        if args.limit > 0 && shown >= args.limit {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    result?;
+    out.flush()?;
+    info!("显示 {} 条记录, 扫描 {} 条记录", shown, summary.records);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use dm_database_parser::RecordMetrics;
+use tracing::{info, warn};
+
+use crate::{
+    analysis::{
+        sqlfmt,
+        stats::{self, GroupBy, StatementExample, StatsAggregator, StatsRow, StatsState},
+    },
+    command::{CategoryArgs, DedupArgs, ReportArgs, pipeline},
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    dedup::DedupKey,
+    error::CommandResult,
+    input,
+    rules::RuleSet,
+};
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct StatsArgs {
+    #[command(subcommand)]
+    pub command: Option<StatsCommand>,
+
+    /// 分组维度，用于跨实例或跨 EP 节点对比
+    #[arg(short, long, value_enum, default_value_t = GroupBy::None)]
+    pub group_by: GroupBy,
+
+    /// 每个分组只保留总耗时最高的前 N 个指纹
+    #[arg(short, long)]
+    pub top: Option<usize>,
+
+    /// 每个指纹保留 N 条示例语句（包括最慢的一条，带完整 SQL 与绑定参数）
+    #[arg(short, long, default_value_t = 0)]
+    pub examples: usize,
+
+    /// 把最慢示例的 SQL 格式化为多行缩进的形式（需要启用 `sql-format` 特性）
+    #[arg(long, requires = "examples")]
+    pub format_sql: bool,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 同时把完整的聚合状态保存到该文件，之后可用 `stats merge` 合并为周/月汇总；
+    /// 扩展名为 `.json` 时为 JSON，否则为 MessagePack
+    #[arg(long)]
+    pub save_state: Option<String>,
+
+    #[command(flatten)]
+    pub categories: CategoryArgs,
+
+    #[command(flatten)]
+    pub dedup: DedupArgs,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StatsCommand {
+    /// 合并之前用 `--save-state` 保存的聚合状态（如每天一份），输出汇总报告
+    Merge(StatsMergeArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct StatsMergeArgs {
+    /// 聚合状态文件，分组维度必须一致
+    #[arg(required = true)]
+    pub states: Vec<String>,
+
+    /// 每个分组只保留总耗时最高的前 N 个指纹
+    #[arg(short, long)]
+    pub top: Option<usize>,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 把合并后的聚合状态保存到该文件，用于逐级汇总
+    #[arg(long)]
+    pub save_state: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 按指纹统计执行次数与耗时，可按实例或 EP 分组
+pub fn run(
+    args: &StatsArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    if let Some(StatsCommand::Merge(merge_args)) = &args.command {
+        return merge(merge_args, analysis_cfg);
+    }
+    let files = input::collect_inputs(cfg)?;
+    let mut agg = StatsAggregator::new(args.group_by)
+        .set_spill(analysis_cfg.stats_max_groups, analysis_cfg.spill_dir())
+        .set_examples(args.examples)
+        .set_save_state(args.save_state.as_ref().map(PathBuf::from));
+    let group_by = args.group_by;
+    let mut dedup = args.dedup.dedup();
+    let wants_dedup = dedup.is_some();
+    // 只需要聚合结果，SQL 正文在计算指纹（和提取示例语句）后即可丢弃
+    let mut result = Ok(());
+    let summary = pipeline(cfg, err_cfg).set_ordered(wants_dedup).run(
+        files,
+        |src, rec| {
+            let m = RecordMetrics {
+                tags: rules.tags(&rec),
+                ..RecordMetrics::from_record_with(&rec, &analysis_cfg.fingerprint)
+            };
+            if !args.categories.matches(m.category) {
+                return None;
+            }
+            let mut s = stats::sample(m, group_by, &src.instance)?;
+            if args.examples > 0 {
+                s.example = Some(StatementExample::from_record(&rec));
+            }
+            let key = if wants_dedup {
+                DedupKey::of(&rec)
+            } else {
+                None
+            };
+            Some((s, key))
+        },
+        |(s, key)| {
+            if result.is_err() {
+                return;
+            }
+            let duplicate = dedup.as_mut().is_some_and(|d| d.is_duplicate(key));
+            result = if duplicate {
+                agg.add_duplicate(s)
+            } else {
+                agg.add_sample(s)
+            };
+        },
+    )?;
+    result?;
+    if agg.spilled() > 0 {
+        info!("统计溢写了 {} 个临时文件，开始归并", agg.spilled());
+    }
+
+    if let Some(dedup) = &dedup {
+        info!("去重折叠了 {} 条重复执行", dedup.folded());
+    }
+
+    let mut rows = agg.finish(args.top)?;
+    if args.format_sql {
+        if !sqlfmt::FORMAT_AVAILABLE {
+            warn!("当前构建未启用 sql-format 特性，--format-sql 不生效");
+        }
+        for row in &mut rows {
+            row.slowest_sql = sqlfmt::format_sql(&row.slowest_sql);
+        }
+    }
+    args.report
+        .write_with_wide_only(&rows, args.output.as_deref(), StatsRow::WIDE_ONLY)?;
+    info!(
+        "统计完成: 共 {} 个文件, {} 条记录, {} 行输出",
+        summary.files,
+        summary.records,
+        rows.len()
+    );
+    Ok(())
+}
+
+/// 合并多份聚合状态并输出报告
+fn merge(args: &StatsMergeArgs, analysis_cfg: &AnalysisConfig) -> CommandResult<()> {
+    let mut agg: Option<StatsAggregator> = None;
+    for path in &args.states {
+        let state = StatsState::open(path)?;
+        let agg = agg.get_or_insert_with(|| {
+            StatsAggregator::new(state.group_by())
+                .set_spill(analysis_cfg.stats_max_groups, analysis_cfg.spill_dir())
+                .set_save_state(args.save_state.as_ref().map(PathBuf::from))
+        });
+        agg.add_state(state)?;
+    }
+    let Some(agg) = agg else {
+        return Ok(());
+    };
+    let rows = agg.finish(args.top)?;
+    args.report
+        .write_with_wide_only(&rows, args.output.as_deref(), StatsRow::WIDE_ONLY)?;
+    info!(
+        "合并完成: 共 {} 个状态文件, {} 行输出",
+        args.states.len(),
+        rows.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::{ops::ControlFlow, time::Instant};
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::summary::FileSummary,
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct SummaryArgs {
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 逐个文件扫描，输出每个文件的记录数、时间范围、会话与用户数、错误行数以及解析吞吐
+pub fn run(
+    args: &SummaryArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let pipeline = pipeline(cfg, err_cfg);
+    let mut rows = Vec::with_capacity(files.len());
+    for path in files {
+        let mut file = FileSummary::new(&path, &input::instance_name(&path));
+        let start = Instant::now();
+        let summary = pipeline.scan(vec![path], |_, rec| {
+            file.add(&rec);
+            ControlFlow::Continue(())
+        })?;
+        rows.push(file.finish(&summary, start.elapsed()));
+    }
+
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "汇总完成: 共 {} 个文件, {} 条记录, {} 行错误",
+        rows.len(),
+        rows.iter().map(|r| r.records).sum::<u64>(),
+        rows.iter().map(|r| r.error_lines).sum::<u64>()
+    );
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    analysis::tables::{self, TableUsage},
+    command::{ReportArgs, pipeline},
+    config::{error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct TablesArgs {
+    /// 只输出访问次数最多的前 N 个对象
+    #[arg(short, long)]
+    pub top: Option<usize>,
+
+    /// 输出路径，缺省时输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// 从语句中提取引用的表/视图名，按对象统计访问次数（近似的热点表报告）
+pub fn run(
+    args: &TablesArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut usage = TableUsage::new();
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| tables::sample(&rec),
+        |s| usage.add_sample(s),
+    )?;
+
+    let rows = usage.rows(args.top);
+    args.report.write(&rows, args.output.as_deref())?;
+    info!(
+        "表访问统计完成: 共 {} 个文件, {} 条记录, {} 个对象",
+        summary.files,
+        summary.records,
+        rows.len()
+    );
+    Ok(())
+}
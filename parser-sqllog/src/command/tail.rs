@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use dm_database_parser::{InstanceInfo, find_next_record_start, parser::parse_records_with};
+use tracing::{debug, info, warn};
+
+use crate::{
+    analysis::{
+        long_trx::{LongTrxAlert, LongTrxTracker},
+        show::humanize_ms,
+    },
+    config::{analysis::AnalysisConfig, export::ExportConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct TailArgs {
+    /// 未提交事务的告警阈值（毫秒），覆盖配置文件中的 analysis.long_trx_threshold_ms
+    #[arg(short, long)]
+    pub threshold_ms: Option<u64>,
+
+    /// 告警时把告警内容以 JSON POST 到该地址（需要启用 http 特性）
+    #[arg(long)]
+    pub webhook: Option<String>,
+
+    /// 检查文件增长的间隔（毫秒）
+    #[arg(long, default_value_t = 1000)]
+    pub poll_ms: u64,
+
+    /// 告警中最多包含的语句数
+    #[arg(long, default_value_t = 20)]
+    pub max_statements: usize,
+
+    /// 从各实例当前文件的开头读取，而不只处理启动后新写入的记录
+    #[arg(long)]
+    pub from_start: bool,
+}
+
+/// 跟踪中的文件：已读取到的位置与尚不完整的最后一条记录
+struct Follow {
+    path: PathBuf,
+    instance: String,
+    offset: u64,
+    pending: Vec<u8>,
+}
+
+impl Follow {
+    /// 读取新写入的内容，返回其中完整的记录。
+    ///
+    /// 最后一条记录在下一条记录出现前可能仍在写入；本次没有新内容且以换行结尾时视为完整，
+    /// 避免 COMMIT 等记录一直停留在缓冲中。`flush` 时（文件已轮转）全部视为完整。
+    fn read(&mut self, flush: bool) -> io::Result<Vec<u8>> {
+        let mut file = fs::File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            debug!("文件被截断，从头读取: {}", self.path.display());
+            self.offset = 0;
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let before = self.pending.len();
+        let read = file.read_to_end(&mut self.pending)?;
+        self.offset += read as u64;
+
+        let idle = read == 0 && self.pending.ends_with(b"\n");
+        let end = if flush || idle {
+            self.pending.len()
+        } else {
+            // 最后一个记录起始位置；只需从新内容附近开始查找
+            let mut last = 0;
+            let mut from = before.saturating_sub(24).max(1);
+            while let Some(pos) = find_next_record_start(&self.pending, from) {
+                last = pos;
+                from = pos + 1;
+            }
+            last
+        };
+        let rest = self.pending.split_off(end);
+        Ok(std::mem::replace(&mut self.pending, rest))
+    }
+}
+
+/// 持续跟踪各实例正在写入的 sqllog 文件，事务开启超过阈值仍未提交时输出告警日志，
+/// 并可发送到 webhook；文件轮转后自动切换到新文件
+pub fn run(
+    args: &TailArgs,
+    cfg: &SqllogConfig,
+    analysis_cfg: &AnalysisConfig,
+    export_cfg: &ExportConfig,
+) -> CommandResult<()> {
+    #[cfg(not(feature = "http"))]
+    if args.webhook.is_some() {
+        return Err(crate::exporter::error::ExportError::FeatureDisabled {
+            kind: "webhook",
+            feature: "http",
+        }
+        .into());
+    }
+    let threshold = args
+        .threshold_ms
+        .unwrap_or(analysis_cfg.long_trx_threshold_ms);
+    let dir = PathBuf::from(&cfg.sqllog_path);
+    let mut tracker = LongTrxTracker::new(threshold, args.max_statements, export_cfg.max_body_len);
+    let mut follows: HashMap<String, Follow> = HashMap::new();
+    // 日志时间为服务器本地时间：以最新记录的时间加上其后经过的时长作为当前时间
+    let mut clock: Option<(i64, Instant)> = None;
+    let poll = Duration::from_millis(args.poll_ms.max(10));
+    info!(
+        "开始跟踪目录: {}, 未提交事务告警阈值 {} ms",
+        dir.display(),
+        threshold
+    );
+
+    loop {
+        // 轮转出的文件被移走或压缩时，列目录、读取都可能失败：记录警告后继续跟踪
+        let files = match input::collect_files(&dir) {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("无法列出目录 {}: {}", dir.display(), e);
+                thread::sleep(poll);
+                continue;
+            }
+        };
+        for path in input::active_files(&files) {
+            let Some(info) = InstanceInfo::from_path(&path) else {
+                continue;
+            };
+            let mut text = Vec::new();
+            let follow = match follows.get_mut(&info.instance) {
+                Some(f) if f.path == path => f,
+                Some(f) => {
+                    // 文件已轮转：读完旧文件后切换到新文件；旧文件已被移走时只处理已缓冲的部分
+                    text = f.read(true).unwrap_or_else(|e| {
+                        warn!("无法读取已轮转的文件 {}: {}", f.path.display(), e);
+                        std::mem::take(&mut f.pending)
+                    });
+                    info!("切换到新文件: {}", path.display());
+                    *f = Follow {
+                        path,
+                        instance: info.instance,
+                        offset: 0,
+                        pending: Vec::new(),
+                    };
+                    f
+                }
+                None => {
+                    let offset = if args.from_start {
+                        0
+                    } else {
+                        match fs::metadata(&path) {
+                            Ok(meta) => meta.len(),
+                            Err(e) => {
+                                warn!("无法读取文件 {}: {}", path.display(), e);
+                                continue;
+                            }
+                        }
+                    };
+                    info!("开始跟踪文件: {} (位置 {})", path.display(), offset);
+                    follows.entry(info.instance.clone()).or_insert(Follow {
+                        path,
+                        instance: info.instance,
+                        offset,
+                        pending: Vec::new(),
+                    })
+                }
+            };
+            match follow.read(false) {
+                Ok(new) => text.extend(new),
+                Err(e) => warn!("无法读取文件 {}: {}", follow.path.display(), e),
+            }
+            let text = input::bytes_to_string(text, cfg.input.encoding);
+            parse_records_with(&text, |rec| tracker.add(&follow.instance, &rec));
+        }
+
+        if let Some(latest) = tracker.latest_ms()
+            && clock.is_none_or(|(t, _)| t != latest)
+        {
+            clock = Some((latest, Instant::now()));
+        }
+        if let Some((latest, seen)) = clock {
+            let now = latest + seen.elapsed().as_millis() as i64;
+            for alert in tracker.check(now) {
+                emit(&alert, args.webhook.as_deref());
+            }
+        }
+        thread::sleep(poll);
+    }
+}
+
+/// 输出告警日志，并在配置了 webhook 时发送；发送失败只记录警告
+fn emit(alert: &LongTrxAlert, webhook: Option<&str>) {
+    warn!(
+        "长事务告警: 实例 {} 会话 {} 用户 {} appname {} 事务 {} 自 {} 起已 {} 未提交, 已执行 {} 条语句: {}",
+        alert.instance,
+        alert.sess,
+        alert.user,
+        alert.appname,
+        alert.trxid,
+        alert.started_at,
+        humanize_ms(alert.open_ms.max(0) as u64),
+        alert.statement_count,
+        alert.statements.join(" | ")
+    );
+    #[cfg(feature = "http")]
+    if let Some(url) = webhook {
+        let body = serde_json::to_vec(alert).unwrap_or_default();
+        if let Err(e) = ureq::post(url)
+            .header("Content-Type", "application/json")
+            .send(&body[..])
+        {
+            warn!("发送告警到 {} 失败: {}", url, e);
+        }
+    }
+    #[cfg(not(feature = "http"))]
+    let _ = webhook;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const REC1: &str = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select 1\n";
+    const REC2: &str = "2025-08-12 10:00:01.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select\n";
+
+    #[test]
+    fn follow_holds_back_partial_records_and_handles_truncation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dmsql_DM1_20250812_100000.log");
+        fs::write(&path, format!("{REC1}{}", &REC2[..60])).unwrap();
+        let mut f = Follow {
+            path: path.clone(),
+            instance: "DM1".to_string(),
+            offset: 0,
+            pending: Vec::new(),
+        };
+
+        // 最后一条记录尚未写完，留待下次读取
+        assert_eq!(f.read(false).unwrap(), REC1.as_bytes());
+        assert_eq!(f.pending, &REC2.as_bytes()[..60]);
+
+        // 续写完整后仍可能有后续行，暂不输出；没有新内容且以换行结尾时视为完整
+        fs::write(&path, format!("{REC1}{REC2}")).unwrap();
+        assert!(f.read(false).unwrap().is_empty());
+        assert_eq!(f.read(false).unwrap(), REC2.as_bytes());
+        assert!(f.pending.is_empty());
+
+        // 文件被截断后从头读取
+        fs::write(&path, REC1).unwrap();
+        assert!(f.read(false).unwrap().is_empty());
+        assert_eq!(f.offset, REC1.len() as u64);
+        assert_eq!(f.read(true).unwrap(), REC1.as_bytes());
+    }
+}
@@ -0,0 +1,300 @@
+use std::io;
+
+use clap::Args;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap},
+};
+use tracing::info;
+
+use crate::{
+    analysis::explore::{self, Explorer, Filter, FingerprintSummary},
+    command::pipeline,
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    input,
+};
+
+#[derive(Debug, Args)]
+pub struct TuiArgs {
+    /// 初始的用户过滤条件
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// 初始的时间范围，形如 `2025-08-12 10:00..2025-08-12 11:00`，两端均可省略
+    #[arg(short, long)]
+    pub time: Option<String>,
+}
+
+/// 加载日志后进入交互式浏览界面
+pub fn run(
+    args: &TuiArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+) -> CommandResult<()> {
+    let files = input::collect_inputs(cfg)?;
+    let mut explorer = Explorer::new();
+    let summary = pipeline(cfg, err_cfg).run(
+        files,
+        |_, rec| explore::sample(&rec, &analysis_cfg.fingerprint),
+        |s| explorer.add(s),
+    )?;
+    info!(
+        "加载完成: 共 {} 个文件, {} 条记录, {} 次执行",
+        summary.files,
+        summary.records,
+        explorer.len()
+    );
+
+    let mut app = App::new(&explorer);
+    app.filter.user = args.user.clone();
+    if let Some(range) = &args.time {
+        app.set_time_range(range);
+    }
+    app.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    Ok(result?)
+}
+
+/// 当前界面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    /// 指纹列表
+    List,
+    /// 选中指纹的示例语句
+    Detail,
+}
+
+/// 正在编辑的过滤条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Editing {
+    User,
+    Time,
+}
+
+struct App<'a> {
+    explorer: &'a Explorer,
+    filter: Filter,
+    rows: Vec<FingerprintSummary>,
+    table: TableState,
+    view: View,
+    detail_scroll: u16,
+    editing: Option<Editing>,
+    input: String,
+    /// 底部状态栏的提示信息
+    message: String,
+}
+
+impl<'a> App<'a> {
+    fn new(explorer: &'a Explorer) -> Self {
+        Self {
+            explorer,
+            filter: Filter::default(),
+            rows: Vec::new(),
+            table: TableState::default(),
+            view: View::List,
+            detail_scroll: 0,
+            editing: None,
+            input: String::new(),
+            message: String::new(),
+        }
+    }
+
+    /// 按当前过滤条件重新汇总
+    fn refresh(&mut self) {
+        self.rows = self.explorer.summarize(&self.filter);
+        self.table.select((!self.rows.is_empty()).then_some(0));
+    }
+
+    fn set_time_range(&mut self, range: &str) {
+        let range = range.trim();
+        if range.is_empty() {
+            self.filter.from = None;
+            self.filter.to = None;
+            return;
+        }
+        match Filter::parse_time_range(range) {
+            Some((from, to)) => {
+                self.filter.from = from;
+                self.filter.to = to;
+            }
+            None => self.message = format!("无法识别的时间范围: {range}"),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|f| self.draw(f))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(editing) = self.editing {
+                self.on_edit_key(editing, key.code);
+                continue;
+            }
+            match (self.view, key.code) {
+                (_, KeyCode::Char('q')) => return Ok(()),
+                (View::List, KeyCode::Down | KeyCode::Char('j')) => self.table.select_next(),
+                (View::List, KeyCode::Up | KeyCode::Char('k')) => self.table.select_previous(),
+                (View::List, KeyCode::Home | KeyCode::Char('g')) => self.table.select_first(),
+                (View::List, KeyCode::End | KeyCode::Char('G')) => self.table.select_last(),
+                (View::List, KeyCode::Enter) if self.table.selected().is_some() => {
+                    self.view = View::Detail;
+                    self.detail_scroll = 0;
+                }
+                (View::List, KeyCode::Char('u')) => self.start_edit(Editing::User),
+                (View::List, KeyCode::Char('t')) => self.start_edit(Editing::Time),
+                (View::List, KeyCode::Char('c')) => {
+                    self.filter = Filter::default();
+                    self.refresh();
+                }
+                (View::Detail, KeyCode::Down | KeyCode::Char('j')) => {
+                    self.detail_scroll = self.detail_scroll.saturating_add(1);
+                }
+                (View::Detail, KeyCode::Up | KeyCode::Char('k')) => {
+                    self.detail_scroll = self.detail_scroll.saturating_sub(1);
+                }
+                (View::Detail, KeyCode::Esc | KeyCode::Backspace) => self.view = View::List,
+                _ => {}
+            }
+        }
+    }
+
+    fn start_edit(&mut self, editing: Editing) {
+        self.editing = Some(editing);
+        self.message.clear();
+        self.input = match editing {
+            Editing::User => self.filter.user.clone().unwrap_or_default(),
+            Editing::Time => String::new(),
+        };
+    }
+
+    fn on_edit_key(&mut self, editing: Editing, code: KeyCode) {
+        match code {
+            KeyCode::Esc => self.editing = None,
+            KeyCode::Enter => {
+                self.editing = None;
+                let input = std::mem::take(&mut self.input);
+                match editing {
+                    Editing::User => {
+                        let user = input.trim();
+                        self.filter.user = (!user.is_empty()).then(|| user.to_string());
+                    }
+                    Editing::Time => self.set_time_range(&input),
+                }
+                self.refresh();
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(f.area());
+        match self.view {
+            View::List => self.draw_list(f, main),
+            View::Detail => self.draw_detail(f, main),
+        }
+
+        let line = match self.editing {
+            Some(Editing::User) => format!("用户: {}", self.input),
+            Some(Editing::Time) => format!("时间范围 (起..止): {}", self.input),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => match self.view {
+                View::List => {
+                    "j/k 移动  Enter 查看示例  u 按用户过滤  t 按时间过滤  c 清除过滤  q 退出"
+                        .into()
+                }
+                View::Detail => "j/k 滚动  Esc 返回  q 退出".into(),
+            },
+        };
+        f.render_widget(Paragraph::new(line), status);
+    }
+
+    fn draw_list(&mut self, f: &mut Frame, area: ratatui::layout::Rect) {
+        let header = Row::new(["总耗时(ms)", "次数", "平均(ms)", "最大(ms)", "指纹"])
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        let rows = self.rows.iter().map(|r| {
+            Row::new([
+                Cell::from(r.total_ms.to_string()),
+                Cell::from(r.executions.to_string()),
+                Cell::from(format!("{:.1}", r.avg_ms())),
+                Cell::from(r.max_ms.to_string()),
+                Cell::from(r.fingerprint.replace(['\n', '\r'], " ")),
+            ])
+        });
+        let widths = [
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ];
+        let title = format!(" 指纹 ({}) {} ", self.rows.len(), describe(&self.filter));
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_detail(&mut self, f: &mut Frame, area: ratatui::layout::Rect) {
+        let Some(row) = self.table.selected().and_then(|i| self.rows.get(i)) else {
+            self.view = View::List;
+            return;
+        };
+        let mut text = Text::default();
+        text.push_line(Line::styled(
+            row.fingerprint.clone(),
+            Style::new().add_modifier(Modifier::BOLD),
+        ));
+        text.push_line("");
+        for e in self.explorer.examples(row.id, &self.filter) {
+            text.push_line(Line::styled(
+                format!("{}  user:{}  {}ms", e.ts, e.user, e.exec_ms),
+                Style::new().add_modifier(Modifier::DIM),
+            ));
+            for l in e.sql.lines() {
+                text.push_line(l.to_string());
+            }
+            text.push_line("");
+        }
+        let title = format!(" 示例 (最多 {} 条) ", explore::MAX_EXAMPLES);
+        let para = Paragraph::new(text)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll, 0));
+        f.render_widget(para, area);
+    }
+}
+
+/// 过滤条件的简短描述，用于标题栏
+fn describe(filter: &Filter) -> String {
+    let mut parts = Vec::new();
+    if let Some(u) = &filter.user {
+        parts.push(format!("user={u}"));
+    }
+    if filter.from.is_some() || filter.to.is_some() {
+        parts.push("按时间过滤".to_string());
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", parts.join(", "))
+    }
+}
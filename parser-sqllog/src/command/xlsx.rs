@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use clap::Args;
+use tracing::info;
+
+use crate::{
+    command::OverviewArgs,
+    config::{analysis::AnalysisConfig, error_exporter::ErrorExporterConfig, sqllog::SqllogConfig},
+    error::CommandResult,
+    exporter::xlsx::WorkbookWriter,
+    rules::RuleSet,
+};
+
+#[derive(Debug, Args)]
+pub struct XlsxArgs {
+    /// 输出的 .xlsx 文件路径
+    #[arg(short, long)]
+    pub output: String,
+
+    #[command(flatten)]
+    pub overview: OverviewArgs,
+}
+
+/// 只解析一遍，把指纹统计（Top SQL）、会话与时间序列写成同一个 Excel 工作簿的三个工作表
+pub fn run(
+    args: &XlsxArgs,
+    cfg: &SqllogConfig,
+    err_cfg: &ErrorExporterConfig,
+    analysis_cfg: &AnalysisConfig,
+    rules: &RuleSet,
+) -> CommandResult<()> {
+    let overview = args.overview.collect(cfg, err_cfg, analysis_cfg, rules)?;
+    let mut workbook = WorkbookWriter::new();
+    workbook.add_sheet("Top SQL", &overview.top_sql)?;
+    workbook.add_sheet("Sessions", &overview.sessions)?;
+    workbook.add_sheet("Time series", &overview.time_series)?;
+    workbook.save(Path::new(&args.output))?;
+    info!(
+        "Excel 报告已写出: {}, 共 {} 个文件, {} 条记录",
+        args.output, overview.summary.files, overview.summary.records
+    );
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use dm_database_parser::FingerprintOptions;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::file::Root;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AnalysisConfig {
+    /// 大结果集阈值：ROWCOUNT 超过该值的语句会出现在大结果集报告中
+    #[serde(default = "default_large_rowcount_threshold")]
+    pub large_rowcount_threshold: u64,
+
+    /// `stats`：内存中最多保留的 (分组, 指纹) 数，超出后把部分统计溢写到临时文件，最后归并；0 表示不限制
+    #[serde(default = "default_stats_max_groups")]
+    pub stats_max_groups: usize,
+
+    /// `stats`：溢写临时文件的目录；为空时使用系统临时目录
+    #[serde(default)]
+    pub spill_tmp_dir: String,
+
+    /// `idle`：事务未提交时会话空闲超过该时长（毫秒）即报告，常见于连接池泄漏
+    #[serde(default = "default_idle_in_trx_threshold_ms")]
+    pub idle_in_trx_threshold_ms: u64,
+
+    /// `tail`：事务开启后超过该时长（毫秒）仍未提交即告警
+    #[serde(default = "default_long_trx_threshold_ms")]
+    pub long_trx_threshold_ms: u64,
+
+    /// `saturation`：同时执行的语句数上限，通常取服务器的工作线程数（WORKER_THREADS）
+    #[serde(default = "default_concurrent_statement_limit")]
+    pub concurrent_statement_limit: u64,
+
+    /// `[analysis.fingerprint]`：指纹归一化规则，作用于所有按指纹聚合的分析与导出的 fingerprint 字段
+    #[serde(default)]
+    pub fingerprint: FingerprintOptions,
+}
+
+fn default_large_rowcount_threshold() -> u64 {
+    10000
+}
+
+fn default_stats_max_groups() -> usize {
+    1_000_000
+}
+
+fn default_idle_in_trx_threshold_ms() -> u64 {
+    30_000
+}
+
+fn default_long_trx_threshold_ms() -> u64 {
+    300_000
+}
+
+fn default_concurrent_statement_limit() -> u64 {
+    16
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisConfig {
+    pub fn new() -> Self {
+        Self {
+            large_rowcount_threshold: 10000,
+            stats_max_groups: default_stats_max_groups(),
+            spill_tmp_dir: String::new(),
+            idle_in_trx_threshold_ms: default_idle_in_trx_threshold_ms(),
+            long_trx_threshold_ms: default_long_trx_threshold_ms(),
+            concurrent_statement_limit: default_concurrent_statement_limit(),
+            fingerprint: FingerprintOptions::default(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let root = Root::from_file(path);
+        root.analysis
+    }
+
+    pub fn set_large_rowcount_threshold(mut self, threshold: u64) -> Self {
+        self.large_rowcount_threshold = threshold;
+        self
+    }
+
+    pub fn set_stats_max_groups(mut self, max_groups: usize) -> Self {
+        self.stats_max_groups = max_groups;
+        self
+    }
+
+    pub fn set_spill_tmp_dir(mut self, spill_tmp_dir: &str) -> Self {
+        self.spill_tmp_dir = spill_tmp_dir.to_string();
+        self
+    }
+
+    pub fn set_idle_in_trx_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.idle_in_trx_threshold_ms = threshold_ms;
+        self
+    }
+
+    pub fn set_long_trx_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.long_trx_threshold_ms = threshold_ms;
+        self
+    }
+
+    pub fn set_concurrent_statement_limit(mut self, limit: u64) -> Self {
+        self.concurrent_statement_limit = limit;
+        self
+    }
+
+    pub fn set_fingerprint(mut self, fingerprint: FingerprintOptions) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// 溢写临时文件的目录
+    pub fn spill_dir(&self) -> PathBuf {
+        match self.spill_tmp_dir.as_str() {
+            "" => std::env::temp_dir(),
+            dir => PathBuf::from(dir),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_analysis_config_default() {
+        let config = AnalysisConfig::new();
+        assert_eq!(config.large_rowcount_threshold, 10000);
+        assert_eq!(config.stats_max_groups, 1_000_000);
+        assert_eq!(config.idle_in_trx_threshold_ms, 30_000);
+        assert_eq!(config.long_trx_threshold_ms, 300_000);
+        assert_eq!(config.concurrent_statement_limit, 16);
+        assert_eq!(config.spill_dir(), std::env::temp_dir());
+        assert_eq!(config.fingerprint, FingerprintOptions::DEFAULT);
+    }
+
+    #[test]
+    fn test_analysis_config_from_file() {
+        let toml_str = r#"
+            [analysis]
+            large_rowcount_threshold = 500
+            stats_max_groups = 20000
+            spill_tmp_dir = "/data/tmp"
+            [analysis.fingerprint]
+            collapse_in_lists = true
+            strip_schema = true
+        "#;
+        let mut config_file = NamedTempFile::new().unwrap();
+        config_file.write_all(toml_str.as_bytes()).unwrap();
+        let config = AnalysisConfig::from_file(config_file.path());
+
+        assert_eq!(config.large_rowcount_threshold, 500);
+        assert_eq!(config.stats_max_groups, 20000);
+        assert_eq!(config.spill_dir(), PathBuf::from("/data/tmp"));
+        assert!(config.fingerprint.collapse_in_lists);
+        assert!(config.fingerprint.strip_schema);
+        assert!(config.fingerprint.lowercase);
+        assert!(!config.fingerprint.keep_comments);
+    }
+}
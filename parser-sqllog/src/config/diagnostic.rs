@@ -0,0 +1,154 @@
+//! 配置诊断：输出出错的 TOML 行并用 `^` 标出位置，输出到终端时带颜色
+
+use std::ops::Range;
+
+use toml::de::{DeTable, DeValue};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// 配置文件中的一处位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: String,
+    /// 行号，从 1 开始
+    pub line: usize,
+    /// 列号（字符数），从 1 开始
+    pub column: usize,
+    /// 所在行的文本
+    pub text: String,
+    /// 标记的字符数，至少为 1
+    pub width: usize,
+}
+
+impl Location {
+    /// 由源文本中的字节范围得到所在行与行列号；范围跨行时只标记到行尾
+    pub fn from_span(path: &str, source: &str, span: Range<usize>) -> Self {
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let end = span.end.clamp(start, line_start + text.len());
+        Self {
+            path: path.to_string(),
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            text: text.to_string(),
+            width: source[start..end].chars().count().max(1),
+        }
+    }
+}
+
+/// 在 TOML 源文本中查找键路径（如 `sqllog.thread_num`、`export.sink[1].paht`）对应的键的位置；
+/// 找不到时返回能找到的最深一级
+pub fn locate(source: &str, key: &str) -> Option<Range<usize>> {
+    let root = DeTable::parse(source).ok()?;
+    let mut table = root.get_ref();
+    let mut span = None;
+    let mut segments = key.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let (name, index) = match segment.split_once('[') {
+            Some((name, rest)) => (name, rest.trim_end_matches(']').parse::<usize>().ok()),
+            None => (segment, None),
+        };
+        let Some((k, v)) = table.iter().find(|(k, _)| k.get_ref() == name) else {
+            return span;
+        };
+        span = Some(k.span());
+        let mut value = v.get_ref();
+        if let Some(i) = index {
+            let DeValue::Array(items) = value else {
+                return span;
+            };
+            let Some(item) = items.get(i) else {
+                return span;
+            };
+            span = Some(item.span());
+            value = item.get_ref();
+        }
+        if segments.peek().is_some() {
+            let DeValue::Table(inner) = value else {
+                return span;
+            };
+            table = inner;
+        }
+    }
+    span
+}
+
+/// 按 rustc 的样式渲染一条诊断：首行为错误信息，有位置时附上所在行与标记
+pub fn render(message: &str, location: Option<&Location>, color: bool) -> String {
+    let paint = |style: &str, text: &str| {
+        if color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    };
+    let mut out = format!(
+        "{}{}",
+        paint(RED, "error"),
+        paint(BOLD, &format!(": {message}"))
+    );
+    if let Some(loc) = location {
+        let gutter = " ".repeat(loc.line.to_string().len());
+        let bar = paint(BLUE, "|");
+        out.push_str(&format!(
+            "\n{gutter}{} {}:{}:{}\n{gutter} {bar}\n{} {bar} {}\n{gutter} {bar} {}{}",
+            paint(BLUE, "-->"),
+            loc.path,
+            loc.line,
+            loc.column,
+            paint(BLUE, &loc.line.to_string()),
+            loc.text,
+            " ".repeat(loc.column - 1),
+            paint(RED, &"^".repeat(loc.width)),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "[sqllog]\npath = \"logs\"\nthread_num = \"four\"\n\n\
+                          [[export.sink]]\npath = \"a.jsonl\"\n[[export.sink]]\npaht = \"s.csv\"\n";
+
+    #[test]
+    fn locates_key_paths() {
+        let at = |key| {
+            let span = locate(SOURCE, key).unwrap();
+            Location::from_span("config.toml", SOURCE, span)
+        };
+        let loc = at("sqllog.thread_num");
+        assert_eq!((loc.line, loc.column, loc.width), (3, 1, 10));
+        assert_eq!(loc.text, "thread_num = \"four\"");
+
+        let loc = at("export.sink[1].paht");
+        assert_eq!((loc.line, loc.column, loc.width), (8, 1, 4));
+
+        // 找不到的键退回到上一级
+        assert_eq!(at("sqllog.missing").line, 1);
+        assert!(locate(SOURCE, "sftp.host").is_none());
+        assert!(locate("[broken", "sqllog").is_none());
+    }
+
+    #[test]
+    fn renders_caret_under_location() {
+        let loc = Location::from_span("config.toml", SOURCE, 23..33);
+        assert_eq!(
+            render("sqllog.thread_num: 类型错误", Some(&loc), false),
+            "error: sqllog.thread_num: 类型错误\n \
+             --> config.toml:3:1\n  \
+             |\n\
+             3 | thread_num = \"four\"\n  \
+             | ^^^^^^^^^^"
+        );
+        assert!(render("出错", None, true).starts_with("\x1b[1;31merror"));
+    }
+}
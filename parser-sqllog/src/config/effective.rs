@@ -0,0 +1,510 @@
+//! 生效配置：按 默认值 < 配置文件 < 配置档 < 环境变量 < 命令行 的顺序逐层合并，并记录每一项的来源
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path, str::FromStr};
+
+use toml::{Table, Value};
+
+use crate::{
+    config::{
+        diagnostic::{self, Location},
+        file::Root,
+        include,
+        migrate::migrate,
+        validate::{check_paths, validate},
+    },
+    error::{ConfigIssue, ConfigParseError, ConfigParseResult},
+};
+
+/// 覆盖配置的环境变量前缀，变量名形如 `PARSER_SQLLOG_SQLLOG__THREAD_NUM`（节与键之间用双下划线分隔）
+pub const ENV_PREFIX: &str = "PARSER_SQLLOG_";
+
+/// 命名配置档所在的顶层键，`[profile.<name>.<section>]` 中的设置在选中该配置档时覆盖基础配置
+pub const PROFILE_KEY: &str = "profile";
+
+/// 输出时隐藏其值的键
+const SECRET_KEYS: &[&str] = &["passphrase"];
+
+/// 配置项的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    /// 配置文件路径
+    File(String),
+    /// 配置档名
+    Profile(String),
+    /// 环境变量名
+    Env(String),
+    /// 命令行参数
+    Cli(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => f.write_str("默认值"),
+            Origin::File(path) => write!(f, "配置文件 {path}"),
+            Origin::Profile(name) => write!(f, "配置档 {name}"),
+            Origin::Env(var) => write!(f, "环境变量 {var}"),
+            Origin::Cli(flag) => write!(f, "命令行 {flag}"),
+        }
+    }
+}
+
+/// 一项配置覆盖：点分隔的键路径（如 `sqllog.thread_num`）、值及其来源
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub key: String,
+    pub value: Value,
+    pub origin: Origin,
+}
+
+impl Override {
+    pub fn new(key: &str, value: impl Into<Value>, origin: Origin) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.into(),
+            origin,
+        }
+    }
+}
+
+/// 解析命令行 `--set 节.键=值`，值按 [`parse_value`] 解析
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, raw) = s
+            .split_once('=')
+            .ok_or_else(|| format!("应为 节.键=值 的形式: {s}"))?;
+        let key = key.trim();
+        if !key.contains('.') || key.split('.').any(str::is_empty) {
+            return Err(format!("键应为点分隔的路径，如 sqllog.thread_num: {key}"));
+        }
+        Ok(Override::new(
+            key,
+            parse_value(raw.trim()),
+            Origin::Cli("--set".to_string()),
+        ))
+    }
+}
+
+/// 逐层合并后的配置及各项来源
+#[derive(Debug, Clone, Default)]
+pub struct EffectiveConfig {
+    pub root: Root,
+    /// 默认值之上各层合并出的 TOML
+    merged: Table,
+    origins: BTreeMap<String, Origin>,
+    /// 各配置文件中定义的配置档，后读取的文件覆盖先读取的
+    profiles: Table,
+    /// 严格模式：配置文件必须存在，未知键与类型错误在 [`EffectiveConfig::check`] 中返回错误
+    strict: bool,
+    /// 检查显式配置的输入路径是否存在，只有读取输入的命令需要
+    check_paths: bool,
+    /// 合并过程中的提示，如旧版配置的迁移说明，由调用方在日志初始化后输出
+    pub warnings: Vec<String>,
+}
+
+impl EffectiveConfig {
+    /// 只含默认值的配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_check_paths(mut self, check_paths: bool) -> Self {
+        self.check_paths = check_paths;
+        self
+    }
+
+    /// 合并配置文件及其 `include` 的文件（被包含的文件优先级更低），逐个迁移旧版布局；
+    /// 文件不存在时跳过（严格模式下报错），语法错误或版本过新时返回错误
+    pub fn merge_file<P: AsRef<Path>>(mut self, path: P) -> ConfigParseResult<Self> {
+        let files = match include::load(path.as_ref()) {
+            Ok(files) => files,
+            Err(ConfigParseError::Io(e)) if e.kind() == io::ErrorKind::NotFound && !self.strict => {
+                return Ok(self);
+            }
+            Err(e) => return Err(e),
+        };
+        for (path, mut table) in files {
+            let origin = path.display().to_string();
+            for note in migrate(&mut table)? {
+                self.warnings.push(format!("{origin}: {note}"));
+            }
+            match table.remove(PROFILE_KEY) {
+                None => {}
+                Some(Value::Table(profiles)) => include::merge_tables(&mut self.profiles, profiles),
+                Some(other) => {
+                    return Err(ConfigParseError::FieldType {
+                        field: PROFILE_KEY.to_string(),
+                        expected: "表".to_string(),
+                        found: other.to_string(),
+                    });
+                }
+            }
+            self = self.merge_table(table, &Origin::File(origin));
+        }
+        Ok(self)
+    }
+
+    /// 在配置文件之上应用选中的配置档；`name` 为 `None` 时不做任何修改
+    pub fn select_profile(self, name: Option<&str>) -> ConfigParseResult<Self> {
+        let Some(name) = name else {
+            return Ok(self);
+        };
+        match self.profiles.get(name) {
+            Some(Value::Table(profile)) => {
+                let profile = profile.clone();
+                Ok(self.merge_table(profile, &Origin::Profile(name.to_string())))
+            }
+            _ => Err(ConfigParseError::UnknownProfile {
+                name: name.to_string(),
+                available: self.profiles.keys().cloned().collect::<Vec<_>>().join(", "),
+            }),
+        }
+    }
+
+    /// 合并以 [`ENV_PREFIX`] 开头的环境变量；值按 TOML 值解析，解析失败时作为字符串
+    pub fn merge_env<I: IntoIterator<Item = (String, String)>>(self, vars: I) -> Self {
+        let overrides = vars
+            .into_iter()
+            .filter_map(|(name, raw)| {
+                let key = name
+                    .strip_prefix(ENV_PREFIX)?
+                    .to_lowercase()
+                    .replace("__", ".");
+                key.contains('.')
+                    .then(|| Override::new(&key, parse_value(&raw), Origin::Env(name)))
+            })
+            .collect();
+        self.apply(overrides)
+    }
+
+    /// 依次应用覆盖项，后面的覆盖前面的
+    pub fn apply(mut self, overrides: Vec<Override>) -> Self {
+        for o in overrides {
+            let mut parts: Vec<&str> = o.key.split('.').collect();
+            let last = parts.pop().unwrap_or_default();
+            let table = parts.iter().rev().fold(
+                Table::from_iter([(last.to_string(), o.value)]),
+                |inner, part| Table::from_iter([(part.to_string(), Value::Table(inner))]),
+            );
+            self = self.merge_table(table, &o.origin);
+        }
+        self
+    }
+
+    /// 检查合并结果中的未知键、类型错误以及（启用时）不存在的路径，每项问题注明来源：
+    /// 严格模式下一并返回错误；宽松模式下记入 `warnings`，出错的键已回退为默认值，路径则原样使用
+    pub fn check(mut self) -> ConfigParseResult<Self> {
+        let (_, issues) = validate(&self.merged);
+        let paths = if self.check_paths {
+            check_paths(&self.merged)
+        } else {
+            Vec::new()
+        };
+        if issues.is_empty() && paths.is_empty() {
+            return Ok(self);
+        }
+        let issues: Vec<_> = issues.into_iter().map(|i| self.locate(i)).collect();
+        let paths: Vec<_> = paths.into_iter().map(|i| self.locate(i)).collect();
+        if self.strict {
+            return Err(ConfigParseError::Invalid(
+                issues.into_iter().chain(paths).collect(),
+            ));
+        }
+        self.warnings
+            .extend(issues.iter().map(|i| format!("配置错误，已忽略: {i}")));
+        self.warnings
+            .extend(paths.iter().map(|i| format!("配置错误: {i}")));
+        Ok(self)
+    }
+
+    /// 为问题注明来源，来自配置文件时附上所在位置
+    fn locate(&self, issue: ConfigIssue) -> ConfigIssue {
+        // 数组元素（如 `paths[0]`）的来源即数组本身的来源
+        let key = issue.key.split('[').next().unwrap_or_default();
+        let origin = self.origin(key);
+        let message = format!("{}（{}）", issue.message, origin);
+        let location = match origin {
+            Origin::File(path) => fs::read_to_string(path).ok().and_then(|source| {
+                diagnostic::locate(&source, &issue.key)
+                    .map(|span| Location::from_span(path, &source, span))
+            }),
+            _ => None,
+        };
+        ConfigIssue {
+            message,
+            location,
+            ..issue
+        }
+    }
+
+    /// 某项配置的来源
+    pub fn origin(&self, key: &str) -> &Origin {
+        self.origins.get(key).unwrap_or(&Origin::Default)
+    }
+
+    /// 以 TOML 格式输出生效配置，每项后注明来源
+    pub fn to_annotated_toml(&self) -> String {
+        let mut out =
+            String::from("# 生效配置（默认值 < 配置文件 < 配置档 < 环境变量 < 命令行）\n");
+        if let Ok(Value::Table(root)) = Value::try_from(&self.root) {
+            for (key, value) in root.iter().filter(|(_, v)| !v.is_table()) {
+                out.push_str(&format!("{key} = {value} # {}\n", self.origin(key)));
+            }
+            for (section, value) in &root {
+                if let Value::Table(table) = value {
+                    self.write_table(&mut out, section, table);
+                }
+            }
+        }
+        out
+    }
+
+    fn write_table(&self, out: &mut String, path: &str, table: &Table) {
+        out.push_str(&format!("\n[{path}]\n"));
+        let mut nested = Vec::new();
+        for (key, value) in table {
+            if let Value::Table(t) = value {
+                nested.push((key, t));
+                continue;
+            }
+            let shown = if SECRET_KEYS.contains(&key.as_str()) {
+                Value::from("***")
+            } else {
+                value.clone()
+            };
+            let full = format!("{path}.{key}");
+            out.push_str(&format!("{key} = {shown} # {}\n", self.origin(&full)));
+        }
+        for (key, t) in nested {
+            self.write_table(out, &format!("{path}.{key}"), t);
+        }
+    }
+
+    fn merge_table(mut self, table: Table, origin: &Origin) -> Self {
+        merge_into(&mut self.merged, table, "", origin, &mut self.origins);
+        self.root = Root::from_value(&Value::Table(self.merged.clone()));
+        self
+    }
+}
+
+/// 把 `src` 深度合并进 `dst`，记录每个叶子键的来源
+fn merge_into(
+    dst: &mut Table,
+    src: Table,
+    prefix: &str,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+) {
+    for (key, value) in src {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (dst.get_mut(&key), value) {
+            (Some(Value::Table(d)), Value::Table(s)) => merge_into(d, s, &path, origin, origins),
+            (_, Value::Table(s)) => {
+                let mut d = Table::new();
+                merge_into(&mut d, s, &path, origin, origins);
+                dst.insert(key, Value::Table(d));
+            }
+            (_, v) => {
+                origins.insert(path, origin.clone());
+                dst.insert(key, v);
+            }
+        }
+    }
+}
+
+/// 把命令行或环境变量中的文本解析为 TOML 值：`8`、`true`、`"a"`、`[1, 2]` 按 TOML 解析，其余作为字符串
+pub fn parse_value(raw: &str) -> Value {
+    format!("v = {raw}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| Value::from(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn layers_override_in_order() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"[sqllog]\nthread_num = 2\npath = \"/data/logs\"\n")
+            .unwrap();
+        let path = file.path().display().to_string();
+
+        let cfg = EffectiveConfig::new()
+            .merge_file(file.path())
+            .unwrap()
+            .merge_env([
+                (
+                    "PARSER_SQLLOG_SQLLOG__THREAD_NUM".to_string(),
+                    "4".to_string(),
+                ),
+                (
+                    "PARSER_SQLLOG_LOGGING__LEVEL".to_string(),
+                    "debug".to_string(),
+                ),
+                ("HOME".to_string(), "/root".to_string()),
+            ])
+            .apply(vec![Override::new(
+                "logging.level",
+                "warn",
+                Origin::Cli("--set".to_string()),
+            )]);
+
+        assert_eq!(cfg.root.sqllog.thread_num, 4);
+        assert_eq!(cfg.root.sqllog.sqllog_path, "/data/logs");
+        assert_eq!(cfg.root.logging.level, "warn");
+        assert_eq!(cfg.origin("sqllog.path"), &Origin::File(path));
+        assert_eq!(
+            cfg.origin("sqllog.thread_num"),
+            &Origin::Env("PARSER_SQLLOG_SQLLOG__THREAD_NUM".to_string())
+        );
+        assert_eq!(cfg.origin("sqllog.batch_size"), &Origin::Default);
+
+        let text = cfg.to_annotated_toml();
+        assert!(text.contains("level = \"warn\" # 命令行 --set\n"));
+        assert!(text.contains("batch_size = "));
+        // 输出本身是合法的 TOML
+        assert!(text.parse::<Table>().is_ok());
+    }
+
+    #[test]
+    fn merge_file_migrates_and_reports_errors() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"[sqllog]\nsqllog_path = \"/old\"\n")
+            .unwrap();
+        let cfg = EffectiveConfig::new().merge_file(file.path()).unwrap();
+        assert_eq!(cfg.root.sqllog.sqllog_path, "/old");
+        assert_eq!(cfg.warnings.len(), 1);
+        assert!(
+            cfg.to_annotated_toml()
+                .contains("\nversion = 2 # 配置文件 ")
+        );
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"version = 3\n").unwrap();
+        assert!(matches!(
+            EffectiveConfig::new().merge_file(file.path()),
+            Err(ConfigParseError::UnsupportedVersion { found: 3, .. })
+        ));
+
+        let cfg = EffectiveConfig::new()
+            .merge_file("/nonexistent/config.toml")
+            .unwrap();
+        assert!(cfg.warnings.is_empty());
+    }
+
+    #[test]
+    fn check_reports_issues_with_origin() {
+        let overrides = vec![
+            Override::new("sqllog.path", "/nonexistent", Origin::Cli("-p".to_string())),
+            Override::new("sqllog.thred_num", 4, Origin::Env("X".to_string())),
+            Override::new("sqllog.batch_size", "x", Origin::Cli("--set".to_string())),
+        ];
+        let cfg = EffectiveConfig::new()
+            .apply(overrides.clone())
+            .check()
+            .unwrap();
+        assert_eq!(cfg.warnings.len(), 2);
+        // 只有读取输入的命令检查路径；不存在的路径不会被忽略，提示中不称已忽略
+        let cfg = EffectiveConfig::new()
+            .set_check_paths(true)
+            .apply(overrides.clone())
+            .check()
+            .unwrap();
+        assert!(cfg.warnings[2].starts_with("配置错误: sqllog.path: 路径不存在: /nonexistent"));
+
+        let err = EffectiveConfig::new()
+            .set_strict(true)
+            .apply(overrides)
+            .check()
+            .unwrap_err();
+        let ConfigParseError::Invalid(issues) = &err else {
+            panic!("expected invalid config");
+        };
+        assert_eq!(
+            issues[1].to_string(),
+            "sqllog.thred_num: 未知的键（环境变量 X）"
+        );
+        assert!(
+            err.to_string()
+                .starts_with("配置有 2 处错误:\n  - sqllog.batch_size: ")
+        );
+
+        assert!(matches!(
+            EffectiveConfig::new()
+                .set_strict(true)
+                .merge_file("/nonexistent/config.toml"),
+            Err(ConfigParseError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn profile_overrides_file_but_not_env() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(
+            b"[sqllog]\nthread_num = 2\nbatch_size = 10\n\
+              [profile.nightly.sqllog]\nthread_num = 16\nbatch_size = 1000\n\
+              [profile.triage.sqllog]\nthread_num = 1\n",
+        )
+        .unwrap();
+        let base = || EffectiveConfig::new().merge_file(file.path()).unwrap();
+
+        assert_eq!(base().root.sqllog.thread_num, 2);
+
+        let cfg = base().select_profile(Some("nightly")).unwrap().merge_env([(
+            "PARSER_SQLLOG_SQLLOG__THREAD_NUM".to_string(),
+            "4".to_string(),
+        )]);
+        assert_eq!(cfg.root.sqllog.thread_num, 4);
+        assert_eq!(cfg.root.sqllog.batch_size, 1000);
+        assert_eq!(
+            cfg.origin("sqllog.batch_size"),
+            &Origin::Profile("nightly".to_string())
+        );
+        // 配置档本身不出现在生效配置中
+        assert!(!cfg.to_annotated_toml().contains("[profile"));
+
+        assert!(matches!(
+            base().select_profile(Some("prod")),
+            Err(ConfigParseError::UnknownProfile { available, .. }) if available == "nightly, triage"
+        ));
+    }
+
+    #[test]
+    fn parses_set_overrides() {
+        let o: Override = "sqllog.thread_num=8".parse().unwrap();
+        assert_eq!(o.key, "sqllog.thread_num");
+        assert_eq!(o.value, Value::Integer(8));
+        assert_eq!(o.origin, Origin::Cli("--set".to_string()));
+
+        let o: Override = "sqllog.path = /data/a=b".parse().unwrap();
+        assert_eq!(o.value, Value::from("/data/a=b"));
+
+        assert!("sqllog.thread_num".parse::<Override>().is_err());
+        assert!("thread_num=8".parse::<Override>().is_err());
+        assert!("sqllog.=8".parse::<Override>().is_err());
+    }
+
+    #[test]
+    fn parse_value_falls_back_to_string() {
+        assert_eq!(parse_value("8"), Value::Integer(8));
+        assert_eq!(parse_value("true"), Value::Boolean(true));
+        assert_eq!(parse_value("/data/logs"), Value::from("/data/logs"));
+    }
+}
@@ -1,10 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 /// 错误导出配置
 use crate::config::file::Root;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ErrorExporterConfig {
     /// 错误日志导出路径 (配置文件中键为 `path`)
     #[serde(rename = "path", default = "default_error_log_path")]
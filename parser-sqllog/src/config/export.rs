@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    analysis::stats::GroupBy,
+    config::file::Root,
+    exporter::{compress::Compression, record::RecordFormat},
+};
+
+/// 导出目标的种类
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// 写出记录文件，压缩与滚动沿用 `[export]` 的设置
+    #[default]
+    File,
+    /// 按指纹汇总后写出 CSV 统计报告，与 `stats` 子命令的输出相同
+    Stats,
+    /// 按批以 JSON Lines 通过 HTTP POST 发送，如 ClickHouse 的 `INSERT ... FORMAT JSONEachRow`（需启用 `http` 特性）
+    Http,
+}
+
+/// `[[export.sink]]`：一次解析同时写出的导出目标
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SinkConfig {
+    #[serde(default)]
+    pub kind: SinkKind,
+
+    /// file / stats 为输出路径（file 同时指定 `split_by_ep` 时为输出目录），http 为目标 URL
+    #[serde(default)]
+    pub path: String,
+
+    /// file：输出格式
+    #[serde(default)]
+    pub format: RecordFormat,
+
+    /// file / http：只输出选中的字段，逗号分隔；为空时输出完整记录
+    #[serde(default)]
+    pub fields: String,
+
+    /// file：按 EP 节点拆分到 `<path>/epN/` 目录
+    #[serde(default)]
+    pub split_by_ep: bool,
+
+    /// stats：分组维度
+    #[serde(default)]
+    pub group_by: GroupBy,
+
+    /// stats：每个分组只保留总耗时最高的前 N 个指纹，0 表示全部保留
+    #[serde(default)]
+    pub top: usize,
+
+    /// http：每次请求发送的记录数
+    #[serde(default = "default_sink_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_sink_batch_size() -> usize {
+    10000
+}
+
+/// `[export.anonymize]`：`export --anonymize` 的脱敏规则。
+///
+/// 名称以带密钥的哈希替换为假名（如用户 `U_3f2a9c1b`、模式 `S_…`、表 `T_…`、过程 `P_…`），同一密钥下
+/// 同一名称（不区分大小写）总是得到相同的假名，语句结构与执行指标保持不变
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AnonymizeConfig {
+    /// 计算假名的密钥，启用脱敏时必须设置；不同批次的导出使用相同密钥才能相互对照
+    #[serde(default)]
+    pub key: String,
+
+    /// 替换用户名
+    #[serde(default = "default_true")]
+    pub users: bool,
+
+    /// 替换 SQL 中的模式名
+    #[serde(default = "default_true")]
+    pub schemas: bool,
+
+    /// 替换 SQL 中的表名
+    #[serde(default = "default_true")]
+    pub tables: bool,
+
+    /// 替换 SQL 中调用的存储过程名（`CALL` / `EXEC` 以及 PL/SQL 块中的调用）
+    #[serde(default = "default_true")]
+    pub procedures: bool,
+
+    /// 把 SQL 中的字面量替换为 `?`（按指纹规则，同时去掉注释）
+    #[serde(default = "default_true")]
+    pub literals: bool,
+
+    /// 替换客户端 IP（替换为 `10.x.x.x` 形式的地址）
+    #[serde(default = "default_true")]
+    pub client_ips: bool,
+
+    /// 替换 appname
+    #[serde(default)]
+    pub appnames: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for AnonymizeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnonymizeConfig {
+    pub fn new() -> Self {
+        Self {
+            key: String::new(),
+            users: true,
+            schemas: true,
+            tables: true,
+            procedures: true,
+            literals: true,
+            client_ips: true,
+            appnames: false,
+        }
+    }
+
+    pub fn set_key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+
+    pub fn set_users(mut self, users: bool) -> Self {
+        self.users = users;
+        self
+    }
+
+    pub fn set_schemas(mut self, schemas: bool) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    pub fn set_tables(mut self, tables: bool) -> Self {
+        self.tables = tables;
+        self
+    }
+
+    pub fn set_procedures(mut self, procedures: bool) -> Self {
+        self.procedures = procedures;
+        self
+    }
+
+    pub fn set_literals(mut self, literals: bool) -> Self {
+        self.literals = literals;
+        self
+    }
+
+    pub fn set_client_ips(mut self, client_ips: bool) -> Self {
+        self.client_ips = client_ips;
+        self
+    }
+
+    pub fn set_appnames(mut self, appnames: bool) -> Self {
+        self.appnames = appnames;
+        self
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExportConfig {
+    /// 导出的 SQL 正文最大长度（字节），超出部分截断并以省略号结尾；0 表示不截断
+    #[serde(default = "default_max_body_len")]
+    pub max_body_len: usize,
+
+    /// 导出文件的压缩格式：none / gzip / zstd，文件名自动加上 `.gz` / `.zst` 扩展名
+    #[serde(default)]
+    pub compress: Compression,
+
+    /// 单个导出文件的最大字节数（压缩前），超出后滚动到下一个文件；0 表示不限制
+    #[serde(default)]
+    pub max_output_size: u64,
+
+    /// 每隔多少秒滚动到下一个导出文件；0 表示不按时间滚动
+    #[serde(default)]
+    pub roll_every: u64,
+
+    /// 写入线程数：多个导出目标时按目标分配到各线程并行写出，超过目标数时按目标数计；1 表示在单个线程中依次写出
+    #[serde(default = "default_writer_threads")]
+    pub writer_threads: usize,
+
+    /// `export --sort`：内存中最多保留的记录数，超出后排好序溢写到临时文件，最后归并输出
+    #[serde(default = "default_sort_run_size")]
+    pub sort_run_size: usize,
+
+    /// `export --sort`：溢写临时文件的目录；为空时使用系统临时目录
+    #[serde(default)]
+    pub sort_tmp_dir: String,
+
+    /// 导出目标（`[[export.sink]]`），一次解析同时写出到所有目标
+    #[serde(default, rename = "sink")]
+    pub sinks: Vec<SinkConfig>,
+
+    /// `[export.anonymize]`：`export --anonymize` 的脱敏规则
+    #[serde(default)]
+    pub anonymize: AnonymizeConfig,
+}
+
+fn default_max_body_len() -> usize {
+    0
+}
+
+fn default_writer_threads() -> usize {
+    1
+}
+
+fn default_sort_run_size() -> usize {
+    1_000_000
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportConfig {
+    pub fn new() -> Self {
+        Self {
+            max_body_len: 0,
+            compress: Compression::None,
+            max_output_size: 0,
+            roll_every: 0,
+            writer_threads: 1,
+            sort_run_size: default_sort_run_size(),
+            sort_tmp_dir: String::new(),
+            sinks: Vec::new(),
+            anonymize: AnonymizeConfig::new(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let root = Root::from_file(path);
+        root.export
+    }
+
+    pub fn set_max_body_len(mut self, max_body_len: usize) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+
+    pub fn set_compress(mut self, compress: Compression) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn set_max_output_size(mut self, max_output_size: u64) -> Self {
+        self.max_output_size = max_output_size;
+        self
+    }
+
+    pub fn set_roll_every(mut self, roll_every: u64) -> Self {
+        self.roll_every = roll_every;
+        self
+    }
+
+    pub fn set_writer_threads(mut self, writer_threads: usize) -> Self {
+        self.writer_threads = writer_threads;
+        self
+    }
+
+    pub fn set_sort_run_size(mut self, sort_run_size: usize) -> Self {
+        self.sort_run_size = sort_run_size;
+        self
+    }
+
+    pub fn set_sort_tmp_dir(mut self, sort_tmp_dir: &str) -> Self {
+        self.sort_tmp_dir = sort_tmp_dir.to_string();
+        self
+    }
+
+    /// 排序溢写临时文件的目录
+    pub fn sort_dir(&self) -> PathBuf {
+        match self.sort_tmp_dir.as_str() {
+            "" => std::env::temp_dir(),
+            dir => PathBuf::from(dir),
+        }
+    }
+
+    pub fn set_sinks(mut self, sinks: Vec<SinkConfig>) -> Self {
+        self.sinks = sinks;
+        self
+    }
+
+    pub fn set_anonymize(mut self, anonymize: AnonymizeConfig) -> Self {
+        self.anonymize = anonymize;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_export_config_default() {
+        let config = ExportConfig::new();
+        assert_eq!(config.max_body_len, 0);
+        assert_eq!(config.compress, Compression::None);
+        assert_eq!(config.max_output_size, 0);
+        assert_eq!(config.roll_every, 0);
+        assert_eq!(config.writer_threads, 1);
+        assert_eq!(config.sort_run_size, 1_000_000);
+        assert_eq!(config.sort_dir(), std::env::temp_dir());
+        assert_eq!(config.anonymize, AnonymizeConfig::new());
+    }
+
+    #[test]
+    fn test_export_config_from_file() {
+        let toml_str = r#"
+            [export]
+            max_body_len = 4096
+            compress = "zstd"
+            max_output_size = 1073741824
+            roll_every = 3600
+            writer_threads = 4
+            sort_run_size = 50000
+            sort_tmp_dir = "/data/tmp"
+        "#;
+        let mut config_file = NamedTempFile::new().unwrap();
+        config_file.write_all(toml_str.as_bytes()).unwrap();
+        let config = ExportConfig::from_file(config_file.path());
+
+        assert_eq!(config.max_body_len, 4096);
+        assert_eq!(config.compress, Compression::Zstd);
+        assert_eq!(config.max_output_size, 1 << 30);
+        assert_eq!(config.roll_every, 3600);
+        assert_eq!(config.writer_threads, 4);
+        assert_eq!(config.sort_run_size, 50000);
+        assert_eq!(config.sort_dir(), PathBuf::from("/data/tmp"));
+    }
+
+    #[test]
+    fn test_export_sinks_from_toml() {
+        let root = Root::from_toml_str(
+            r#"
+            [[export.sink]]
+            path = "archive/records.jsonl"
+
+            [[export.sink]]
+            kind = "stats"
+            path = "stats.csv"
+            group_by = "instance"
+            top = 20
+
+            [[export.sink]]
+            kind = "http"
+            path = "http://clickhouse:8123/?query=INSERT%20INTO%20sqllog%20FORMAT%20JSONEachRow"
+            fields = "ts,user,sql,exec_time_ms"
+            "#,
+        );
+        let sinks = root.export.sinks;
+        assert_eq!(sinks.len(), 3);
+        assert_eq!(sinks[0].kind, SinkKind::File);
+        assert_eq!(sinks[0].format, RecordFormat::Jsonl);
+        assert_eq!(sinks[1].kind, SinkKind::Stats);
+        assert_eq!(sinks[1].group_by, GroupBy::Instance);
+        assert_eq!(sinks[1].top, 20);
+        assert_eq!(sinks[2].kind, SinkKind::Http);
+        assert_eq!(sinks[2].batch_size, 10000);
+    }
+}
@@ -1,65 +1,170 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{fs, path::Path};
 
 use crate::{
-    config::{error_exporter::ErrorExporterConfig, logging::LogConfig, sqllog::SqllogConfig},
-    error::ConfigParseError,
+    config::{
+        analysis::AnalysisConfig,
+        error_exporter::ErrorExporterConfig,
+        export::ExportConfig,
+        include,
+        logging::LogConfig,
+        migrate::{CONFIG_VERSION, migrate},
+        rules::RulesConfig,
+        sftp::SftpConfig,
+        sqllog::SqllogConfig,
+        validate::{check_paths, validate},
+    },
+    error::{ConfigParseError, ConfigParseResult},
 };
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Root {
+    /// 配置布局版本，见 [`CONFIG_VERSION`]
+    #[serde(default = "default_version")]
+    pub version: u32,
     pub logging: LogConfig,
     pub error_exporter: ErrorExporterConfig,
     pub sqllog: SqllogConfig,
+    pub analysis: AnalysisConfig,
+    pub export: ExportConfig,
+    pub sftp: SftpConfig,
+    pub rules: RulesConfig,
+}
+
+fn default_version() -> u32 {
+    CONFIG_VERSION
+}
+
+impl Default for Root {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Root {
     pub fn new() -> Self {
         Self {
+            version: CONFIG_VERSION,
             logging: LogConfig::default(),
             error_exporter: ErrorExporterConfig::default(),
             sqllog: SqllogConfig::default(),
+            analysis: AnalysisConfig::default(),
+            export: ExportConfig::default(),
+            sftp: SftpConfig::default(),
+            rules: RulesConfig::default(),
         }
     }
 
+    /// 读取配置文件，文件不存在或无法解析时静默使用默认配置
     pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
-        let content = fs::read_to_string(path)
-            .map_err(ConfigParseError::Io)
-            .unwrap_or_default();
-        Self::from_toml_str(&content)
+        Self::try_from_file(path).unwrap_or_default()
     }
 
+    /// 宽松解析 TOML 字符串，语法错误时静默使用默认配置；需要发现配置错误时使用 [`Root::from_toml_str_strict`]
     pub fn from_toml_str(s: &str) -> Self {
-        // 从默认值开始，并应用 TOML 字符串中存在的各个节。
-        let mut root = Root::default();
+        Self::try_from_toml_str(s).unwrap_or_default()
+    }
 
-        let s_trim = s.trim();
-        if s_trim.is_empty() {
-            return root;
+    /// 读取配置文件及其 `include` 的文件（见 [`crate::config::include`]），读取失败、TOML 语法错误或
+    /// 版本过新时返回错误
+    pub fn try_from_file<P: AsRef<Path>>(path: P) -> ConfigParseResult<Self> {
+        let mut merged = toml::Table::new();
+        for (_, mut table) in include::load(path.as_ref())? {
+            migrate(&mut table)?;
+            include::merge_tables(&mut merged, table);
         }
+        Ok(Self::from_value(&toml::Value::Table(merged)))
+    }
 
+    /// 解析 TOML 字符串，语法错误或版本过新时返回错误；旧版布局自动迁移，缺失或无法解析的节使用默认值
+    pub fn try_from_toml_str(s: &str) -> ConfigParseResult<Self> {
+        if s.trim().is_empty() {
+            return Ok(Root::default());
+        }
         // 解析为 toml::Value 以便有选择地合并各个节。
-        let parsed: toml::Value = match toml::from_str(s) {
-            Ok(v) => v,
-            Err(_) => return root,
-        };
+        let mut parsed: toml::Table = toml::from_str(s).map_err(ConfigParseError::Parser)?;
+        migrate(&mut parsed)?;
+        Ok(Self::from_value(&toml::Value::Table(parsed)))
+    }
+
+    /// 序列化为 TOML 字符串，可由 [`Root::try_from_toml_str`] 原样读回
+    pub fn to_toml_string(&self) -> ConfigParseResult<String> {
+        toml::to_string_pretty(self).map_err(ConfigParseError::Serialize)
+    }
+
+    /// 写入配置文件，必要时创建上级目录
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> ConfigParseResult<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir).map_err(ConfigParseError::Io)?;
+        }
+        fs::write(path, self.to_toml_string()?).map_err(ConfigParseError::Io)
+    }
+
+    /// 严格解析 TOML 字符串：未知的节或键、类型错误与不存在的路径都返回错误，而不是回退到默认值
+    pub fn from_toml_str_strict(s: &str) -> ConfigParseResult<Self> {
+        let mut parsed: toml::Table = toml::from_str(s).map_err(ConfigParseError::Parser)?;
+        migrate(&mut parsed)?;
+        Self::from_table_strict(&parsed)
+    }
 
-        if let Some(logging_val) = parsed.get("logging") {
-            if let Ok(cfg) = logging_val.clone().try_into::<LogConfig>() {
-                root.logging = cfg;
-            }
+    /// 严格模式下从已迁移的 TOML 表构建配置，一次返回所有问题
+    pub(crate) fn from_table_strict(parsed: &toml::Table) -> ConfigParseResult<Self> {
+        let (root, mut issues) = validate(parsed);
+        issues.extend(check_paths(parsed));
+        if issues.is_empty() {
+            Ok(root)
+        } else {
+            Err(ConfigParseError::Invalid(issues))
         }
+    }
 
-        if let Some(err_val) = parsed.get("error_exporter") {
-            if let Ok(cfg) = err_val.clone().try_into::<ErrorExporterConfig>() {
-                root.error_exporter = cfg;
-            }
+    pub(crate) fn from_value(parsed: &toml::Value) -> Self {
+        // 从默认值开始，并应用 TOML 中存在的各个节。
+        let mut root = Root::default();
+
+        if let Some(logging_val) = parsed.get("logging")
+            && let Ok(cfg) = logging_val.clone().try_into::<LogConfig>()
+        {
+            root.logging = cfg;
         }
 
-        if let Some(sqllog_val) = parsed.get("sqllog") {
-            if let Ok(cfg) = sqllog_val.clone().try_into::<SqllogConfig>() {
-                root.sqllog = cfg;
-            }
+        if let Some(err_val) = parsed.get("error_exporter")
+            && let Ok(cfg) = err_val.clone().try_into::<ErrorExporterConfig>()
+        {
+            root.error_exporter = cfg;
+        }
+
+        if let Some(sqllog_val) = parsed.get("sqllog")
+            && let Ok(cfg) = sqllog_val.clone().try_into::<SqllogConfig>()
+        {
+            root.sqllog = cfg;
+        }
+
+        if let Some(analysis_val) = parsed.get("analysis")
+            && let Ok(cfg) = analysis_val.clone().try_into::<AnalysisConfig>()
+        {
+            root.analysis = cfg;
+        }
+
+        if let Some(export_val) = parsed.get("export")
+            && let Ok(cfg) = export_val.clone().try_into::<ExportConfig>()
+        {
+            root.export = cfg;
+        }
+
+        if let Some(sftp_val) = parsed.get("sftp")
+            && let Ok(cfg) = sftp_val.clone().try_into::<SftpConfig>()
+        {
+            root.sftp = cfg;
+        }
+
+        if let Some(rules_val) = parsed.get("rules")
+            && let Ok(cfg) = rules_val.clone().try_into::<RulesConfig>()
+        {
+            root.rules = cfg;
         }
 
         root
@@ -74,6 +179,31 @@ impl Root {
         self.error_exporter = error_exporter;
         self
     }
+
+    pub fn set_sqllog(mut self, sqllog: SqllogConfig) -> Self {
+        self.sqllog = sqllog;
+        self
+    }
+
+    pub fn set_analysis(mut self, analysis: AnalysisConfig) -> Self {
+        self.analysis = analysis;
+        self
+    }
+
+    pub fn set_export(mut self, export: ExportConfig) -> Self {
+        self.export = export;
+        self
+    }
+
+    pub fn set_sftp(mut self, sftp: SftpConfig) -> Self {
+        self.sftp = sftp;
+        self
+    }
+
+    pub fn set_rules(mut self, rules: RulesConfig) -> Self {
+        self.rules = rules;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -125,6 +255,64 @@ mod tests {
         assert!(error_exporter.append);
     }
 
+    #[test]
+    fn test_root_try_from_reports_errors() {
+        assert!(matches!(
+            Root::try_from_toml_str("[logging"),
+            Err(ConfigParseError::Parser(_))
+        ));
+        assert!(matches!(
+            Root::try_from_file("/nonexistent/config.toml"),
+            Err(ConfigParseError::Io(_))
+        ));
+        assert_eq!(
+            Root::from_toml_str("[logging").logging.level,
+            Root::new().logging.level
+        );
+    }
+
+    #[test]
+    fn test_root_from_toml_str_strict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = format!("path = {:?}\n", dir.path().display().to_string());
+        let root = Root::from_toml_str_strict(&format!(
+            "[sqllog]\n{path}thread_num = 4\n[sftp]\nhost = \"h\"\npassphrase = \"p\"\n"
+        ))
+        .unwrap();
+        assert_eq!(root.sqllog.thread_num, 4);
+        assert_eq!(root.sftp.passphrase.as_deref(), Some("p"));
+
+        // 宽松模式下出错的键被忽略，严格模式一次报告全部问题
+        let bad = format!("[sqllog]\n{path}thred_num = 4\nbatch_size = \"x\"\n[sqlog]\n");
+        assert_eq!(Root::from_toml_str(&bad).sqllog.thread_num, 0);
+        let Err(ConfigParseError::Invalid(issues)) = Root::from_toml_str_strict(&bad) else {
+            panic!("expected invalid config");
+        };
+        let keys: Vec<_> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["sqllog.batch_size", "sqllog.thred_num", "sqlog"]);
+    }
+
+    #[test]
+    fn test_root_try_from_file_with_include() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("common.toml"),
+            "[sqllog]\nsqllog_path = \"/data\"\nthread_num = 2\n",
+        )
+        .unwrap();
+        let host = dir.path().join("host.toml");
+        fs::write(
+            &host,
+            "version = 2\ninclude = [\"common.toml\"]\n[sqllog]\nthread_num = 8\n",
+        )
+        .unwrap();
+
+        let root = Root::try_from_file(&host).unwrap();
+        // 被包含的旧版文件单独迁移
+        assert_eq!(root.sqllog.sqllog_path, "/data");
+        assert_eq!(root.sqllog.thread_num, 8);
+    }
+
     #[test]
     fn test_root_setters() {
         let logging = LogConfig::new().set_level("warn").set_path("logs/warn.log");
@@ -150,4 +338,29 @@ mod tests {
         );
         assert_eq!(root.error_exporter.clone().append, error_exporter.append);
     }
+
+    #[test]
+    fn test_root_save_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tuned").join("config.toml");
+        let root = Root::new()
+            .set_logging(LogConfig::new().set_level("warn"))
+            .set_sqllog(
+                SqllogConfig::new()
+                    .set_thread_num(8)
+                    .set_chunk_size_kb(16384),
+            )
+            .set_export(ExportConfig::new().set_max_body_len(256));
+        root.save(&path).unwrap();
+
+        let loaded = Root::try_from_file(&path).unwrap();
+        assert_eq!(loaded.logging.level, "warn");
+        assert_eq!(loaded.sqllog.thread_num, 8);
+        assert_eq!(loaded.sqllog.chunk_size_kb, 16384);
+        assert_eq!(loaded.export.max_body_len, 256);
+        assert_eq!(
+            loaded.to_toml_string().unwrap(),
+            root.to_toml_string().unwrap()
+        );
+    }
 }
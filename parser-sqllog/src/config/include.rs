@@ -0,0 +1,152 @@
+//! 配置文件包含：顶层 `include = ["common.toml", "site.toml"]` 引入其他配置文件。
+//!
+//! 合并优先级从低到高为：`include` 中靠前的文件 < 靠后的文件 < 包含它们的文件本身，
+//! 即被包含的文件作为基础配置，当前文件只需写出差异。相对路径相对于当前文件所在目录，
+//! 被包含的文件也可以继续包含其他文件。
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::error::{ConfigParseError, ConfigParseResult};
+
+/// 引入其他配置文件的顶层键
+pub const INCLUDE_KEY: &str = "include";
+
+/// 读取配置文件及其包含的文件，按合并顺序（优先级从低到高）返回各文件路径与内容，`include` 键已移除
+pub fn load(path: &Path) -> ConfigParseResult<Vec<(PathBuf, Table)>> {
+    let mut files = Vec::new();
+    let mut stack = Vec::new();
+    load_into(path, &mut stack, &mut files)?;
+    Ok(files)
+}
+
+fn load_into(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<(PathBuf, Table)>,
+) -> ConfigParseResult<()> {
+    let content = fs::read_to_string(path).map_err(ConfigParseError::Io)?;
+    let mut table: Table = toml::from_str(&content)
+        .map_err(|e| ConfigParseError::syntax(&path.display().to_string(), &content, e))?;
+
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        return Err(ConfigParseError::IncludeCycle(path.display().to_string()));
+    }
+    stack.push(canonical);
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for name in includes(table.remove(INCLUDE_KEY))? {
+        let included = dir.join(&name);
+        load_into(&included, stack, files).map_err(|e| match e {
+            ConfigParseError::Io(source) => ConfigParseError::Include {
+                path: included.display().to_string(),
+                source,
+            },
+            e => e,
+        })?;
+    }
+
+    stack.pop();
+    files.push((path.to_path_buf(), table));
+    Ok(())
+}
+
+fn includes(value: Option<Value>) -> ConfigParseResult<Vec<String>> {
+    let invalid = |found: &Value| ConfigParseError::FieldType {
+        field: INCLUDE_KEY.to_string(),
+        expected: "字符串数组".to_string(),
+        found: found.to_string(),
+    };
+    match value {
+        None => Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| v.as_str().map(str::to_string).ok_or_else(|| invalid(v)))
+            .collect(),
+        Some(other) => Err(invalid(&other)),
+    }
+}
+
+/// 把 `src` 深度合并进 `dst`，同名的值以 `src` 为准
+pub fn merge_tables(dst: &mut Table, src: Table) {
+    for (key, value) in src {
+        match (dst.get_mut(&key), value) {
+            (Some(Value::Table(d)), Value::Table(s)) => merge_tables(d, s),
+            (_, v) => {
+                dst.insert(key, v);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn loads_includes_before_the_including_file() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("base")).unwrap();
+        fs::write(
+            dir.path().join("base/common.toml"),
+            "include = [\"site.toml\"]\n[sqllog]\nthread_num = 2\nbatch_size = 100\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("base/site.toml"),
+            "[sqllog]\nthread_num = 1\n",
+        )
+        .unwrap();
+        let host = dir.path().join("host.toml");
+        fs::write(
+            &host,
+            "include = [\"base/common.toml\"]\n[sqllog]\nthread_num = 8\n",
+        )
+        .unwrap();
+
+        let files = load(&host).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|(p, _)| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["site.toml", "common.toml", "host.toml"]);
+
+        let mut merged = Table::new();
+        for (_, table) in files {
+            merge_tables(&mut merged, table);
+        }
+        assert_eq!(merged["sqllog"]["thread_num"].as_integer(), Some(8));
+        assert_eq!(merged["sqllog"]["batch_size"].as_integer(), Some(100));
+        assert!(merged.get(INCLUDE_KEY).is_none());
+    }
+
+    #[test]
+    fn reports_cycles_and_missing_files() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.toml");
+        fs::write(&a, "include = [\"b.toml\"]\n").unwrap();
+        fs::write(dir.path().join("b.toml"), "include = [\"a.toml\"]\n").unwrap();
+        assert!(matches!(load(&a), Err(ConfigParseError::IncludeCycle(_))));
+
+        fs::write(&a, "include = [\"missing.toml\"]\n").unwrap();
+        assert!(matches!(
+            load(&a),
+            Err(ConfigParseError::Include { path, .. }) if path.ends_with("missing.toml")
+        ));
+
+        fs::write(&a, "[sqllog]\nthread_num = 2\nbatch_size = \n").unwrap();
+        assert!(matches!(
+            load(&a),
+            Err(ConfigParseError::Syntax { location: Some(loc), .. }) if loc.line == 3
+        ));
+
+        fs::write(&a, "include = \"b.toml\"\n").unwrap();
+        assert!(matches!(load(&a), Err(ConfigParseError::FieldType { .. })));
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::exporter::compress::Compression;
+
+/// 输入文件的字符编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputEncoding {
+    /// UTF-8，非法字节以替换字符代替
+    #[default]
+    Utf8,
+    /// ISO-8859-1，每个字节对应一个字符，不会丢失字节
+    Latin1,
+    /// GBK（中文区域设置的 Windows 主机上的常见编码），非法字节以替换字符代替
+    Gbk,
+    /// GB18030，GBK 的超集，非法字节以替换字符代替
+    Gb18030,
+}
+
+/// `[sqllog.input]`：批处理的输入文件定义
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InputConfig {
+    /// 输入文件、目录或通配符（`*`、`?`、`**`），为空时使用 `sqllog.path`
+    #[serde(default)]
+    pub paths: Vec<String>,
+
+    /// 递归读取目录下的子目录
+    #[serde(default)]
+    pub recursive: bool,
+
+    /// 排除的文件通配符；不含 `/` 时只匹配文件名，否则匹配完整路径
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// 输入文件的压缩格式，`none` 时按扩展名（`.gz` / `.zst`）推断
+    #[serde(default)]
+    pub compression: Compression,
+
+    /// 输入文件的字符编码
+    #[serde(default)]
+    pub encoding: InputEncoding,
+}
+
+impl InputConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_paths(mut self, paths: Vec<String>) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    pub fn set_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn set_exclude(mut self, exclude: Vec<String>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    pub fn set_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn set_encoding(mut self, encoding: InputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::Root;
+
+    #[test]
+    fn parses_input_section() {
+        let root = Root::from_toml_str(
+            r#"
+            [sqllog]
+            path = "unused"
+
+            [sqllog.input]
+            paths = ["/data/a/*.log", "/data/b"]
+            recursive = true
+            exclude = ["*_old.log"]
+            compression = "gzip"
+            encoding = "latin1"
+            "#,
+        );
+        let input = root.sqllog.input;
+        assert_eq!(input.paths, ["/data/a/*.log", "/data/b"]);
+        assert!(input.recursive);
+        assert_eq!(input.exclude, ["*_old.log"]);
+        assert_eq!(input.compression, Compression::Gzip);
+        assert_eq!(input.encoding, InputEncoding::Latin1);
+
+        assert_eq!(Root::new().sqllog.input, InputConfig::new());
+    }
+}
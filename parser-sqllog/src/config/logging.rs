@@ -1,9 +1,9 @@
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
 
-use crate::config::file::Root;
+use crate::config::{file::Root, syslog::SyslogConfig};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LogConfig {
     /// 日志级别文本: "error", "warn", "info", "debug", "trace"
     #[serde(default = "default_log_level")]
@@ -12,6 +12,26 @@ pub struct LogConfig {
     /// 日志输出文件路径，默认输出到 logs 目录
     #[serde(default = "default_log_path")]
     pub path: String,
+
+    /// 日志文件名前缀，文件按天滚动为 `<前缀>.YYYY-MM-DD`
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String,
+
+    /// 启动及每日轮换时删除超过该天数的日志文件，0 表示不按时间清理
+    #[serde(default)]
+    pub max_age_days: u64,
+
+    /// 最多保留的日志文件数（含当天），0 表示不限制
+    #[serde(default)]
+    pub max_files: usize,
+
+    /// 按模块设置的日志级别，如 `dm_database_parser = "trace"`，优先于 `level`
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+
+    /// syslog 输出（`[logging.syslog]`）
+    #[serde(default)]
+    pub syslog: SyslogConfig,
 }
 
 fn default_log_level() -> String {
@@ -22,6 +42,10 @@ fn default_log_path() -> String {
     "logs".to_string()
 }
 
+fn default_file_prefix() -> String {
+    "sqllog".to_string()
+}
+
 impl Default for LogConfig {
     fn default() -> Self {
         Self::new()
@@ -33,6 +57,11 @@ impl LogConfig {
         Self {
             level: "info".to_string(),
             path: "logs".to_string(),
+            file_prefix: default_file_prefix(),
+            max_age_days: 0,
+            max_files: 0,
+            targets: BTreeMap::new(),
+            syslog: SyslogConfig::new(),
         }
     }
 
@@ -51,6 +80,38 @@ impl LogConfig {
         self.path = path.to_string();
         self
     }
+
+    pub fn set_file_prefix(mut self, prefix: &str) -> Self {
+        self.file_prefix = prefix.to_string();
+        self
+    }
+
+    pub fn set_max_age_days(mut self, days: u64) -> Self {
+        self.max_age_days = days;
+        self
+    }
+
+    pub fn set_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    pub fn set_target(mut self, target: &str, level: &str) -> Self {
+        self.targets.insert(target.to_string(), level.to_string());
+        self
+    }
+
+    pub fn set_syslog(mut self, syslog: SyslogConfig) -> Self {
+        self.syslog = syslog;
+        self
+    }
+
+    /// 转换为 `EnvFilter` 的指令串，如 `info,dm_database_parser=trace,sqlx=warn`
+    pub fn filter_directives(&self) -> String {
+        let mut directives = vec![self.level.clone()];
+        directives.extend(self.targets.iter().map(|(t, l)| format!("{t}={l}")));
+        directives.join(",")
+    }
 }
 
 #[cfg(test)]
@@ -65,14 +126,26 @@ mod tests {
 
         assert_eq!(cfg.level, "info".to_string());
         assert_eq!(cfg.path, "logs".to_string());
+        assert_eq!(cfg.file_prefix, "sqllog");
+        assert_eq!((cfg.max_age_days, cfg.max_files), (0, 0));
+        assert!(cfg.targets.is_empty());
+        assert_eq!(cfg.filter_directives(), "info");
     }
 
     #[test]
     fn setters_update_values() {
-        let cfg = LogConfig::new().set_level("debug").set_path("/tmp/mylogs");
+        let cfg = LogConfig::new()
+            .set_level("debug")
+            .set_path("/tmp/mylogs")
+            .set_file_prefix("parser")
+            .set_max_age_days(7)
+            .set_max_files(10);
 
         assert_eq!(cfg.level, "debug".to_string());
         assert_eq!(cfg.path, "/tmp/mylogs".to_string());
+        assert_eq!(cfg.file_prefix, "parser");
+        assert_eq!(cfg.max_age_days, 7);
+        assert_eq!(cfg.max_files, 10);
     }
 
     #[test]
@@ -81,6 +154,10 @@ mod tests {
             [logging]
             level = "error"
             path = "/var/logs/errors"
+
+            [logging.targets]
+            dm_database_parser = "trace"
+            sqlx = "warn"
         "#;
         let mut config_file = NamedTempFile::new().unwrap();
         config_file.write_all(toml_str.as_bytes()).unwrap();
@@ -88,5 +165,9 @@ mod tests {
 
         assert_eq!(config_content.level, "error".to_string());
         assert_eq!(config_content.path, "/var/logs/errors".to_string());
+        assert_eq!(
+            config_content.filter_directives(),
+            "error,dm_database_parser=trace,sqlx=warn"
+        );
     }
 }
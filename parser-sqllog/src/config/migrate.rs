@@ -0,0 +1,130 @@
+//! 配置文件版本与旧版布局的迁移
+
+use toml::{Table, Value};
+
+use crate::{
+    config::effective::PROFILE_KEY,
+    error::{ConfigParseError, ConfigParseResult},
+};
+
+/// 当前程序支持的配置版本，写入配置文件顶层的 `version` 键
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 各版本升级时改名的键：(旧版本, 节, 旧键名, 新键名)
+const RENAMES: &[(u32, &str, &str, &str)] = &[
+    (1, "sqllog", "sqllog_path", "path"),
+    (1, "error_exporter", "error_log_path", "path"),
+    (1, "logging", "log_path", "path"),
+    (1, "logging", "log_level", "level"),
+];
+
+/// 把配置迁移到 [`CONFIG_VERSION`] 的布局，返回所做修改的说明。
+///
+/// 未写 `version` 的配置视为版本 1；版本高于当前程序时返回错误，而不是静默回退到默认值。
+pub fn migrate(table: &mut Table) -> ConfigParseResult<Vec<String>> {
+    let version = match table.get("version") {
+        None => 1,
+        Some(Value::Integer(v)) if *v >= 1 => *v as u64,
+        Some(other) => {
+            return Err(ConfigParseError::FieldType {
+                field: "version".to_string(),
+                expected: "正整数".to_string(),
+                found: other.to_string(),
+            });
+        }
+    };
+    if version > CONFIG_VERSION as u64 {
+        return Err(ConfigParseError::UnsupportedVersion {
+            found: version,
+            supported: CONFIG_VERSION,
+        });
+    }
+
+    let mut notes = Vec::new();
+    rename_keys(table, version, "", &mut notes);
+    // 配置档中的各节与顶层布局相同，一并迁移
+    if let Some(Value::Table(profiles)) = table.get_mut(PROFILE_KEY) {
+        for (name, profile) in profiles.iter_mut() {
+            if let Value::Table(profile) = profile {
+                rename_keys(
+                    profile,
+                    version,
+                    &format!("{PROFILE_KEY}.{name}."),
+                    &mut notes,
+                );
+            }
+        }
+    }
+    table.insert("version".to_string(), Value::Integer(CONFIG_VERSION as i64));
+    Ok(notes)
+}
+
+fn rename_keys(table: &mut Table, version: u64, prefix: &str, notes: &mut Vec<String>) {
+    for &(from, section, old, new) in RENAMES {
+        if (from as u64) < version {
+            continue;
+        }
+        let Some(Value::Table(t)) = table.get_mut(section) else {
+            continue;
+        };
+        let Some(value) = t.remove(old) else {
+            continue;
+        };
+        if t.contains_key(new) {
+            notes.push(format!("忽略 [{prefix}{section}] {old}，已存在新键 {new}"));
+        } else {
+            t.insert(new.to_string(), value);
+            notes.push(format!("[{prefix}{section}] {old} 已改名为 {new}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_v1_layout() {
+        let mut table: Table =
+            "[sqllog]\nsqllog_path = \"/data\"\n[logging]\nlog_level = \"warn\"\n\
+                                [profile.prod.sqllog]\nsqllog_path = \"/prod\"\n"
+                .parse()
+                .unwrap();
+        let notes = migrate(&mut table).unwrap();
+
+        assert_eq!(notes.len(), 3);
+        assert_eq!(
+            table["profile"]["prod"]["sqllog"]["path"].as_str(),
+            Some("/prod")
+        );
+        assert_eq!(table["version"].as_integer(), Some(CONFIG_VERSION as i64));
+        assert_eq!(table["sqllog"]["path"].as_str(), Some("/data"));
+        assert!(table["sqllog"].get("sqllog_path").is_none());
+        assert_eq!(table["logging"]["level"].as_str(), Some("warn"));
+    }
+
+    #[test]
+    fn keeps_current_layout() {
+        let mut table: Table = "version = 2\n[sqllog]\npath = \"/data\"\nsqllog_path = \"x\"\n"
+            .parse()
+            .unwrap();
+        assert!(migrate(&mut table).unwrap().is_empty());
+        // 当前版本中旧键名不再特殊处理
+        assert_eq!(table["sqllog"]["sqllog_path"].as_str(), Some("x"));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut table: Table = "version = 99\n".parse().unwrap();
+        assert!(matches!(
+            migrate(&mut table),
+            Err(ConfigParseError::UnsupportedVersion { found: 99, .. })
+        ));
+
+        let mut table: Table = "version = \"2\"\n".parse().unwrap();
+        assert!(matches!(
+            migrate(&mut table),
+            Err(ConfigParseError::FieldType { .. })
+        ));
+    }
+}
@@ -1,4 +1,15 @@
+pub mod analysis;
+pub mod diagnostic;
+pub mod effective;
 pub mod error_exporter;
+pub mod export;
 pub mod file;
+pub mod include;
+pub mod input;
 pub mod logging;
+pub mod migrate;
+pub mod rules;
+pub mod sftp;
 pub mod sqllog;
+pub mod syslog;
+pub mod validate;
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::config::file::Root;
+
+/// `[[rules.rule]]`：按条件给记录打标签，标签写入导出记录的 `tags` 字段，也可作为统计的分组维度
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RuleConfig {
+    /// 规则名称，用于报错信息
+    #[serde(default)]
+    pub name: String,
+
+    /// 条件表达式，如 `appname LIKE 'ETL%' AND exec_time_ms > 1000`，语法见 [`crate::rules`]
+    pub condition: String,
+
+    /// 命中时给记录打的标签
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RulesConfig {
+    /// 标签规则，按顺序逐条求值，一条记录可以命中多条规则
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<RuleConfig>,
+}
+
+impl RulesConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let root = Root::from_file(path);
+        root.rules
+    }
+
+    pub fn set_rules(mut self, rules: Vec<RuleConfig>) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_from_toml() {
+        let root = Root::from_toml_str(
+            r#"
+            [[rules.rule]]
+            name = "etl"
+            condition = "appname LIKE 'ETL%'"
+            tag = "batch"
+            [[rules.rule]]
+            condition = "exec_time_ms > 1000"
+            tag = "slow"
+        "#,
+        );
+        let rules = root.rules.rules;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "etl");
+        assert_eq!(rules[0].tag, "batch");
+        assert_eq!(rules[1].condition, "exec_time_ms > 1000");
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::config::file::Root;
+
+/// `sftp://` 输入的连接配置
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SftpConfig {
+    /// 默认主机，输入路径为 `sftp:///路径` 时使用
+    #[serde(default)]
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// 登录用户名，输入路径中未指定 `user@` 时使用
+    #[serde(default)]
+    pub user: String,
+
+    /// 私钥文件路径；为空时使用 ssh-agent 认证
+    #[serde(default)]
+    pub key_path: String,
+
+    /// 私钥口令
+    #[serde(default)]
+    pub passphrase: Option<String>,
+
+    /// known_hosts 文件路径，为空时使用 `~/.ssh/known_hosts`
+    #[serde(default)]
+    pub known_hosts: String,
+
+    /// 严格校验主机密钥：主机不在 known_hosts 中时拒绝连接
+    #[serde(default = "default_strict_host_key")]
+    pub strict_host_key: bool,
+}
+
+fn default_port() -> u16 {
+    22
+}
+
+fn default_strict_host_key() -> bool {
+    true
+}
+
+impl Default for SftpConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SftpConfig {
+    pub fn new() -> Self {
+        Self {
+            host: String::new(),
+            port: 22,
+            user: String::new(),
+            key_path: String::new(),
+            passphrase: None,
+            known_hosts: String::new(),
+            strict_host_key: true,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let root = Root::from_file(path);
+        root.sftp
+    }
+
+    pub fn set_host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    pub fn set_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn set_user(mut self, user: &str) -> Self {
+        self.user = user.to_string();
+        self
+    }
+
+    pub fn set_key_path(mut self, key_path: &str) -> Self {
+        self.key_path = key_path.to_string();
+        self
+    }
+
+    pub fn set_strict_host_key(mut self, strict: bool) -> Self {
+        self.strict_host_key = strict;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_sftp_config_default() {
+        let config = SftpConfig::new();
+        assert_eq!(config.port, 22);
+        assert!(config.host.is_empty());
+        assert!(config.strict_host_key);
+    }
+
+    #[test]
+    fn test_sftp_config_from_file() {
+        let toml_str = r#"
+            [sftp]
+            host = "jump.example.com"
+            port = 2222
+            user = "dmdba"
+            key_path = "~/.ssh/id_ed25519"
+            strict_host_key = false
+        "#;
+        let mut config_file = NamedTempFile::new().unwrap();
+        config_file.write_all(toml_str.as_bytes()).unwrap();
+        let config = SftpConfig::from_file(config_file.path());
+
+        assert_eq!(config.host, "jump.example.com");
+        assert_eq!(config.port, 2222);
+        assert_eq!(config.user, "dmdba");
+        assert_eq!(config.key_path, "~/.ssh/id_ed25519");
+        assert_eq!(config.passphrase, None);
+        assert!(!config.strict_host_key);
+    }
+}
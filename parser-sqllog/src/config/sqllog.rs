@@ -1,9 +1,36 @@
-use serde::Deserialize;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-use crate::config::file::Root;
+use crate::config::{file::Root, input::InputConfig};
 
-#[derive(Debug, Deserialize, Clone)]
+/// 遇到无法解析的记录时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// 立即中止处理并返回错误
+    Abort,
+    /// 跳过错误记录，只计数
+    #[default]
+    Skip,
+    /// 跳过错误记录，并导出到 `[error_exporter]` 配置的文件
+    Collect,
+}
+
+/// 处理进度的输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressMode {
+    /// 不输出进度
+    #[default]
+    None,
+    /// 定期以日志形式输出进度，结束时输出吞吐率汇总
+    Text,
+    /// 定期向标准错误输出 NDJSON 格式的进度事件
+    Json,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SqllogConfig {
     /// 批处理大小 (配置文件中键为 `batch-size`)
     #[serde(default = "default_batch_size")]
@@ -16,12 +43,72 @@ pub struct SqllogConfig {
     /// 日志输出文件路径，默认输出到 sqllog 目录
     #[serde(default = "default_sqllog_path", rename = "path")]
     pub sqllog_path: String,
+
+    /// 流水线内存上限（MB），0 表示不限制。超出时读取阶段暂停，直到下游释放内存
+    #[serde(default = "default_memory_limit_mb")]
+    pub memory_limit_mb: usize,
+
+    /// 流式读取时每块的大小（KB）
+    #[serde(default = "default_chunk_size_kb")]
+    pub chunk_size_kb: usize,
+
+    /// 预读深度：读取阶段最多领先解析阶段的块数
+    #[serde(default = "default_read_ahead")]
+    pub read_ahead: usize,
+
+    /// 错误记录处理策略：abort / skip / collect
+    #[serde(default)]
+    pub on_error: OnError,
+
+    /// 严格模式：校验元数据字段及其顺序，不符合的记录按 `on_error` 处理
+    #[serde(default)]
+    pub strict: bool,
+
+    /// 遇到无法读取的文件时立即中止；默认记录日志后跳过该文件继续处理
+    #[serde(default)]
+    pub fail_fast: bool,
+
+    /// 错误记录占全部记录的比例上限（0~1），超过时以退出码 65 失败；默认 1 表示不限制
+    #[serde(default = "default_max_error_rate")]
+    pub max_error_rate: f64,
+
+    /// 进度输出方式：none / text / json
+    #[serde(default)]
+    pub progress: ProgressMode,
+
+    /// 进度事件的最小间隔（毫秒）
+    #[serde(default = "default_progress_interval_ms")]
+    pub progress_interval_ms: u64,
+
+    /// 输入文件定义（`[sqllog.input]`），未配置 `paths` 时读取 `path`
+    #[serde(default)]
+    pub input: InputConfig,
 }
 
 fn default_sqllog_path() -> String {
     "sqllog".to_string()
 }
 
+fn default_chunk_size_kb() -> usize {
+    4096
+}
+
+fn default_read_ahead() -> usize {
+    2
+}
+
+fn default_memory_limit_mb() -> usize {
+    0
+}
+
+fn default_max_error_rate() -> f64 {
+    1.0
+}
+
+fn default_progress_interval_ms() -> u64 {
+    1000
+}
+
 fn default_thread_num() -> usize {
     0
 }
@@ -42,6 +129,16 @@ impl SqllogConfig {
             thread_num: 0,
             batch_size: 0,
             sqllog_path: "sqllog".to_string(),
+            memory_limit_mb: 0,
+            chunk_size_kb: 4096,
+            read_ahead: 2,
+            on_error: OnError::Skip,
+            strict: false,
+            fail_fast: false,
+            max_error_rate: default_max_error_rate(),
+            progress: ProgressMode::None,
+            progress_interval_ms: 1000,
+            input: InputConfig::new(),
         }
     }
 
@@ -64,6 +161,56 @@ impl SqllogConfig {
         self.sqllog_path = path.to_string();
         self
     }
+
+    pub fn set_memory_limit_mb(mut self, limit: usize) -> Self {
+        self.memory_limit_mb = limit;
+        self
+    }
+
+    pub fn set_chunk_size_kb(mut self, chunk_size_kb: usize) -> Self {
+        self.chunk_size_kb = chunk_size_kb;
+        self
+    }
+
+    pub fn set_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    pub fn set_on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    pub fn set_max_error_rate(mut self, max_error_rate: f64) -> Self {
+        self.max_error_rate = max_error_rate;
+        self
+    }
+
+    pub fn set_progress(mut self, progress: ProgressMode) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn set_progress_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.progress_interval_ms = interval_ms;
+        self
+    }
+
+    pub fn set_input(mut self, input: InputConfig) -> Self {
+        self.input = input;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -78,6 +225,15 @@ mod tests {
         assert_eq!(config.batch_size, 0);
         assert_eq!(config.thread_num, 0);
         assert_eq!(config.sqllog_path, "sqllog".to_string());
+        assert_eq!(config.memory_limit_mb, 0);
+        assert_eq!(config.chunk_size_kb, 4096);
+        assert_eq!(config.read_ahead, 2);
+        assert_eq!(config.on_error, OnError::Skip);
+        assert!(!config.strict);
+        assert!(!config.fail_fast);
+        assert_eq!(config.max_error_rate, 1.0);
+        assert_eq!(config.progress, ProgressMode::None);
+        assert_eq!(config.progress_interval_ms, 1000);
     }
 
     #[test]
@@ -98,6 +254,15 @@ mod tests {
             path = "/var/logs/errors"
             batch_size = 10
             thread_num = 10
+            memory_limit_mb = 256
+            chunk_size_kb = 1024
+            read_ahead = 8
+            on_error = "collect"
+            strict = true
+            fail_fast = true
+            max_error_rate = 0.01
+            progress = "json"
+            progress_interval_ms = 5000
         "#;
         let mut config_file = NamedTempFile::new().unwrap();
         config_file.write_all(toml_str.as_bytes()).unwrap();
@@ -106,5 +271,14 @@ mod tests {
         assert_eq!(config_content.sqllog_path, "/var/logs/errors".to_string());
         assert_eq!(config_content.batch_size, 10);
         assert_eq!(config_content.thread_num, 10);
+        assert_eq!(config_content.memory_limit_mb, 256);
+        assert_eq!(config_content.chunk_size_kb, 1024);
+        assert_eq!(config_content.read_ahead, 8);
+        assert_eq!(config_content.on_error, OnError::Collect);
+        assert!(config_content.strict);
+        assert!(config_content.fail_fast);
+        assert_eq!(config_content.max_error_rate, 0.01);
+        assert_eq!(config_content.progress, ProgressMode::Json);
+        assert_eq!(config_content.progress_interval_ms, 5000);
     }
 }
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+/// syslog 设施（facility）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFacility {
+    User,
+    #[default]
+    Daemon,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// RFC 3164 中的设施编号
+    pub fn code(self) -> u8 {
+        match self {
+            Self::User => 1,
+            Self::Daemon => 3,
+            Self::Local0 => 16,
+            Self::Local1 => 17,
+            Self::Local2 => 18,
+            Self::Local3 => 19,
+            Self::Local4 => 20,
+            Self::Local5 => 21,
+            Self::Local6 => 22,
+            Self::Local7 => 23,
+        }
+    }
+}
+
+/// `[logging.syslog]`：把日志发送到本机 syslog（journald 同样监听 `/dev/log`）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SyslogConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// syslog 的 Unix 数据报套接字
+    #[serde(default = "default_socket")]
+    pub socket: String,
+
+    /// 设施
+    #[serde(default)]
+    pub facility: SyslogFacility,
+
+    /// 消息中的程序标识
+    #[serde(default = "default_ident")]
+    pub ident: String,
+
+    /// 启用 syslog 时是否仍写日志文件
+    #[serde(default)]
+    pub keep_files: bool,
+}
+
+fn default_socket() -> String {
+    "/dev/log".to_string()
+}
+
+fn default_ident() -> String {
+    "parser-sqllog".to_string()
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyslogConfig {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            socket: default_socket(),
+            facility: SyslogFacility::default(),
+            ident: default_ident(),
+            keep_files: false,
+        }
+    }
+
+    pub fn set_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn set_socket(mut self, socket: &str) -> Self {
+        self.socket = socket.to_string();
+        self
+    }
+
+    pub fn set_facility(mut self, facility: SyslogFacility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    pub fn set_ident(mut self, ident: &str) -> Self {
+        self.ident = ident.to_string();
+        self
+    }
+
+    pub fn set_keep_files(mut self, keep_files: bool) -> Self {
+        self.keep_files = keep_files;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::Root;
+
+    #[test]
+    fn parses_syslog_section() {
+        let root = Root::from_toml_str(
+            r#"
+            [logging.syslog]
+            enabled = true
+            facility = "local3"
+            ident = "sqllog-daemon"
+            "#,
+        );
+        let syslog = root.logging.syslog;
+        assert!(syslog.enabled);
+        assert_eq!(syslog.socket, "/dev/log");
+        assert_eq!(syslog.facility.code(), 19);
+        assert_eq!(syslog.ident, "sqllog-daemon");
+        assert!(!syslog.keep_files);
+
+        assert_eq!(Root::new().logging.syslog, SyslogConfig::new());
+    }
+}
@@ -0,0 +1,192 @@
+//! 配置校验：一次收集所有未知键、类型错误与无效路径，并给出各自的 TOML 键路径
+
+use std::path::Path;
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_path_to_error::Segment;
+use toml::{Table, Value};
+
+use crate::{
+    config::{
+        analysis::AnalysisConfig, effective::PROFILE_KEY, error_exporter::ErrorExporterConfig,
+        export::ExportConfig, file::Root, logging::LogConfig, rules::RulesConfig, sftp::SftpConfig,
+        sqllog::SqllogConfig,
+    },
+    error::ConfigIssue,
+    rules::RuleSet,
+};
+
+/// 校验已迁移的配置表，返回去掉出错项后构建的配置以及全部问题
+pub fn validate(parsed: &Table) -> (Root, Vec<ConfigIssue>) {
+    let mut root = Root::default();
+    let mut issues = Vec::new();
+    for (key, value) in parsed {
+        let issues = &mut issues;
+        match key.as_str() {
+            // 配置档在选中时合并到各节后再校验
+            "version" | PROFILE_KEY => {}
+            "logging" => root.logging = section::<LogConfig>(key, value, issues),
+            "error_exporter" => {
+                root.error_exporter = section::<ErrorExporterConfig>(key, value, issues)
+            }
+            "sqllog" => root.sqllog = section::<SqllogConfig>(key, value, issues),
+            "analysis" => root.analysis = section::<AnalysisConfig>(key, value, issues),
+            "export" => root.export = section::<ExportConfig>(key, value, issues),
+            "sftp" => root.sftp = section::<SftpConfig>(key, value, issues),
+            "rules" => root.rules = section::<RulesConfig>(key, value, issues),
+            _ => issues.push(ConfigIssue::new(key, "未知的节")),
+        }
+    }
+    issues.extend(RuleSet::check(&root.rules));
+    (root, issues)
+}
+
+/// 校验一个节：借助 `serde_path_to_error` 把类型错误定位到具体的键，去掉出错的键后重试，
+/// 直到其余的键都能接受，再比对重新序列化的结果找出未知的键
+fn section<T: DeserializeOwned + Serialize + Default>(
+    name: &str,
+    value: &Value,
+    issues: &mut Vec<ConfigIssue>,
+) -> T {
+    let Value::Table(input) = value else {
+        issues.push(ConfigIssue::new(name, "应为表"));
+        return T::default();
+    };
+    let mut accepted = input.clone();
+    loop {
+        match serde_path_to_error::deserialize::<_, T>(Value::Table(accepted.clone())) {
+            Ok(cfg) => {
+                // 反序列化时被忽略的键不会出现在重新序列化的结果中
+                match Value::try_from(&cfg) {
+                    Ok(known) => unknown_keys(&Value::Table(accepted), &known, name, issues),
+                    Err(e) => issues.push(ConfigIssue::new(name, e.to_string())),
+                }
+                return cfg;
+            }
+            Err(e) => {
+                let key = match e.path().iter().next() {
+                    Some(Segment::Map { key }) => key.clone(),
+                    // 错误无法定位到某个键（如缺少必填字段），整节回退为默认值
+                    _ => {
+                        issues.push(ConfigIssue::new(name, e.inner().message().trim()));
+                        return T::default();
+                    }
+                };
+                issues.push(ConfigIssue::new(
+                    &format!("{name}.{}", e.path()),
+                    e.inner().message().trim(),
+                ));
+                accepted.remove(&key);
+            }
+        }
+    }
+}
+
+/// 嵌套表及表数组（如 `[[export.sink]]`）中的未知键
+fn unknown_keys(value: &Value, known: &Value, path: &str, issues: &mut Vec<ConfigIssue>) {
+    match (value, known) {
+        (Value::Table(value), Value::Table(known)) => {
+            for (key, v) in value {
+                let full = format!("{path}.{key}");
+                match known.get(key) {
+                    None => issues.push(ConfigIssue::new(&full, "未知的键")),
+                    Some(k) => unknown_keys(v, k, &full, issues),
+                }
+            }
+        }
+        (Value::Array(value), Value::Array(known)) => {
+            for (i, (v, k)) in value.iter().zip(known).enumerate() {
+                unknown_keys(v, k, &format!("{path}[{i}]"), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 配置中显式给出的本地路径必须存在；远程路径（含 `://`）在运行时检查，通配符不检查。
+/// 只有读取输入的命令用到这些路径，因此不在 [`validate`] 中检查，由调用方按需调用
+pub fn check_paths(parsed: &Table) -> Vec<ConfigIssue> {
+    let get = |section: &str, key: &str| parsed.get(section).and_then(|s| s.get(key));
+    let input_paths = get("sqllog", "input")
+        .and_then(|i| i.get("paths"))
+        .and_then(Value::as_array)
+        .filter(|paths| !paths.is_empty());
+    let inputs: Vec<_> = match input_paths {
+        Some(paths) => paths
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| Some((format!("sqllog.input.paths[{i}]"), p.as_str()?)))
+            .collect(),
+        None => get("sqllog", "path")
+            .and_then(Value::as_str)
+            .map(|p| ("sqllog.path".to_string(), p))
+            .into_iter()
+            .collect(),
+    };
+    let mut issues = Vec::new();
+    for (key, path) in inputs {
+        if !path.contains("://") && !path.contains(['*', '?']) && !Path::new(path).exists() {
+            issues.push(ConfigIssue::new(&key, format!("路径不存在: {path}")));
+        }
+    }
+    for key in ["key_path", "known_hosts"] {
+        if let Some(path) = get("sftp", key).and_then(Value::as_str)
+            && !path.is_empty()
+            && !Path::new(path).exists()
+        {
+            issues.push(ConfigIssue::new(
+                &format!("sftp.{key}"),
+                format!("文件不存在: {path}"),
+            ));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_every_issue_with_key_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let table: Table = format!(
+            "[sqllog]\npath = {:?}\nthread_num = \"four\"\nthred_num = 2\nbatch_size = 10\n\
+             [logging]\nlevel = 3\n[sqlog]\n[sftp]\nkey_path = \"/nonexistent/id_rsa\"\n",
+            dir.path().display().to_string()
+        )
+        .parse()
+        .unwrap();
+        let (root, issues) = validate(&table);
+
+        let keys: Vec<_> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "logging.level",
+                "sqllog.thread_num",
+                "sqllog.thred_num",
+                "sqlog"
+            ]
+        );
+        let keys: Vec<_> = check_paths(&table).into_iter().map(|i| i.key).collect();
+        assert_eq!(keys, ["sftp.key_path"]);
+
+        // 未显式给出的路径不检查，默认的 sqllog.path 不会报错
+        assert!(check_paths(&"[sqllog]\nthread_num = 2\n".parse().unwrap()).is_empty());
+        let table: Table = "[sqllog]\npath = \"/nonexistent\"\n[sqllog.input]\npaths = [\"/nonexistent/a\", \"s3://b/c\"]\n"
+            .parse()
+            .unwrap();
+        let keys: Vec<_> = check_paths(&table).into_iter().map(|i| i.key).collect();
+        assert_eq!(keys, ["sqllog.input.paths[0]"]);
+        let table: Table = "[[export.sink]]\npath = \"a.jsonl\"\n[[export.sink]]\nkind = \"stats\"\npaht = \"s.csv\"\n"
+            .parse()
+            .unwrap();
+        let (_, issues) = validate(&table);
+        assert_eq!(issues[0].key, "export.sink[1].paht");
+
+        // 出错的键被跳过，同一节中正确的键仍然生效
+        assert_eq!(root.sqllog.batch_size, 10);
+        assert_eq!(root.sqllog.thread_num, SqllogConfig::default().thread_num);
+    }
+}
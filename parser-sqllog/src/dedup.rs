@@ -0,0 +1,109 @@
+//! 按时间窗口去重：同一会话在 N 秒内重复提交的相同语句（客户端重试风暴）只保留第一条。
+//!
+//! 判重键为会话号与语句正文（语句标记、SQL 文本与绑定参数，不含 EXECTIME 等执行指标）的哈希；
+//! 与同一键上一次出现的间隔不超过窗口时视为重复，因此持续的重试会被整段折叠。
+//! 没有会话号的记录不参与去重。
+
+use std::collections::HashMap;
+
+use dm_database_parser::{parser::ParsedRecord, sql, ts_to_epoch_millis};
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+/// 内存中的判重键数超过该值时清理已过期的键
+const PRUNE_THRESHOLD: usize = 65536;
+
+/// 一条记录的判重键与时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DedupKey {
+    hash: u64,
+    ts_ms: i64,
+}
+
+impl DedupKey {
+    /// 计算记录的判重键；没有会话号或时间戳无法解析时返回 None
+    pub fn of(rec: &ParsedRecord<'_>) -> Option<Self> {
+        let sess = rec.sess?;
+        let ts_ms = ts_to_epoch_millis(rec.ts)?;
+        let mut h = Xxh3::new();
+        for part in [
+            sess,
+            sql::split_tag(&rec.body).0.unwrap_or_default(),
+            sql::sql_text(&rec.body),
+            sql::params_text(&rec.body).unwrap_or_default(),
+        ] {
+            h.update(part.as_bytes());
+            h.update(&[0]);
+        }
+        Some(Self {
+            hash: h.digest(),
+            ts_ms,
+        })
+    }
+}
+
+/// 去重状态：记录每个键最近一次出现的时间，需要按时间顺序依次传入记录
+#[derive(Debug)]
+pub struct Dedup {
+    window_ms: i64,
+    last_seen: HashMap<u64, i64>,
+    folded: u64,
+}
+
+impl Dedup {
+    pub fn new(window_ms: i64) -> Self {
+        Self {
+            window_ms,
+            last_seen: HashMap::new(),
+            folded: 0,
+        }
+    }
+
+    /// 判断记录是否为窗口内的重复；None（不参与去重）总是返回 false
+    pub fn is_duplicate(&mut self, key: Option<DedupKey>) -> bool {
+        let Some(key) = key else {
+            return false;
+        };
+        let previous = self.last_seen.insert(key.hash, key.ts_ms);
+        let duplicate = previous.is_some_and(|t| (key.ts_ms - t).abs() <= self.window_ms);
+        if duplicate {
+            self.folded += 1;
+        }
+        if self.last_seen.len() > PRUNE_THRESHOLD {
+            let horizon = key.ts_ms - self.window_ms;
+            self.last_seen.retain(|_, t| *t >= horizon);
+        }
+        duplicate
+    }
+
+    /// 已折叠的重复记录数
+    pub fn folded(&self) -> u64 {
+        self.folded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    #[test]
+    fn folds_retries_within_window() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:01.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)} EXECTIME: 7(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:02.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 2)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.
+2025-08-12 10:00:02.500 (EP[0] sess:0x9 thrd:2 user:A trxid:1 stmt:0x3 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 4.
+2025-08-12 10:00:03.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 5.
+2025-08-12 10:00:09.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 6.
+";
+        let mut dedup = Dedup::new(2000);
+        let mut kept = Vec::new();
+        parse_records_with(log, |rec| {
+            if !dedup.is_duplicate(DedupKey::of(&rec)) {
+                kept.push(rec.execute_id.unwrap());
+            }
+        });
+        assert_eq!(kept, [1, 3, 4, 6]);
+        assert_eq!(dedup.folded(), 2);
+    }
+}
@@ -1,6 +1,9 @@
+use crate::config::diagnostic::{self, Location};
+
 /// 定义日志相关的错误类型和结果类型
 pub type ConfigParseResult<T> = std::result::Result<T, ConfigParseError>;
 pub type LogResult<T> = std::result::Result<T, LogError>;
+pub type CommandResult<T> = std::result::Result<T, CommandError>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum LogError {
@@ -19,6 +22,13 @@ pub enum ConfigParseError {
     #[error("TOML 解析错误: {0}")]
     Parser(toml::de::Error),
 
+    #[error("{path}: TOML 语法错误: {}", .source.message())]
+    Syntax {
+        path: String,
+        location: Option<Box<Location>>,
+        source: toml::de::Error,
+    },
+
     #[error("缺少字段: {0}")]
     MissingField(String),
 
@@ -31,4 +41,228 @@ pub enum ConfigParseError {
 
     #[error("未知字段: {0}")]
     UnknownField(String),
+
+    #[error("配置有 {} 处错误:{}", .0.len(), .0.iter().map(|i| format!("\n  - {i}")).collect::<String>())]
+    Invalid(Vec<ConfigIssue>),
+
+    #[error("读取包含的配置文件 {path} 失败: {source}")]
+    Include {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("配置文件循环包含: {0}")]
+    IncludeCycle(String),
+
+    #[error("未定义的配置档: {name}（可选: {available}）")]
+    UnknownProfile { name: String, available: String },
+
+    #[error("配置版本 {found} 高于当前程序支持的版本 {supported}，请升级程序")]
+    UnsupportedVersion { found: u64, supported: u32 },
+
+    #[error("TOML 序列化错误: {0}")]
+    Serialize(toml::ser::Error),
+}
+
+/// 配置中的一处问题及其 TOML 键路径（如 `sqllog.thread_num`）
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{key}: {message}")]
+pub struct ConfigIssue {
+    pub key: String,
+    pub message: String,
+    /// 问题所在的配置文件位置，来自命令行、环境变量等其他来源时为 None
+    pub location: Option<Location>,
+}
+
+impl ConfigIssue {
+    pub fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+            location: None,
+        }
+    }
+}
+
+impl ConfigParseError {
+    /// 文件中的 TOML 语法错误，记录出错的位置
+    pub fn syntax(path: &str, source_text: &str, source: toml::de::Error) -> Self {
+        let location = source
+            .span()
+            .map(|span| Box::new(Location::from_span(path, source_text, span)));
+        Self::Syntax {
+            path: path.to_string(),
+            location,
+            source,
+        }
+    }
+
+    /// 渲染为带出错行与 `^` 标记的诊断，`color` 为 true 时使用 ANSI 颜色
+    pub fn render(&self, color: bool) -> String {
+        match self {
+            Self::Invalid(issues) => {
+                let mut out = format!("配置有 {} 处错误:", issues.len());
+                for issue in issues {
+                    out.push_str("\n\n");
+                    out.push_str(&diagnostic::render(
+                        &issue.to_string(),
+                        issue.location.as_ref(),
+                        color,
+                    ));
+                }
+                out
+            }
+            Self::Syntax { location, .. } => {
+                diagnostic::render(&self.to_string(), location.as_deref(), color)
+            }
+            _ => diagnostic::render(&self.to_string(), None, color),
+        }
+    }
+}
+
+/// 进程退出码，供调度系统区分可重试与不可重试的失败；取值参照 sysexits.h，
+/// 命令行参数错误时由 clap 以 2 退出
+pub mod exit_code {
+    /// 其他错误
+    pub const FAILURE: u8 = 1;
+    /// 错误记录超过阈值或遇到错误记录时中止（EX_DATAERR）
+    pub const DATA: u8 = 65;
+    /// 输入文件不存在或无法读取（EX_NOINPUT）
+    pub const INPUT: u8 = 66;
+    /// 导出结果写出失败（EX_IOERR）
+    pub const EXPORT: u8 = 74;
+    /// 配置错误（EX_CONFIG）
+    pub const CONFIG: u8 = 78;
+}
+
+/// 输入文件无法读取；包装在 `io::Error` 中返回，以便与写出错误区分
+#[derive(Debug, thiserror::Error)]
+#[error("读取输入 {path} 失败: {source}")]
+pub struct InputError {
+    pub path: String,
+    #[source]
+    pub source: std::io::Error,
+}
+
+impl InputError {
+    /// 保留原错误的 `ErrorKind`
+    pub fn wrap(path: impl std::fmt::Display, source: std::io::Error) -> std::io::Error {
+        std::io::Error::new(
+            source.kind(),
+            Self {
+                path: path.to_string(),
+                source,
+            },
+        )
+    }
+}
+
+/// 错误记录占比超过 `sqllog.max_error_rate`
+#[derive(Debug, thiserror::Error)]
+#[error("错误记录 {bad} 条，占全部 {records} 条记录的比例超过上限 {max}")]
+pub struct ErrorRateExceeded {
+    pub bad: u64,
+    pub records: u64,
+    pub max: f64,
+}
+
+/// 子命令执行过程中的错误
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("IO 错误: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("CSV 写入错误: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Log(#[from] LogError),
+
+    #[error("配置错误: {0}")]
+    Config(#[from] ConfigParseError),
+
+    #[error(transparent)]
+    Export(#[from] crate::exporter::error::ExportError),
+
+    #[error("目录监听错误: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[cfg(feature = "query")]
+    #[error("查询错误: {0}")]
+    Query(#[from] duckdb::Error),
+
+    #[cfg(feature = "xlsx")]
+    #[error("Excel 写入错误: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+
+    #[cfg(feature = "template")]
+    #[error("模板渲染错误: {0}")]
+    Template(#[from] tera::Error),
+}
+
+impl CommandError {
+    /// 该错误对应的进程退出码，见 [`exit_code`]
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Config(_) => exit_code::CONFIG,
+            Self::Export(_) | Self::Csv(_) => exit_code::EXPORT,
+            #[cfg(feature = "xlsx")]
+            Self::Xlsx(_) => exit_code::EXPORT,
+            #[cfg(feature = "template")]
+            Self::Template(_) => exit_code::EXPORT,
+            Self::Io(e) => match e.get_ref() {
+                Some(inner) if inner.is::<InputError>() => exit_code::INPUT,
+                Some(inner) if inner.is::<ErrorRateExceeded>() => exit_code::DATA,
+                // `on_error = "abort"` 时的错误记录
+                _ if e.kind() == std::io::ErrorKind::InvalidData => exit_code::DATA,
+                _ => exit_code::EXPORT,
+            },
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn exit_codes_by_failure_class() {
+        let input = InputError::wrap("a.log", io::Error::from(io::ErrorKind::NotFound));
+        let rate = io::Error::new(
+            io::ErrorKind::InvalidData,
+            ErrorRateExceeded {
+                bad: 5,
+                records: 10,
+                max: 0.1,
+            },
+        );
+        let cases = [
+            (CommandError::Io(input), exit_code::INPUT),
+            (CommandError::Io(rate), exit_code::DATA),
+            (
+                CommandError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "a.log: 缺少元数据",
+                )),
+                exit_code::DATA,
+            ),
+            (
+                CommandError::Io(io::Error::from(io::ErrorKind::PermissionDenied)),
+                exit_code::EXPORT,
+            ),
+            (
+                CommandError::Config(ConfigParseError::IncludeCycle("a.toml".to_string())),
+                exit_code::CONFIG,
+            ),
+            (
+                CommandError::Log(LogError::Init(String::new())),
+                exit_code::FAILURE,
+            ),
+        ];
+        for (err, code) in cases {
+            assert_eq!(err.exit_code(), code, "{err}");
+        }
+    }
 }
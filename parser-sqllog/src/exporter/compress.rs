@@ -0,0 +1,170 @@
+//! 输出文件的流式压缩与输入文件的流式解压（gzip / zstd）
+
+use std::{
+    io::{self, BufReader, Read, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// zstd 压缩级别
+const ZSTD_LEVEL: i32 = 3;
+
+/// 文件的压缩格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// 不压缩
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// 按路径的扩展名（`.gz` / `.zst`）推断压缩格式
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    /// 压缩文件的扩展名（含前导点），不压缩时为空
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Gzip => ".gz",
+            Self::Zstd => ".zst",
+        }
+    }
+
+    /// 在读取端解压 `inner`
+    pub fn decoder<R: Read + Send + 'static>(self, inner: R) -> io::Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            Self::None => Box::new(inner),
+            Self::Gzip => Box::new(MultiGzDecoder::new(BufReader::new(inner))),
+            Self::Zstd => Box::new(zstd::Decoder::new(inner)?),
+        })
+    }
+
+    /// 给路径补上压缩扩展名（已有时不重复添加）
+    pub fn apply_to(self, path: &str) -> String {
+        if path.ends_with(self.extension()) {
+            path.to_string()
+        } else {
+            format!("{path}{}", self.extension())
+        }
+    }
+}
+
+enum Kind<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+/// 边写边压缩的写入器；drop 时写出压缩流的结尾，也可调用 [`finish`](Self::finish) 以获得错误
+pub struct Encoder<W: Write> {
+    inner: Option<Kind<W>>,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(inner: W, compression: Compression) -> io::Result<Self> {
+        let kind = match compression {
+            Compression::None => Kind::Plain(inner),
+            Compression::Gzip => Kind::Gzip(GzEncoder::new(inner, flate2::Compression::default())),
+            Compression::Zstd => Kind::Zstd(zstd::Encoder::new(inner, ZSTD_LEVEL)?),
+        };
+        Ok(Self { inner: Some(kind) })
+    }
+
+    /// 写出压缩流的结尾并刷新底层写入器，之后不能再写入
+    pub fn finish(&mut self) -> io::Result<()> {
+        let mut w = match self.inner.take() {
+            None => return Ok(()),
+            Some(Kind::Plain(w)) => w,
+            Some(Kind::Gzip(e)) => e.finish()?,
+            Some(Kind::Zstd(e)) => e.finish()?,
+        };
+        w.flush()
+    }
+
+    fn get(&mut self) -> io::Result<&mut dyn Write> {
+        match &mut self.inner {
+            Some(Kind::Plain(w)) => Ok(w),
+            Some(Kind::Gzip(e)) => Ok(e),
+            Some(Kind::Zstd(e)) => Ok(e),
+            None => Err(io::Error::other("压缩流已结束")),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.get()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            None => Ok(()),
+            Some(_) => self.get()?.flush(),
+        }
+    }
+}
+
+impl<W: Write> Drop for Encoder<W> {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            warn!("写出压缩文件结尾失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compress(c: Compression, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut e = Encoder::new(&mut out, c).unwrap();
+        e.write_all(data).unwrap();
+        e.finish().unwrap();
+        drop(e);
+        out
+    }
+
+    #[test]
+    fn round_trips_gzip_and_zstd() {
+        let data = b"{\"user\":\"A\"}\n".repeat(100);
+
+        for c in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let mut decoded = Vec::new();
+            c.decoder(io::Cursor::new(compress(c, &data)))
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, data);
+        }
+        assert_eq!(compress(Compression::None, &data), data);
+    }
+
+    #[test]
+    fn infers_and_applies_extension() {
+        assert_eq!(
+            Compression::from_path(Path::new("out.csv.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("out.jsonl")),
+            Compression::None
+        );
+        assert_eq!(Compression::Zstd.apply_to("a.jsonl"), "a.jsonl.zst");
+        assert_eq!(Compression::Gzip.apply_to("a.jsonl.gz"), "a.jsonl.gz");
+        assert_eq!(Compression::None.apply_to("a.jsonl"), "a.jsonl");
+    }
+}
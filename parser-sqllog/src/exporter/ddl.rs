@@ -0,0 +1,283 @@
+//! 导入数据库的建表语句与 PostgreSQL 的 INSERT 脚本（`export --format sql`）：
+//! 以稳定的记录标识 `record_id` 为键，作业失败后重新导入同一批文件时不会产生重复行
+
+use std::io::{self, Write};
+
+use dm_database_parser::Sqllog;
+
+use crate::exporter::{
+    error::ExportError,
+    schema::{Field, FieldValue, Projection, columns},
+};
+
+/// `export --format sql` 写入的表名，与 `schema --format postgres` 的默认表名一致
+pub const SQL_TABLE: &str = "sqllog";
+
+/// 每条 INSERT 语句包含的最大行数
+const SQL_BATCH_ROWS: usize = 1000;
+
+/// 目标数据库
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// ClickHouse：ReplacingMergeTree 按 `record_id` 合并重复行
+    Clickhouse,
+    /// PostgreSQL：`record_id` 为主键，导入时 `ON CONFLICT DO NOTHING`
+    Postgres,
+}
+
+impl Dialect {
+    fn column_type(self, field: Field) -> &'static str {
+        match self {
+            Dialect::Clickhouse => match field {
+                Field::Ts => "DateTime64(3)",
+                Field::Ep => "UInt8",
+                Field::ThreadId | Field::Trxid | Field::ExecId => "Int64",
+                Field::RowCount => "UInt64",
+                Field::ExecTimeMs => "Float64",
+                Field::Truncated => "Bool",
+                Field::Instance => "Tuple(instance String, rotated_at Nullable(String))",
+                _ => "String",
+            },
+            Dialect::Postgres => match field {
+                Field::Ts => "timestamp(3)",
+                Field::Ep => "smallint",
+                Field::ThreadId | Field::Trxid | Field::ExecId | Field::RowCount => "bigint",
+                Field::ExecTimeMs => "double precision",
+                Field::Truncated => "boolean",
+                Field::Instance => "jsonb",
+                Field::RecordId => "text PRIMARY KEY",
+                _ => "text",
+            },
+        }
+    }
+}
+
+/// 生成建表语句及幂等导入的 INSERT 语句（作为注释附在末尾）；`fields` 为 None 时对应完整记录。
+///
+/// 去重依赖 `record_id`，选中的字段中不含 `record_id` 时返回错误。
+pub fn create_table(
+    dialect: Dialect,
+    table: &str,
+    fields: Option<&Projection>,
+) -> Result<String, ExportError> {
+    let columns = columns(fields);
+    if !columns.iter().any(|(_, f)| *f == Field::RecordId) {
+        return Err(ExportError::MissingRecordId);
+    }
+    let mut sql = format!("CREATE TABLE IF NOT EXISTS {table} (\n");
+    let defs: Vec<String> = columns
+        .iter()
+        // 列名加引号，避免与 user 等保留字冲突
+        .map(|(name, field)| format!("    \"{name}\" {}", dialect.column_type(*field)))
+        .collect();
+    sql.push_str(&defs.join(",\n"));
+    sql.push_str("\n)");
+    sql.push_str(&match dialect {
+        Dialect::Clickhouse => format!(
+            "\nENGINE = ReplacingMergeTree\nORDER BY record_id;\n\
+             -- 导入：INSERT INTO {table} FORMAT JSONEachRow，重复的 record_id 在合并时去除，查询时可加 FINAL\n"
+        ),
+        Dialect::Postgres => format!(
+            ";\n\
+             -- 导入：export --format sql 生成 INSERT INTO {SQL_TABLE} 语句，已存在的 record_id 被忽略；\n\
+             -- 或以 JSON 数组形式的一批记录作为 $1：\n\
+             -- INSERT INTO {table} SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1) ON CONFLICT (record_id) DO NOTHING;\n"
+        ),
+    });
+    Ok(sql)
+}
+
+/// PostgreSQL INSERT 脚本写入器：每 [`SQL_BATCH_ROWS`] 条记录写出一条
+/// `INSERT … ON CONFLICT (record_id) DO NOTHING` 语句，列与 `schema --format postgres` 的建表语句一致。
+/// 调用 [`into_inner`](Self::into_inner) 或 [`flush`](Self::flush) 时写出未满的一批
+pub struct SqlWriter<W: Write> {
+    inner: W,
+    fields: Projection,
+    /// `INSERT INTO … VALUES` 语句头
+    insert: String,
+    batch: String,
+    batch_rows: usize,
+    count: u64,
+    bytes: u64,
+}
+
+impl<W: Write> SqlWriter<W> {
+    /// 选中的字段中不含 `record_id` 时返回错误
+    pub fn new(inner: W, fields: Option<Projection>) -> Result<Self, ExportError> {
+        let columns = columns(fields.as_ref());
+        if !columns.iter().any(|(_, f)| *f == Field::RecordId) {
+            return Err(ExportError::MissingRecordId);
+        }
+        let names: Vec<String> = columns
+            .iter()
+            .map(|(name, _)| format!("\"{name}\""))
+            .collect();
+        Ok(Self {
+            inner,
+            fields: fields.unwrap_or_else(Projection::full_record),
+            insert: format!("INSERT INTO {SQL_TABLE} ({}) VALUES\n", names.join(", ")),
+            batch: String::new(),
+            batch_rows: 0,
+            count: 0,
+            bytes: 0,
+        })
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        if self.batch_rows == 0 {
+            self.batch.push_str(&self.insert);
+        } else {
+            self.batch.push_str(",\n");
+        }
+        self.batch.push('(');
+        for (i, value) in self.fields.values(log).iter().enumerate() {
+            if i > 0 {
+                self.batch.push_str(", ");
+            }
+            push_literal(&mut self.batch, value);
+        }
+        self.batch.push(')');
+        self.batch_rows += 1;
+        self.count += 1;
+        if self.batch_rows == SQL_BATCH_ROWS {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// 已写入的记录数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 已交给底层写入器的字节数；不含尚未写出的一批
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_batch()?;
+        self.inner.flush()
+    }
+
+    /// 写出最后一批并取回底层写入器
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.write_batch()?;
+        Ok(self.inner)
+    }
+
+    fn write_batch(&mut self) -> io::Result<()> {
+        if self.batch_rows == 0 {
+            return Ok(());
+        }
+        self.batch
+            .push_str("\nON CONFLICT (record_id) DO NOTHING;\n");
+        self.inner.write_all(self.batch.as_bytes())?;
+        self.bytes += self.batch.len() as u64;
+        self.batch.clear();
+        self.batch_rows = 0;
+        Ok(())
+    }
+}
+
+/// 按建表语句中的列类型写出字面量：文本加单引号（`''` 转义），实例为 jsonb 文本
+fn push_literal(out: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(s) => push_quoted(out, s),
+        FieldValue::Owned(s) => push_quoted(out, s),
+        FieldValue::Int(v) => out.push_str(&v.to_string()),
+        FieldValue::Float(v) if v.is_finite() => out.push_str(&v.to_string()),
+        FieldValue::Float(_) => out.push_str("NULL"),
+        FieldValue::Bool(v) => out.push_str(if *v { "TRUE" } else { "FALSE" }),
+        FieldValue::Instance(None) => out.push_str("NULL"),
+        FieldValue::Instance(Some(info)) => {
+            let json = serde_json::to_string(info).expect("实例信息总能序列化为 JSON");
+            push_quoted(out, &json);
+        }
+    }
+}
+
+fn push_quoted(out: &mut String, s: &str) {
+    out.push('\'');
+    out.push_str(&s.replace('\'', "''"));
+    out.push('\'');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_table_keys_on_record_id() {
+        let ch = create_table(Dialect::Clickhouse, "sqllog", None).unwrap();
+        assert!(ch.starts_with(
+            "CREATE TABLE IF NOT EXISTS sqllog (\n    \"sqllog_datetime\" DateTime64(3),"
+        ));
+        assert!(ch.contains("    \"record_id\" String,\n"));
+        assert!(ch.contains(")\nENGINE = ReplacingMergeTree\nORDER BY record_id;"));
+
+        let p: Projection = "ts,user,record_id".parse().unwrap();
+        let pg = create_table(Dialect::Postgres, "logs", Some(&p)).unwrap();
+        assert!(pg.starts_with(
+            "CREATE TABLE IF NOT EXISTS logs (\n    \"ts\" timestamp(3),\n    \"user\" text,\n    \"record_id\" text PRIMARY KEY\n);"
+        ));
+        assert!(pg.contains("ON CONFLICT (record_id) DO NOTHING"));
+
+        let p: Projection = "ts,user".parse().unwrap();
+        assert!(matches!(
+            create_table(Dialect::Postgres, "logs", Some(&p)),
+            Err(ExportError::MissingRecordId)
+        ));
+    }
+
+    #[test]
+    fn sql_writer_emits_idempotent_insert_batches() {
+        let p: Projection = "ts,user,row_count,truncated,instance,record_id"
+            .parse()
+            .unwrap();
+        let log = Sqllog {
+            sqllog_datetime: "2025-08-12 10:57:09.548".to_string(),
+            username: "O'NEIL".to_string(),
+            row_count: 5_000_000_000,
+            record_id: "00ff".to_string(),
+            ..Sqllog::new()
+        };
+        let mut w = SqlWriter::new(Vec::new(), Some(p)).unwrap();
+        w.write(&log).unwrap();
+        w.write(&Sqllog {
+            truncated: true,
+            ..log.clone()
+        })
+        .unwrap();
+        assert_eq!(w.bytes(), 0);
+        let out = String::from_utf8(w.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            out,
+            "INSERT INTO sqllog (\"ts\", \"user\", \"row_count\", \"truncated\", \"instance\", \"record_id\") VALUES\n\
+             ('2025-08-12 10:57:09.548', 'O''NEIL', 5000000000, FALSE, NULL, '00ff'),\n\
+             ('2025-08-12 10:57:09.548', 'O''NEIL', 5000000000, TRUE, NULL, '00ff')\n\
+             ON CONFLICT (record_id) DO NOTHING;\n"
+        );
+
+        let mut w = SqlWriter::new(Vec::new(), None).unwrap();
+        for _ in 0..SQL_BATCH_ROWS + 1 {
+            w.write(&log).unwrap();
+        }
+        let out = String::from_utf8(w.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            out.matches("INSERT INTO sqllog (\"sqllog_datetime\"")
+                .count(),
+            2
+        );
+        assert_eq!(
+            out.matches("ON CONFLICT (record_id) DO NOTHING;").count(),
+            2
+        );
+
+        let p: Projection = "ts,user".parse().unwrap();
+        assert!(matches!(
+            SqlWriter::new(Vec::new(), Some(p)),
+            Err(ExportError::MissingRecordId)
+        ));
+    }
+}
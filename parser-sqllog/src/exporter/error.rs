@@ -0,0 +1,21 @@
+/// 导出层的错误
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("未知的导出字段: {name}，可用字段: {available}")]
+    UnknownField { name: String, available: String },
+
+    #[error("至少需要选择一个导出字段")]
+    EmptyFields,
+
+    #[error("第 {index} 个导出目标（[[export.sink]]，从 0 开始）缺少 path")]
+    MissingSinkPath { index: usize },
+
+    #[error("建表与 INSERT 语句以 record_id 去重，选中的字段中必须包含 record_id")]
+    MissingRecordId,
+
+    #[error("导出目标 {kind} 需要启用 {feature} 特性")]
+    FeatureDisabled {
+        kind: &'static str,
+        feature: &'static str,
+    },
+}
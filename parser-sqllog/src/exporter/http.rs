@@ -0,0 +1,68 @@
+//! HTTP 导出目标：按批把 JSON Lines 以 POST 请求发送，
+//! 如 ClickHouse 的 `http://host:8123/?query=INSERT INTO t FORMAT JSONEachRow`
+
+use std::io;
+
+use dm_database_parser::Sqllog;
+
+use crate::exporter::{schema::Projection, sink::SinkReport};
+
+/// 按批 POST JSON Lines 的导出目标
+pub struct HttpSink {
+    url: String,
+    batch_size: usize,
+    fields: Option<Projection>,
+    buf: Vec<u8>,
+    pending: usize,
+    count: u64,
+}
+
+impl HttpSink {
+    pub fn new(url: &str, batch_size: usize, fields: Option<Projection>) -> Self {
+        Self {
+            url: url.to_string(),
+            batch_size: batch_size.max(1),
+            fields,
+            buf: Vec::new(),
+            pending: 0,
+            count: 0,
+        }
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        match &self.fields {
+            Some(fields) => serde_json::to_writer(&mut self.buf, &fields.view(log))?,
+            None => serde_json::to_writer(&mut self.buf, log)?,
+        }
+        self.buf.push(b'\n');
+        self.pending += 1;
+        if self.pending >= self.batch_size {
+            self.send()?;
+        }
+        Ok(())
+    }
+
+    /// 发送剩余的记录
+    pub fn finish(mut self) -> io::Result<SinkReport> {
+        self.send()?;
+        Ok(SinkReport {
+            target: self.url,
+            records: self.count,
+            files: 0,
+        })
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        ureq::post(&self.url)
+            .header("Content-Type", "application/x-ndjson")
+            .send(&self.buf[..])
+            .map_err(|e| io::Error::other(format!("发送到 {} 失败: {e}", self.url)))?;
+        self.count += self.pending as u64;
+        self.pending = 0;
+        self.buf.clear();
+        Ok(())
+    }
+}
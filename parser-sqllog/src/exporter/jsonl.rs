@@ -0,0 +1,106 @@
+//! 以 JSON Lines 格式（每行一个 [`Sqllog`] 对象）导出记录，以及读回完整记录的导出文件
+
+use std::io::{self, BufRead, Write};
+
+use dm_database_parser::Sqllog;
+
+use crate::exporter::schema::Projection;
+
+/// JSON Lines 写入器，序列化缓冲区在记录之间复用
+pub struct JsonlWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+    count: u64,
+    bytes: u64,
+    fields: Option<Projection>,
+}
+
+impl<W: Write> JsonlWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(1024),
+            count: 0,
+            bytes: 0,
+            fields: None,
+        }
+    }
+
+    /// 只输出选中的字段；未设置时输出完整的 [`Sqllog`] 对象
+    pub fn set_fields(mut self, fields: Option<Projection>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        self.buf.clear();
+        match &self.fields {
+            Some(fields) => serde_json::to_writer(&mut self.buf, &fields.view(log))?,
+            None => serde_json::to_writer(&mut self.buf, log)?,
+        }
+        self.buf.push(b'\n');
+        self.inner.write_all(&self.buf)?;
+        self.count += 1;
+        self.bytes += self.buf.len() as u64;
+        Ok(())
+    }
+
+    /// 已写入的记录数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 已写入的字节数（压缩前）
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// 读取未指定 `--fields` 导出的 JSON Lines 文件，逐行解析为 [`Sqllog`]，跳过空行
+pub struct JsonlReader<R: BufRead> {
+    inner: R,
+    line: String,
+    line_no: u64,
+}
+
+impl<R: BufRead> JsonlReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: String::new(),
+            line_no: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonlReader<R> {
+    type Item = io::Result<Sqllog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.inner.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_no += 1,
+                Err(e) => return Some(Err(e)),
+            }
+            if self.line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&self.line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("第 {} 行: {e}", self.line_no),
+                )
+            }));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! 已处理输入文件的清单：记录每个文件的大小、SHA-256、记录数与时间范围，
+//! 供后续运行跳过未变化的文件
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::exporter::schema::SCHEMA_VERSION;
+
+/// 单个输入文件的清单条目
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub records: u64,
+    pub first_ts: Option<String>,
+    pub last_ts: Option<String>,
+}
+
+impl ManifestEntry {
+    /// 计算文件的大小与 SHA-256，记录数与时间范围留空待填
+    pub fn for_file(path: &Path) -> io::Result<Self> {
+        let (size, sha256) = sha256_file(path)?;
+        Ok(Self {
+            path: path.display().to_string(),
+            size,
+            sha256,
+            ..Self::default()
+        })
+    }
+
+    /// 记录一条记录的时间戳（时间戳格式固定，可按字符串比较先后）
+    pub fn add(&mut self, ts: &str) {
+        self.records += 1;
+        if self.first_ts.as_deref().is_none_or(|f| ts < f) {
+            self.first_ts = Some(ts.to_string());
+        }
+        if self.last_ts.as_deref().is_none_or(|l| ts > l) {
+            self.last_ts = Some(ts.to_string());
+        }
+    }
+}
+
+/// 已处理文件清单，以 JSON 格式保存
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 生成导出文件时使用的记录结构版本，见 [`SCHEMA_VERSION`]；旧清单中没有该字段时为 0
+    #[serde(default)]
+    pub schema_version: u32,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// 以当前记录结构版本创建清单
+    pub fn new(files: Vec<ManifestEntry>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            files,
+        }
+    }
+
+    /// 读取清单文件；文件不存在时返回空清单
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut w, self)?;
+        w.write_all(b"\n")?;
+        w.flush()
+    }
+
+    /// 合并多份清单（如各主机分别导出的清单）：同一路径以后出现的条目为准，按路径排序
+    pub fn merge(manifests: impl IntoIterator<Item = Manifest>) -> Self {
+        let mut files: Vec<ManifestEntry> = Vec::new();
+        for entry in manifests.into_iter().flat_map(|m| m.files) {
+            match files.iter_mut().find(|e| e.path == entry.path) {
+                Some(existing) => *existing = entry,
+                None => files.push(entry),
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Self::new(files)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ManifestEntry> {
+        self.files.iter().find(|e| e.path == path)
+    }
+
+    /// 清单中是否有大小与 SHA-256 都相同的同名文件
+    pub fn is_unchanged(&self, current: &ManifestEntry) -> bool {
+        self.get(&current.path)
+            .is_some_and(|e| e.size == current.size && e.sha256 == current.sha256)
+    }
+}
+
+/// 流式计算文件的大小与 SHA-256（小写十六进制）
+pub fn sha256_file(path: &Path) -> io::Result<(u64, String)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 256 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    let hex = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    Ok((size, hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn manifest_detects_unchanged_files() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("a.log");
+        fs::write(&log, "abc").unwrap();
+
+        let mut entry = ManifestEntry::for_file(&log).unwrap();
+        assert_eq!(
+            entry.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        entry.add("2025-08-12 10:00:01.000");
+        entry.add("2025-08-12 10:00:00.000");
+        assert_eq!(entry.first_ts.as_deref(), Some("2025-08-12 10:00:00.000"));
+
+        let path = dir.path().join("manifest.json");
+        assert!(Manifest::load(&path).unwrap().files.is_empty());
+        Manifest::new(vec![entry.clone()]).save(&path).unwrap();
+        let loaded = Manifest::load(&path).unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        assert!(loaded.is_unchanged(&ManifestEntry::for_file(&log).unwrap()));
+
+        fs::write(&log, "abd").unwrap();
+        assert!(!loaded.is_unchanged(&ManifestEntry::for_file(&log).unwrap()));
+    }
+}
@@ -0,0 +1,124 @@
+//! 合并多次导出的 JSON Lines 结果：跨文件按时间戳全局排序，按稳定的记录标识去重，
+//! 用于汇总各主机分别运行得到的导出文件
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use dm_database_parser::Sqllog;
+
+use crate::{
+    exporter::{compress::Compression, jsonl::JsonlReader},
+    sort::ExternalSorter,
+};
+
+/// 合并的结果统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// 读入的记录数
+    pub read: u64,
+    /// 写出的记录数
+    pub written: u64,
+    /// 因记录标识重复而丢弃的记录数
+    pub duplicates: u64,
+}
+
+/// 打开一个导出文件，扩展名为 `.gz` / `.zst` 时解压
+fn open_records(path: &Path) -> io::Result<JsonlReader<BufReader<Box<dyn io::Read + Send>>>> {
+    let file = File::open(path)?;
+    let reader = Compression::from_path(path).decoder(file)?;
+    Ok(JsonlReader::new(BufReader::new(reader)))
+}
+
+/// 读取 `inputs` 中的全部记录，按时间戳排序后依次交给 `write`；
+/// `record_id` 相同的记录只保留第一条，没有 `record_id` 的记录全部保留。
+///
+/// 记录数超过 `run_size` 时溢写到 `tmp_dir` 下的临时文件再归并。
+pub fn merge_records(
+    inputs: &[PathBuf],
+    run_size: usize,
+    tmp_dir: &Path,
+    mut write: impl FnMut(Sqllog) -> io::Result<()>,
+) -> io::Result<MergeSummary> {
+    let mut summary = MergeSummary::default();
+    let mut sorter = ExternalSorter::new(run_size, tmp_dir);
+    for path in inputs {
+        for log in open_records(path)? {
+            let log =
+                log.map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+            summary.read += 1;
+            // 同一记录的时间戳相同，键中带上记录标识使重复的记录在排序后相邻
+            let key = format!("{}\0{}", log.sqllog_datetime, log.record_id);
+            sorter.push(key, log)?;
+        }
+    }
+    let mut last_id = String::new();
+    for log in sorter.finish()? {
+        let log = log?;
+        if !log.record_id.is_empty() && log.record_id == last_id {
+            summary.duplicates += 1;
+            continue;
+        }
+        last_id.clone_from(&log.record_id);
+        write(log)?;
+        summary.written += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::jsonl::JsonlWriter;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write_jsonl(path: &Path, logs: &[Sqllog]) {
+        let mut w = JsonlWriter::new(Vec::new());
+        for log in logs {
+            w.write(log).unwrap();
+        }
+        fs::write(path, w.into_inner()).unwrap();
+    }
+
+    #[test]
+    fn merges_in_time_order_and_drops_duplicate_ids() {
+        let dir = tempdir().unwrap();
+        let log = |ts: &str, id: &str, exec_id: i64| Sqllog {
+            sqllog_datetime: format!("2025-08-12 10:00:{ts}"),
+            record_id: id.to_string(),
+            execute_id: exec_id,
+            ..Sqllog::new()
+        };
+        let host1 = dir.path().join("host1.jsonl");
+        let host2 = dir.path().join("host2.jsonl");
+        write_jsonl(&host1, &[log("01.000", "a", 1), log("03.000", "b", 2)]);
+        write_jsonl(
+            &host2,
+            &[
+                log("00.000", "", 3),
+                log("01.000", "a", 1),
+                log("01.000", "", 4),
+                log("02.000", "c", 5),
+            ],
+        );
+
+        let mut ids = Vec::new();
+        let summary = merge_records(&[host1, host2], 2, dir.path(), |log| {
+            ids.push(log.execute_id);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(ids, [3, 4, 1, 5, 2]);
+        assert_eq!(
+            summary,
+            MergeSummary {
+                read: 6,
+                written: 5,
+                duplicates: 1,
+            }
+        );
+    }
+}
@@ -1 +1,20 @@
+pub mod avro;
+pub mod compress;
+pub mod ddl;
 pub mod error;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod jsonl;
+pub mod manifest;
+pub mod merge;
+pub mod otlp;
+pub mod protobuf;
+pub mod record;
+pub mod replay;
+pub mod rolling;
+pub mod schema;
+pub mod sink;
+#[cfg(feature = "template")]
+pub mod template;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
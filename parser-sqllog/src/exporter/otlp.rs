@@ -0,0 +1,310 @@
+//! 把语句执行转换为 OpenTelemetry span，以 OTLP/JSON 写出：每行一个 `ExportTraceServiceRequest`，
+//! 可由 OpenTelemetry Collector 的 `otlpjsonfile` 接收器读取，也可逐行 POST 到 Jaeger / Tempo 的
+//! `/v1/traces`，在追踪界面中浏览历史日志。
+//!
+//! 同一实例、同一会话的语句归入同一个 trace；span 标识取记录标识，重复导出时保持不变。
+
+use std::io::{self, Write};
+
+use dm_database_parser::{
+    FingerprintOptions, RecordCategory, fingerprint_with, parser::ParsedRecord, sql,
+    ts_to_epoch_millis,
+};
+use serde::Serialize;
+use xxhash_rust::xxh3::xxh3_128;
+
+use crate::{analysis::truncate_body, pipeline::Source};
+
+/// span 类型 `SPAN_KIND_CLIENT`：数据库调用按客户端 span 表示
+const SPAN_KIND_CLIENT: u8 = 3;
+
+/// 属性值，序列化为 OTLP/JSON 的 `AnyValue`（64 位整数按十进制字符串表示）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum AnyValue {
+    #[serde(rename = "stringValue")]
+    String(String),
+    #[serde(rename = "intValue")]
+    Int(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyValue {
+    pub key: &'static str,
+    pub value: AnyValue,
+}
+
+impl KeyValue {
+    fn string(key: &'static str, value: &str) -> Self {
+        Self {
+            key,
+            value: AnyValue::String(value.to_string()),
+        }
+    }
+
+    fn int(key: &'static str, value: u64) -> Self {
+        Self {
+            key,
+            value: AnyValue::Int(value.to_string()),
+        }
+    }
+}
+
+/// 一次语句执行对应的 span
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    /// 32 位十六进制，由实例名与会话地址计算
+    pub trace_id: String,
+    /// 16 位十六进制，即记录标识
+    pub span_id: String,
+    /// 语句的首个关键字（大写），如 `SELECT`
+    pub name: String,
+    pub kind: u8,
+    pub start_time_unix_nano: String,
+    pub end_time_unix_nano: String,
+    pub attributes: Vec<KeyValue>,
+}
+
+impl Span {
+    /// 由带执行耗时的语句记录构造 span；其余记录返回 None。
+    ///
+    /// 日志时间为服务器本地时间，`utc_offset_min` 为其相对 UTC 的偏移（分钟）；
+    /// `max_body_len` 限制 `db.statement` 的长度，0 表示不截断；
+    /// `dm.fingerprint` 按 `fingerprint` 中的规则计算
+    pub fn from_record(
+        src: &Source,
+        rec: &ParsedRecord<'_>,
+        utc_offset_min: i64,
+        max_body_len: usize,
+        fingerprint: &FingerprintOptions,
+    ) -> Option<Self> {
+        let exec_ms = rec.execute_time_ms?;
+        if sql::categorize(&rec.body) != RecordCategory::Statement {
+            return None;
+        }
+        let start_ms = ts_to_epoch_millis(rec.ts)? - utc_offset_min * 60_000;
+        let start_ns = u64::try_from(start_ms).ok()? * 1_000_000;
+        let end_ns = start_ns + exec_ms * 1_000_000;
+
+        let session = rec.sess.or(rec.thrd).unwrap_or_default();
+        let trace_key = format!("{}\0{}", src.instance, session);
+        let text = sql::sql_text(&rec.body);
+        let name = text
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .find(|w| !w.is_empty())
+            .unwrap_or("SQL")
+            .to_ascii_uppercase();
+        let mut statement = text.to_string();
+        truncate_body(&mut statement, max_body_len);
+
+        let mut attributes = vec![
+            KeyValue::string("db.system", "dameng"),
+            KeyValue::string("db.statement", &statement),
+            KeyValue::string("dm.fingerprint", &fingerprint_with(text, fingerprint).text),
+            KeyValue::string("dm.instance", &src.instance),
+            KeyValue::string("dm.sess", session),
+        ];
+        let optional = [
+            ("db.user", rec.user),
+            ("dm.appname", rec.appname),
+            ("dm.ep", rec.ep),
+            ("dm.trxid", rec.trxid),
+            ("client.address", rec.ip),
+        ];
+        for (key, value) in optional {
+            if let Some(v) = value.filter(|v| !v.is_empty()) {
+                attributes.push(KeyValue::string(key, v));
+            }
+        }
+        if let Some(rows) = rec.row_count {
+            attributes.push(KeyValue::int("dm.row_count", rows));
+        }
+        if let Some(id) = rec.execute_id {
+            attributes.push(KeyValue::int("dm.exec_id", id));
+        }
+
+        Some(Self {
+            trace_id: format!("{:032x}", xxh3_128(trace_key.as_bytes())),
+            span_id: src.record_id(rec),
+            name,
+            kind: SPAN_KIND_CLIENT,
+            start_time_unix_nano: start_ns.to_string(),
+            end_time_unix_nano: end_ns.to_string(),
+            attributes,
+        })
+    }
+}
+
+/// 解析相对 UTC 的偏移，如 `+08:00`、`-05:30`、`+8`、`Z`，返回分钟数
+pub fn parse_utc_offset(s: &str) -> Result<i64, String> {
+    let err = || format!("无效的 UTC 偏移 {s:?}，应为 +HH:MM、-HH:MM 或 Z");
+    if s.eq_ignore_ascii_case("z") {
+        return Ok(0);
+    }
+    let (sign, rest) = match s.as_bytes().first() {
+        Some(b'+') => (1, &s[1..]),
+        Some(b'-') => (-1, &s[1..]),
+        _ => return Err(err()),
+    };
+    let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+    let h: i64 = h.parse().map_err(|_| err())?;
+    let m: i64 = m.parse().map_err(|_| err())?;
+    if h > 14 || m >= 60 {
+        return Err(err());
+    }
+    Ok(sign * (h * 60 + m))
+}
+
+/// OTLP/JSON 写入器：累积 `batch_size` 个 span 后写出一行请求
+pub struct OtlpWriter<W: Write> {
+    inner: W,
+    service_name: String,
+    batch_size: usize,
+    batch: Vec<Span>,
+    count: u64,
+}
+
+impl<W: Write> OtlpWriter<W> {
+    pub fn new(inner: W, service_name: &str) -> Self {
+        Self {
+            inner,
+            service_name: service_name.to_string(),
+            batch_size: 1000,
+            batch: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// 每行请求包含的 span 数
+    pub fn set_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn write(&mut self, span: Span) -> io::Result<()> {
+        self.batch.push(span);
+        self.count += 1;
+        if self.batch.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// 已写入的 span 数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 写出剩余的 span 并刷新，返回底层写入器
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_batch()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_batch(&mut self) -> io::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let request = serde_json::json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [KeyValue::string("service.name", &self.service_name)],
+                },
+                "scopeSpans": [{
+                    "scope": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                    "spans": &self.batch,
+                }],
+            }],
+        });
+        serde_json::to_writer(&mut self.inner, &request)?;
+        self.inner.write_all(b"\n")?;
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_record;
+    use std::path::PathBuf;
+
+    #[test]
+    fn converts_statements_to_spans_grouped_by_session() {
+        let src = Source::new(PathBuf::from("dmsql_DM1_20250812_100000.log"), "");
+        let rec = |sess: &str, body: &str| {
+            format!(
+                "2025-08-12 10:00:00.000 (EP[0] sess:{sess} thrd:1 user:A trxid:1 stmt:0x2 appname:app) {body}"
+            )
+        };
+        let a = rec(
+            "0x1",
+            "[SEL] select * from t EXECTIME: 5(ms) ROWCOUNT: 2(rows) EXEC_ID: 7.",
+        );
+        let b = rec(
+            "0x1",
+            "[UPD] update t set a = 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 8.",
+        );
+        let c = rec(
+            "0x9",
+            "[SEL] select 1 from dual EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 9.",
+        );
+        let span = |text: &str| {
+            Span::from_record(
+                &src,
+                &parse_record(text),
+                8 * 60,
+                0,
+                &FingerprintOptions::DEFAULT,
+            )
+            .unwrap()
+        };
+        let (a, b, c) = (span(&a), span(&b), span(&c));
+
+        assert_eq!(a.name, "SELECT");
+        assert_eq!(b.name, "UPDATE");
+        assert_eq!(a.trace_id, b.trace_id);
+        assert_ne!(a.trace_id, c.trace_id);
+        assert_eq!(a.trace_id.len(), 32);
+        assert_eq!(a.span_id.len(), 16);
+        // 2025-08-12 10:00:00 +08:00 = 02:00:00 UTC
+        assert_eq!(a.start_time_unix_nano, "1754964000000000000");
+        assert_eq!(a.end_time_unix_nano, "1754964000005000000");
+        assert!(a.attributes.contains(&KeyValue::string("db.user", "A")));
+        assert!(a.attributes.contains(&KeyValue::int("dm.row_count", 2)));
+
+        let login = rec("0x1", "login success");
+        assert!(
+            Span::from_record(
+                &src,
+                &parse_record(&login),
+                0,
+                0,
+                &FingerprintOptions::DEFAULT
+            )
+            .is_none()
+        );
+
+        let mut w = OtlpWriter::new(Vec::new(), "dm").set_batch_size(2);
+        for s in [a, b, c] {
+            w.write(s).unwrap();
+        }
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let req: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        let spans = &req["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans.as_array().unwrap().len(), 2);
+        assert_eq!(spans[0]["kind"], 3);
+        assert_eq!(spans[0]["attributes"][0]["value"]["stringValue"], "dameng");
+
+        assert_eq!(parse_utc_offset("+08:00"), Ok(480));
+        assert_eq!(parse_utc_offset("-5:30"), Ok(-330));
+        assert_eq!(parse_utc_offset("Z"), Ok(0));
+        assert!(parse_utc_offset("08:00").is_err());
+    }
+}
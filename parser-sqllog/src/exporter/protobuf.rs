@@ -0,0 +1,123 @@
+//! 按 `proto/sqllog_record.proto` 把记录编码为 protobuf，每条消息带 varint 长度前缀
+
+use dm_database_parser::Sqllog;
+
+use crate::exporter::schema::{FieldValue, Projection};
+
+/// 随程序提供的 .proto 定义
+pub const PROTO: &str = include_str!("../../proto/sqllog_record.proto");
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_FIXED64: u32 = 1;
+const WIRE_LEN: u32 = 2;
+
+/// 把一条记录编码为带长度前缀的 `Record` 消息并追加到 `buf`。
+///
+/// 与 proto3 的默认行为一致，空字符串与 0 不写出。
+pub fn encode_record(buf: &mut Vec<u8>, log: &Sqllog, fields: &Projection) {
+    let start = buf.len();
+    for (field, value) in fields.fields().iter().zip(fields.values(log)) {
+        let number = field.number();
+        match value {
+            FieldValue::Str(s) => put_str(buf, number, s),
+            FieldValue::Owned(s) => put_str(buf, number, &s),
+            FieldValue::Int(0) => {}
+            FieldValue::Int(v) => {
+                put_key(buf, number, WIRE_VARINT);
+                // int64 的负数按补码编码为 10 字节 varint
+                put_varint(buf, v as u64);
+            }
+            FieldValue::Float(0.0) => {}
+            FieldValue::Float(v) => {
+                put_key(buf, number, WIRE_FIXED64);
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            FieldValue::Bool(false) => {}
+            FieldValue::Bool(true) => {
+                put_key(buf, number, WIRE_VARINT);
+                put_varint(buf, 1);
+            }
+            FieldValue::Instance(None) => {}
+            FieldValue::Instance(Some(info)) => {
+                let mut nested = Vec::new();
+                put_str(&mut nested, 1, &info.instance);
+                if let Some(rotated_at) = &info.rotated_at {
+                    // optional 字段即使为空也要写出，以区分“未设置”
+                    put_key(&mut nested, 2, WIRE_LEN);
+                    put_varint(&mut nested, rotated_at.len() as u64);
+                    nested.extend_from_slice(rotated_at.as_bytes());
+                }
+                put_key(buf, number, WIRE_LEN);
+                put_varint(buf, nested.len() as u64);
+                buf.extend_from_slice(&nested);
+            }
+        }
+    }
+    let mut prefix = Vec::with_capacity(5);
+    put_varint(&mut prefix, (buf.len() - start) as u64);
+    buf.splice(start..start, prefix);
+}
+
+fn put_str(buf: &mut Vec<u8>, number: u32, s: &str) {
+    if s.is_empty() {
+        return;
+    }
+    put_key(buf, number, WIRE_LEN);
+    put_varint(buf, s.len() as u64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn put_key(buf: &mut Vec<u8>, number: u32, wire: u32) {
+    put_varint(buf, u64::from(number << 3 | wire));
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::schema::Field;
+
+    #[test]
+    fn encodes_delimited_record() {
+        let log = Sqllog {
+            ep: 1,
+            username: "A".to_string(),
+            trxid: -1,
+            ..Sqllog::new()
+        };
+        let mut buf = Vec::new();
+        encode_record(&mut buf, &log, &"user,ep,row_count".parse().unwrap());
+        assert_eq!(buf, [5, 0x22, 1, b'A', 0x10, 1]);
+
+        buf.clear();
+        encode_record(&mut buf, &log, &"trxid".parse().unwrap());
+        assert_eq!(buf.len(), 1 + 1 + 10);
+        assert_eq!(buf[1], 5 << 3);
+
+        let log = Sqllog {
+            truncated: true,
+            ..log
+        };
+        buf.clear();
+        encode_record(&mut buf, &log, &"truncated".parse().unwrap());
+        // 字段号 19 的键超过 7 位，编码为两字节 varint
+        assert_eq!(buf, [3, 0x98, 0x01, 1]);
+    }
+
+    #[test]
+    fn proto_matches_field_numbers() {
+        for field in Field::ALL {
+            let decl = format!(" {} = {};", field.name(), field.number());
+            assert!(PROTO.contains(&decl), ".proto 缺少字段声明:{decl}");
+        }
+        // 行数可能超过 u32 范围，与 RecordMetrics 一致使用 64 位
+        assert!(PROTO.contains("uint64 row_count = 12;"));
+    }
+}
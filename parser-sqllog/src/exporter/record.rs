@@ -0,0 +1,428 @@
+//! 按所选格式（JSON Lines / CSV / MessagePack / protobuf / Avro / SQL）写出导出记录，以及按 EP 拆分输出
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use dm_database_parser::Sqllog;
+use serde::{Deserialize, Serialize};
+
+use crate::exporter::{
+    avro::AvroWriter,
+    compress::Compression,
+    ddl::SqlWriter,
+    jsonl::JsonlWriter,
+    protobuf,
+    rolling::{RollPolicy, RollingWriter},
+    schema::Projection,
+};
+
+/// 按 EP 拆分时每个 EP 目录下的文件名主干，扩展名取决于输出格式
+pub const SPLIT_FILE_STEM: &str = "records";
+
+/// 导出记录的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    /// 每行一个 JSON 对象
+    #[default]
+    Jsonl,
+    /// 带表头的 CSV
+    Csv,
+    /// 连续的 MessagePack map，键名与 JSON Lines 相同
+    Msgpack,
+    /// 带 varint 长度前缀的 protobuf 消息，定义见 `schema --format proto`
+    Protobuf,
+    /// Avro 对象容器文件，schema 见 `schema --format avro`
+    Avro,
+    /// PostgreSQL 的 INSERT 脚本，重复导入时忽略已存在的 record_id，建表语句见 `schema --format postgres`
+    Sql,
+}
+
+impl RecordFormat {
+    /// 输出文件的扩展名（不含前导点）
+    pub fn extension(self) -> &'static str {
+        match self {
+            RecordFormat::Jsonl => "jsonl",
+            RecordFormat::Csv => "csv",
+            RecordFormat::Msgpack => "msgpack",
+            RecordFormat::Protobuf => "pb",
+            RecordFormat::Avro => "avro",
+            RecordFormat::Sql => "sql",
+        }
+    }
+}
+
+/// 按 [`RecordFormat`] 写出记录的写入器
+pub enum RecordWriter<W: Write> {
+    Jsonl(JsonlWriter<W>),
+    // csv::Writer 自带缓冲区，装箱以免撑大整个枚举
+    Csv(Box<CsvWriter<W>>),
+    Binary(BinaryWriter<W>),
+    Avro(Box<AvroWriter<W>>),
+    Sql(SqlWriter<W>),
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// `fields` 为 None 时 JSON Lines 输出完整的 [`Sqllog`] 对象，CSV 输出全部字段
+    pub fn new(inner: W, format: RecordFormat, fields: Option<&Projection>) -> io::Result<Self> {
+        Ok(match format {
+            RecordFormat::Jsonl => {
+                RecordWriter::Jsonl(JsonlWriter::new(inner).set_fields(fields.cloned()))
+            }
+            RecordFormat::Csv => {
+                let fields = fields.cloned().unwrap_or_default();
+                RecordWriter::Csv(Box::new(CsvWriter::new(inner, fields)?))
+            }
+            RecordFormat::Msgpack => RecordWriter::Binary(BinaryWriter::new(
+                inner,
+                BinaryEncoding::Msgpack,
+                fields.cloned(),
+            )),
+            RecordFormat::Protobuf => RecordWriter::Binary(BinaryWriter::new(
+                inner,
+                BinaryEncoding::Protobuf,
+                fields.cloned(),
+            )),
+            RecordFormat::Avro => {
+                RecordWriter::Avro(Box::new(AvroWriter::new(inner, fields.cloned())?))
+            }
+            RecordFormat::Sql => RecordWriter::Sql(
+                SqlWriter::new(inner, fields.cloned())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            ),
+        })
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        match self {
+            RecordWriter::Jsonl(w) => w.write(log),
+            RecordWriter::Csv(w) => w.write(log),
+            RecordWriter::Binary(w) => w.write(log),
+            RecordWriter::Avro(w) => w.write(log),
+            RecordWriter::Sql(w) => w.write(log),
+        }
+    }
+
+    /// 已写入的记录数
+    pub fn count(&self) -> u64 {
+        match self {
+            RecordWriter::Jsonl(w) => w.count(),
+            RecordWriter::Csv(w) => w.count(),
+            RecordWriter::Binary(w) => w.count,
+            RecordWriter::Avro(w) => w.count(),
+            RecordWriter::Sql(w) => w.count(),
+        }
+    }
+
+    /// 已写入的字节数（压缩前）
+    pub fn bytes(&self) -> u64 {
+        match self {
+            RecordWriter::Jsonl(w) => w.bytes(),
+            RecordWriter::Csv(w) => w.bytes(),
+            RecordWriter::Binary(w) => w.bytes,
+            RecordWriter::Avro(w) => w.bytes(),
+            RecordWriter::Sql(w) => w.bytes(),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            RecordWriter::Jsonl(w) => w.flush(),
+            RecordWriter::Csv(w) => w.flush(),
+            RecordWriter::Binary(w) => w.inner.flush(),
+            RecordWriter::Avro(w) => w.flush(),
+            RecordWriter::Sql(w) => w.flush(),
+        }
+    }
+
+    /// 刷新缓冲区并取回底层写入器
+    pub fn into_inner(self) -> io::Result<W> {
+        match self {
+            RecordWriter::Jsonl(w) => Ok(w.into_inner()),
+            RecordWriter::Csv(w) => w.into_inner(),
+            RecordWriter::Binary(w) => Ok(w.inner),
+            RecordWriter::Avro(w) => w.into_inner(),
+            RecordWriter::Sql(w) => w.into_inner(),
+        }
+    }
+}
+
+/// 二进制编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryEncoding {
+    Msgpack,
+    Protobuf,
+}
+
+/// MessagePack / protobuf 写入器，编码缓冲区在记录之间复用
+pub struct BinaryWriter<W: Write> {
+    inner: W,
+    encoding: BinaryEncoding,
+    fields: Option<Projection>,
+    buf: Vec<u8>,
+    count: u64,
+    bytes: u64,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    fn new(inner: W, encoding: BinaryEncoding, fields: Option<Projection>) -> Self {
+        // protobuf 总是按字段编码，未选择字段时输出完整记录中的字段
+        let fields = match encoding {
+            BinaryEncoding::Protobuf => Some(fields.unwrap_or_else(Projection::full_record)),
+            BinaryEncoding::Msgpack => fields,
+        };
+        Self {
+            inner,
+            encoding,
+            fields,
+            buf: Vec::with_capacity(1024),
+            count: 0,
+            bytes: 0,
+        }
+    }
+
+    fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        self.buf.clear();
+        match (self.encoding, &self.fields) {
+            (BinaryEncoding::Msgpack, Some(fields)) => {
+                rmp_serde::encode::write_named(&mut self.buf, &fields.view(log))
+                    .map_err(io::Error::other)?
+            }
+            (BinaryEncoding::Msgpack, None) => {
+                rmp_serde::encode::write_named(&mut self.buf, log).map_err(io::Error::other)?
+            }
+            (BinaryEncoding::Protobuf, fields) => protobuf::encode_record(
+                &mut self.buf,
+                log,
+                fields.as_ref().expect("protobuf 写入器总是带有字段列表"),
+            ),
+        }
+        self.inner.write_all(&self.buf)?;
+        self.count += 1;
+        self.bytes += self.buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// CSV 写入器，创建时写出表头
+pub struct CsvWriter<W: Write> {
+    inner: csv::Writer<Counting<W>>,
+    fields: Projection,
+    count: u64,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(inner: W, fields: Projection) -> io::Result<Self> {
+        let mut inner = csv::Writer::from_writer(Counting { inner, bytes: 0 });
+        inner.write_record(fields.header())?;
+        Ok(Self {
+            inner,
+            fields,
+            count: 0,
+        })
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        let values = self.fields.values(log);
+        self.inner
+            .write_record(values.iter().map(|v| v.to_string()))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已写入的记录数（不含表头）
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 已交给底层写入器的字节数；不含 CSV 内部缓冲区中尚未写出的部分
+    pub fn bytes(&self) -> u64 {
+        self.inner.get_ref().bytes
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> io::Result<W> {
+        self.inner
+            .into_inner()
+            .map(|c| c.inner)
+            .map_err(|e| e.into_error())
+    }
+}
+
+/// 统计写出字节数的写入器
+struct Counting<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for Counting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 按 EP 拆分的写入器：EP N 的记录写入 `<dir>/epN/records.jsonl`，目录在首条记录到达时创建
+pub struct EpSplitWriter {
+    dir: PathBuf,
+    compression: Compression,
+    policy: RollPolicy,
+    format: RecordFormat,
+    fields: Option<Projection>,
+    writers: BTreeMap<u8, RollingWriter>,
+}
+
+impl EpSplitWriter {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            compression: Compression::None,
+            policy: RollPolicy::default(),
+            format: RecordFormat::Jsonl,
+            fields: None,
+            writers: BTreeMap::new(),
+        }
+    }
+
+    /// 设置输出文件的压缩格式，文件名相应加上 `.gz` / `.zst` 扩展名
+    pub fn set_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// 设置每个 EP 输出文件的滚动条件，滚动后的文件名为 `records-0001.jsonl` 等
+    pub fn set_roll_policy(mut self, policy: RollPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 设置输出格式与选中的字段
+    pub fn set_format(mut self, format: RecordFormat, fields: Option<Projection>) -> Self {
+        self.format = format;
+        self.fields = fields;
+        self
+    }
+
+    /// EP `ep` 的输出文件路径
+    pub fn path_for(&self, ep: u8) -> PathBuf {
+        self.dir
+            .join(format!("ep{ep}"))
+            .join(format!("{SPLIT_FILE_STEM}.{}", self.format.extension()))
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        let w = match self.writers.get_mut(&log.ep) {
+            Some(w) => w,
+            None => {
+                let w = RollingWriter::new(self.path_for(log.ep), self.compression, self.policy)
+                    .set_format(self.format, self.fields.clone());
+                self.writers.entry(log.ep).or_insert(w)
+            }
+        };
+        w.write(log)
+    }
+
+    /// 写完并关闭所有文件，返回各 EP 写入的记录数
+    pub fn finish(self) -> io::Result<BTreeMap<u8, u64>> {
+        let mut counts = BTreeMap::new();
+        for (ep, w) in self.writers {
+            let files = w.finish()?;
+            counts.insert(ep, files.iter().map(|(_, n)| n).sum());
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, fs::File};
+    use tempfile::tempdir;
+
+    fn log(ep: u8, user: &str) -> Sqllog {
+        Sqllog {
+            ep,
+            username: user.to_string(),
+            ..Sqllog::new()
+        }
+    }
+
+    #[test]
+    fn ep_split_writer_writes_one_file_per_ep() {
+        let dir = tempdir().unwrap();
+        let mut w = EpSplitWriter::new(dir.path());
+        for (ep, user) in [(0, "A"), (1, "B"), (0, "C")] {
+            w.write(&log(ep, user)).unwrap();
+        }
+        let counts = w.finish().unwrap();
+        assert_eq!(counts, BTreeMap::from([(0, 2), (1, 1)]));
+
+        let ep0 = fs::read_to_string(dir.path().join("ep0").join("records.jsonl")).unwrap();
+        let users: Vec<String> = ep0
+            .lines()
+            .map(|l| serde_json::from_str::<Sqllog>(l).unwrap().username)
+            .collect();
+        assert_eq!(users, ["A", "C"]);
+        assert!(dir.path().join("ep1").join("records.jsonl").is_file());
+    }
+
+    #[test]
+    fn ep_split_writer_compresses_files() {
+        let dir = tempdir().unwrap();
+        let mut w = EpSplitWriter::new(dir.path()).set_compression(Compression::Zstd);
+        w.write(&log(2, "A")).unwrap();
+        w.finish().unwrap();
+
+        let path = dir.path().join("ep2").join("records.jsonl.zst");
+        let data = zstd::decode_all(File::open(path).unwrap()).unwrap();
+        let rec: Sqllog = serde_json::from_slice(&data).unwrap();
+        assert_eq!(rec.username, "A");
+    }
+
+    #[test]
+    fn writes_selected_fields() {
+        let fields: Projection = "user,ep".parse().unwrap();
+
+        let mut out = Vec::new();
+        let mut w = RecordWriter::new(&mut out, RecordFormat::Csv, Some(&fields)).unwrap();
+        w.write(&log(1, "A")).unwrap();
+        w.into_inner().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "user,ep\nA,1\n");
+
+        let mut out = Vec::new();
+        let mut w = RecordWriter::new(&mut out, RecordFormat::Jsonl, Some(&fields)).unwrap();
+        w.write(&log(1, "A")).unwrap();
+        assert_eq!(w.count(), 1);
+        drop(w);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"user\":\"A\",\"ep\":1}\n"
+        );
+    }
+
+    #[test]
+    fn writes_msgpack_maps() {
+        let mut out = Vec::new();
+        let mut w = RecordWriter::new(&mut out, RecordFormat::Msgpack, None).unwrap();
+        w.write(&log(1, "A")).unwrap();
+        w.write(&log(2, "B")).unwrap();
+        drop(w);
+
+        let mut rd = &out[..];
+        let first: Sqllog = rmp_serde::from_read(&mut rd).unwrap();
+        let second: Sqllog = rmp_serde::from_read(&mut rd).unwrap();
+        assert_eq!((first.ep, second.username.as_str()), (1, "B"));
+    }
+}
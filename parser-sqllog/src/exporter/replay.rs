@@ -0,0 +1,357 @@
+//! 生成回放脚本：按日志顺序把执行过的语句写成可由 disql 执行的 SQL 脚本，
+//! 预编译语句的绑定参数代入为字面量。
+//!
+//! 可选地保留原始的到达节奏：在语句之间插入 `DBMS_LOCK.SLEEP`（需要已创建 DBMS_LOCK 系统包），
+//! 或另外输出调度文件，记录每条语句相对第一条语句的时间偏移与在脚本中的行号，供回放工具按时发送。
+
+use std::io::{self, Write};
+
+use dm_database_parser::{RecordCategory, parser::ParsedRecord, sql, ts_to_epoch_millis};
+use serde::Serialize;
+
+/// 回放脚本中的一条语句
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayStatement {
+    pub ts: String,
+    pub ts_ms: i64,
+    pub sess: String,
+    pub user: String,
+    /// 已代入绑定参数的 SQL
+    pub sql: String,
+    /// 绑定参数无法代入时保留的参数块
+    pub unbound_params: Option<String>,
+}
+
+impl ReplayStatement {
+    /// 由带语句文本的语句或事务控制记录构造；只有执行指标的记录与其他消息返回 None
+    pub fn from_record(rec: &ParsedRecord<'_>) -> Option<Self> {
+        if !matches!(
+            sql::categorize(&rec.body),
+            RecordCategory::Statement | RecordCategory::Transaction
+        ) {
+            return None;
+        }
+        let text = sql::sql_text(&rec.body);
+        if text.is_empty() {
+            return None;
+        }
+        let ts_ms = ts_to_epoch_millis(rec.ts)?;
+        let (sql, unbound_params) = match sql::params_text(&rec.body) {
+            Some(params) => match bind_params(text, params) {
+                Some(bound) => (bound, None),
+                None => (text.to_string(), Some(params.to_string())),
+            },
+            None => (text.to_string(), None),
+        };
+        Some(Self {
+            ts: rec.ts.to_string(),
+            ts_ms,
+            sess: rec.sess.unwrap_or_default().to_string(),
+            user: rec.user.unwrap_or_default().to_string(),
+            sql,
+            unbound_params,
+        })
+    }
+}
+
+/// 解析参数块 `PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1), (1, VARCHAR, 'a')}` 中各参数的值，
+/// 非数值、非 NULL 且未加引号的值加上单引号
+fn parse_params(params: &str) -> Option<Vec<String>> {
+    let body = params.split_once("={")?.1.strip_suffix('}')?;
+    let mut values = Vec::new();
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        rest = rest.strip_prefix('(')?;
+        // 序号与类型
+        let (_, after_seq) = rest.split_once(',')?;
+        let (_, after_type) = after_seq.split_once(',')?;
+        let data = after_type.trim_start();
+        let (value, tail) = if let Some(quoted) = data.strip_prefix('\'') {
+            // 引号内的 '' 为转义的单引号
+            let mut end = None;
+            let bytes = quoted.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() {
+                if bytes[i] == b'\'' {
+                    if bytes.get(i + 1) == Some(&b'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    end = Some(i);
+                    break;
+                }
+                i += 1;
+            }
+            let end = end?;
+            (data[..end + 2].to_string(), &quoted[end + 1..])
+        } else {
+            let end = data.find(')')?;
+            let raw = data[..end].trim();
+            let literal = if raw.eq_ignore_ascii_case("null") || raw.parse::<f64>().is_ok() {
+                raw.to_string()
+            } else {
+                format!("'{}'", raw.replace('\'', "''"))
+            };
+            (literal, &data[end..])
+        };
+        values.push(value);
+        rest = tail.trim_start().strip_prefix(')')?.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Some(values)
+}
+
+/// 把 SQL 中引号外的 `?` 依次替换为绑定参数的值；参数个数不一致或无法解析时返回 None
+pub fn bind_params(sql: &str, params: &str) -> Option<String> {
+    let values = parse_params(params)?;
+    let mut values = values.iter();
+    let mut out = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    for c in sql.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '?') => {
+                out.push_str(values.next()?);
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    values.next().is_none().then_some(out)
+}
+
+/// 以 BEGIN、DECLARE 或 CREATE [OR REPLACE] PROCEDURE/FUNCTION/TRIGGER/PACKAGE 开头、
+/// 以独立的单词 END（可带块名）结尾的语句视为 PL/SQL 块
+fn is_plsql_block(sql: &str) -> bool {
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(4)
+        .map(str::to_ascii_uppercase)
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let starts_block = match words[..] {
+        ["BEGIN", ..] | ["DECLARE", ..] => true,
+        ["CREATE", "OR", "REPLACE", kind, ..] | ["CREATE", kind, ..] => {
+            matches!(kind, "PROCEDURE" | "FUNCTION" | "TRIGGER" | "PACKAGE")
+        }
+        _ => false,
+    };
+    if !starts_block {
+        return false;
+    }
+    // 去掉结尾可选的块名，如 `END p_name`
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | '#' | '"');
+    let mut rest = sql;
+    for _ in 0..2 {
+        let (head, tail) = rest.split_at(rest.trim_end_matches(is_word).len());
+        if tail.eq_ignore_ascii_case("end") {
+            return true;
+        }
+        rest = head.trim_end();
+    }
+    false
+}
+
+/// 调度文件中的一项
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScheduleEntry {
+    /// 语句序号，从 1 开始
+    pub seq: u64,
+    /// 语句在脚本中的起始行号，从 1 开始
+    pub line: u64,
+    /// 相对第一条语句的时间偏移（毫秒，已按回放速度换算）
+    pub offset_ms: i64,
+    pub ts: String,
+    pub sess: String,
+    pub user: String,
+}
+
+/// 回放脚本写入器
+pub struct ReplayWriter<W: Write> {
+    inner: W,
+    speed: f64,
+    sleep: bool,
+    min_sleep_ms: u64,
+    line: u64,
+    first_ms: Option<i64>,
+    last_offset_ms: i64,
+    schedule: Vec<ScheduleEntry>,
+    count: u64,
+}
+
+impl<W: Write> ReplayWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            speed: 1.0,
+            sleep: false,
+            min_sleep_ms: 10,
+            line: 1,
+            first_ms: None,
+            last_offset_ms: 0,
+            schedule: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// 回放速度倍数，2 表示以两倍速回放（间隔减半）
+    pub fn set_speed(mut self, speed: f64) -> Self {
+        self.speed = if speed > 0.0 { speed } else { 1.0 };
+        self
+    }
+
+    /// 是否在语句之间插入 `DBMS_LOCK.SLEEP` 以保留原始间隔
+    pub fn set_sleep(mut self, sleep: bool) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// 短于该时长（毫秒）的间隔不插入等待
+    pub fn set_min_sleep_ms(mut self, min_sleep_ms: u64) -> Self {
+        self.min_sleep_ms = min_sleep_ms;
+        self
+    }
+
+    pub fn write(&mut self, stmt: &ReplayStatement) -> io::Result<()> {
+        let first = *self.first_ms.get_or_insert(stmt.ts_ms);
+        let offset_ms = ((stmt.ts_ms - first) as f64 / self.speed).round() as i64;
+        // 日志跨文件或乱序时不倒退
+        let offset_ms = offset_ms.max(self.last_offset_ms);
+        let gap = offset_ms - self.last_offset_ms;
+        if self.sleep && gap > 0 && gap as u64 >= self.min_sleep_ms {
+            self.write_lines(&format!(
+                "CALL DBMS_LOCK.SLEEP({}.{:03});\n",
+                gap / 1000,
+                gap % 1000
+            ))?;
+        }
+        self.last_offset_ms = offset_ms;
+        self.count += 1;
+
+        self.write_lines(&format!(
+            "-- {} sess:{} user:{}\n",
+            stmt.ts, stmt.sess, stmt.user
+        ))?;
+        if let Some(params) = &stmt.unbound_params {
+            self.write_lines(&format!(
+                "-- 未能代入绑定参数: {}\n",
+                params.replace('\n', " ")
+            ))?;
+        }
+        self.schedule.push(ScheduleEntry {
+            seq: self.count,
+            line: self.line,
+            offset_ms,
+            ts: stmt.ts.clone(),
+            sess: stmt.sess.clone(),
+            user: stmt.user.clone(),
+        });
+        let sql = stmt.sql.trim().trim_end_matches(';').trim_end();
+        // PL/SQL 块以 END; 结尾，另起一行 `/` 执行
+        if is_plsql_block(sql) {
+            self.write_lines(&format!("{sql};\n/\n"))
+        } else {
+            self.write_lines(&format!("{sql};\n"))
+        }
+    }
+
+    fn write_lines(&mut self, text: &str) -> io::Result<()> {
+        self.line += text.matches('\n').count() as u64;
+        self.inner.write_all(text.as_bytes())
+    }
+
+    /// 已写入的语句数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 各语句的调度信息
+    pub fn schedule(&self) -> &[ScheduleEntry] {
+        &self.schedule
+    }
+
+    /// 刷新并返回底层写入器
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser::parser::parse_records_with;
+
+    #[test]
+    fn writes_paced_script_with_bound_params_and_schedule() {
+        let log = "2025-08-12 10:00:00.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [INS] insert into t values(?, ?, '?') PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1), (1, VARCHAR, 'it''s')} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.
+2025-08-12 10:00:00.005 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select * from t where id = ? PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1), (1, INT, 2)} EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.
+2025-08-12 10:00:02.500 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [ORA] commit EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 3.
+2025-08-12 10:00:03.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 4.
+2025-08-12 10:00:04.000 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) login success
+";
+        let mut stmts = Vec::new();
+        parse_records_with(log, |rec| stmts.extend(ReplayStatement::from_record(&rec)));
+        assert_eq!(stmts.len(), 3);
+        assert_eq!(stmts[0].sql, "insert into t values(1, 'it''s', '?')");
+        assert!(stmts[1].unbound_params.is_some());
+
+        let mut w = ReplayWriter::new(Vec::new()).set_sleep(true).set_speed(2.0);
+        for s in &stmts {
+            w.write(s).unwrap();
+        }
+        let schedule = w.schedule().to_vec();
+        let script = String::from_utf8(w.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(lines[1], "insert into t values(1, 'it''s', '?');");
+        assert!(lines[2].starts_with("-- 2025-08-12 10:00:00.005"));
+        assert_eq!(lines[4], "select * from t where id = ?;");
+        // 按两倍速回放：5 ms 的间隔缩为 3 ms，不插入等待；之后的间隔缩为 1247 ms
+        assert_eq!(lines[5], "CALL DBMS_LOCK.SLEEP(1.247);");
+        assert_eq!(lines[7], "commit;");
+
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(schedule[2].seq, 3);
+        assert_eq!(schedule[2].line, 8);
+        assert_eq!(schedule[2].offset_ms, 1250);
+        assert_eq!(lines[schedule[1].line as usize - 1], lines[4]);
+
+        let mut w = ReplayWriter::new(Vec::new());
+        for sql in [
+            "select * from t order by weekend",
+            "begin p(1); end",
+            "create or replace procedure p as begin null; end p",
+        ] {
+            w.write(&ReplayStatement {
+                sql: sql.to_string(),
+                ..stmts[0].clone()
+            })
+            .unwrap();
+        }
+        let script = String::from_utf8(w.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(
+            lines[1..3],
+            [
+                "select * from t order by weekend;",
+                "-- 2025-08-12 10:00:00.000 sess:0x1 user:A"
+            ]
+        );
+        assert_eq!(lines[3..5], ["begin p(1); end;", "/"]);
+        assert_eq!(
+            lines[6..8],
+            ["create or replace procedure p as begin null; end p;", "/"]
+        );
+        assert!(!is_plsql_block("update t set c = 1 where c = legend"));
+
+        assert_eq!(
+            bind_params(
+                "begin p(?); end",
+                "PARAMS(SEQNO, TYPE, DATA)={(0, DATE, 2025-08-12)}"
+            ),
+            Some("begin p('2025-08-12'); end".to_string())
+        );
+    }
+}
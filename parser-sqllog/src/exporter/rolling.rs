@@ -0,0 +1,216 @@
+//! 按大小或时间滚动的导出文件：`records.jsonl` 依次滚动为 `records-0001.jsonl`、`records-0002.jsonl`……
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use dm_database_parser::Sqllog;
+
+use crate::{
+    config::export::ExportConfig,
+    exporter::{
+        compress::{Compression, Encoder},
+        record::{RecordFormat, RecordWriter},
+        schema::Projection,
+    },
+};
+
+/// 滚动条件，两项都为空时不滚动
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RollPolicy {
+    /// 单个文件写入的最大字节数（压缩前），0 表示不限制
+    pub max_size: u64,
+    /// 单个文件的最长写入时间
+    pub every: Option<Duration>,
+}
+
+impl RollPolicy {
+    pub fn from_config(cfg: &ExportConfig) -> Self {
+        Self {
+            max_size: cfg.max_output_size,
+            every: (cfg.roll_every > 0).then(|| Duration::from_secs(cfg.roll_every)),
+        }
+    }
+
+    /// 是否设置了滚动条件
+    pub fn is_set(&self) -> bool {
+        self.max_size > 0 || self.every.is_some()
+    }
+}
+
+type FileWriter = RecordWriter<Encoder<BufWriter<File>>>;
+
+/// 按 [`RollPolicy`] 滚动输出文件的写入器；未设置滚动条件时只写一个文件。
+///
+/// 滚动只发生在记录之间，单条记录不会跨文件，因此文件可能略大于 `max_size`。
+pub struct RollingWriter {
+    /// 不含压缩扩展名的输出路径
+    base: PathBuf,
+    compression: Compression,
+    policy: RollPolicy,
+    format: RecordFormat,
+    fields: Option<Projection>,
+    current: Option<(FileWriter, Instant)>,
+    /// 已关闭的文件及其记录数
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl RollingWriter {
+    /// `compression` 为 `None` 时按路径扩展名推断
+    pub fn new<P: AsRef<Path>>(path: P, compression: Compression, policy: RollPolicy) -> Self {
+        let path = path.as_ref();
+        let compression = match compression {
+            Compression::None => Compression::from_path(path),
+            c => c,
+        };
+        let path = path.to_string_lossy();
+        let base = PathBuf::from(
+            path.strip_suffix(compression.extension())
+                .unwrap_or(&path)
+                .to_string(),
+        );
+        Self {
+            base,
+            compression,
+            policy,
+            format: RecordFormat::Jsonl,
+            fields: None,
+            current: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// 设置输出格式与选中的字段，默认输出完整记录的 JSON Lines
+    pub fn set_format(mut self, format: RecordFormat, fields: Option<Projection>) -> Self {
+        self.format = format;
+        self.fields = fields;
+        self
+    }
+
+    /// 第 `index` 个文件（从 1 开始）的路径；不滚动时即输出路径本身
+    pub fn path_for(&self, index: usize) -> PathBuf {
+        let name = if self.policy.is_set() {
+            let stem = self.base.file_stem().unwrap_or_default().to_string_lossy();
+            match self.base.extension() {
+                Some(ext) => format!("{stem}-{index:04}.{}", ext.to_string_lossy()),
+                None => format!("{stem}-{index:04}"),
+            }
+        } else {
+            self.base
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        self.base.with_file_name(self.compression.apply_to(&name))
+    }
+
+    pub fn write(&mut self, log: &Sqllog) -> io::Result<()> {
+        if let Some((w, opened)) = &self.current {
+            let full = self.policy.max_size > 0 && w.bytes() >= self.policy.max_size;
+            let expired = self.policy.every.is_some_and(|d| opened.elapsed() >= d);
+            if full || expired {
+                self.close()?;
+            }
+        }
+        self.current()?.write(log)
+    }
+
+    /// 写完并关闭当前文件，返回所有输出文件及其记录数；没有写入任何记录时也会留下一个空文件
+    pub fn finish(mut self) -> io::Result<Vec<(PathBuf, u64)>> {
+        if self.files.is_empty() {
+            self.current()?;
+        }
+        self.close()?;
+        Ok(self.files)
+    }
+
+    /// 当前文件的写入器，尚未打开时创建下一个文件
+    fn current(&mut self) -> io::Result<&mut FileWriter> {
+        if self.current.is_none() {
+            let path = self.path_for(self.files.len() + 1);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let file = Encoder::new(BufWriter::new(File::create(&path)?), self.compression)?;
+            let writer = RecordWriter::new(file, self.format, self.fields.as_ref())?;
+            self.current = Some((writer, Instant::now()));
+        }
+        Ok(self.current.as_mut().map(|(w, _)| w).unwrap())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if let Some((w, _)) = self.current.take() {
+            let path = self.path_for(self.files.len() + 1);
+            let count = w.count();
+            w.into_inner()?.finish()?;
+            self.files.push((path, count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn log(user: &str) -> Sqllog {
+        Sqllog {
+            username: user.to_string(),
+            ..Sqllog::new()
+        }
+    }
+
+    #[test]
+    fn rolls_by_size() {
+        let dir = tempdir().unwrap();
+        let policy = RollPolicy {
+            max_size: 1,
+            every: None,
+        };
+        let mut w = RollingWriter::new(dir.path().join("records.jsonl"), Compression::None, policy);
+        for user in ["A", "B", "C"] {
+            w.write(&log(user)).unwrap();
+        }
+        let files = w.finish().unwrap();
+
+        let names: Vec<_> = files
+            .iter()
+            .map(|(p, n)| (p.file_name().unwrap().to_string_lossy().into_owned(), *n))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("records-0001.jsonl".to_string(), 1),
+                ("records-0002.jsonl".to_string(), 1),
+                ("records-0003.jsonl".to_string(), 1),
+            ]
+        );
+        let second = fs::read_to_string(dir.path().join("records-0002.jsonl")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Sqllog>(&second).unwrap().username,
+            "B"
+        );
+    }
+
+    #[test]
+    fn keeps_single_file_without_policy() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out.jsonl.gz");
+        let mut w = RollingWriter::new(&path, Compression::None, RollPolicy::default());
+        w.write(&log("A")).unwrap();
+        w.write(&log("B")).unwrap();
+        assert_eq!(w.finish().unwrap(), [(path, 2)]);
+
+        let policy = RollPolicy {
+            max_size: 0,
+            every: Some(Duration::from_secs(60)),
+        };
+        let w = RollingWriter::new(dir.path().join("out.jsonl"), Compression::Zstd, policy);
+        assert_eq!(w.path_for(2), dir.path().join("out-0002.jsonl.zst"));
+    }
+}
@@ -0,0 +1,514 @@
+//! 导出记录的字段定义与字段选择（`--fields`），各输出格式共用；
+//! 并据此生成带版本号的 JSON Schema 与 Arrow schema，供下游校验与升级加载程序
+
+use std::{fmt, str::FromStr};
+
+use dm_database_parser::{
+    FingerprintOptions, InstanceInfo, RecordCategory, Sqllog, fingerprint_with,
+};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::{Value, json};
+
+use crate::exporter::error::ExportError;
+
+/// 导出记录结构的版本号。
+///
+/// 新增字段不改变版本号；删除字段、重命名字段或改变已有字段的类型与含义时递增。
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 可导出的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Ts,
+    Ep,
+    ThreadId,
+    User,
+    Trxid,
+    Statement,
+    Appname,
+    ClientIp,
+    SqlType,
+    /// 语句文本
+    Sql,
+    ExecTimeMs,
+    RowCount,
+    ExecId,
+    Instance,
+    /// 语句文本的 SQL 指纹
+    Fingerprint,
+    /// 稳定的记录标识
+    RecordId,
+    /// 记录类别
+    Category,
+    /// 命中的规则标签
+    Tags,
+    /// 语句文本是否被截断
+    Truncated,
+}
+
+impl Field {
+    /// 全部字段，按默认输出顺序排列
+    pub const ALL: [Field; 19] = [
+        Field::Ts,
+        Field::Ep,
+        Field::ThreadId,
+        Field::User,
+        Field::Trxid,
+        Field::Statement,
+        Field::Appname,
+        Field::ClientIp,
+        Field::SqlType,
+        Field::Sql,
+        Field::ExecTimeMs,
+        Field::RowCount,
+        Field::ExecId,
+        Field::Instance,
+        Field::Fingerprint,
+        Field::RecordId,
+        Field::Category,
+        Field::Tags,
+        Field::Truncated,
+    ];
+
+    /// 字段在输出中的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Ts => "ts",
+            Field::Ep => "ep",
+            Field::ThreadId => "thread_id",
+            Field::User => "user",
+            Field::Trxid => "trxid",
+            Field::Statement => "statement",
+            Field::Appname => "appname",
+            Field::ClientIp => "client_ip",
+            Field::SqlType => "sql_type",
+            Field::Sql => "sql",
+            Field::ExecTimeMs => "exec_time_ms",
+            Field::RowCount => "row_count",
+            Field::ExecId => "exec_id",
+            Field::Instance => "instance",
+            Field::Fingerprint => "fingerprint",
+            Field::RecordId => "record_id",
+            Field::Category => "category",
+            Field::Tags => "tags",
+            Field::Truncated => "truncated",
+        }
+    }
+
+    /// 字段在 protobuf 消息中的编号，见 `proto/sqllog_record.proto`
+    pub fn number(self) -> u32 {
+        Field::ALL
+            .iter()
+            .position(|f| *f == self)
+            .unwrap_or_default() as u32
+            + 1
+    }
+
+    /// 字段在完整记录（未指定 `--fields` 时的 JSON Lines 输出）中的键名；
+    /// 指纹不属于完整记录，返回 None
+    pub fn record_name(self) -> Option<&'static str> {
+        match self {
+            Field::Ts => Some("sqllog_datetime"),
+            Field::User => Some("username"),
+            Field::Sql => Some("description"),
+            Field::ExecTimeMs => Some("execute_time"),
+            Field::ExecId => Some("execute_id"),
+            Field::Fingerprint => None,
+            other => Some(other.name()),
+        }
+    }
+
+    /// 字段说明，写入生成的 schema
+    pub fn description(self) -> &'static str {
+        match self {
+            Field::Ts => "记录时间，格式 YYYY-MM-DD HH:MM:SS.mmm",
+            Field::Ep => "EP 节点号",
+            Field::ThreadId => "线程号",
+            Field::User => "数据库用户名",
+            Field::Trxid => "事务号",
+            Field::Statement => "语句句柄",
+            Field::Appname => "应用名",
+            Field::ClientIp => "客户端 IP",
+            Field::SqlType => "语句标记，如 SEL、INS、ORA；没有时为空",
+            Field::Sql => "语句文本",
+            Field::ExecTimeMs => "执行耗时（毫秒）",
+            Field::RowCount => "影响或返回的行数",
+            Field::ExecId => "执行号",
+            Field::Instance => {
+                "记录所属实例，由日志文件名解析得到；无法解析时为 null（完整记录中省略该键）"
+            }
+            Field::Fingerprint => "语句文本的 SQL 指纹：字面量替换为 ?、统一大小写与空白",
+            Field::RecordId => {
+                "稳定的记录标识：文件标识与记录在文件中的字节偏移的哈希（16 位十六进制），重复导入同一文件时不变"
+            }
+            Field::Category => "记录类别：statement / transaction / login / error / system / other",
+            Field::Tags => {
+                "命中的配置规则（[[rules.rule]]）的标签，按规则顺序逗号分隔；没有命中时为空"
+            }
+            Field::Truncated => "语句文本是否因超出 max_body_len 被截断；截断后的文本以 ... 结尾",
+        }
+    }
+
+    /// 字段的 JSON Schema 类型定义
+    fn json_type(self) -> Value {
+        match self {
+            Field::Ep => json!({"type": "integer", "minimum": 0, "maximum": 255}),
+            Field::RowCount => json!({"type": "integer", "minimum": 0}),
+            Field::ThreadId | Field::Trxid | Field::ExecId => json!({"type": "integer"}),
+            Field::ExecTimeMs => json!({"type": "number"}),
+            Field::Truncated => json!({"type": "boolean"}),
+            Field::Category => json!({
+                "type": "string",
+                "enum": RecordCategory::ALL.map(|c| c.as_str()),
+            }),
+            Field::Instance => json!({
+                "type": ["object", "null"],
+                "properties": {
+                    "instance": {"type": "string"},
+                    "rotated_at": {"type": ["string", "null"]},
+                },
+                "required": ["instance"],
+            }),
+            _ => json!({"type": "string"}),
+        }
+    }
+
+    /// 字段的 Arrow 类型（Arrow JSON 格式）及子字段
+    fn arrow_type(self) -> (Value, Value) {
+        let utf8 = json!({"name": "utf8"});
+        let int =
+            |bits: u32, signed: bool| json!({"name": "int", "bitWidth": bits, "isSigned": signed});
+        match self {
+            Field::Ep => (int(8, false), json!([])),
+            Field::RowCount => (int(64, false), json!([])),
+            Field::ThreadId | Field::Trxid | Field::ExecId => (int(64, true), json!([])),
+            Field::Truncated => (json!({"name": "bool"}), json!([])),
+            Field::ExecTimeMs => (
+                json!({"name": "floatingpoint", "precision": "DOUBLE"}),
+                json!([]),
+            ),
+            Field::Instance => (
+                json!({"name": "struct"}),
+                json!([
+                    {"name": "instance", "nullable": false, "type": utf8, "children": []},
+                    {"name": "rotated_at", "nullable": true, "type": utf8, "children": []},
+                ]),
+            ),
+            _ => (utf8, json!([])),
+        }
+    }
+
+    /// 按名称查找字段，同时接受 [`Sqllog`] 中的原始字段名（如 `username`、`execute_time`）
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = match name {
+            "sqllog_datetime" => "ts",
+            "username" => "user",
+            "description" => "sql",
+            "execute_time" => "exec_time_ms",
+            "execute_id" => "exec_id",
+            other => other,
+        };
+        Field::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// 取出记录中该字段的值，指纹按 `fingerprint` 规则计算
+    pub fn value<'a>(self, log: &'a Sqllog, fingerprint: &FingerprintOptions) -> FieldValue<'a> {
+        match self {
+            Field::Ts => FieldValue::Str(&log.sqllog_datetime),
+            Field::Ep => FieldValue::Int(log.ep.into()),
+            Field::ThreadId => FieldValue::Int(log.thread_id),
+            Field::User => FieldValue::Str(&log.username),
+            Field::Trxid => FieldValue::Int(log.trxid),
+            Field::Statement => FieldValue::Str(&log.statement),
+            Field::Appname => FieldValue::Str(&log.appname),
+            Field::ClientIp => FieldValue::Str(&log.client_ip),
+            Field::SqlType => FieldValue::Str(&log.sql_type),
+            Field::Sql => FieldValue::Str(&log.description),
+            Field::ExecTimeMs => FieldValue::Float(log.execute_time.into()),
+            Field::RowCount => FieldValue::Int(log.row_count.try_into().unwrap_or(i64::MAX)),
+            Field::ExecId => FieldValue::Int(log.execute_id),
+            Field::Instance => FieldValue::Instance(log.instance.as_ref()),
+            Field::Fingerprint => {
+                FieldValue::Owned(fingerprint_with(&log.description, fingerprint).text)
+            }
+            Field::RecordId => FieldValue::Str(&log.record_id),
+            Field::Category => FieldValue::Str(log.category.as_str()),
+            Field::Tags => FieldValue::Str(&log.tags),
+            Field::Truncated => FieldValue::Bool(log.truncated),
+        }
+    }
+}
+
+/// 单个字段的值：JSON 中保留原类型，CSV 中转换为文本
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue<'a> {
+    Str(&'a str),
+    Owned(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Instance(Option<&'a InstanceInfo>),
+}
+
+impl Serialize for FieldValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FieldValue::Str(s) => serializer.serialize_str(s),
+            FieldValue::Owned(s) => serializer.serialize_str(s),
+            FieldValue::Int(v) => serializer.serialize_i64(*v),
+            FieldValue::Float(v) => serializer.serialize_f64(*v),
+            FieldValue::Bool(v) => serializer.serialize_bool(*v),
+            FieldValue::Instance(v) => v.serialize(serializer),
+        }
+    }
+}
+
+impl fmt::Display for FieldValue<'_> {
+    /// CSV 中实例只输出实例名
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Str(s) => f.write_str(s),
+            FieldValue::Owned(s) => f.write_str(s),
+            FieldValue::Int(v) => write!(f, "{v}"),
+            FieldValue::Float(v) => write!(f, "{v}"),
+            FieldValue::Bool(v) => write!(f, "{v}"),
+            FieldValue::Instance(v) => f.write_str(v.map_or("", |i| i.instance.as_str())),
+        }
+    }
+}
+
+/// 选中的字段列表，由逗号分隔的字段名解析得到，如 `ts,user,exec_time_ms,fingerprint`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    fields: Vec<Field>,
+    fingerprint: FingerprintOptions,
+}
+
+impl Default for Projection {
+    /// 全部字段
+    fn default() -> Self {
+        Self {
+            fields: Field::ALL.to_vec(),
+            fingerprint: FingerprintOptions::DEFAULT,
+        }
+    }
+}
+
+impl Projection {
+    /// 完整记录中的字段，即除指纹外的全部字段
+    pub fn full_record() -> Self {
+        Self {
+            fields: Field::ALL
+                .into_iter()
+                .filter(|f| f.record_name().is_some())
+                .collect(),
+            fingerprint: FingerprintOptions::DEFAULT,
+        }
+    }
+
+    /// `fingerprint` 字段的归一化规则，对应配置中的 `[analysis.fingerprint]`
+    pub fn set_fingerprint(mut self, fingerprint: FingerprintOptions) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// 各列名称
+    pub fn header(&self) -> Vec<&'static str> {
+        self.fields.iter().map(|f| f.name()).collect()
+    }
+
+    /// 按选中字段的顺序取出记录中的值
+    pub fn values<'a>(&self, log: &'a Sqllog) -> Vec<FieldValue<'a>> {
+        self.fields
+            .iter()
+            .map(|f| f.value(log, &self.fingerprint))
+            .collect()
+    }
+
+    /// 序列化为只含选中字段的对象
+    pub fn view<'a>(&'a self, log: &'a Sqllog) -> Projected<'a> {
+        Projected { fields: self, log }
+    }
+}
+
+impl FromStr for Projection {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = Vec::new();
+        for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let field = Field::from_name(name).ok_or_else(|| ExportError::UnknownField {
+                name: name.to_string(),
+                available: Projection::default().header().join(","),
+            })?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        if fields.is_empty() {
+            return Err(ExportError::EmptyFields);
+        }
+        Ok(Self {
+            fields,
+            fingerprint: FingerprintOptions::DEFAULT,
+        })
+    }
+}
+
+/// 输出中的各列：(键名, 字段)。`fields` 为 None 时为完整记录的布局
+pub(crate) fn columns(fields: Option<&Projection>) -> Vec<(&'static str, Field)> {
+    match fields {
+        Some(p) => p.fields.iter().map(|f| (f.name(), *f)).collect(),
+        None => Field::ALL
+            .into_iter()
+            .filter_map(|f| f.record_name().map(|n| (n, f)))
+            .collect(),
+    }
+}
+
+/// 生成导出记录的 JSON Schema（draft 2020-12）；`fields` 为 None 时描述完整记录
+pub fn json_schema(fields: Option<&Projection>) -> Value {
+    let columns = columns(fields);
+    let mut properties = serde_json::Map::new();
+    for (name, field) in &columns {
+        let mut ty = field.json_type();
+        ty["description"] = field.description().into();
+        properties.insert(name.to_string(), ty);
+    }
+    // 完整记录中实例为 None 时省略该键，其余字段总是输出
+    let required: Vec<&str> = columns
+        .iter()
+        .filter(|(_, f)| fields.is_some() || *f != Field::Instance)
+        .map(|(n, _)| *n)
+        .collect();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:parser-sqllog:record:v{SCHEMA_VERSION}"),
+        "title": "parser-sqllog 导出记录",
+        "x-schema-version": SCHEMA_VERSION,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// 生成导出记录的 Arrow schema（Arrow 集成测试使用的 JSON 格式），版本号写入元数据
+pub fn arrow_schema(fields: Option<&Projection>) -> Value {
+    let fields: Vec<Value> = columns(fields)
+        .into_iter()
+        .map(|(name, field)| {
+            let (ty, children) = field.arrow_type();
+            json!({
+                "name": name,
+                "nullable": field == Field::Instance,
+                "type": ty,
+                "children": children,
+            })
+        })
+        .collect();
+    json!({
+        "fields": fields,
+        "metadata": [{"key": "schema_version", "value": SCHEMA_VERSION.to_string()}],
+    })
+}
+
+/// [`Projection::view`] 的结果，序列化为 `{字段名: 值}` 对象
+pub struct Projected<'a> {
+    fields: &'a Projection,
+    log: &'a Sqllog,
+}
+
+impl Serialize for Projected<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.fields.len()))?;
+        for field in &self.fields.fields {
+            map.serialize_entry(
+                field.name(),
+                &field.value(self.log, &self.fields.fingerprint),
+            )?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fields_and_project() {
+        let p: Projection = "ts, username,exec_time_ms,fingerprint,ts".parse().unwrap();
+        assert_eq!(p.header(), ["ts", "user", "exec_time_ms", "fingerprint"]);
+
+        let log = Sqllog {
+            sqllog_datetime: "2025-08-12 10:57:09.548".to_string(),
+            username: "SYSDBA".to_string(),
+            description: "select * from t where id = 42".to_string(),
+            execute_time: 1.5,
+            ..Sqllog::new()
+        };
+        let json = serde_json::to_string(&p.view(&log)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"ts":"2025-08-12 10:57:09.548","user":"SYSDBA","exec_time_ms":1.5,"fingerprint":"select * from t where id = ?"}"#
+        );
+        let values: Vec<String> = p.values(&log).iter().map(|v| v.to_string()).collect();
+        assert_eq!(values[2], "1.5");
+
+        let p = p.set_fingerprint(FingerprintOptions {
+            lowercase: false,
+            ..FingerprintOptions::DEFAULT
+        });
+        let values: Vec<String> = p.values(&log).iter().map(|v| v.to_string()).collect();
+        assert_eq!(values[3], "select * from t where id = ?");
+        let log = Sqllog {
+            description: "SELECT * FROM T".to_string(),
+            ..log
+        };
+        let values: Vec<String> = p.values(&log).iter().map(|v| v.to_string()).collect();
+        assert_eq!(values[3], "SELECT * FROM T");
+
+        assert!(matches!(
+            "ts,nope".parse::<Projection>(),
+            Err(ExportError::UnknownField { name, .. }) if name == "nope"
+        ));
+        assert!(matches!(
+            " , ".parse::<Projection>(),
+            Err(ExportError::EmptyFields)
+        ));
+    }
+
+    #[test]
+    fn schema_matches_serialized_record() {
+        let log = Sqllog::new();
+        let record = serde_json::to_value(&log).unwrap();
+        let schema = json_schema(None);
+        let properties = schema["properties"].as_object().unwrap();
+        for key in record.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "schema 缺少字段 {key}");
+        }
+        assert_eq!(properties.len(), Field::ALL.len() - 1);
+        assert_eq!(schema["x-schema-version"], SCHEMA_VERSION);
+
+        let p: Projection = "user,row_count".parse().unwrap();
+        assert_eq!(
+            json_schema(Some(&p))["required"],
+            json!(["user", "row_count"])
+        );
+        let arrow = arrow_schema(Some(&p));
+        assert_eq!(arrow["fields"][1]["type"]["bitWidth"], 64);
+
+        let p: Projection = "sql,truncated".parse().unwrap();
+        assert_eq!(
+            json_schema(Some(&p))["properties"]["truncated"]["type"],
+            "boolean"
+        );
+        assert_eq!(arrow_schema(Some(&p))["fields"][1]["type"]["name"], "bool");
+        assert_eq!(arrow["metadata"][0]["value"], SCHEMA_VERSION.to_string());
+    }
+}
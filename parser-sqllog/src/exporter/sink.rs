@@ -0,0 +1,558 @@
+//! 导出目标：一次解析同时写出到多个目标（记录文件、统计报告、HTTP 接口）
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        Arc, Barrier,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, SyncSender},
+    },
+    thread::{self, JoinHandle},
+};
+
+use dm_database_parser::{FingerprintOptions, Sqllog};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::stats::{StatsAggregator, StatsSample},
+    config::export::{ExportConfig, SinkConfig, SinkKind},
+    exporter::{
+        compress::{Compression, Encoder},
+        error::ExportError,
+        record::{EpSplitWriter, RecordFormat, RecordWriter},
+        rolling::{RollPolicy, RollingWriter},
+        schema::Projection,
+    },
+};
+
+/// 导出目标写完后的汇总
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkReport {
+    /// 目标的描述：路径、目录或 URL
+    pub target: String,
+    /// 写出的记录数
+    pub records: u64,
+    /// 写出的文件数，非文件目标为 0
+    pub files: usize,
+}
+
+/// 一个导出目标
+pub enum Sink {
+    /// 写到已打开的流（如标准输出）
+    Stream(RecordWriter<Box<dyn Write + Send>>),
+    File(RollingWriter),
+    EpSplit {
+        dir: String,
+        writer: EpSplitWriter,
+    },
+    Stats(StatsSink),
+    #[cfg(feature = "http")]
+    Http(crate::exporter::http::HttpSink),
+}
+
+impl Sink {
+    /// 按 `[[export.sink]]` 的配置打开目标，`index` 用于错误信息；
+    /// 选中的 `fingerprint` 字段按 `fingerprint` 规则计算
+    pub fn open(
+        index: usize,
+        cfg: &SinkConfig,
+        export: &ExportConfig,
+        fingerprint: FingerprintOptions,
+    ) -> Result<Self, ExportError> {
+        if cfg.path.is_empty() {
+            return Err(ExportError::MissingSinkPath { index });
+        }
+        let fields = match cfg.fields.trim() {
+            "" => None,
+            f => Some(f.parse::<Projection>()?.set_fingerprint(fingerprint)),
+        };
+        match cfg.kind {
+            SinkKind::File => Ok(Self::file(
+                &cfg.path,
+                cfg.format,
+                fields,
+                cfg.split_by_ep,
+                export,
+            )),
+            SinkKind::Stats => Ok(Self::Stats(StatsSink {
+                path: cfg.path.clone(),
+                agg: StatsAggregator::new(cfg.group_by),
+                top: (cfg.top > 0).then_some(cfg.top),
+                records: 0,
+            })),
+            #[cfg(feature = "http")]
+            SinkKind::Http => Ok(Self::Http(crate::exporter::http::HttpSink::new(
+                &cfg.path,
+                cfg.batch_size,
+                fields,
+            ))),
+            #[cfg(not(feature = "http"))]
+            SinkKind::Http => Err(ExportError::FeatureDisabled {
+                kind: "http",
+                feature: "http",
+            }),
+        }
+    }
+
+    /// 记录文件目标，压缩与滚动沿用 `[export]` 的设置；`split_by_ep` 时 `path` 为输出目录
+    pub fn file(
+        path: &str,
+        format: RecordFormat,
+        fields: Option<Projection>,
+        split_by_ep: bool,
+        export: &ExportConfig,
+    ) -> Self {
+        let policy = RollPolicy::from_config(export);
+        if split_by_ep {
+            let writer = EpSplitWriter::new(path)
+                .set_compression(export.compress)
+                .set_roll_policy(policy)
+                .set_format(format, fields);
+            Self::EpSplit {
+                dir: path.to_string(),
+                writer,
+            }
+        } else {
+            Self::File(RollingWriter::new(path, export.compress, policy).set_format(format, fields))
+        }
+    }
+
+    /// 是否需要统计样本；只有统计目标使用 `write` 的 `sample` 参数
+    pub fn wants_samples(&self) -> bool {
+        matches!(self, Self::Stats(_))
+    }
+
+    /// 写入一条记录；`sample` 为该记录按本目标的分组维度提取的统计样本
+    pub fn write(&mut self, log: &Sqllog, sample: Option<&StatsSample>) -> io::Result<()> {
+        match self {
+            Self::Stream(w) => w.write(log),
+            Self::File(w) => w.write(log),
+            Self::EpSplit { writer, .. } => writer.write(log),
+            Self::Stats(s) => {
+                if let Some(sample) = sample {
+                    s.agg.add_sample(sample.clone())?;
+                    s.records += 1;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "http")]
+            Self::Http(h) => h.write(log),
+        }
+    }
+
+    /// 写完并关闭目标
+    pub fn finish(self) -> io::Result<SinkReport> {
+        Ok(match self {
+            Self::Stream(mut w) => {
+                w.flush()?;
+                SinkReport {
+                    target: "标准输出".to_string(),
+                    records: w.count(),
+                    files: 0,
+                }
+            }
+            Self::File(w) => {
+                let target = w.path_for(1).display().to_string();
+                let files = w.finish()?;
+                SinkReport {
+                    target,
+                    records: files.iter().map(|(_, n)| n).sum(),
+                    files: files.len(),
+                }
+            }
+            Self::EpSplit { dir, writer } => {
+                let counts = writer.finish()?;
+                SinkReport {
+                    target: dir,
+                    records: counts.values().sum(),
+                    files: counts.len(),
+                }
+            }
+            Self::Stats(s) => s.finish()?,
+            #[cfg(feature = "http")]
+            Self::Http(h) => h.finish()?,
+        })
+    }
+}
+
+/// 一条待写出的记录及其按各目标的分组维度提取的统计样本（与目标按下标对应）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SinkRecord {
+    pub log: Sqllog,
+    pub samples: Vec<Option<StatsSample>>,
+}
+
+/// 每批交给写入线程的记录数
+const WRITER_BATCH: usize = 1024;
+
+/// 把记录写到一组导出目标，可使用多个写入线程。
+///
+/// 多线程时按目标划分到各线程（同一目标始终由同一线程写入），记录按批广播给所有线程，
+/// 每个目标仍按记录到达的顺序写入。结束时分两阶段：所有线程写完全部记录后才开始关闭各自的目标，
+/// 任一目标写入失败则所有目标都不再关闭；汇总按目标的下标顺序返回。
+pub struct SinkWriters {
+    mode: Mode,
+}
+
+enum Mode {
+    /// 单线程：在调用方线程中依次写入
+    Inline(Vec<(usize, Sink)>),
+    Threads {
+        pending: Vec<SinkRecord>,
+        workers: Vec<Worker>,
+        abort: Arc<AtomicBool>,
+    },
+}
+
+struct Worker {
+    tx: SyncSender<Arc<Vec<SinkRecord>>>,
+    handle: JoinHandle<io::Result<Vec<(usize, SinkReport)>>>,
+}
+
+impl SinkWriters {
+    /// `threads` 为写入线程数，超过目标数时按目标数计；不超过 1 时在调用方线程中写入
+    pub fn new(sinks: Vec<Sink>, threads: usize) -> Self {
+        let threads = threads.min(sinks.len());
+        let sinks = sinks.into_iter().enumerate();
+        if threads <= 1 {
+            return Self {
+                mode: Mode::Inline(sinks.collect()),
+            };
+        }
+        let mut parts: Vec<Vec<(usize, Sink)>> = (0..threads).map(|_| Vec::new()).collect();
+        for (i, sink) in sinks {
+            parts[i % threads].push((i, sink));
+        }
+        let abort = Arc::new(AtomicBool::new(false));
+        let barrier = Arc::new(Barrier::new(threads));
+        let workers = parts
+            .into_iter()
+            .map(|mut sinks| {
+                let (tx, rx) = mpsc::sync_channel::<Arc<Vec<SinkRecord>>>(4);
+                let (abort, barrier) = (abort.clone(), barrier.clone());
+                let handle = thread::spawn(move || {
+                    let mut result = Ok(());
+                    for batch in rx {
+                        result = batch.iter().try_for_each(|r| write_to(&mut sinks, r));
+                        if result.is_err() {
+                            abort.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                    // 等所有线程写完后再关闭目标
+                    barrier.wait();
+                    result?;
+                    if abort.load(Ordering::Relaxed) {
+                        return Ok(Vec::new());
+                    }
+                    sinks
+                        .into_iter()
+                        .map(|(i, sink)| sink.finish().map(|r| (i, r)))
+                        .collect()
+                });
+                Worker { tx, handle }
+            })
+            .collect();
+        Self {
+            mode: Mode::Threads {
+                pending: Vec::with_capacity(WRITER_BATCH),
+                workers,
+                abort,
+            },
+        }
+    }
+
+    /// 写入一条记录；多线程时攒满一批后交给写入线程，出错时返回最先出错的线程的错误
+    pub fn write(&mut self, record: SinkRecord) -> io::Result<()> {
+        match &mut self.mode {
+            Mode::Inline(sinks) => write_to(sinks, &record),
+            Mode::Threads {
+                pending,
+                workers,
+                abort,
+            } => {
+                pending.push(record);
+                if pending.len() < WRITER_BATCH {
+                    return Ok(());
+                }
+                let batch = Arc::new(std::mem::replace(pending, Vec::with_capacity(WRITER_BATCH)));
+                if workers.iter().all(|w| w.tx.send(batch.clone()).is_ok()) {
+                    return Ok(());
+                }
+                abort.store(true, Ordering::Relaxed);
+                join(std::mem::take(workers)).map(|_| ())
+            }
+        }
+    }
+
+    /// 写完剩余记录并关闭所有目标，按目标的下标顺序返回汇总
+    pub fn finish(self) -> io::Result<Vec<SinkReport>> {
+        match self.mode {
+            Mode::Inline(sinks) => sinks.into_iter().map(|(_, s)| s.finish()).collect(),
+            Mode::Threads {
+                pending, workers, ..
+            } => {
+                let batch = Arc::new(pending);
+                for w in &workers {
+                    // 发送失败说明该线程已出错退出，错误在 join 时返回
+                    let _ = w.tx.send(batch.clone());
+                }
+                let mut reports = join(workers)?;
+                reports.sort_by_key(|(i, _)| *i);
+                Ok(reports.into_iter().map(|(_, r)| r).collect())
+            }
+        }
+    }
+}
+
+fn write_to(sinks: &mut [(usize, Sink)], record: &SinkRecord) -> io::Result<()> {
+    for (i, sink) in sinks {
+        let sample = record.samples.get(*i).and_then(Option::as_ref);
+        sink.write(&record.log, sample)?;
+    }
+    Ok(())
+}
+
+/// 关闭发送端并等待所有写入线程结束，返回第一个错误或各线程的汇总
+fn join(workers: Vec<Worker>) -> io::Result<Vec<(usize, SinkReport)>> {
+    let handles: Vec<_> = workers.into_iter().map(|w| w.handle).collect();
+    let mut reports = Vec::new();
+    let mut error = None;
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok(r)) => reports.extend(r),
+            Ok(Err(e)) => {
+                error.get_or_insert(e);
+            }
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+    error.map_or(Ok(reports), Err)
+}
+
+/// 统计报告目标：结束时写出 CSV，扩展名为 `.gz` / `.zst` 时压缩
+pub struct StatsSink {
+    path: String,
+    agg: StatsAggregator,
+    top: Option<usize>,
+    records: u64,
+}
+
+impl StatsSink {
+    fn finish(self) -> io::Result<SinkReport> {
+        let path = Path::new(&self.path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        let mut out = Encoder::new(file, Compression::from_path(path))?;
+        {
+            let mut wtr = csv::Writer::from_writer(&mut out);
+            for row in self.agg.finish(self.top)? {
+                wtr.serialize(row)?;
+            }
+            wtr.flush()?;
+        }
+        out.finish()?;
+        Ok(SinkReport {
+            target: self.path,
+            records: self.records,
+            files: 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::truncate_body;
+    use tempfile::tempdir;
+
+    #[test]
+    fn writes_every_sink() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).display().to_string();
+        let export = ExportConfig::new();
+        let configs = [
+            SinkConfig {
+                path: path("records.jsonl"),
+                ..Default::default()
+            },
+            SinkConfig {
+                path: path("users.csv"),
+                format: RecordFormat::Csv,
+                fields: "user".to_string(),
+                ..Default::default()
+            },
+            SinkConfig {
+                kind: SinkKind::Stats,
+                path: path("stats.csv"),
+                ..Default::default()
+            },
+        ];
+        let mut sinks: Vec<Sink> = configs
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Sink::open(i, c, &export, FingerprintOptions::DEFAULT).unwrap())
+            .collect();
+
+        let log = Sqllog {
+            username: "A".to_string(),
+            ..Sqllog::new()
+        };
+        let sample = StatsSample {
+            group: String::new(),
+            fingerprint: "select ?".to_string(),
+            exec_ms: 3,
+            rows: 1,
+            example: None,
+        };
+        for _ in 0..2 {
+            for sink in &mut sinks {
+                let s = sink.wants_samples().then_some(&sample);
+                sink.write(&log, s).unwrap();
+            }
+        }
+        let reports: Vec<_> = sinks.into_iter().map(|s| s.finish().unwrap()).collect();
+
+        assert!(reports.iter().all(|r| r.records == 2));
+        assert_eq!(
+            fs::read_to_string(path("records.jsonl"))
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+        assert_eq!(
+            fs::read_to_string(path("users.csv")).unwrap(),
+            "user\nA\nA\n"
+        );
+        let stats = fs::read_to_string(path("stats.csv")).unwrap();
+        assert!(stats.contains(",select ?,2,6,3.0,3,2"));
+    }
+
+    #[test]
+    fn writer_threads_keep_order_and_report_by_index() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).display().to_string();
+        let export = ExportConfig::new();
+        let names = ["a.csv", "b.csv", "c.csv"];
+        let sinks = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let cfg = SinkConfig {
+                    path: path(name),
+                    format: RecordFormat::Csv,
+                    fields: "user".to_string(),
+                    ..Default::default()
+                };
+                Sink::open(i, &cfg, &export, FingerprintOptions::DEFAULT).unwrap()
+            })
+            .collect();
+        let mut writers = SinkWriters::new(sinks, 2);
+        let n = WRITER_BATCH * 2 + 7;
+        for i in 0..n {
+            let log = Sqllog {
+                username: i.to_string(),
+                ..Sqllog::new()
+            };
+            writers
+                .write(SinkRecord {
+                    log,
+                    samples: Vec::new(),
+                })
+                .unwrap();
+        }
+        let reports = writers.finish().unwrap();
+
+        let expected: String = std::iter::once("user".to_string())
+            .chain((0..n).map(|i| i.to_string()))
+            .map(|l| l + "\n")
+            .collect();
+        for (report, name) in reports.iter().zip(names) {
+            assert_eq!(report.target, path(name));
+            assert_eq!(report.records, n as u64);
+            assert_eq!(fs::read_to_string(path(name)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn exports_truncated_marker() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).display().to_string();
+        let export = ExportConfig::new();
+        let configs = [
+            SinkConfig {
+                path: path("records.jsonl"),
+                ..Default::default()
+            },
+            SinkConfig {
+                path: path("sql.csv"),
+                format: RecordFormat::Csv,
+                fields: "sql,truncated".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut sinks: Vec<Sink> = configs
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Sink::open(i, c, &export, FingerprintOptions::DEFAULT).unwrap())
+            .collect();
+
+        let mut long = Sqllog {
+            description: "select * from orders where id = 1".to_string(),
+            ..Sqllog::new()
+        };
+        long.truncated = truncate_body(&mut long.description, 14);
+        let short = Sqllog {
+            description: "select 1".to_string(),
+            ..Sqllog::new()
+        };
+        for sink in &mut sinks {
+            sink.write(&long, None).unwrap();
+            sink.write(&short, None).unwrap();
+        }
+        for sink in sinks {
+            sink.finish().unwrap();
+        }
+
+        let jsonl = fs::read_to_string(path("records.jsonl")).unwrap();
+        let logs: Vec<Sqllog> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(logs[0].description, "select * from ...");
+        assert!(logs[0].truncated);
+        assert!(!logs[1].truncated);
+        assert_eq!(
+            fs::read_to_string(path("sql.csv")).unwrap(),
+            "sql,truncated\nselect * from ...,true\nselect 1,false\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_sinks() {
+        let export = ExportConfig::new();
+        assert!(matches!(
+            Sink::open(
+                0,
+                &SinkConfig::default(),
+                &export,
+                FingerprintOptions::DEFAULT
+            ),
+            Err(ExportError::MissingSinkPath { index: 0 })
+        ));
+        let bad_fields = SinkConfig {
+            path: "out.jsonl".to_string(),
+            fields: "nope".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Sink::open(1, &bad_fields, &export, FingerprintOptions::DEFAULT),
+            Err(ExportError::UnknownField { .. })
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+//! 自定义报告模板：用 [Tera](https://keats.github.io/tera/docs/) 模板渲染概览数据，
+//! 使各团队无需修改代码即可调整报告的结构与样式。
+//!
+//! 模板中可用的变量见 [`ReportContext`]；扩展名为 `.html` / `.htm` / `.xml` 的模板自动转义输出。
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dm_database_parser::epoch_millis_to_ts;
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::{
+    analysis::{
+        activity::{SessionRow, TimeSeriesRow},
+        stats::StatsRow,
+    },
+    command::Overview,
+};
+
+/// 模板的数据模型
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportContext<'a> {
+    /// 报告生成时间（UTC）
+    pub generated_at: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub records: u64,
+    pub bad_records: u64,
+    /// 最早 / 最晚时间桶的起点，没有记录时为空
+    pub first_bucket: String,
+    pub last_bucket: String,
+    /// 按总耗时从高到低排列的指纹统计
+    pub top_sql: &'a [StatsRow],
+    /// 按总耗时从高到低排列的会话
+    pub sessions: &'a [SessionRow],
+    /// 按时间顺序排列的时间桶
+    pub time_series: &'a [TimeSeriesRow],
+    /// 命令行 `--var 键=值` 传入的自定义变量，如公司名、报告标题
+    pub vars: &'a BTreeMap<String, String>,
+}
+
+impl<'a> ReportContext<'a> {
+    pub fn new(overview: &'a Overview, vars: &'a BTreeMap<String, String>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let bucket = |row: Option<&TimeSeriesRow>| row.map(|r| r.bucket_start.clone());
+        Self {
+            generated_at: epoch_millis_to_ts(now),
+            files: overview.summary.files,
+            bytes: overview.summary.bytes,
+            records: overview.summary.records,
+            bad_records: overview.summary.bad_records,
+            first_bucket: bucket(overview.time_series.first()).unwrap_or_default(),
+            last_bucket: bucket(overview.time_series.last()).unwrap_or_default(),
+            top_sql: &overview.top_sql,
+            sessions: &overview.sessions,
+            time_series: &overview.time_series,
+            vars,
+        }
+    }
+}
+
+/// 读取模板文件并渲染
+pub fn render(template: &Path, ctx: &ReportContext<'_>) -> tera::Result<String> {
+    // 以文件名注册模板，Tera 据扩展名决定是否自动转义
+    let name = template
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut tera = Tera::default();
+    tera.add_template_file(template, Some(&name))?;
+    tera.render(&name, &Context::from_serialize(ctx)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::PipelineSummary;
+    use tempfile::tempdir;
+
+    #[test]
+    fn renders_templates_with_overview_and_vars() {
+        let overview = Overview {
+            summary: PipelineSummary {
+                files: 2,
+                records: 10,
+                ..Default::default()
+            },
+            top_sql: vec![StatsRow {
+                fingerprint: "select * from t where id = ?".to_string(),
+                executions: 3,
+                total_ms: 30,
+                ..Default::default()
+            }],
+            sessions: Vec::new(),
+            time_series: vec![TimeSeriesRow {
+                bucket_start: "2025-08-12 10:00:00.000".to_string(),
+                ..Default::default()
+            }],
+        };
+        let vars = BTreeMap::from([("company".to_string(), "ACME <DBA>".to_string())]);
+        let ctx = ReportContext::new(&overview, &vars);
+
+        let dir = tempdir().unwrap();
+        let md = dir.path().join("report.md");
+        std::fs::write(
+            &md,
+            "# {{ vars.company }}\n{{ files }} files, {{ records }} records since {{ first_bucket }}\n\
+             {% for s in top_sql %}- {{ s.fingerprint }}: {{ s.executions }}x\n{% endfor %}",
+        )
+        .unwrap();
+        assert_eq!(
+            render(&md, &ctx).unwrap(),
+            "# ACME <DBA>\n2 files, 10 records since 2025-08-12 10:00:00.000\n\
+             - select * from t where id = ?: 3x\n"
+        );
+
+        let html = dir.path().join("report.html");
+        std::fs::write(&html, "<h1>{{ vars.company }}</h1>").unwrap();
+        assert_eq!(render(&html, &ctx).unwrap(), "<h1>ACME &lt;DBA&gt;</h1>");
+
+        std::fs::write(&md, "{{ missing.field }}").unwrap();
+        assert!(render(&md, &ctx).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Excel 工作簿：每份报告写成一个工作表，首行为加粗冻结的表头并带筛选。
+//!
+//! 单元格文本与 CSV 输出相同；能解析为数值或布尔值的单元格写成对应类型，便于在 Excel 中排序与计算。
+
+use std::{io, path::Path};
+
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+
+use crate::analysis::truncate_body;
+
+/// Excel 单元格中文本的最大长度（字符）
+const MAX_CELL_LEN: usize = 32_767;
+
+/// 由多个报告工作表组成的 Excel 工作簿
+pub struct WorkbookWriter {
+    workbook: Workbook,
+    header: Format,
+}
+
+impl Default for WorkbookWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkbookWriter {
+    pub fn new() -> Self {
+        Self {
+            workbook: Workbook::new(),
+            header: Format::new().set_bold(),
+        }
+    }
+
+    /// 把报告行写成名为 `name` 的工作表；没有行时为空表
+    pub fn add_sheet<T: Serialize>(&mut self, name: &str, rows: &[T]) -> Result<(), XlsxError> {
+        let (header, cells) = to_cells(rows).map_err(|e| XlsxError::IoError(e.into()))?;
+        let sheet = self.workbook.add_worksheet();
+        sheet.set_name(name)?;
+        for (col, title) in header.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, title, &self.header)?;
+        }
+        for (i, row) in cells.iter().enumerate() {
+            for (col, cell) in row.iter().enumerate() {
+                write_cell(sheet, i as u32 + 1, col as u16, cell)?;
+            }
+        }
+        if !header.is_empty() {
+            sheet.set_freeze_panes(1, 0)?;
+            sheet.autofilter(0, 0, cells.len() as u32, header.len() as u16 - 1)?;
+        }
+        sheet.autofit();
+        Ok(())
+    }
+
+    pub fn save(mut self, path: &Path) -> Result<(), XlsxError> {
+        self.workbook.save(path)
+    }
+}
+
+/// 借助 CSV 序列化得到与 CSV 输出相同的表头和单元格文本
+fn to_cells<T: Serialize>(rows: &[T]) -> csv::Result<(Vec<String>, Vec<Vec<String>>)> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    let data = wtr
+        .into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    if data.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let mut rdr = csv::Reader::from_reader(data.as_slice());
+    let header = rdr.headers()?.iter().map(str::to_string).collect();
+    let mut cells = Vec::with_capacity(rows.len());
+    for rec in rdr.records() {
+        cells.push(rec?.iter().map(str::to_string).collect());
+    }
+    Ok((header, cells))
+}
+
+fn write_cell(sheet: &mut Worksheet, row: u32, col: u16, cell: &str) -> Result<(), XlsxError> {
+    if cell.is_empty() {
+        return Ok(());
+    }
+    // 以 0 开头的多位数字（如编号）保持为文本
+    let leading_zero = cell.len() > 1 && cell.starts_with('0') && !cell.starts_with("0.");
+    if cell == "true" || cell == "false" {
+        sheet.write_boolean(row, col, cell == "true")?;
+    } else if let Ok(n) = cell.parse::<f64>()
+        && n.is_finite()
+        && !leading_zero
+    {
+        sheet.write_number(row, col, n)?;
+    } else if cell.chars().count() > MAX_CELL_LEN {
+        // 截断到字符数上限（含省略号）
+        let end = cell
+            .char_indices()
+            .nth(MAX_CELL_LEN - 3)
+            .map_or(cell.len(), |(i, _)| i);
+        let mut text = cell.to_string();
+        truncate_body(&mut text, end);
+        sheet.write_string(row, col, text)?;
+    } else {
+        sheet.write_string(row, col, cell)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        name: &'static str,
+        count: u64,
+        ok: bool,
+    }
+
+    #[test]
+    fn writes_sheets_with_typed_cells() {
+        let mut w = WorkbookWriter::new();
+        w.add_sheet(
+            "Top SQL",
+            &[Row {
+                name: "007",
+                count: 3,
+                ok: true,
+            }],
+        )
+        .unwrap();
+        w.add_sheet::<Row>("Empty", &[]).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.xlsx");
+        w.save(&path).unwrap();
+        // xlsx 是 zip 压缩包
+        assert!(std::fs::read(&path).unwrap().starts_with(b"PK"));
+
+        let (header, cells) = to_cells(&[Row {
+            name: "a",
+            count: 1,
+            ok: false,
+        }])
+        .unwrap();
+        assert_eq!(header, ["name", "count", "ok"]);
+        assert_eq!(cells, [["a", "1", "false"]]);
+        assert!(WorkbookWriter::new().add_sheet::<Row>("a/b", &[]).is_err());
+    }
+}
@@ -0,0 +1,669 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use dm_database_parser::{InstanceInfo, is_ts_millis};
+
+use crate::{
+    config::{input::InputEncoding, sqllog::SqllogConfig},
+    error::InputError,
+    exporter::compress::Compression,
+};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use tracing::warn;
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "object-store")]
+pub mod s3;
+pub mod seek;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
+
+/// 收集待解析的 sqllog 文件。
+///
+/// - 若 `path` 指向文件，则只返回该文件；
+/// - 若 `path` 指向目录，则返回目录下（不递归）所有扩展名为 `log` 的文件，按文件名排序；
+/// - 启用 `object-store` 特性时，`s3://bucket/prefix` 返回该前缀下所有 `.log` 对象（以及压缩的 `.log.gz` / `.log.zst`）；
+/// - 启用 `http` 特性时，`http(s)://` URL 作为单个文件返回；
+/// - 启用 `sftp` 特性时，`sftp://` 路径按上述文件/目录规则列出远程主机上的文件，目录中同时包括压缩的 `.log.gz` / `.log.zst`。
+pub fn collect_files<P: AsRef<Path>>(path: P) -> io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    #[cfg(feature = "sftp")]
+    if sftp::is_sftp_path(path) {
+        return sftp::list(path);
+    }
+    #[cfg(feature = "http")]
+    if http::is_http_url(path) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    #[cfg(feature = "object-store")]
+    if s3::is_s3_path(path) {
+        return s3::list(path);
+    }
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let p = entry?.path();
+        if p.is_file() && p.extension().is_some_and(|e| e == "log") {
+            files.push(p);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// 按 `[sqllog.input]` 收集待解析的文件；未配置 `paths` 时读取 `sqllog.path`。
+///
+/// - 含通配符（`*`、`?`、`**`）的路径展开为所有匹配的文件，`*` 与 `?` 不跨越目录；
+/// - 目录返回其中的 `.log` 文件（以及压缩的 `.log.gz` / `.log.zst`），`recursive` 为 true 时包括子目录；
+/// - 远程路径与文件路径按 [`collect_files`] 的规则处理。
+///
+/// 结果按 `paths` 的顺序排列、每项内部按路径排序，去掉重复的文件以及匹配 `exclude` 的文件。
+pub fn collect_inputs(cfg: &SqllogConfig) -> io::Result<Vec<PathBuf>> {
+    let input = &cfg.input;
+    let paths = if input.paths.is_empty() {
+        std::slice::from_ref(&cfg.sqllog_path)
+    } else {
+        &input.paths[..]
+    };
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        let mut found =
+            expand_path(path, input.recursive).map_err(|e| InputError::wrap(path, e))?;
+        found.sort();
+        for file in found {
+            if !input.exclude.iter().any(|pat| is_excluded(pat, &file)) && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// 展开一个输入项：远程 URL、通配符、目录或单个文件
+fn expand_path(path: &str, recursive: bool) -> io::Result<Vec<PathBuf>> {
+    if path.contains("://") {
+        collect_files(path)
+    } else if is_glob(path) {
+        expand_glob(path)
+    } else if Path::new(path).is_dir() {
+        let mut found = Vec::new();
+        walk_dir(Path::new(path), recursive, &mut found)?;
+        found.retain(|p| is_log_file(p));
+        Ok(found)
+    } else {
+        collect_files(path)
+    }
+}
+
+/// 扩展名为 `log`，或压缩后的 `.log.gz` / `.log.zst`
+fn is_log_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let name = name
+        .strip_suffix(Compression::from_path(path).extension())
+        .unwrap_or(&name);
+    name.ends_with(".log")
+}
+
+fn walk_dir(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let p = entry?.path();
+        if p.is_dir() {
+            if recursive {
+                walk_dir(&p, recursive, out)?;
+            }
+        } else {
+            out.push(p);
+        }
+    }
+    Ok(())
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// 展开通配符：从第一个含通配符的路径段之前的目录开始查找，按相对该目录的路径匹配其余部分
+fn expand_glob(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let split = parts.iter().position(|p| is_glob(p)).unwrap_or(parts.len());
+    let base = match parts[..split].join("/") {
+        b if b.is_empty() && pattern.starts_with('/') => "/".to_string(),
+        b if b.is_empty() => ".".to_string(),
+        b => b,
+    };
+    let rest = parts[split..].join("/");
+    let recursive = rest.contains("**") || rest.contains('/');
+
+    let mut candidates = Vec::new();
+    if Path::new(&base).is_dir() {
+        walk_dir(Path::new(&base), recursive, &mut candidates)?;
+    }
+    Ok(candidates
+        .into_iter()
+        .filter(|p| {
+            p.strip_prefix(&base)
+                .is_ok_and(|rel| glob_match(&rest, &rel.to_string_lossy()))
+        })
+        .collect())
+}
+
+/// 不含 `/` 的模式匹配文件名，否则匹配完整路径
+fn is_excluded(pattern: &str, path: &Path) -> bool {
+    if pattern.contains('/') {
+        glob_match(pattern, &path.to_string_lossy())
+    } else {
+        path.file_name()
+            .is_some_and(|name| glob_match(pattern, &name.to_string_lossy()))
+    }
+}
+
+/// 通配符匹配：`*` 匹配除 `/` 外的任意字符，`?` 匹配除 `/` 外的单个字符，`**/` 匹配任意层目录
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn go(p: &[u8], t: &[u8]) -> bool {
+        match p {
+            [] => t.is_empty(),
+            [b'*', b'*', b'/', rest @ ..] => {
+                go(rest, t)
+                    || t.iter()
+                        .enumerate()
+                        .any(|(i, &c)| c == b'/' && go(rest, &t[i + 1..]))
+            }
+            [b'*', b'*', rest @ ..] => (0..=t.len()).any(|i| go(rest, &t[i..])),
+            [b'*', rest @ ..] => {
+                let limit = t.iter().position(|&c| c == b'/').unwrap_or(t.len());
+                (0..=limit).any(|i| go(rest, &t[i..]))
+            }
+            [b'?', rest @ ..] => t.first().is_some_and(|&c| c != b'/') && go(rest, &t[1..]),
+            [c, rest @ ..] => t.first() == Some(c) && go(rest, &t[1..]),
+        }
+    }
+    go(pattern.as_bytes(), text.as_bytes())
+}
+
+/// 每个实例中文件名最大（即最新）的文件
+fn latest_per_instance(files: &[PathBuf]) -> HashMap<String, &PathBuf> {
+    let mut latest: HashMap<String, &PathBuf> = HashMap::new();
+    for path in files {
+        if let Some(info) = InstanceInfo::from_path(path) {
+            let entry = latest.entry(info.instance).or_insert(path);
+            if path.file_name() > entry.file_name() {
+                *entry = path;
+            }
+        }
+    }
+    latest
+}
+
+/// 从 `files` 中挑出已轮转完成的 `dmsql_*.log` 文件，按文件名排序。
+///
+/// 同一实例中文件名最大（即最新）的文件视为仍在写入，不包含在内；
+/// 文件名不符合 `dmsql_<实例名>_<日期>_<时间>.log` 的文件被忽略。
+pub fn rotated_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    let latest = latest_per_instance(files);
+    let mut rotated: Vec<PathBuf> = files
+        .iter()
+        .filter(|p| InstanceInfo::from_path(p).is_some_and(|info| latest[&info.instance] != *p))
+        .cloned()
+        .collect();
+    rotated.sort();
+    rotated
+}
+
+/// 从 `files` 中挑出各实例仍在写入的文件（文件名最大的 `dmsql_*.log`），按文件名排序；
+/// 与 [`rotated_files`] 互补
+pub fn active_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut active: Vec<PathBuf> = latest_per_instance(files).into_values().cloned().collect();
+    active.sort();
+    active
+}
+
+/// 读取整个文件为字符串，非法的 UTF-8 字节以替换字符代替。
+pub fn read_text<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    Ok(bytes_to_string(fs::read(path)?, InputEncoding::Utf8))
+}
+
+/// 打开输入文件并解压，`compression` 为 `None` 时按扩展名推断，见 [`open_stream`]
+pub fn open_input(
+    path: &Path,
+    chunk_size: usize,
+    compression: Compression,
+) -> io::Result<Box<dyn Read + Send>> {
+    let compression = match compression {
+        Compression::None => Compression::from_path(path),
+        c => c,
+    };
+    compression.decoder(open_stream(path, chunk_size)?)
+}
+
+/// 打开输入文件用于流式读取，`chunk_size` 为流式读取的块大小。
+///
+/// 启用 `io-uring` 特性时在 Linux 上使用 io_uring 预读约一个块的数据，
+/// 使 I/O 与解析重叠；内核不支持 io_uring 时退回普通文件读取。
+/// `s3://` 路径以流的形式从对象存储下载，`http(s)://` URL 以流的形式下载并在中断时断点续传，
+/// `sftp://` 路径通过 SFTP 读取远程文件。
+pub fn open_stream(path: &Path, chunk_size: usize) -> io::Result<Box<dyn Read + Send>> {
+    #[cfg(feature = "sftp")]
+    if sftp::is_sftp_path(path) {
+        return sftp::open(path);
+    }
+    #[cfg(feature = "http")]
+    if http::is_http_url(path) {
+        return http::open(path);
+    }
+    #[cfg(feature = "object-store")]
+    if s3::is_s3_path(path) {
+        return s3::open(path);
+    }
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    {
+        let depth = chunk_size.div_ceil(uring::BLOCK_SIZE).max(2);
+        match uring::UringReader::open(path, depth) {
+            Ok(reader) => return Ok(Box::new(reader)),
+            Err(e) => warn!("io_uring 不可用，退回普通读取: {}", e),
+        }
+    }
+    #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+    let _ = chunk_size;
+    Ok(Box::new(fs::File::open(path)?))
+}
+
+/// 返回输入文件所属的实例名。
+///
+/// 文件名符合 `dmsql_<实例名>_<日期>_<时间>.log` 时返回其中的实例名，否则退化为文件名主干；
+/// 压缩文件先去掉 `.gz` / `.zst` 扩展名。
+pub fn instance_name(path: &Path) -> String {
+    let path = match Compression::from_path(path) {
+        Compression::None => path,
+        _ => Path::new(path.file_stem().unwrap_or_default()),
+    };
+    match InstanceInfo::from_path(path) {
+        Some(info) => info.instance,
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// 按 `encoding` 将字节转换为字符串，UTF-8、GBK 与 GB18030 中非法的字节以替换字符代替。
+pub(crate) fn bytes_to_string(bytes: Vec<u8>, encoding: InputEncoding) -> String {
+    match encoding {
+        InputEncoding::Utf8 => match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        },
+        InputEncoding::Latin1 => bytes.into_iter().map(char::from).collect(),
+        InputEncoding::Gbk => encoding_rs::GBK
+            .decode_without_bom_handling(&bytes)
+            .0
+            .into_owned(),
+        InputEncoding::Gb18030 => encoding_rs::GB18030
+            .decode_without_bom_handling(&bytes)
+            .0
+            .into_owned(),
+    }
+}
+
+/// 查找 `buf` 中最后一个记录起始位置（行首时间戳），不包括位置 0。
+fn last_record_start(buf: &[u8]) -> Option<usize> {
+    let mut end = buf.len();
+    while let Some(nl) = buf[..end].iter().rposition(|&b| b == b'\n') {
+        let p = nl + 1;
+        if p + 23 <= buf.len() && std::str::from_utf8(&buf[p..p + 23]).is_ok_and(is_ts_millis) {
+            return Some(p);
+        }
+        end = nl;
+    }
+    None
+}
+
+/// [`ChunkReader`] 读出的一块文本
+#[derive(Debug)]
+pub struct Chunk {
+    pub text: String,
+    /// 块在（解压后的）输入中占的字节数；解码非 UTF-8 输入时与 `text` 的长度不同
+    pub raw_len: usize,
+    /// 解码改变了字节时，各行行首在 `text` 与原始字节中的位置；为空表示两者逐字节相同
+    lines: Vec<(usize, usize)>,
+}
+
+impl Chunk {
+    /// 按 `encoding` 解码一块原始字节
+    fn decode(bytes: Vec<u8>, encoding: InputEncoding) -> Self {
+        let raw_len = bytes.len();
+        let identical = match encoding {
+            InputEncoding::Utf8 => std::str::from_utf8(&bytes).is_ok(),
+            _ => bytes.is_ascii(),
+        };
+        if identical {
+            return Self {
+                text: bytes_to_string(bytes, InputEncoding::Utf8),
+                raw_len,
+                lines: Vec::new(),
+            };
+        }
+        // 换行符是 ASCII，各编码下都逐字节保留，因此第 k 个行首在两边一一对应
+        let raw_lines = line_starts(&bytes);
+        let text = bytes_to_string(bytes, encoding);
+        let lines = line_starts(text.as_bytes())
+            .into_iter()
+            .zip(raw_lines)
+            .collect();
+        Self {
+            text,
+            raw_len,
+            lines,
+        }
+    }
+
+    /// `text` 中行首位置 `pos` 在原始字节中相对于块起始的偏移
+    pub fn raw_offset(&self, pos: usize) -> usize {
+        let i = self.lines.partition_point(|&(t, _)| t <= pos);
+        match i.checked_sub(1).map(|i| self.lines[i]) {
+            Some((t, r)) => r + (pos - t),
+            None => pos,
+        }
+    }
+}
+
+/// 各行的起始位置（含位置 0）
+fn line_starts(bytes: &[u8]) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(
+            bytes
+                .iter()
+                .enumerate()
+                .filter(|&(_, &b)| b == b'\n')
+                .map(|(i, _)| i + 1),
+        )
+        .collect()
+}
+
+/// 按块读取 sqllog 文本的流式读取器。
+///
+/// 每次返回约 `chunk_size` 字节的文本，且块只在记录起始处切分，
+/// 因此除第一块可能带有前导错误行外，每块都以完整的记录开头和结尾。
+/// 单条记录超过 `chunk_size` 时，该块会扩大到能容纳整条记录为止。
+pub struct ChunkReader<R> {
+    inner: R,
+    chunk_size: usize,
+    buf: Vec<u8>,
+    eof: bool,
+    encoding: InputEncoding,
+}
+
+impl<R: Read> ChunkReader<R> {
+    pub fn new(inner: R, chunk_size: usize) -> Self {
+        Self::with_buffer(inner, chunk_size, Vec::new())
+    }
+
+    /// 使用已有的缓冲区创建读取器
+    pub fn with_buffer(inner: R, chunk_size: usize, mut buf: Vec<u8>) -> Self {
+        buf.clear();
+        Self {
+            inner,
+            chunk_size: chunk_size.max(1),
+            buf,
+            eof: false,
+            encoding: InputEncoding::Utf8,
+        }
+    }
+
+    /// 设置输入文本的字符编码，默认 UTF-8
+    pub fn set_encoding(mut self, encoding: InputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// 取回内部缓冲区（读取完毕后调用以便复用）
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// 从底层读取数据，直到缓冲区达到 `target` 字节或到达文件末尾
+    fn fill(&mut self, target: usize) -> io::Result<()> {
+        if self.eof || self.buf.len() >= target {
+            return Ok(());
+        }
+        let want = target - self.buf.len();
+        let n = (&mut self.inner)
+            .take(want as u64)
+            .read_to_end(&mut self.buf)?;
+        // 未读满说明底层已到达文件末尾
+        self.eof = n < want;
+        Ok(())
+    }
+
+    /// 返回下一块文本，读取完毕时返回 None
+    pub fn next_chunk(&mut self) -> io::Result<Option<Chunk>> {
+        self.next_chunk_reusing(Vec::new())
+    }
+
+    /// 同 [`next_chunk`](Self::next_chunk)，但复用 `spare` 的容量作为下一块的读取缓冲区，
+    /// 用于在大量文件之间循环使用缓冲区，减少分配
+    pub fn next_chunk_reusing(&mut self, mut spare: Vec<u8>) -> io::Result<Option<Chunk>> {
+        let mut target = self.chunk_size;
+        loop {
+            self.fill(target)?;
+            if self.buf.is_empty() {
+                return Ok(None);
+            }
+            if self.eof {
+                spare.clear();
+                let chunk = std::mem::replace(&mut self.buf, spare);
+                return Ok(Some(Chunk::decode(chunk, self.encoding)));
+            }
+            if let Some(p) = last_record_start(&self.buf) {
+                // 只需把不足一条记录的剩余部分拷入新缓冲区
+                spare.clear();
+                spare.extend_from_slice(&self.buf[p..]);
+                self.buf.truncate(p);
+                let chunk = std::mem::replace(&mut self.buf, spare);
+                return Ok(Some(Chunk::decode(chunk, self.encoding)));
+            }
+            // 缓冲区内没有记录边界：继续扩大读取范围
+            target = self.buf.len() + self.chunk_size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::input::InputConfig;
+    use tempfile::tempdir;
+
+    #[test]
+    fn collect_files_from_dir_filters_and_sorts() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("b.log"), "").unwrap();
+        fs::write(dir.path().join("a.log"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let files = collect_files(dir.path()).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["a.log", "b.log"]);
+    }
+
+    #[test]
+    fn rotated_files_excludes_latest_per_instance() {
+        let files: Vec<PathBuf> = [
+            "/logs/dmsql_DM1_20250812_110000.log",
+            "/logs/dmsql_DM1_20250812_100000.log",
+            "/logs/dmsql_DM2_20250812_100000.log",
+            "/logs/other.log",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(
+            rotated_files(&files),
+            vec![PathBuf::from("/logs/dmsql_DM1_20250812_100000.log")]
+        );
+        assert_eq!(
+            active_files(&files),
+            vec![
+                PathBuf::from("/logs/dmsql_DM1_20250812_110000.log"),
+                PathBuf::from("/logs/dmsql_DM2_20250812_100000.log"),
+            ]
+        );
+    }
+
+    #[test]
+    fn instance_name_from_file_name() {
+        assert_eq!(
+            instance_name(Path::new("/logs/dmsql_DMSERVER_20250812_105700.log")),
+            "DMSERVER"
+        );
+        assert_eq!(instance_name(Path::new("/logs/other.log")), "other");
+    }
+
+    #[test]
+    fn chunk_reader_splits_on_record_boundaries() {
+        let mut text = String::from("garbage\n");
+        for n in 0..20 {
+            text.push_str(&format!(
+                "2025-08-12 10:57:{:02}.000 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app) [SEL] select {}\n",
+                n, n
+            ));
+        }
+        let mut reader = ChunkReader::new(text.as_bytes(), 50);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = reader.next_chunk().unwrap() {
+            assert_eq!(chunk.raw_len, chunk.text.len());
+            chunks.push(chunk.text);
+        }
+
+        assert_eq!(chunks.concat(), text);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks[1..] {
+            assert!(is_ts_millis(&chunk[..23]));
+        }
+    }
+
+    #[test]
+    fn collect_inputs_expands_globs_and_excludes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("a/old")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        for f in [
+            "a/x.log",
+            "a/x_bak.log",
+            "a/y.log.gz",
+            "a/notes.txt",
+            "a/old/z.log",
+            "b/w.log",
+        ] {
+            fs::write(root.join(f), "").unwrap();
+        }
+        let names = |cfg: &SqllogConfig| -> Vec<String> {
+            collect_inputs(cfg)
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        let p = |s: &str| root.join(s).to_string_lossy().into_owned();
+
+        let dir_only = SqllogConfig::new().set_sqllog_path(&p("a"));
+        assert_eq!(names(&dir_only), ["a/x.log", "a/x_bak.log", "a/y.log.gz"]);
+
+        let input = InputConfig::new()
+            .set_paths(vec![p("a"), p("**/*.log"), p("b/*.log")])
+            .set_recursive(true)
+            .set_exclude(vec!["*_bak.log".to_string()]);
+        let cfg = SqllogConfig::new().set_input(input);
+        assert_eq!(
+            names(&cfg),
+            ["a/old/z.log", "a/x.log", "a/y.log.gz", "b/w.log"]
+        );
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_match("*.log", "a.log"));
+        assert!(!glob_match("*.log", "d/a.log"));
+        assert!(glob_match("**/*.log", "a.log"));
+        assert!(glob_match("**/*.log", "d/e/a.log"));
+        assert!(glob_match("dmsql_??_*.log", "dmsql_DM_1.log"));
+        assert!(!glob_match("dmsql_??_*.log", "dmsql_DMS_1.log"));
+    }
+
+    #[test]
+    fn reads_compressed_latin1_input() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dmsql_DM1_20250812_100000.log.gz");
+        let mut w = crate::exporter::compress::Encoder::new(
+            fs::File::create(&path).unwrap(),
+            Compression::Gzip,
+        )
+        .unwrap();
+        io::Write::write_all(&mut w, b"caf\xe9\n").unwrap();
+        w.finish().unwrap();
+        drop(w);
+
+        let stream = open_input(&path, 1024, Compression::None).unwrap();
+        let mut reader = ChunkReader::new(stream, 1024).set_encoding(InputEncoding::Latin1);
+        let chunk = reader.next_chunk().unwrap().unwrap();
+        assert_eq!(chunk.text, "caf\u{e9}\n");
+        assert_eq!(chunk.raw_len, 5);
+        assert_eq!(instance_name(&path), "DM1");
+    }
+
+    #[test]
+    fn decodes_gbk_input() {
+        // "达梦" 的 GBK 编码
+        let bytes = b"select '\xb4\xef\xc3\xce' from dual\n".to_vec();
+        assert_eq!(
+            bytes_to_string(bytes.clone(), InputEncoding::Gbk),
+            "select '达梦' from dual\n"
+        );
+        assert_eq!(
+            bytes_to_string(bytes.clone(), InputEncoding::Gb18030),
+            "select '达梦' from dual\n"
+        );
+        assert!(bytes_to_string(bytes, InputEncoding::Utf8).contains('\u{fffd}'));
+    }
+
+    #[test]
+    fn chunk_maps_line_starts_to_raw_offsets() {
+        let raw = b"caf\xe9\n\xff\xfe x\nend\n".to_vec();
+        let latin1 = Chunk::decode(raw.clone(), InputEncoding::Latin1);
+        assert_eq!(latin1.raw_len, raw.len());
+        // é 解码为两字节，两个非法字节各变为三字节的替换字符
+        let lossy = Chunk::decode(raw, InputEncoding::Utf8);
+        for chunk in [latin1, lossy] {
+            let x = chunk.text.find('\n').unwrap() + 1;
+            let end = chunk.text.find("end").unwrap();
+            assert_eq!(chunk.raw_offset(0), 0);
+            assert_eq!(chunk.raw_offset(x), 5);
+            assert_eq!(chunk.raw_offset(end), 10);
+        }
+        assert_eq!(
+            Chunk::decode(b"a\nb".to_vec(), InputEncoding::Gbk).raw_offset(2),
+            2
+        );
+    }
+
+    #[test]
+    fn collect_files_single_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("one.txt");
+        fs::write(&file, "").unwrap();
+        assert_eq!(collect_files(&file).unwrap(), vec![file]);
+    }
+}
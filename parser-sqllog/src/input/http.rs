@@ -0,0 +1,175 @@
+//! HTTP(S) URL 输入：以流的形式下载日志文件，连接中断时用 Range 请求从断点续传
+
+use std::{
+    io::{self, Read},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use tracing::warn;
+
+/// 连续失败时最多重试的次数
+const MAX_RETRIES: u32 = 5;
+
+/// 首次重试前的等待时间，之后每次翻倍
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// 判断输入路径是否为 `http://` 或 `https://` URL
+pub fn is_http_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|s| s.starts_with("http://") || s.starts_with("https://"))
+}
+
+/// 以流的形式下载 URL 的内容，读取出错时自动断点续传
+pub fn open(path: &Path) -> io::Result<Box<dyn Read + Send>> {
+    let url = path.to_str().unwrap_or_default().to_string();
+    let fetch = move |offset| fetch(&url, offset);
+    Ok(Box::new(ResumingReader::new(fetch)?))
+}
+
+/// 发送 GET 请求，`offset` 大于 0 时只请求该字节之后的内容
+fn fetch(url: &str, offset: u64) -> io::Result<Box<dyn Read + Send>> {
+    let mut req = ureq::get(url);
+    if offset > 0 {
+        req = req.header("Range", format!("bytes={offset}-"));
+    }
+    let resp = req
+        .call()
+        .map_err(|e| io::Error::other(format!("请求 {url} 失败: {e}")))?;
+    // 服务器忽略 Range 时会从头返回整个文件，无法续传
+    if offset > 0 && resp.status().as_u16() != 206 {
+        return Err(io::Error::other(format!(
+            "服务器不支持 Range 请求，无法从第 {offset} 字节续传: {url}"
+        )));
+    }
+    Ok(Box::new(resp.into_body().into_reader()))
+}
+
+/// 出错时通过 `fetch(已读取字节数)` 重新建立连接并继续读取的读取器
+struct ResumingReader<F> {
+    fetch: F,
+    inner: Box<dyn Read + Send>,
+    pos: u64,
+    retries: u32,
+}
+
+impl<F> ResumingReader<F>
+where
+    F: FnMut(u64) -> io::Result<Box<dyn Read + Send>>,
+{
+    fn new(mut fetch: F) -> io::Result<Self> {
+        let inner = fetch(0)?;
+        Ok(Self {
+            fetch,
+            inner,
+            pos: 0,
+            retries: 0,
+        })
+    }
+}
+
+impl<F> Read for ResumingReader<F>
+where
+    F: FnMut(u64) -> io::Result<Box<dyn Read + Send>>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.inner.read(buf) {
+                Ok(n) => {
+                    self.pos += n as u64;
+                    if n > 0 {
+                        self.retries = 0;
+                    }
+                    return Ok(n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if self.retries < MAX_RETRIES => {
+                    let wait = RETRY_BACKOFF * 2u32.pow(self.retries);
+                    self.retries += 1;
+                    warn!(
+                        "下载中断（已读取 {} 字节）: {}，{:?} 后第 {} 次续传",
+                        self.pos, e, wait, self.retries
+                    );
+                    thread::sleep(wait);
+                    match (self.fetch)(self.pos) {
+                        Ok(inner) => self.inner = inner,
+                        Err(e) if self.retries < MAX_RETRIES => {
+                            warn!("续传失败: {}", e);
+                            self.inner = Box::new(FailedRead(Some(e)));
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// 重新连接失败时占位，下次读取时返回该错误以触发下一次重试
+struct FailedRead(Option<io::Error>);
+
+impl Read for FailedRead {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(self
+            .0
+            .take()
+            .unwrap_or_else(|| io::Error::other("连接已断开")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// 读取 `limit` 字节后出错的数据源
+    struct Flaky {
+        data: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Ok(0);
+            }
+            if self.limit == 0 {
+                return Err(io::Error::from(io::ErrorKind::ConnectionReset));
+            }
+            let n = buf.len().min(self.limit).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data.drain(..n);
+            self.limit -= n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn resumes_from_last_offset() {
+        let data: Vec<u8> = (0..=255).collect();
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let seen = offsets.clone();
+        let source = data.clone();
+        let mut reader = ResumingReader::new(move |offset| {
+            seen.lock().unwrap().push(offset);
+            Ok(Box::new(Flaky {
+                data: source[offset as usize..].to_vec(),
+                limit: 100,
+            }) as Box<dyn Read + Send>)
+        })
+        .unwrap();
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+        assert_eq!(*offsets.lock().unwrap(), vec![0, 100, 200]);
+    }
+
+    #[test]
+    fn detects_http_urls() {
+        assert!(is_http_url(Path::new("https://artifacts/logs/a.log")));
+        assert!(!is_http_url(Path::new("/data/logs/a.log")));
+    }
+}
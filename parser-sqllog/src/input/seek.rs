@@ -0,0 +1,259 @@
+//! 按时间戳在日志文件中定位：sqllog 按时间顺序写出，可以在文件的字节偏移上二分查找，
+//! 只读取几十个块就能跳到几十 GB 文件中的指定时刻，再按记录边界原样读出记录文本。
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use dm_database_parser::find_next_record_start;
+
+/// 每次探测读取的块大小；二分到区间小于该值后改为顺序扫描
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// 时间戳的格式，用于校验命令行给出的时间前缀
+const TS_TEMPLATE: &[u8] = b"0000-00-00 00:00:00.000";
+
+/// 校验并规范化命令行给出的时间：可以是完整时间戳的任意前缀（至少到日期），
+/// 如 `2025-08-12`、`2025-08-12 10:30`、`2025-08-12T10:30:00.5`；返回以空格分隔日期与时间的形式
+pub fn parse_ts_prefix(s: &str) -> Result<String, String> {
+    let ts = s.trim().replacen('T', " ", 1);
+    let ok = (10..=TS_TEMPLATE.len()).contains(&ts.len())
+        && ts.bytes().zip(TS_TEMPLATE).all(|(b, &t)| {
+            if t == b'0' {
+                b.is_ascii_digit()
+            } else {
+                b == t
+            }
+        });
+    if ok {
+        Ok(ts)
+    } else {
+        Err(format!(
+            "应为 YYYY-MM-DD[ HH:MM[:SS[.mmm]]] 格式的时间: {s}"
+        ))
+    }
+}
+
+/// 原始记录的时间戳（前 23 字节）；不以时间戳开头的内容返回 None
+pub fn raw_ts(record: &[u8]) -> Option<&str> {
+    let ts = std::str::from_utf8(record.get(..TS_TEMPLATE.len())?).ok()?;
+    dm_database_parser::is_ts_millis(ts).then_some(ts)
+}
+
+/// 返回偏移 `pos` 处或之后第一条记录的起始偏移与时间戳；之后没有记录时返回 None
+fn record_at_or_after<R: Read + Seek>(
+    reader: &mut R,
+    pos: u64,
+) -> io::Result<Option<(u64, String)>> {
+    // 从 pos 的前一个字节读起，以便判断 pos 处是否紧随换行符
+    let base = pos.saturating_sub(1);
+    let skip = (pos - base) as usize;
+    reader.seek(SeekFrom::Start(base))?;
+    let mut buf = Vec::new();
+    let mut from = skip;
+    loop {
+        let read = reader
+            .by_ref()
+            .take(BLOCK_SIZE as u64)
+            .read_to_end(&mut buf)?;
+        if let Some(start) = find_next_record_start(&buf, from)
+            && let Some(ts) = raw_ts(&buf[start..])
+        {
+            return Ok(Some((base + start as u64, ts.to_string())));
+        }
+        if read == 0 {
+            return Ok(None);
+        }
+        // 超长记录：继续读下一块，保留可能被块边界截断的时间戳
+        from = buf.len().saturating_sub(TS_TEMPLATE.len() + 1).max(skip);
+    }
+}
+
+/// 在按时间顺序写出的日志中二分查找第一条时间戳不早于 `ts`（可为时间前缀）的记录，
+/// 返回其起始偏移；所有记录都早于 `ts` 时返回流的长度。
+pub fn seek_ts<R: Read + Seek>(reader: &mut R, ts: &str) -> io::Result<u64> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    // lo 为 0 或一条早于 ts 的记录的起始；hi 之后的第一条记录（如有）不早于 ts
+    let mut lo = 0u64;
+    let mut hi = len;
+    while hi - lo > BLOCK_SIZE as u64 {
+        let mid = lo + (hi - lo) / 2;
+        match record_at_or_after(reader, mid)? {
+            Some((start, t)) if start < hi && t.as_str() < ts => lo = start,
+            _ => hi = mid,
+        }
+    }
+    let mut pos = lo;
+    while let Some((start, t)) = record_at_or_after(reader, pos)? {
+        if t.as_str() >= ts {
+            return Ok(start);
+        }
+        pos = start + 1;
+    }
+    Ok(len)
+}
+
+/// 从流的末尾向前按块读取，返回倒数第 `n` 条记录的起始偏移；记录不足 `n` 条时返回第一条记录的起始
+/// （没有记录时为 0）
+pub fn tail_offset<R: Read + Seek>(reader: &mut R, n: usize) -> io::Result<u64> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    if n == 0 {
+        return Ok(len);
+    }
+    // 已读部分 [base, len) 中找到的记录起始数，以及其中最早的一个；
+    // carry 为 [base, base + 23) 的内容，用于判断跨块的时间戳与 base 处的记录起始
+    let (mut found, mut earliest) = (0usize, None);
+    let mut carry: Vec<u8> = Vec::new();
+    let mut base = len;
+    loop {
+        let next = base.saturating_sub(BLOCK_SIZE as u64);
+        let mut buf = vec![0u8; (base - next) as usize];
+        reader.seek(SeekFrom::Start(next))?;
+        reader.read_exact(&mut buf)?;
+        buf.extend_from_slice(&carry);
+
+        // 只查找新块中的位置，以及此前缺少前一字节而未判断的 base 处；next > 0 时首个字节只用于判断换行
+        let mut starts = Vec::new();
+        let mut pos = if next > 0 { 1 } else { 0 };
+        while let Some(start) = find_next_record_start(&buf, pos) {
+            starts.push(start);
+            pos = start + 1;
+        }
+        if found + starts.len() >= n {
+            return Ok(next + starts[starts.len() - (n - found)] as u64);
+        }
+        found += starts.len();
+        if let Some(&first) = starts.first() {
+            earliest = Some(next + first as u64);
+        }
+        if next == 0 {
+            return Ok(earliest.unwrap_or(0));
+        }
+        buf.truncate(TS_TEMPLATE.len());
+        carry = buf;
+        base = next;
+    }
+}
+
+/// 按记录边界依次读出原始记录文本（字节原样保留，包括换行）；
+/// 第一条记录之前不以时间戳开头的内容单独作为一项返回
+pub struct RawRecords<R> {
+    inner: R,
+    buf: Vec<u8>,
+    /// `buf` 中已确认不含下一条记录起始的长度，超长记录读入新块时不必从头查找
+    scanned: usize,
+    eof: bool,
+}
+
+impl<R: Read> RawRecords<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            scanned: 0,
+            eof: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for RawRecords<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // 至少从 1 开始查找，跳过当前记录自身的起始
+            let from = self.scanned.saturating_sub(TS_TEMPLATE.len() + 1).max(1);
+            if let Some(end) = find_next_record_start(&self.buf, from) {
+                let rest = self.buf.split_off(end);
+                self.scanned = 0;
+                return Some(Ok(std::mem::replace(&mut self.buf, rest)));
+            }
+            self.scanned = self.buf.len();
+            if self.eof {
+                self.scanned = 0;
+                return (!self.buf.is_empty()).then(|| Ok(std::mem::take(&mut self.buf)));
+            }
+            match self
+                .inner
+                .by_ref()
+                .take(BLOCK_SIZE as u64)
+                .read_to_end(&mut self.buf)
+            {
+                Ok(0) => self.eof = true,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn log(n: usize) -> Vec<u8> {
+        let mut text = b"garbage\n".to_vec();
+        for i in 0..n {
+            // 每秒 10 条记录，部分记录跨多行
+            let ts = format!(
+                "2025-08-12 10:{:02}:{:02}.{:03}",
+                i / 600,
+                i / 10 % 60,
+                i % 10 * 100
+            );
+            text.extend_from_slice(
+                format!("{ts} (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:app) [SEL] select {i}\nfrom t\n").as_bytes(),
+            );
+        }
+        text
+    }
+
+    #[test]
+    fn seeks_by_timestamp_prefix() {
+        let text = log(20_000);
+        let mut cur = Cursor::new(text.clone());
+        let start = seek_ts(&mut cur, "2025-08-12 10:15:30").unwrap() as usize;
+        assert!(text[start..].starts_with(b"2025-08-12 10:15:30.000 "));
+        assert_eq!(seek_ts(&mut cur, "2025-08-12").unwrap(), 8);
+        assert_eq!(seek_ts(&mut cur, "2025-08-13").unwrap(), text.len() as u64);
+
+        cur.set_position(start as u64);
+        let records: Vec<Vec<u8>> = RawRecords::new(cur)
+            .take(2)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(raw_ts(&records[1]), Some("2025-08-12 10:15:30.100"));
+        assert!(records[0].ends_with(b"select 9300\nfrom t\n"));
+
+        let first = RawRecords::new(Cursor::new(text.clone()))
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, b"garbage\n");
+
+        let mut cur = Cursor::new(text.clone());
+        let tail = tail_offset(&mut cur, 3).unwrap() as usize;
+        assert!(text[tail..].starts_with(b"2025-08-12 10:33:19.700 "));
+        assert_eq!(tail_offset(&mut cur, 0).unwrap(), text.len() as u64);
+        assert_eq!(tail_offset(&mut cur, 30_000).unwrap(), 8);
+        // 与逐条查找全部记录起始的结果一致，包括跨块边界的记录
+        let mut starts = Vec::new();
+        let mut pos = 0;
+        while let Some(start) = find_next_record_start(&text, pos) {
+            starts.push(start as u64);
+            pos = start + 1;
+        }
+        for n in [1, 2, 700, 701, 1500, starts.len() - 1, starts.len()] {
+            assert_eq!(
+                tail_offset(&mut cur, n).unwrap(),
+                starts[starts.len() - n],
+                "n = {n}"
+            );
+        }
+        assert_eq!(
+            parse_ts_prefix("2025-08-12T10:30").unwrap(),
+            "2025-08-12 10:30"
+        );
+        assert!(parse_ts_prefix("2025-8-12").is_err());
+        assert!(parse_ts_prefix("2025-08").is_err());
+    }
+}
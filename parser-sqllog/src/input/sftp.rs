@@ -0,0 +1,229 @@
+//! SFTP 输入：直接读取远程主机（如客户跳板机）上的日志文件，无需先复制到本地。
+//!
+//! 输入路径形如 `sftp://[user@]host[:port]/path`，省略的部分取自 `[sftp]` 配置；
+//! `sftp:///path` 使用配置中的默认主机。认证使用配置的私钥文件，未配置时使用 ssh-agent。
+
+use std::{
+    env,
+    io::{self, Read},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use lazy_static::lazy_static;
+use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+
+use crate::{config::sftp::SftpConfig, input::is_log_file};
+
+/// SFTP 路径前缀
+pub const SCHEME: &str = "sftp://";
+
+lazy_static! {
+    // 输入层的 collect_files / open_stream 只接收路径，连接配置在启动时设置一次
+    static ref CONFIG: Mutex<SftpConfig> = Mutex::new(SftpConfig::default());
+}
+
+/// 设置 `sftp://` 输入使用的连接配置
+pub fn configure(cfg: SftpConfig) {
+    *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = cfg;
+}
+
+/// 判断输入路径是否为 `sftp://` 路径
+pub fn is_sftp_path(path: &Path) -> bool {
+    path.to_str().is_some_and(|s| s.starts_with(SCHEME))
+}
+
+/// 远程路径指向文件时返回该文件，指向目录时返回目录下（不递归）所有 `.log` 文件（以及压缩的 `.log.gz` / `.log.zst`），按文件名排序
+pub fn list(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let target = Target::parse(path, &config())?;
+    let sftp = connect(&target)?;
+    let remote = Path::new(&target.path);
+    if sftp.stat(remote)?.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut names: Vec<String> = sftp
+        .readdir(remote)?
+        .into_iter()
+        .filter(|(p, stat)| stat.is_file() && is_log_file(p))
+        .filter_map(|(p, _)| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    let base = path.to_string_lossy();
+    let base = base.trim_end_matches('/');
+    Ok(names
+        .into_iter()
+        .map(|n| PathBuf::from(format!("{base}/{n}")))
+        .collect())
+}
+
+/// 以流的形式读取远程文件；每个文件使用独立的连接
+pub fn open(path: &Path) -> io::Result<Box<dyn Read + Send>> {
+    let target = Target::parse(path, &config())?;
+    let sftp = connect(&target)?;
+    Ok(Box::new(sftp.open(Path::new(&target.path))?))
+}
+
+fn config() -> SftpConfig {
+    CONFIG.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 解析后的连接目标
+#[derive(Debug, PartialEq, Eq)]
+struct Target {
+    user: String,
+    host: String,
+    port: u16,
+    path: String,
+    key_path: String,
+    passphrase: Option<String>,
+    known_hosts: String,
+    strict_host_key: bool,
+}
+
+impl Target {
+    fn parse(path: &Path, cfg: &SftpConfig) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{msg}: {}", path.display()),
+            )
+        };
+        let rest = path
+            .to_str()
+            .and_then(|s| s.strip_prefix(SCHEME))
+            .ok_or_else(|| invalid("不是 SFTP 路径"))?;
+        let (authority, remote) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => return Err(invalid("SFTP 路径缺少远程路径")),
+        };
+        let (user, hostport) = match authority.rsplit_once('@') {
+            Some((u, h)) => (u.to_string(), h),
+            None => (cfg.user.clone(), authority),
+        };
+        let (host, port) = match hostport.rsplit_once(':') {
+            Some((h, p)) => (
+                h.to_string(),
+                p.parse().map_err(|_| invalid("SFTP 端口无效"))?,
+            ),
+            None => (hostport.to_string(), cfg.port),
+        };
+        let host = if host.is_empty() {
+            cfg.host.clone()
+        } else {
+            host
+        };
+        if host.is_empty() {
+            return Err(invalid("SFTP 路径缺少主机，且未配置 sftp.host"));
+        }
+        if user.is_empty() {
+            return Err(invalid("SFTP 路径缺少用户名，且未配置 sftp.user"));
+        }
+        Ok(Self {
+            user,
+            host,
+            port,
+            path: remote.to_string(),
+            key_path: expand_home(&cfg.key_path),
+            passphrase: cfg.passphrase.clone(),
+            known_hosts: if cfg.known_hosts.is_empty() {
+                expand_home("~/.ssh/known_hosts")
+            } else {
+                expand_home(&cfg.known_hosts)
+            },
+            strict_host_key: cfg.strict_host_key,
+        })
+    }
+}
+
+/// 建立 SSH 连接、校验主机密钥并完成认证
+fn connect(target: &Target) -> io::Result<Sftp> {
+    let tcp = TcpStream::connect((target.host.as_str(), target.port))?;
+    let mut session = Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+    verify_host_key(&session, target)?;
+
+    if target.key_path.is_empty() {
+        session.userauth_agent(&target.user)?;
+    } else {
+        session.userauth_pubkey_file(
+            &target.user,
+            None,
+            Path::new(&target.key_path),
+            target.passphrase.as_deref(),
+        )?;
+    }
+    if !session.authenticated() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("SFTP 认证失败: {}@{}", target.user, target.host),
+        ));
+    }
+    Ok(session.sftp()?)
+}
+
+/// 按 known_hosts 校验主机密钥：密钥不符时总是拒绝，主机未登记时仅在严格模式下拒绝
+fn verify_host_key(session: &Session, target: &Target) -> io::Result<()> {
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| io::Error::other("无法获取主机密钥"))?;
+    let mut known = session.known_hosts()?;
+    // known_hosts 文件不存在时视为没有登记任何主机
+    let _ = known.read_file(Path::new(&target.known_hosts), KnownHostFileKind::OpenSSH);
+    let refused = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "主机密钥校验失败（{reason}）: {}:{}",
+                target.host, target.port
+            ),
+        )
+    };
+    match known.check_port(&target.host, target.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(refused("密钥与 known_hosts 不符")),
+        CheckResult::NotFound if target.strict_host_key => Err(refused(
+            "主机不在 known_hosts 中，可先 ssh 登录一次或设置 sftp.strict_host_key = false",
+        )),
+        CheckResult::NotFound => Ok(()),
+        CheckResult::Failure => Err(refused("无法完成校验")),
+    }
+}
+
+/// 展开开头的 `~/` 为用户主目录
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{rest}", home.trim_end_matches('/')),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_target_with_config_defaults() {
+        let cfg = SftpConfig::new()
+            .set_host("jump")
+            .set_user("dmdba")
+            .set_port(2222);
+
+        let t = Target::parse(Path::new("sftp:///dm/log/a.log"), &cfg).unwrap();
+        assert_eq!(
+            (t.user.as_str(), t.host.as_str(), t.port, t.path.as_str()),
+            ("dmdba", "jump", 2222, "/dm/log/a.log")
+        );
+
+        let t = Target::parse(Path::new("sftp://root@10.0.0.1:22/dm/log"), &cfg).unwrap();
+        assert_eq!(
+            (t.user.as_str(), t.host.as_str(), t.port, t.path.as_str()),
+            ("root", "10.0.0.1", 22, "/dm/log")
+        );
+
+        assert!(Target::parse(Path::new("sftp:///dm"), &SftpConfig::new()).is_err());
+        assert!(Target::parse(Path::new("sftp://jump"), &cfg).is_err());
+        assert!(is_sftp_path(Path::new("sftp://jump/dm")));
+    }
+}
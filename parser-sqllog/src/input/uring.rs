@@ -0,0 +1,195 @@
+//! 基于 io_uring 的预读文件读取器（Linux，`io-uring` 特性）
+//!
+//! 始终保持若干个读请求在内核中排队，使磁盘/网络文件系统 I/O 与下游的解析并行进行。
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    os::unix::{fs::FileExt, io::AsRawFd},
+    path::Path,
+};
+
+use io_uring::{IoUring, opcode, types};
+
+/// 单个读请求的大小
+pub(crate) const BLOCK_SIZE: usize = 256 * 1024;
+
+/// 一个已提交的读请求
+struct Slot {
+    offset: u64,
+    buf: Vec<u8>,
+    /// 完成结果：读取的字节数或负的 errno，未完成时为 None
+    result: Option<i32>,
+}
+
+/// 以 io_uring 预读实现 [`Read`] 的文件读取器，按文件顺序返回数据
+pub struct UringReader {
+    ring: IoUring,
+    file: File,
+    len: u64,
+    depth: usize,
+    /// 下一个待提交请求的偏移
+    next_offset: u64,
+    /// 下一个待提交 / 待消费请求的序号
+    next_submit: u64,
+    next_read: u64,
+    slots: HashMap<u64, Slot>,
+    current: Vec<u8>,
+    pos: usize,
+}
+
+impl UringReader {
+    /// 打开文件，最多保持 `depth` 个读请求在途
+    pub fn open<P: AsRef<Path>>(path: P, depth: usize) -> io::Result<Self> {
+        let depth = depth.clamp(1, 64);
+        let ring = IoUring::new(depth as u32)?;
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            ring,
+            file,
+            len,
+            depth,
+            next_offset: 0,
+            next_submit: 0,
+            next_read: 0,
+            slots: HashMap::new(),
+            current: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    /// 补充读请求直到在途数量达到 `depth` 或已覆盖整个文件
+    fn submit_ahead(&mut self) -> io::Result<()> {
+        let mut pushed = false;
+        while self.slots.len() < self.depth && self.next_offset < self.len {
+            let len = BLOCK_SIZE.min((self.len - self.next_offset) as usize);
+            let mut slot = Slot {
+                offset: self.next_offset,
+                buf: vec![0; len],
+                result: None,
+            };
+            let entry = opcode::Read::new(
+                types::Fd(self.file.as_raw_fd()),
+                slot.buf.as_mut_ptr(),
+                len as u32,
+            )
+            .offset(slot.offset)
+            .build()
+            .user_data(self.next_submit);
+            // SAFETY: 缓冲区归 `slots` 所有，直到请求完成前不会被释放（见 Drop）
+            unsafe {
+                if self.ring.submission().push(&entry).is_err() {
+                    break;
+                }
+            }
+            self.slots.insert(self.next_submit, slot);
+            self.next_submit += 1;
+            self.next_offset += len as u64;
+            pushed = true;
+        }
+        if pushed {
+            self.ring.submit()?;
+        }
+        Ok(())
+    }
+
+    /// 等待至少一个请求完成并记录结果
+    fn wait_completion(&mut self) -> io::Result<()> {
+        self.ring.submit_and_wait(1)?;
+        for cqe in self.ring.completion() {
+            if let Some(slot) = self.slots.get_mut(&cqe.user_data()) {
+                slot.result = Some(cqe.result());
+            }
+        }
+        Ok(())
+    }
+
+    /// 取出下一个按顺序完成的块，文件读完时返回 None
+    fn next_block(&mut self) -> io::Result<Option<Vec<u8>>> {
+        self.submit_ahead()?;
+        if !self.slots.contains_key(&self.next_read) {
+            return Ok(None);
+        }
+        while self.slots[&self.next_read].result.is_none() {
+            self.wait_completion()?;
+        }
+        let mut slot = self.slots.remove(&self.next_read).expect("slot exists");
+        self.next_read += 1;
+
+        let res = slot.result.unwrap_or_default();
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        // 短读：用同步读取补齐，保证与后续已提交的块首尾相接
+        let mut filled = res as usize;
+        while filled < slot.buf.len() {
+            let n = self
+                .file
+                .read_at(&mut slot.buf[filled..], slot.offset + filled as u64)?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        slot.buf.truncate(filled);
+        Ok(Some(slot.buf))
+    }
+}
+
+impl Read for UringReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.current.len() {
+            match self.next_block()? {
+                Some(block) => {
+                    self.current = block;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = out.len().min(self.current.len() - self.pos);
+        out[..n].copy_from_slice(&self.current[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Drop for UringReader {
+    fn drop(&mut self) {
+        // 内核仍可能写入在途请求的缓冲区，必须等其全部完成后才能释放
+        while self.slots.values().any(|s| s.result.is_none()) {
+            if self.wait_completion().is_err() {
+                // 无法确认完成时宁可泄漏缓冲区也不能释放
+                for (_, slot) in self.slots.drain() {
+                    std::mem::forget(slot.buf);
+                }
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reads_file_in_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dmsql_DM1_20250812_105700.log");
+        let data: Vec<u8> = (0..BLOCK_SIZE * 3 + 123).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut reader = match UringReader::open(&path, 2) {
+            Ok(r) => r,
+            // 内核不支持或被禁用 io_uring 时跳过
+            Err(_) => return,
+        };
+        let mut got = Vec::new();
+        reader.read_to_end(&mut got).unwrap();
+        assert_eq!(got, data);
+    }
+}
@@ -1,13 +1,25 @@
+pub mod analysis;
+pub mod anonymize;
 pub mod command;
 pub mod config;
+pub mod dedup;
 pub mod error;
+pub mod exporter;
+pub mod hmac;
+pub mod input;
+pub mod lock;
 pub mod logging;
+pub mod pipeline;
+pub mod progress;
+pub mod resource;
+pub mod rules;
+pub mod sort;
 
 // 重新导出主要的公共接口
 pub use command::cli::Cli;
 pub use config::logging::LogConfig;
 pub use error::ConfigParseResult;
-pub use logging::{init_default_logging, init_logging};
+pub use logging::{build_dispatch, init_default_logging, init_logging, try_init_logging};
 
 /// 库版本信息
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
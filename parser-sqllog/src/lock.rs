@@ -0,0 +1,138 @@
+//! 目录锁：防止多个实例同时处理同一个目录
+
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use tracing::warn;
+
+/// 锁文件名，位于被处理的目录中
+pub const LOCK_FILE_NAME: &str = ".parser-sqllog.lock";
+
+/// 已持有的目录锁。
+///
+/// 互斥由锁文件上的操作系统建议锁（`flock` / `LockFileEx`）保证，进程退出后由系统自动释放，
+/// 因此不存在陈旧锁；文件中的 PID 只用于提示持有者。drop 时清空 PID 并释放锁，文件本身保留。
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl LockFile {
+    /// 在 `dir` 中获取目录锁。
+    ///
+    /// 锁已被其他实例持有时返回 `AlreadyExists` 错误；`force` 为 true 时删除现有锁文件并在新文件上加锁，
+    /// 原持有者此后不再与本实例互斥（仅在确认其他实例已卡死时使用）。
+    pub fn acquire<P: AsRef<Path>>(dir: P, force: bool) -> io::Result<Self> {
+        let path = dir.as_ref().join(LOCK_FILE_NAME);
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => {
+                    let holder =
+                        read_pid(&path).map_or_else(|| "未知".to_string(), |p| p.to_string());
+                    if !force {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!(
+                                "目录已被进程 {holder} 锁定: {}（确认无其他实例运行后可使用 --force）",
+                                path.display()
+                            ),
+                        ));
+                    }
+                    warn!("强制接管锁文件 {}（持有者 PID {holder}）", path.display());
+                    match fs::remove_file(&path) {
+                        Ok(()) => {}
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                        Err(e) => return Err(e),
+                    }
+                    continue;
+                }
+                Err(TryLockError::Error(e)) => return Err(e),
+            }
+            if let Some(pid) = read_pid(&path) {
+                warn!("上次运行（PID {pid}）未正常释放锁文件 {}", path.display());
+            }
+            file.set_len(0)?;
+            writeln!(&file, "{}", std::process::id())?;
+            file.sync_all()?;
+            return Ok(Self { path, file });
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // 锁文件不删除：删除与其他实例打开文件之间存在竞争，可能让两个实例锁住不同的文件
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// 读取锁文件中记录的 PID，文件不存在或内容无法解析时返回 None
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn lock_is_exclusive_until_dropped() {
+        let dir = tempdir().unwrap();
+        let lock = LockFile::acquire(dir.path(), false).unwrap();
+        assert_eq!(read_pid(lock.path()), Some(std::process::id()));
+
+        let err = LockFile::acquire(dir.path(), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        drop(lock);
+        assert_eq!(read_pid(&dir.path().join(LOCK_FILE_NAME)), None);
+        LockFile::acquire(dir.path(), false).unwrap();
+    }
+
+    #[test]
+    fn force_takes_over_existing_lock() {
+        let dir = tempdir().unwrap();
+        let _held = LockFile::acquire(dir.path(), false).unwrap();
+        let lock = LockFile::acquire(dir.path(), true).unwrap();
+        assert_eq!(read_pid(lock.path()), Some(std::process::id()));
+        // 接管后的锁仍然互斥
+        let err = LockFile::acquire(dir.path(), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn leftover_lock_file_without_holder_is_reused() {
+        let dir = tempdir().unwrap();
+        // 进程被杀死后留下的锁文件：内容残留但没有进程持有系统锁
+        fs::write(dir.path().join(LOCK_FILE_NAME), "4294967295\n").unwrap();
+        let lock = LockFile::acquire(dir.path(), false).unwrap();
+        assert_eq!(read_pid(lock.path()), Some(std::process::id()));
+    }
+
+    #[test]
+    fn unparseable_lock_file_without_holder_is_reused() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(LOCK_FILE_NAME), "not a pid").unwrap();
+        let lock = LockFile::acquire(dir.path(), false).unwrap();
+        assert_eq!(read_pid(lock.path()), Some(std::process::id()));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -1,6 +1,17 @@
+use dm_database_parser::civil_from_days;
 use lazy_static::lazy_static;
-use std::sync::Mutex;
-use tracing_appender::non_blocking::WorkerGuard;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime as StdSystemTime, UNIX_EPOCH},
+};
+use tracing::Dispatch;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
 use tracing_subscriber::{
     EnvFilter, Registry,
     fmt::{self, time::SystemTime},
@@ -9,9 +20,17 @@ use tracing_subscriber::{
 
 use crate::{LogConfig, error::LogError, error::LogResult};
 
+#[cfg(unix)]
+pub mod syslog;
+
 lazy_static! {
     // 保存 WorkerGuard 防止其被 drop。使用 Mutex 以便在多线程中安全写入一次。
-    static ref LOG_GUARD: Mutex<Option<WorkerGuard>> = Mutex::new(None);
+    static ref LOG_GUARD: Mutex<Option<LogGuard>> = Mutex::new(None);
+}
+
+/// 日志输出需要保持存活的资源，drop 时把尚未写出的日志刷到文件
+pub struct LogGuard {
+    _file: Option<WorkerGuard>,
 }
 
 /// 日志初始化
@@ -26,12 +45,42 @@ pub fn init_logging(config: &LogConfig) -> LogResult<()> {
     {
         return Ok(());
     }
-    // 创建环境过滤器，默认使用配置的级别
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(config.level.clone()));
+    let (dispatch, guard) = build_dispatch(config)?;
+
+    tracing::dispatcher::set_global_default(dispatch)
+        .map_err(|e| LogError::Init(format!("failed to set global subscriber: {}", e)))?;
+
+    // 保持 guard
+    *LOG_GUARD
+        .lock()
+        .map_err(|e| LogError::Init(format!("mutex poisoned: {}", e)))? = Some(guard);
+
+    Ok(())
+}
+
+/// 尚未设置全局订阅者时初始化日志并返回 `true`；宿主程序已有自己的 tracing 设置时不做任何事，返回 `false`
+pub fn try_init_logging(config: &LogConfig) -> LogResult<bool> {
+    if tracing::dispatcher::has_been_set() {
+        return Ok(false);
+    }
+    init_logging(config)?;
+    Ok(true)
+}
 
-    // 控制台输出层
+/// 按配置构建日志订阅者但不设为全局默认，供嵌入本库的程序在局部使用：
+/// `tracing::dispatcher::with_default(&dispatch, || ...)`。
+/// 返回的 guard 需在使用期间保持存活。
+pub fn build_dispatch(config: &LogConfig) -> LogResult<(Dispatch, LogGuard)> {
+    // 创建环境过滤器，默认使用配置的级别及按模块设置的级别
+    let env_filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(config.filter_directives())
+            .map_err(|e| LogError::Init(format!("invalid log filter: {}", e)))?,
+    };
+
+    // 控制台输出层，写到标准错误，标准输出留给导出数据
     let console_layer = fmt::layer()
+        .with_writer(std::io::stderr)
         .with_timer(SystemTime)
         .with_target(true)
         // 显示文件和行号，可以帮助定位到函数（如需精确函数名，请使用 #[tracing::instrument]）
@@ -41,36 +90,186 @@ pub fn init_logging(config: &LogConfig) -> LogResult<()> {
         .with_thread_names(true)
         .with_ansi(true);
 
-    // 文件输出层 - 每日轮换，输出到指定路径，文件名前缀为 sqllog
-    let file_appender = tracing_appender::rolling::daily(&config.path, "sqllog");
-    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+    // 启用 syslog 且不保留文件时不写日志文件
+    let write_files = !config.syslog.enabled || config.syslog.keep_files;
 
-    let file_layer = fmt::layer()
-        .with_writer(non_blocking)
-        .with_timer(SystemTime)
-        .with_target(true)
-        // 在文件日志中也包含文件和行号
-        .with_file(true)
-        .with_line_number(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_ansi(false); // 文件中不使用颜色
+    // 启动时清理过期的日志文件，失败不影响日志初始化
+    let cleanup = if write_files {
+        cleanup_logs(config)
+    } else {
+        Ok(0)
+    };
+
+    // 文件输出层 - 每日轮换，输出到指定路径，文件名前缀可配置
+    let (file_layer, guard) = if write_files {
+        // 保留策略只由 cleanup_logs 执行，不使用 tracing-appender 的 max_log_files：
+        // 后者会删除所有以前缀开头的文件
+        let file_appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(&config.file_prefix)
+            .build(&config.path)
+            .map_err(|e| LogError::Init(format!("failed to create log file: {}", e)))?;
+        let (non_blocking, guard) = tracing_appender::non_blocking(PruningAppender {
+            inner: file_appender,
+            config: config.clone(),
+            day: utc_day(StdSystemTime::now()),
+        });
+
+        let file_layer = fmt::layer()
+            .with_writer(non_blocking)
+            .with_timer(SystemTime)
+            .with_target(true)
+            // 在文件日志中也包含文件和行号
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_ansi(false); // 文件中不使用颜色
+        (Some(file_layer), Some(guard))
+    } else {
+        (None, None)
+    };
 
-    // 将层添加到订阅者并设置为全局默认
+    // syslog 输出层，时间与主机名由 syslog 添加
+    let syslog_layer = if config.syslog.enabled {
+        Some(syslog_layer(config)?)
+    } else {
+        None
+    };
+
+    // 将层添加到订阅者
     let subscriber = Registry::default()
         .with(env_filter)
         .with(console_layer)
-        .with(file_layer);
+        .with(file_layer)
+        .with(syslog_layer);
+    let dispatch = Dispatch::new(subscriber);
 
-    tracing::subscriber::set_global_default(subscriber)
-        .map_err(|e| LogError::Init(format!("failed to set global subscriber: {}", e)))?;
+    tracing::dispatcher::with_default(&dispatch, || match cleanup {
+        Ok(0) => {}
+        Ok(n) => tracing::info!("已清理 {} 个过期的日志文件", n),
+        Err(e) => tracing::warn!("清理日志文件失败: {}", e),
+    });
 
-    // 保持 guard
-    *LOG_GUARD
-        .lock()
-        .map_err(|e| LogError::Init(format!("mutex poisoned: {}", e)))? = Some(guard);
+    Ok((dispatch, LogGuard { _file: guard }))
+}
 
-    Ok(())
+#[cfg(unix)]
+fn syslog_layer<S>(config: &LogConfig) -> LogResult<impl tracing_subscriber::Layer<S>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let writer = syslog::SyslogWriter::connect(&config.syslog).map_err(|e| {
+        LogError::Init(format!(
+            "failed to connect to syslog {}: {}",
+            config.syslog.socket, e
+        ))
+    })?;
+    Ok(fmt::layer()
+        .with_writer(writer)
+        .without_time()
+        .with_target(true)
+        .with_ansi(false))
+}
+
+#[cfg(not(unix))]
+fn syslog_layer<S>(_config: &LogConfig) -> LogResult<fmt::Layer<S>> {
+    Err(LogError::Init(
+        "syslog is only supported on Unix".to_string(),
+    ))
+}
+
+/// 每日轮换的日志文件，跨天（即将切换到新文件）时先按保留策略清理旧文件，
+/// 使长时间运行的进程同样遵守 `max_age_days` / `max_files`
+struct PruningAppender {
+    inner: RollingFileAppender,
+    config: LogConfig,
+    day: u64,
+}
+
+impl Write for PruningAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let day = utc_day(StdSystemTime::now());
+        if day != self.day {
+            self.day = day;
+            // 清理失败不影响日志写入
+            let _ = cleanup_logs(&self.config);
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 自 1970-01-01 起的 UTC 天数；tracing-appender 按 UTC 日期命名日志文件
+fn utc_day(t: StdSystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400)
+}
+
+/// 文件名是否为 `<prefix>.YYYY-MM-DD`，即本程序按天滚动写出的日志文件
+fn is_log_file_name(name: &str, prefix: &str) -> bool {
+    let Some(date) = name
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('.'))
+    else {
+        return false;
+    };
+    date.len() == 10
+        && date.bytes().enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// 删除日志目录中超过 `max_age_days` 天或超出 `max_files` 个（按修改时间保留最新的）的日志文件，
+/// 只处理名为 `<file_prefix>.YYYY-MM-DD` 的文件，返回删除的文件数
+pub fn cleanup_logs(config: &LogConfig) -> io::Result<usize> {
+    cleanup_logs_at(config, StdSystemTime::now())
+}
+
+fn cleanup_logs_at(config: &LogConfig, now: StdSystemTime) -> io::Result<usize> {
+    if config.max_age_days == 0 && config.max_files == 0 {
+        return Ok(0);
+    }
+    let dir = Path::new(&config.path);
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let (y, m, d) = civil_from_days(utc_day(now) as i64);
+    let today = format!("{}.{y:04}-{m:02}-{d:02}", config.file_prefix);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if meta.is_file() && is_log_file_name(&name, &config.file_prefix) {
+            // 当天的文件正在写入，不删除
+            if name != today {
+                files.push((meta.modified()?, entry.path()));
+            }
+        }
+    }
+    // 最新的在前
+    files.sort_by(|a, b| b.cmp(a));
+
+    let cutoff =
+        (config.max_age_days > 0).then(|| now - Duration::from_secs(config.max_age_days * 86_400));
+    // 当天的文件无论已存在还是即将创建都算一个名额
+    let keep = config.max_files.saturating_sub(1);
+    let mut removed = 0;
+    for (i, (modified, path)) in files.iter().enumerate() {
+        let expired = cutoff.is_some_and(|c| *modified < c);
+        let excess = config.max_files > 0 && i >= keep;
+        if expired || excess {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
 }
 
 /// 使用默认参数初始化日志
@@ -78,3 +277,97 @@ pub fn init_default_logging() -> LogResult<()> {
     let default_config = LogConfig::new();
     init_logging(&default_config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn scoped_dispatch_writes_to_its_own_file() {
+        let dir = tempdir().unwrap();
+        let cfg = LogConfig::new()
+            .set_path(&dir.path().display().to_string())
+            .set_file_prefix("scoped");
+        let (dispatch, guard) = build_dispatch(&cfg).unwrap();
+        tracing::dispatcher::with_default(&dispatch, || tracing::info!("scoped hello"));
+        tracing::info!("outside the scope");
+        drop(guard);
+
+        let entry = fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+        assert!(entry.file_name().to_string_lossy().starts_with("scoped."));
+        let content = fs::read_to_string(entry.path()).unwrap();
+        assert!(content.contains("scoped hello"));
+        assert!(!content.contains("outside the scope"));
+    }
+
+    #[test]
+    fn cleanup_removes_old_and_excess_files() {
+        let dir = tempdir().unwrap();
+        let now = StdSystemTime::now();
+        for (name, days) in [
+            ("sqllog.2026-01-01", 30),
+            ("sqllog.2026-01-20", 11),
+            ("sqllog.2026-01-29", 2),
+            ("sqllog.2026-01-30", 1),
+            ("other.2026-01-01", 30),
+        ] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(now - Duration::from_secs(days * 86_400))
+                .unwrap();
+        }
+        let cfg = LogConfig::new()
+            .set_path(&dir.path().display().to_string())
+            .set_max_age_days(10);
+        assert_eq!(cleanup_logs(&cfg).unwrap(), 2);
+
+        let cfg = cfg.set_max_files(2);
+        assert_eq!(cleanup_logs(&cfg).unwrap(), 1);
+
+        let mut left: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["other.2026-01-01", "sqllog.2026-01-30"]);
+    }
+
+    #[test]
+    fn cleanup_only_counts_dated_logs_and_keeps_today() {
+        let dir = tempdir().unwrap();
+        // 2026-01-31 12:00 UTC
+        let now = UNIX_EPOCH + Duration::from_secs(20_484 * 86_400 + 43_200);
+        for (name, days) in [
+            ("sqllog.2026-01-29", 2),
+            ("sqllog.2026-01-30", 1),
+            ("sqllog.2026-01-31", 0),
+            ("sqllog.toml", 30),
+            ("sqllog.2026-01-01.bak", 30),
+        ] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(now - Duration::from_secs(days * 86_400))
+                .unwrap();
+        }
+        let cfg = LogConfig::new()
+            .set_path(&dir.path().display().to_string())
+            .set_max_age_days(10)
+            .set_max_files(2);
+        assert_eq!(cleanup_logs_at(&cfg, now).unwrap(), 1);
+
+        let mut left: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "sqllog.2026-01-01.bak",
+                "sqllog.2026-01-30",
+                "sqllog.2026-01-31",
+                "sqllog.toml"
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! syslog 输出：每条日志事件作为一个 RFC 3164 数据报发送到本机 syslog 套接字
+
+use std::{
+    io::{self, Write},
+    os::unix::net::UnixDatagram,
+    process,
+};
+
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::config::syslog::SyslogConfig;
+
+/// 连接到 syslog 套接字的 `MakeWriter`
+pub struct SyslogWriter {
+    socket: UnixDatagram,
+    facility: u8,
+    ident: String,
+    pid: u32,
+}
+
+impl SyslogWriter {
+    pub fn connect(config: &SyslogConfig) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&config.socket)?;
+        Ok(Self {
+            socket,
+            facility: config.facility.code(),
+            ident: config.ident.clone(),
+            pid: process::id(),
+        })
+    }
+
+    fn event(&self, level: &Level) -> SyslogEvent<'_> {
+        let severity = match *level {
+            Level::ERROR => 3,
+            Level::WARN => 4,
+            Level::INFO => 6,
+            Level::DEBUG | Level::TRACE => 7,
+        };
+        SyslogEvent {
+            writer: self,
+            priority: self.facility * 8 + severity,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogWriter {
+    type Writer = SyslogEvent<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.event(&Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.event(meta.level())
+    }
+}
+
+/// 缓存一条事件的格式化输出，drop 时发送
+pub struct SyslogEvent<'a> {
+    writer: &'a SyslogWriter,
+    priority: u8,
+    buf: Vec<u8>,
+}
+
+impl SyslogEvent<'_> {
+    fn datagram(&self) -> Vec<u8> {
+        let w = self.writer;
+        let mut out = format!("<{}>{}[{}]: ", self.priority, w.ident, w.pid).into_bytes();
+        out.extend_from_slice(self.buf.trim_ascii_end());
+        out
+    }
+}
+
+impl Write for SyslogEvent<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogEvent<'_> {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            // syslog 不可用时无处报告，丢弃该条日志
+            let _ = self.writer.socket.send(&self.datagram());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::syslog::SyslogFacility;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sends_one_datagram_per_event() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("log.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+        let config = SyslogConfig::new()
+            .set_socket(&path.display().to_string())
+            .set_facility(SyslogFacility::Local0)
+            .set_ident("test");
+        let writer = SyslogWriter::connect(&config).unwrap();
+
+        let mut event = writer.event(&Level::WARN);
+        event.write_all(b"disk almost full\n").unwrap();
+        drop(event);
+
+        let mut buf = [0; 256];
+        let n = server.recv(&mut buf).unwrap();
+        let expected = format!("<132>test[{}]: disk almost full", process::id());
+        assert_eq!(&buf[..n], expected.as_bytes());
+    }
+}
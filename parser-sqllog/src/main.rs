@@ -1,37 +1,211 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    process::ExitCode,
+};
+
 use clap::Parser;
 
 use parser_sqllog::LogConfig;
-use parser_sqllog::command::cli::Cli;
-use parser_sqllog::config::error_exporter::ErrorExporterConfig;
-use parser_sqllog::config::sqllog::SqllogConfig;
-use parser_sqllog::error::LogError;
+use parser_sqllog::command::cli::{Cli, Commands};
+use parser_sqllog::command::{
+    audit, bench, concurrency, daemon, doctor, events, exec, export, heatmap, idle, large_result,
+    locks, merge, otlp, pool, prepared, replay, rollback, row_latency, saturation, schema, show,
+    slice, stats, summary, tables, tail, verify,
+};
+use parser_sqllog::config::effective::EffectiveConfig;
+use parser_sqllog::config::file::Root;
+use parser_sqllog::error::{CommandResult, ConfigParseResult, exit_code};
+use parser_sqllog::rules::RuleSet;
+
+use tracing::{debug, error, info, warn};
 
-use tracing::{debug, info};
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: parser_sqllog::resource::CountingAlloc = parser_sqllog::resource::CountingAlloc;
 
 fn init_logging(log_cfg: &LogConfig) {
-    if let Err(_) = parser_sqllog::init_logging(&log_cfg) {
+    if parser_sqllog::init_logging(log_cfg).is_err() {
         let _ = parser_sqllog::init_default_logging();
     }
 }
 
-fn main() -> Result<(), LogError> {
+/// 按 默认值 < 配置文件 < 配置档 < 环境变量 < 命令行 合并配置
+fn load_config(cli: &Cli) -> ConfigParseResult<EffectiveConfig> {
+    EffectiveConfig::new()
+        .set_strict(cli.strict_config)
+        .set_check_paths(cli.command.as_ref().is_some_and(Commands::reads_input))
+        .merge_file(&cli.config_path)?
+        .select_profile(cli.profile.as_deref())?
+        .merge_env(env::vars())
+        .apply(cli.config_overrides())
+        .check()
+}
+
+/// 退出码见 [`exit_code`]：配置错误、输入无法读取、错误记录过多与导出失败各不相同
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    // 加载日志配置
-    let log_cfg = LogConfig::from_file(&cli.config_path);
+    let cfg = match load_config(&cli) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            // 日志尚未初始化，配置错误逐条输出到标准错误
+            eprintln!("{}", e.render(io::stderr().is_terminal()));
+            return ExitCode::from(exit_code::CONFIG);
+        }
+    };
+    if cli.print_config {
+        print!("{}", cfg.to_annotated_toml());
+        return ExitCode::SUCCESS;
+    }
+    match run(&cli, cfg) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{e}");
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run(cli: &Cli, cfg: EffectiveConfig) -> CommandResult<()> {
+    let Root {
+        version: _,
+        logging: log_cfg,
+        error_exporter: error_exporter_cfg,
+        sqllog: sqllog_cfg,
+        analysis: analysis_cfg,
+        export: export_cfg,
+        sftp: _sftp_cfg,
+        rules: rules_cfg,
+    } = cfg.root;
+
+    let log_cfg = if cli.verbose >= 3 {
+        LogConfig {
+            targets: Default::default(),
+            ..log_cfg
+        }
+    } else {
+        log_cfg
+    };
     init_logging(&log_cfg);
+    for warning in &cfg.warnings {
+        warn!("{warning}");
+    }
 
     // 启动日志解析工具
     info!("SQL 日志解析工具启动");
 
-    let sqllog_cfg = SqllogConfig::from_file(&cli.config_path);
-    let error_exporter_cfg = ErrorExporterConfig::from_file(&cli.config_path);
+    #[cfg(feature = "sftp")]
+    parser_sqllog::input::sftp::configure(_sftp_cfg);
 
     info!("配置文件路径: {}", cli.config_path);
 
     debug!("日志配置: {:?}", log_cfg);
     debug!("解析配置: {:?}", sqllog_cfg);
     debug!("错误导出配置: {:?}", error_exporter_cfg);
+    debug!("分析配置: {:?}", analysis_cfg);
+    debug!("导出配置: {:?}", export_cfg);
+    debug!("标签规则: {:?}", rules_cfg);
+    let rules = RuleSet::compile(&rules_cfg)?;
+
+    match &cli.command {
+        Some(Commands::Audit(args)) => {
+            audit::run(args, &sqllog_cfg, &error_exporter_cfg, &export_cfg)?
+        }
+        Some(Commands::Prepared(args)) => prepared::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::LargeResults(args)) => large_result::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &export_cfg,
+        )?,
+        Some(Commands::RowLatency(args)) => {
+            row_latency::run(args, &sqllog_cfg, &error_exporter_cfg, &analysis_cfg)?
+        }
+        Some(Commands::Saturation(args)) => {
+            saturation::run(args, &sqllog_cfg, &error_exporter_cfg, &analysis_cfg)?
+        }
+        Some(Commands::Concurrency(args)) => {
+            concurrency::run(args, &sqllog_cfg, &error_exporter_cfg)?
+        }
+        Some(Commands::Heatmap(args)) => heatmap::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Rollback(args)) => rollback::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Idle(args)) => {
+            idle::run(args, &sqllog_cfg, &error_exporter_cfg, &analysis_cfg)?
+        }
+        Some(Commands::Events(args)) => {
+            events::run(args, &sqllog_cfg, &error_exporter_cfg, &export_cfg)?
+        }
+        Some(Commands::Locks(args)) => {
+            locks::run(args, &sqllog_cfg, &error_exporter_cfg, &export_cfg)?
+        }
+        Some(Commands::Merge(args)) => merge::run(args, &analysis_cfg, &export_cfg)?,
+        Some(Commands::Otlp(args)) => otlp::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &export_cfg,
+        )?,
+        Some(Commands::Pool(args)) => pool::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Show(args)) => show::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Slice(args)) => slice::run(args, &sqllog_cfg)?,
+        Some(Commands::Stats(args)) => stats::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &rules,
+        )?,
+        Some(Commands::Tables(args)) => tables::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Exec(args)) => {
+            exec::run(args, &sqllog_cfg, &error_exporter_cfg, &export_cfg)?
+        }
+        Some(Commands::Replay(args)) => replay::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Export(args)) => export::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &export_cfg,
+            &rules,
+        )?,
+        Some(Commands::Summary(args)) => summary::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Verify(args)) => verify::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Doctor(args)) => doctor::run(args)?,
+        Some(Commands::Bench(args)) => bench::run(args, &sqllog_cfg, &error_exporter_cfg)?,
+        Some(Commands::Daemon(args)) => {
+            daemon::run(args, &sqllog_cfg, &error_exporter_cfg, &export_cfg, &rules)?
+        }
+        Some(Commands::Tail(args)) => tail::run(args, &sqllog_cfg, &analysis_cfg, &export_cfg)?,
+        Some(Commands::Schema(args)) => schema::run(args)?,
+        #[cfg(feature = "query")]
+        Some(Commands::Query(args)) => {
+            parser_sqllog::command::query::run(args, &sqllog_cfg, &error_exporter_cfg)?
+        }
+        #[cfg(feature = "tui")]
+        Some(Commands::Tui(args)) => {
+            parser_sqllog::command::tui::run(args, &sqllog_cfg, &error_exporter_cfg, &analysis_cfg)?
+        }
+        #[cfg(feature = "xlsx")]
+        Some(Commands::Xlsx(args)) => parser_sqllog::command::xlsx::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &rules,
+        )?,
+        #[cfg(feature = "template")]
+        Some(Commands::Render(args)) => parser_sqllog::command::render::run(
+            args,
+            &sqllog_cfg,
+            &error_exporter_cfg,
+            &analysis_cfg,
+            &rules,
+        )?,
+        None => {}
+    }
 
     Ok(())
 }
@@ -0,0 +1,1655 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    ops::{ControlFlow, Deref},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, bounded};
+use dm_database_parser::parser::{ParseMode, ParsedRecord, RecordOrError, RecordSplitter};
+use dm_database_parser::{FingerprintOptions, RecordMetrics};
+use tracing::{debug, info, warn};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::{
+    config::{
+        error_exporter::ErrorExporterConfig,
+        input::InputEncoding,
+        sqllog::{OnError, ProgressMode, SqllogConfig},
+    },
+    error::{ErrorRateExceeded, InputError},
+    exporter::compress::Compression,
+    input::{self, Chunk},
+    progress::{DEFAULT_PROGRESS_INTERVAL, ProgressReporter, ProgressTracker},
+    resource::{ResourceUsage, Stage, StageClock, StageTimes},
+};
+
+/// 未配置批大小时每个批次包含的记录数
+const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// 未配置块大小时流式读取的块大小
+const DEFAULT_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// 未配置预读深度时读取阶段最多领先的块数
+const DEFAULT_READ_AHEAD: usize = 2;
+
+/// 输入源信息，随每条记录一起传给处理函数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub path: PathBuf,
+    /// 所属实例名，见 [`input::instance_name`]
+    pub instance: String,
+    /// 文件标识：去掉压缩扩展名后的文件名与文件首行的哈希。
+    /// 文件移动目录、压缩或追加写入后保持不变；不同主机上的同名文件首行不同，标识也不同
+    pub file_id: u64,
+}
+
+impl Source {
+    /// `head` 为文件开头的文本（如读到的第一块），只取其中的首行
+    pub fn new(path: PathBuf, head: &str) -> Self {
+        let name = match Compression::from_path(&path) {
+            Compression::None => path.file_name(),
+            _ => path.file_stem(),
+        };
+        let first_line = head.lines().next().unwrap_or_default();
+        let mut key = name.unwrap_or_default().as_encoded_bytes().to_vec();
+        key.push(0);
+        key.extend_from_slice(first_line.as_bytes());
+        Self {
+            instance: input::instance_name(&path),
+            file_id: xxh3_64(&key),
+            path,
+        }
+    }
+
+    /// 记录的稳定标识：文件标识与记录在文件中的字节偏移的哈希，以 16 位十六进制表示
+    pub fn record_id(&self, rec: &ParsedRecord<'_>) -> String {
+        let mut key = [0u8; 16];
+        key[..8].copy_from_slice(&self.file_id.to_le_bytes());
+        key[8..].copy_from_slice(&rec.offset.to_le_bytes());
+        format!("{:016x}", xxh3_64(&key))
+    }
+}
+
+/// 无法解析的记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadRecord {
+    pub path: PathBuf,
+    pub reason: String,
+    pub text: String,
+}
+
+impl BadRecord {
+    fn new(source: &Source, reason: impl Into<String>, text: &str) -> Self {
+        Self {
+            path: source.path.clone(),
+            reason: reason.into(),
+            text: text.to_string(),
+        }
+    }
+
+    /// 错误记录包含的文本行数
+    pub fn lines(&self) -> u64 {
+        self.text.lines().count().max(1) as u64
+    }
+}
+
+/// 无法读取的输入文件（权限不足、读取中途出错等）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl FailedFile {
+    fn new(path: &Path, err: &io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        }
+    }
+}
+
+/// 流水线运行结束后的统计信息
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PipelineSummary {
+    pub files: usize,
+    pub bytes: u64,
+    pub records: u64,
+    /// 处理函数返回 Some 并送达 sink 的条数
+    pub outputs: u64,
+    /// 运行期间同时驻留在流水线中的输入文本字节数峰值
+    pub peak_buffered_bytes: u64,
+    /// 从缓冲池中复用（而非新分配）的缓冲区个数
+    pub reused_buffers: u64,
+    /// 遇到的错误记录数（前导垃圾文本、缺少元数据或严格模式下校验失败的记录）
+    pub bad_records: u64,
+    /// 错误记录包含的文本行数
+    pub bad_lines: u64,
+    /// 因无法读取而跳过的文件（中途出错的文件保留已读取部分的记录）
+    pub failed_files: Vec<FailedFile>,
+    /// 各阶段的累计耗时
+    pub stage_times: StageTimes,
+}
+
+/// 内存预算：限制流水线中同时驻留的输入文本字节数
+#[derive(Debug, Default)]
+struct MemoryBudget {
+    /// 字节数上限，0 表示不限制
+    limit: usize,
+    state: Mutex<BudgetState>,
+    released: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    used: usize,
+    peak: usize,
+}
+
+impl MemoryBudget {
+    fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            ..Default::default()
+        })
+    }
+
+    /// 申请 `bytes` 字节的预算，超出上限时阻塞直到下游释放。
+    ///
+    /// 流水线中没有任何驻留数据时总是立即成功，避免单个超过上限的文件永远无法处理。
+    fn acquire(self: &Arc<Self>, bytes: usize) -> BudgetPermit {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while self.limit > 0 && state.used > 0 && state.used + bytes > self.limit {
+            state = self.released.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.used += bytes;
+        state.peak = state.peak.max(state.used);
+        BudgetPermit {
+            budget: self.clone(),
+            bytes,
+        }
+    }
+
+    fn peak(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).peak
+    }
+}
+
+/// 已申请的预算，drop 时归还
+#[derive(Debug)]
+struct BudgetPermit {
+    budget: Arc<MemoryBudget>,
+    bytes: usize,
+}
+
+impl Drop for BudgetPermit {
+    fn drop(&mut self) {
+        let mut state = self.budget.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.used -= self.bytes;
+        self.budget.released.notify_all();
+    }
+}
+
+/// 可复用对象池，用于在文件之间循环使用缓冲区
+#[derive(Debug)]
+struct Pool<T> {
+    items: Mutex<Vec<T>>,
+    /// 池中最多保留的对象数，多余的直接释放
+    max: usize,
+    reused: AtomicU64,
+}
+
+impl<T: Default> Pool<T> {
+    fn new(max: usize) -> Arc<Self> {
+        Arc::new(Self {
+            items: Mutex::new(Vec::with_capacity(max)),
+            max,
+            reused: AtomicU64::new(0),
+        })
+    }
+
+    /// 取出一个对象，池为空时新建
+    fn take(&self) -> T {
+        let item = self.items.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match item {
+            Some(item) => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                item
+            }
+            None => T::default(),
+        }
+    }
+
+    /// 归还对象；调用方负责清空内容
+    fn put(&self, item: T) {
+        let mut items = self.items.lock().unwrap_or_else(|e| e.into_inner());
+        if items.len() < self.max {
+            items.push(item);
+        }
+    }
+}
+
+/// 读入内存的一块文件文本；最后一个引用释放时归还内存预算，缓冲区归还到池中
+struct Text {
+    chunk: Chunk,
+    pool: Arc<Pool<Vec<u8>>>,
+    _permit: BudgetPermit,
+}
+
+impl Drop for Text {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.chunk.text).into_bytes();
+        buf.clear();
+        self.pool.put(buf);
+    }
+}
+
+impl Deref for Text {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.chunk.text
+    }
+}
+
+/// 一块已读入内存的文件文本，`seq` 为读取顺序
+struct Loaded {
+    seq: u64,
+    source: Arc<Source>,
+    /// 块在文件中的起始字节偏移
+    offset: u64,
+    text: Arc<Text>,
+}
+
+/// 批次的序号：所属块的读取顺序与块内的批次顺序，`last` 标记块内的最后一批
+#[derive(Debug, Clone, Copy)]
+struct Seq {
+    chunk: u64,
+    part: u32,
+    last: bool,
+}
+
+impl Seq {
+    fn key(&self) -> (u64, u32) {
+        (self.chunk, self.part)
+    }
+}
+
+/// 解析阶段发往 sink 的一批结果
+struct Output<T> {
+    seq: Seq,
+    items: Vec<T>,
+    bad: Vec<BadRecord>,
+}
+
+/// 一批待解析的记录，以在 `text` 中的字节区间表示
+struct Batch {
+    seq: Seq,
+    source: Arc<Source>,
+    /// 所属块在文件中的起始字节偏移，`ranges` 相对于块
+    offset: u64,
+    text: Arc<Text>,
+    ranges: Vec<(usize, usize)>,
+}
+
+/// 重排解析结果：按序号排列时只交出下一个应到的批次，先到的批次暂存等待；
+/// 否则按到达顺序交出
+struct Reorder<T> {
+    ordered: bool,
+    next: (u64, u32),
+    pending: BTreeMap<(u64, u32), Output<T>>,
+}
+
+impl<T> Reorder<T> {
+    fn new(ordered: bool) -> Self {
+        Self {
+            ordered,
+            next: (0, 0),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    fn push(&mut self, out: Output<T>) {
+        self.pending.insert(out.seq.key(), out);
+    }
+
+    fn pop(&mut self) -> Option<Output<T>> {
+        let entry = self.pending.first_entry()?;
+        if self.ordered && *entry.key() != self.next {
+            return None;
+        }
+        let out = entry.remove();
+        let (chunk, part) = out.seq.key();
+        self.next = if out.seq.last {
+            (chunk + 1, 0)
+        } else {
+            (chunk, part + 1)
+        };
+        Some(out)
+    }
+}
+
+/// 多线程处理流水线
+///
+/// 处理分为若干阶段，阶段之间通过有界通道连接：
+///
+/// ```text
+/// read（分块读取文件） -> split（拆分记录批次） -> parse（解析 + 过滤/提取） -> sink（汇总）
+/// ```
+///
+/// 通道满时上游阶段阻塞等待（背压），因此慢速的 sink 不会导致内存无限增长；
+/// split 与 parse 阶段的线程数可以分别配置。
+///
+/// 错误记录按 [`OnError`] 策略处理：`abort` 时第一条错误记录即中止并返回
+/// `InvalidData` 错误，`collect` 时写入 `[error_exporter]` 配置的文件。
+///
+/// 无法打开或读取的文件默认记录日志后跳过，继续处理其余文件，并列入
+/// [`PipelineSummary::failed_files`]（`collect` 策略下同时写入错误记录文件）；
+/// 设置 `fail_fast` 后第一个读取错误即中止并返回该错误（包装为 [`InputError`]）。
+/// 错误记录占比超过 `max_error_rate` 时，运行结束后返回 [`ErrorRateExceeded`]。
+///
+/// 设置了 [`ProgressReporter`] 时，调用线程按间隔发出进度事件，结束时再发出一次 `done` 事件。
+///
+/// 设置 `ordered` 后，各批结果按序号重新排列，sink 按输入文件及文件内的记录顺序接收，
+/// 与单线程处理的结果一致；先完成的批次在 sink 中暂存，直到之前的批次都已交出。
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    split_workers: usize,
+    parse_workers: usize,
+    batch_size: usize,
+    queue_capacity: usize,
+    memory_limit: usize,
+    chunk_size: usize,
+    compression: Compression,
+    encoding: InputEncoding,
+    read_ahead: usize,
+    on_error: OnError,
+    error_exporter: ErrorExporterConfig,
+    parse_mode: ParseMode,
+    fail_fast: bool,
+    max_error_rate: f64,
+    ordered: bool,
+    fingerprint: FingerprintOptions,
+    progress: Option<ProgressReporter>,
+    progress_interval: Duration,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        let parse_workers = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            split_workers: 1,
+            parse_workers,
+            batch_size: DEFAULT_BATCH_SIZE,
+            queue_capacity: parse_workers * 2,
+            memory_limit: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            compression: Compression::None,
+            encoding: InputEncoding::Utf8,
+            read_ahead: DEFAULT_READ_AHEAD,
+            on_error: OnError::default(),
+            error_exporter: ErrorExporterConfig::new(),
+            parse_mode: ParseMode::Lenient,
+            fail_fast: false,
+            max_error_rate: 1.0,
+            ordered: false,
+            fingerprint: FingerprintOptions::DEFAULT,
+            progress: None,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// 根据 `[sqllog]` 配置创建流水线：`thread_num` 为解析线程数，`batch_size` 为批大小，0 表示使用默认值；
+    /// `memory_limit_mb` 为驻留输入文本的内存上限，`chunk_size_kb` 与 `read_ahead` 控制流式读取，
+    /// `progress` 选择进度事件的输出方式，`input` 中的压缩格式与编码决定如何解码输入文件
+    pub fn from_config(cfg: &SqllogConfig) -> Self {
+        let mut pipeline = Self::new()
+            .set_memory_limit(cfg.memory_limit_mb * 1024 * 1024)
+            .set_input_format(cfg.input.compression, cfg.input.encoding)
+            .set_on_error(cfg.on_error)
+            .set_fail_fast(cfg.fail_fast)
+            .set_max_error_rate(cfg.max_error_rate)
+            .set_progress_interval(Duration::from_millis(cfg.progress_interval_ms));
+        match cfg.progress {
+            ProgressMode::None => {}
+            ProgressMode::Text => pipeline = pipeline.set_progress(ProgressReporter::text()),
+            ProgressMode::Json => pipeline = pipeline.set_progress(ProgressReporter::json()),
+        }
+        if cfg.strict {
+            pipeline = pipeline.set_parse_mode(ParseMode::Strict);
+        }
+        if cfg.chunk_size_kb > 0 {
+            pipeline = pipeline.set_chunk_size(cfg.chunk_size_kb * 1024);
+        }
+        if cfg.read_ahead > 0 {
+            pipeline = pipeline.set_read_ahead(cfg.read_ahead);
+        }
+        if cfg.thread_num > 0 {
+            pipeline = pipeline.set_parse_workers(cfg.thread_num);
+        }
+        if cfg.batch_size > 0 {
+            pipeline = pipeline.set_batch_size(cfg.batch_size);
+        }
+        pipeline
+    }
+
+    pub fn set_split_workers(mut self, workers: usize) -> Self {
+        self.split_workers = workers.max(1);
+        self
+    }
+
+    pub fn set_parse_workers(mut self, workers: usize) -> Self {
+        self.parse_workers = workers.max(1);
+        self.queue_capacity = self.parse_workers * 2;
+        self
+    }
+
+    pub fn set_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn set_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// 设置驻留输入文本的内存上限（字节），0 表示不限制
+    pub fn set_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions {
+            chunk_size: self.chunk_size,
+            compression: self.compression,
+            encoding: self.encoding,
+        }
+    }
+
+    /// 设置流式读取的块大小（字节）
+    pub fn set_chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// 设置输入文件的压缩格式（`None` 时按扩展名推断）与字符编码
+    pub fn set_input_format(mut self, compression: Compression, encoding: InputEncoding) -> Self {
+        self.compression = compression;
+        self.encoding = encoding;
+        self
+    }
+
+    /// 设置预读深度：已读入但尚未拆分的块数上限
+    pub fn set_read_ahead(mut self, chunks: usize) -> Self {
+        self.read_ahead = chunks.max(1);
+        self
+    }
+
+    pub fn set_on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    pub fn set_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// 遇到无法读取的文件时立即中止，而不是跳过
+    pub fn set_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// 错误记录占全部记录的比例上限（0~1），运行结束时超过则返回错误；1 表示不限制
+    pub fn set_max_error_rate(mut self, max_error_rate: f64) -> Self {
+        self.max_error_rate = max_error_rate;
+        self
+    }
+
+    /// 为 true 时各批结果按序号重新排列，sink 按输入文件及文件内的记录顺序接收；
+    /// 为 false（默认）时按完成顺序接收，吞吐更高
+    pub fn set_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// [`Pipeline::run_stats_only`] 计算指纹使用的规则
+    pub fn set_fingerprint(mut self, fingerprint: FingerprintOptions) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// 设置进度事件的接收者
+    pub fn set_progress(mut self, reporter: ProgressReporter) -> Self {
+        self.progress = Some(reporter);
+        self
+    }
+
+    /// 设置进度事件的最小间隔
+    pub fn set_progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// 设置 `collect` 策略下错误记录的导出位置
+    pub fn set_error_exporter(mut self, cfg: ErrorExporterConfig) -> Self {
+        self.error_exporter = cfg;
+        self
+    }
+
+    /// 运行流水线。
+    ///
+    /// `map` 在解析线程中对每条记录调用，负责过滤并提取需要的数据（返回 None 表示丢弃）；
+    /// `sink` 在调用线程中接收提取结果：设置了 `ordered` 时按输入顺序，否则按到达顺序，
+    /// 不同批次之间的顺序不作保证。
+    pub fn run<T, M, S>(
+        &self,
+        files: Vec<PathBuf>,
+        map: M,
+        mut sink: S,
+    ) -> io::Result<PipelineSummary>
+    where
+        T: Send,
+        M: Fn(&Source, ParsedRecord<'_>) -> Option<T> + Sync,
+        S: FnMut(T),
+    {
+        let (loaded_tx, loaded_rx) = bounded::<Loaded>(self.read_ahead);
+        let (batch_tx, batch_rx) = bounded::<Batch>(self.queue_capacity);
+        let (out_tx, out_rx) = bounded::<Output<T>>(self.queue_capacity);
+        let stop = AtomicBool::new(false);
+        let stop = &stop;
+        let batch_size = self.batch_size;
+        let read = self.read_options();
+        let fail_fast = self.fail_fast;
+        let budget = MemoryBudget::new(self.memory_limit);
+        // 池容量按各阶段间可能同时在途的对象数估算
+        let text_pool = Pool::new(self.read_ahead + self.split_workers + self.queue_capacity + 1);
+        let ranges_pool = Pool::new(self.split_workers + self.parse_workers + self.queue_capacity);
+        let map = &map;
+        let counters = RunCounters::default();
+        let counters = &counters;
+        let mut progress = ProgressTracker::new(
+            self.progress.as_ref(),
+            self.progress_interval,
+            total_bytes(&files),
+        );
+
+        let mut summary = PipelineSummary {
+            files: files.len(),
+            ..Default::default()
+        };
+
+        thread::scope(|scope| {
+            let reader_budget = budget.clone();
+            let reader_pool = text_pool.clone();
+            let reader = scope.spawn(move || {
+                read_stage(
+                    files,
+                    loaded_tx,
+                    read,
+                    &reader_budget,
+                    &reader_pool,
+                    fail_fast,
+                    counters,
+                )
+            });
+
+            let mut splitters = Vec::with_capacity(self.split_workers);
+            for _ in 0..self.split_workers {
+                let rx = loaded_rx.clone();
+                let tx = batch_tx.clone();
+                let bad_tx = out_tx.clone();
+                let pool = ranges_pool.clone();
+                splitters.push(
+                    scope.spawn(move || {
+                        split_stage(rx, tx, bad_tx, batch_size, &pool, stop, counters)
+                    }),
+                );
+            }
+            drop(loaded_rx);
+            drop(batch_tx);
+
+            for _ in 0..self.parse_workers {
+                let rx = batch_rx.clone();
+                let tx = out_tx.clone();
+                let pool = ranges_pool.clone();
+                let mode = self.parse_mode;
+                scope.spawn(move || parse_stage(rx, tx, map, mode, &pool, stop, &counters.clock));
+            }
+            drop(batch_rx);
+            drop(out_tx);
+
+            let mut errors = ErrorSink::new(self.on_error, &self.error_exporter);
+            let mut reorder = Reorder::new(self.ordered);
+            'recv: loop {
+                // 等待结果时也按间隔发出进度事件，避免 sink 长时间收不到结果时进度停滞
+                let out = match out_rx.recv_timeout(self.progress_interval) {
+                    Ok(out) => out,
+                    Err(RecvTimeoutError::Timeout) => {
+                        progress.tick(
+                            counters.bytes_read.load(Ordering::Relaxed),
+                            counters.records_split.load(Ordering::Relaxed),
+                        );
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                progress.tick(
+                    counters.bytes_read.load(Ordering::Relaxed),
+                    counters.records_split.load(Ordering::Relaxed),
+                );
+                reorder.push(out);
+                while let Some(out) = reorder.pop() {
+                    summary.outputs += out.items.len() as u64;
+                    let start = Instant::now();
+                    for item in out.items {
+                        sink(item);
+                    }
+                    counters.clock.add(Stage::Sink, start);
+                    summary.bad_records += out.bad.len() as u64;
+                    summary.bad_lines += out.bad.iter().map(BadRecord::lines).sum::<u64>();
+                    if let Err(e) = errors.handle(out.bad) {
+                        // 通知各阶段尽快退出，并丢弃剩余结果
+                        stop.store(true, Ordering::Relaxed);
+                        drop(out_rx);
+                        errors.fail(e);
+                        break 'recv;
+                    }
+                }
+            }
+
+            summary.records = splitters
+                .into_iter()
+                .map(|h| h.join().expect("split worker panicked"))
+                .sum();
+            let read = reader.join().expect("reader panicked");
+            if let Ok((bytes, failed)) = &read {
+                summary.bytes = *bytes;
+                for f in failed {
+                    errors.file_failed(f)?;
+                }
+            }
+            errors.finish()?;
+            (_, summary.failed_files) = read?;
+            Ok::<(), io::Error>(())
+        })?;
+        self.warn_skipped(&summary);
+        summary.peak_buffered_bytes = budget.peak() as u64;
+        summary.reused_buffers =
+            text_pool.reused.load(Ordering::Relaxed) + ranges_pool.reused.load(Ordering::Relaxed);
+        summary.stage_times = counters.clock.times();
+        progress.finish(summary.bytes, summary.records);
+
+        debug!("流水线完成: {:?}", summary);
+        info!("资源使用: {}", ResourceUsage::collect(summary.stage_times));
+        self.check_error_rate(&summary)?;
+        Ok(summary)
+    }
+
+    /// 仅统计模式运行流水线：每条记录在解析线程中被转换为不含 SQL 正文的 [`RecordMetrics`]
+    /// （计算指纹后正文即被丢弃），`map` 只能看到摘要。适合只需要聚合报表的场景。
+    pub fn run_stats_only<T, M, S>(
+        &self,
+        files: Vec<PathBuf>,
+        map: M,
+        sink: S,
+    ) -> io::Result<PipelineSummary>
+    where
+        T: Send,
+        M: Fn(&Source, RecordMetrics) -> Option<T> + Sync,
+        S: FnMut(T),
+    {
+        self.run(
+            files,
+            |src, rec| {
+                map(
+                    src,
+                    RecordMetrics::from_record_with(&rec, &self.fingerprint),
+                )
+            },
+            sink,
+        )
+    }
+
+    /// 在调用线程中按文件顺序逐块读取、逐条解析记录，不启动工作线程。
+    ///
+    /// `f` 返回 [`ControlFlow::Break`] 时立即停止，不再读取剩余的块和文件，
+    /// 适合只需要前若干条记录的场景（如 `--limit`）。错误记录同样按 [`OnError`] 策略处理；
+    /// 返回的 `outputs` 为交给 `f` 的记录数。
+    pub fn scan<F>(&self, files: Vec<PathBuf>, mut f: F) -> io::Result<PipelineSummary>
+    where
+        F: FnMut(&Source, ParsedRecord<'_>) -> ControlFlow<()>,
+    {
+        let mut summary = PipelineSummary {
+            files: files.len(),
+            ..Default::default()
+        };
+        let mut progress = ProgressTracker::new(
+            self.progress.as_ref(),
+            self.progress_interval,
+            total_bytes(&files),
+        );
+        let mut errors = ErrorSink::new(self.on_error, &self.error_exporter);
+        'files: for path in files {
+            let mut reader = match self.read_options().open(&path, Vec::new()) {
+                Ok(reader) => reader,
+                Err(e) if !self.fail_fast => {
+                    let failed = FailedFile::new(&path, &e);
+                    errors.file_failed(&failed)?;
+                    summary.failed_files.push(failed);
+                    continue;
+                }
+                Err(e) => return Err(InputError::wrap(path.display(), e)),
+            };
+            let mut source = None;
+            let mut offset = 0u64;
+            loop {
+                let chunk = match reader.next_chunk() {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(e) if !self.fail_fast => {
+                        let failed = FailedFile::new(&path, &e);
+                        errors.file_failed(&failed)?;
+                        summary.failed_files.push(failed);
+                        break;
+                    }
+                    Err(e) => return Err(InputError::wrap(path.display(), e)),
+                };
+                let text = &chunk.text;
+                let source = &*source.get_or_insert_with(|| Source::new(path.clone(), text));
+                progress.tick(summary.bytes, summary.records);
+                summary.bytes += chunk.raw_len as u64;
+                summary.peak_buffered_bytes = summary.peak_buffered_bytes.max(text.len() as u64);
+                let base = offset;
+                offset += chunk.raw_len as u64;
+                for item in RecordSplitter::new(text).with_errors() {
+                    let rec = match item {
+                        RecordOrError::Record(rec) => rec,
+                        RecordOrError::Garbage(garbage) => {
+                            let bad = BadRecord::new(source, GARBAGE, garbage);
+                            summary.bad_records += 1;
+                            summary.bad_lines += bad.lines();
+                            errors.handle(vec![bad])?;
+                            continue;
+                        }
+                    };
+                    summary.records += 1;
+                    let at = base
+                        + chunk.raw_offset(rec.as_ptr() as usize - text.as_ptr() as usize) as u64;
+                    match parse_one(self.parse_mode, source, rec, at) {
+                        Ok(rec) => {
+                            summary.outputs += 1;
+                            if f(source, rec).is_break() {
+                                break 'files;
+                            }
+                        }
+                        Err(bad) => {
+                            summary.bad_records += 1;
+                            summary.bad_lines += bad.lines();
+                            errors.handle(vec![bad])?;
+                        }
+                    }
+                }
+            }
+        }
+        errors.finish()?;
+        self.warn_skipped(&summary);
+        progress.finish(summary.bytes, summary.records);
+        debug!("顺序扫描完成: {:?}", summary);
+        self.check_error_rate(&summary)?;
+        Ok(summary)
+    }
+
+    /// 错误记录占比超过 `max_error_rate` 时返回 [`ErrorRateExceeded`]
+    fn check_error_rate(&self, summary: &PipelineSummary) -> io::Result<()> {
+        let records = summary.records.max(summary.bad_records);
+        if records > 0 && summary.bad_records as f64 / records as f64 > self.max_error_rate {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                ErrorRateExceeded {
+                    bad: summary.bad_records,
+                    records,
+                    max: self.max_error_rate,
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    /// 汇总输出被跳过的错误记录与文件
+    fn warn_skipped(&self, summary: &PipelineSummary) {
+        if summary.bad_records > 0 && self.on_error == OnError::Skip {
+            warn!("已跳过 {} 条无法解析的记录", summary.bad_records);
+        }
+        if !summary.failed_files.is_empty() {
+            warn!(
+                "{} 个文件无法读取，已跳过（使用 --fail-fast 在首个读取错误时中止）",
+                summary.failed_files.len()
+            );
+        }
+    }
+}
+
+/// 按 [`OnError`] 策略处理错误记录，在调用线程中运行
+struct ErrorSink<'a> {
+    policy: OnError,
+    cfg: &'a ErrorExporterConfig,
+    writer: Option<BufWriter<File>>,
+    error: Option<io::Error>,
+}
+
+impl<'a> ErrorSink<'a> {
+    fn new(policy: OnError, cfg: &'a ErrorExporterConfig) -> Self {
+        Self {
+            policy,
+            cfg,
+            writer: None,
+            error: None,
+        }
+    }
+
+    /// 处理一批错误记录；`abort` 策略下返回第一条错误
+    fn handle(&mut self, bad: Vec<BadRecord>) -> io::Result<()> {
+        for rec in bad {
+            match self.policy {
+                OnError::Skip => {}
+                OnError::Abort => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {}", rec.path.display(), rec.reason),
+                    ));
+                }
+                OnError::Collect => {
+                    let w = match &mut self.writer {
+                        Some(w) => w,
+                        None => self.writer.insert(open_error_file(self.cfg)?),
+                    };
+                    writeln!(w, "[{}] {}", rec.path.display(), rec.reason)?;
+                    writeln!(w, "{}", rec.text.trim_end())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录一个无法读取的文件；`collect` 策略下同时写入错误记录文件
+    fn file_failed(&mut self, failed: &FailedFile) -> io::Result<()> {
+        warn!("无法读取文件 {}: {}", failed.path.display(), failed.reason);
+        if self.policy == OnError::Collect {
+            let w = match &mut self.writer {
+                Some(w) => w,
+                None => self.writer.insert(open_error_file(self.cfg)?),
+            };
+            writeln!(
+                w,
+                "[{}] 无法读取文件: {}",
+                failed.path.display(),
+                failed.reason
+            )?;
+        }
+        Ok(())
+    }
+
+    fn fail(&mut self, e: io::Error) {
+        self.error = Some(e);
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if let Some(w) = &mut self.writer {
+            w.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// 输入文件的总字节数（无法读取元数据的文件按 0 计），用于估算剩余时间
+fn total_bytes(files: &[PathBuf]) -> u64 {
+    files
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 按 `[error_exporter]` 配置打开错误记录导出文件
+fn open_error_file(cfg: &ErrorExporterConfig) -> io::Result<BufWriter<File>> {
+    let path = Path::new(&cfg.error_log_path);
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)?;
+    }
+    let mut opts = OpenOptions::new();
+    if cfg.append {
+        opts.append(true).create(true);
+    } else if cfg.overwrite {
+        opts.write(true).create(true).truncate(true);
+    } else {
+        opts.write(true).create_new(true);
+    }
+    Ok(BufWriter::new(opts.open(path)?))
+}
+
+/// 打开输入文件的参数
+#[derive(Debug, Clone, Copy)]
+struct ReadOptions {
+    chunk_size: usize,
+    compression: Compression,
+    encoding: InputEncoding,
+}
+
+impl ReadOptions {
+    /// 打开 `path` 并解压，`buf` 作为读取缓冲区复用
+    fn open(
+        &self,
+        path: &Path,
+        buf: Vec<u8>,
+    ) -> io::Result<input::ChunkReader<Box<dyn Read + Send>>> {
+        let stream = input::open_input(path, self.chunk_size, self.compression)?;
+        Ok(
+            input::ChunkReader::with_buffer(stream, self.chunk_size, buf)
+                .set_encoding(self.encoding),
+        )
+    }
+}
+
+/// 读取阶段：按块依次读入文件，每块在内存预算允许时才发送，返回读取的总字节数与无法读取的文件。
+///
+/// 读取缓冲区在文件之间复用，块缓冲区在下游释放后回到 `pool` 中循环使用；
+/// 已读取的字节数同时累加到 `progress` 中。`fail_fast` 为 false 时跳过无法读取的文件。
+fn read_stage(
+    files: Vec<PathBuf>,
+    tx: Sender<Loaded>,
+    read: ReadOptions,
+    budget: &Arc<MemoryBudget>,
+    pool: &Arc<Pool<Vec<u8>>>,
+    fail_fast: bool,
+    counters: &RunCounters,
+) -> io::Result<(u64, Vec<FailedFile>)> {
+    let mut bytes = 0u64;
+    let mut failed = Vec::new();
+    let mut carry = Vec::new();
+    let mut seq = 0u64;
+    for path in files {
+        let start = Instant::now();
+        let opened = read.open(&path, std::mem::take(&mut carry));
+        counters.clock.add(Stage::Read, start);
+        let mut reader = match opened {
+            Ok(reader) => reader,
+            Err(e) if !fail_fast => {
+                failed.push(FailedFile::new(&path, &e));
+                continue;
+            }
+            Err(e) => return Err(InputError::wrap(path.display(), e)),
+        };
+        let mut source = None;
+        let mut offset = 0u64;
+        loop {
+            let start = Instant::now();
+            let chunk = reader.next_chunk_reusing(pool.take());
+            counters.clock.add(Stage::Read, start);
+            let chunk = match chunk {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) if !fail_fast => {
+                    failed.push(FailedFile::new(&path, &e));
+                    break;
+                }
+                Err(e) => return Err(InputError::wrap(path.display(), e)),
+            };
+            let source =
+                source.get_or_insert_with(|| Arc::new(Source::new(path.clone(), &chunk.text)));
+            let raw_len = chunk.raw_len as u64;
+            bytes += raw_len;
+            counters.bytes_read.fetch_add(raw_len, Ordering::Relaxed);
+            let permit = budget.acquire(chunk.text.len());
+            let loaded = Loaded {
+                seq,
+                source: source.clone(),
+                offset,
+                text: Arc::new(Text {
+                    chunk,
+                    pool: pool.clone(),
+                    _permit: permit,
+                }),
+            };
+            offset += raw_len;
+            if tx.send(loaded).is_err() {
+                return Ok((bytes, failed));
+            }
+            seq += 1;
+        }
+        carry = reader.into_buffer();
+    }
+    Ok((bytes, failed))
+}
+
+/// 各阶段共享的计数：已读取的字节数与已拆分的记录数供进度事件使用，另有各阶段耗时
+#[derive(Debug, Default)]
+struct RunCounters {
+    bytes_read: AtomicU64,
+    records_split: AtomicU64,
+    clock: StageClock,
+}
+
+/// 拆分阶段：把每块文本拆分为记录区间并按批发送，返回拆分出的记录数。
+///
+/// 第一条记录之前无法识别的文本作为错误记录直接发往 sink；拆分出的记录数同时累加到 `progress` 中。
+/// 每块文本的最后一批（没有记录时为空批次）带有 `last` 标记，供 sink 按序号重排。
+fn split_stage<T>(
+    rx: Receiver<Loaded>,
+    tx: Sender<Batch>,
+    bad_tx: Sender<Output<T>>,
+    batch_size: usize,
+    pool: &Pool<Vec<(usize, usize)>>,
+    stop: &AtomicBool,
+    counters: &RunCounters,
+) -> u64 {
+    let take_ranges = || {
+        let mut ranges = pool.take();
+        ranges.reserve(batch_size);
+        ranges
+    };
+    let mut records = 0u64;
+    for loaded in rx {
+        if stop.load(Ordering::Relaxed) {
+            return records;
+        }
+        // 只计拆分本身的耗时，不含等待下游队列的时间
+        let mut timer = Instant::now();
+        let mut part = 0;
+        let mut seq = |last| {
+            part += 1;
+            Seq {
+                chunk: loaded.seq,
+                part: part - 1,
+                last,
+            }
+        };
+        let splitter = RecordSplitter::new(&loaded.text);
+        if let Some(bad) = leading_garbage(&loaded.source, &loaded.text, &splitter) {
+            let out = Output {
+                seq: seq(false),
+                items: Vec::new(),
+                bad: vec![bad],
+            };
+            counters.clock.add(Stage::Split, timer);
+            if bad_tx.send(out).is_err() {
+                return records;
+            }
+            timer = Instant::now();
+        }
+
+        let base = loaded.text.as_ptr() as usize;
+        let mut ranges = take_ranges();
+        for rec in splitter {
+            let start = rec.as_ptr() as usize - base;
+            ranges.push((start, start + rec.len()));
+            if ranges.len() == batch_size {
+                records += ranges.len() as u64;
+                counters
+                    .records_split
+                    .fetch_add(ranges.len() as u64, Ordering::Relaxed);
+                let batch = Batch {
+                    seq: seq(false),
+                    source: loaded.source.clone(),
+                    offset: loaded.offset,
+                    text: loaded.text.clone(),
+                    ranges: std::mem::replace(&mut ranges, take_ranges()),
+                };
+                counters.clock.add(Stage::Split, timer);
+                if tx.send(batch).is_err() {
+                    return records;
+                }
+                timer = Instant::now();
+            }
+        }
+        records += ranges.len() as u64;
+        counters
+            .records_split
+            .fetch_add(ranges.len() as u64, Ordering::Relaxed);
+        let batch = Batch {
+            seq: seq(true),
+            source: loaded.source,
+            offset: loaded.offset,
+            text: loaded.text,
+            ranges,
+        };
+        counters.clock.add(Stage::Split, timer);
+        if tx.send(batch).is_err() {
+            return records;
+        }
+    }
+    records
+}
+
+/// 解析阶段：解析记录并调用处理函数，将结果按批发送给 sink；批次的区间数组归还到 `pool`。
+///
+/// 缺少元数据（严格模式下为校验失败）的记录作为错误记录发送，不交给处理函数。
+fn parse_stage<T, M>(
+    rx: Receiver<Batch>,
+    tx: Sender<Output<T>>,
+    map: &M,
+    mode: ParseMode,
+    pool: &Pool<Vec<(usize, usize)>>,
+    stop: &AtomicBool,
+    clock: &StageClock,
+) where
+    M: Fn(&Source, ParsedRecord<'_>) -> Option<T>,
+{
+    for Batch {
+        seq,
+        source,
+        offset,
+        text,
+        mut ranges,
+    } in rx
+    {
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let start = Instant::now();
+        let mut items = Vec::new();
+        let mut bad = Vec::new();
+        for &(s, e) in &ranges {
+            let at = offset + text.chunk.raw_offset(s) as u64;
+            match parse_one(mode, &source, &text[s..e], at) {
+                Ok(rec) => items.extend(map(&source, rec)),
+                Err(b) => bad.push(b),
+            }
+        }
+        drop(text);
+        ranges.clear();
+        pool.put(ranges);
+        clock.add(Stage::Parse, start);
+        // 空批次也要发送，sink 按序号重排时依赖每个序号都到达
+        if tx.send(Output { seq, items, bad }).is_err() {
+            return;
+        }
+    }
+}
+
+/// 无法识别的文本对应的错误原因
+const GARBAGE: &str = "无法识别的文本";
+
+/// 第一条记录之前无法识别的文本（整块都不含记录时即整块文本），没有时返回 None
+fn leading_garbage(
+    source: &Source,
+    text: &str,
+    splitter: &RecordSplitter<'_>,
+) -> Option<BadRecord> {
+    let garbage = splitter.leading_errors_slice().unwrap_or(text);
+    (!garbage.trim().is_empty()).then(|| BadRecord::new(source, GARBAGE, garbage))
+}
+
+/// 按解析模式解析单条记录并填写其在文件中的偏移 `offset`，无法解析或缺少元数据时返回对应的错误记录
+fn parse_one<'t>(
+    mode: ParseMode,
+    source: &Source,
+    text: &'t str,
+    offset: u64,
+) -> Result<ParsedRecord<'t>, BadRecord> {
+    match mode.parse(text) {
+        Ok(rec) if rec.meta_raw.is_empty() => Err(BadRecord::new(source, "缺少元数据", text)),
+        Ok(rec) => Ok(ParsedRecord { offset, ..rec }),
+        Err(err) => Err(BadRecord::new(source, err.to_string(), text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write_logs(dir: &std::path::Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for (i, inst) in ["DM1", "DM2"].iter().enumerate() {
+            let mut text = String::new();
+            for n in 0..25 {
+                text.push_str(&format!(
+                    "2025-08-12 10:57:{:02}.000 (EP[0] sess:0x1 thrd:1 user:U{} trxid:1 stmt:0x2 appname:app) [SEL] select {} EXECTIME: {}(ms) ROWCOUNT: 1(rows) EXEC_ID: {}.\n",
+                    n, i, n, n, n
+                ));
+            }
+            let path = dir.join(format!("dmsql_{}_20250812_105700.log", inst));
+            fs::write(&path, text).unwrap();
+            files.push(path);
+        }
+        files
+    }
+
+    #[test]
+    fn run_processes_all_records_across_workers() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+
+        let mut got: Vec<(String, u64)> = Vec::new();
+        let summary = Pipeline::new()
+            .set_split_workers(2)
+            .set_parse_workers(3)
+            .set_batch_size(4)
+            .set_queue_capacity(1)
+            .run(
+                files,
+                |src, rec| Some((src.instance.clone(), rec.execute_time_ms.unwrap())),
+                |item| got.push(item),
+            )
+            .unwrap();
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.records, 50);
+        assert_eq!(summary.outputs, 50);
+        got.sort();
+        assert_eq!(got.iter().filter(|(i, _)| i == "DM1").count(), 25);
+        assert_eq!(
+            got.iter().map(|(_, e)| e).sum::<u64>(),
+            2 * (0..25).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn run_keeps_input_order_when_ordered() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+
+        let mut got: Vec<(String, u64)> = Vec::new();
+        Pipeline::new()
+            .set_split_workers(3)
+            .set_parse_workers(4)
+            .set_batch_size(2)
+            .set_chunk_size(300)
+            .set_ordered(true)
+            .run(
+                files,
+                // 丢弃部分记录，使部分批次为空
+                |src, rec| {
+                    let ms = rec.execute_time_ms.unwrap();
+                    (ms % 5 != 1).then(|| (src.instance.clone(), ms))
+                },
+                |item| got.push(item),
+            )
+            .unwrap();
+
+        let expected: Vec<(String, u64)> = ["DM1", "DM2"]
+            .iter()
+            .flat_map(|i| (0..25).filter(|n| n % 5 != 1).map(|n| (i.to_string(), n)))
+            .collect();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn run_reads_files_in_small_chunks() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+
+        let mut count = 0;
+        let summary = Pipeline::new()
+            .set_chunk_size(300)
+            .set_read_ahead(1)
+            .set_memory_limit(1024)
+            .run(files, |_, rec| rec.execute_time_ms, |_| count += 1)
+            .unwrap();
+        assert_eq!(count, 50);
+        assert_eq!(summary.records, 50);
+        assert!(summary.peak_buffered_bytes <= 1024);
+    }
+
+    #[test]
+    fn record_offsets_and_ids_are_stable_across_chunking() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+        let text = fs::read_to_string(&files[0]).unwrap();
+
+        let collect = |pipeline: Pipeline| {
+            let mut got: Vec<(u64, String)> = Vec::new();
+            pipeline
+                .set_ordered(true)
+                .run(
+                    files.clone(),
+                    |src, rec| Some((rec.offset, src.record_id(&rec))),
+                    |item| got.push(item),
+                )
+                .unwrap();
+            got
+        };
+        let whole = collect(Pipeline::new());
+        assert_eq!(whole, collect(Pipeline::new().set_chunk_size(300)));
+        // 偏移指向记录在文件中的起始位置
+        let second = text.find("\n2025").unwrap() as u64 + 1;
+        assert_eq!(whole[0].0, 0);
+        assert_eq!(whole[1].0, second);
+        // 同一文件移到其他目录或追加写入后标识不变，不同文件的同一偏移标识不同
+        let moved = Source::new(
+            PathBuf::from("/elsewhere").join(files[0].file_name().unwrap()),
+            text.lines().next().unwrap(),
+        );
+        assert_eq!(moved.file_id, Source::new(files[0].clone(), &text).file_id);
+        assert_ne!(whole[0].1, whole[25].1);
+    }
+
+    #[test]
+    fn record_offsets_count_raw_bytes_of_non_utf8_input() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dmsql_DM1_20250812_105700.log");
+        let mut raw = Vec::new();
+        for n in 0..20 {
+            raw.extend_from_slice(format!(
+                "2025-08-12 10:57:{n:02}.000 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app) [SEL] select '"
+            ).as_bytes());
+            // Latin1 下每个字节解码为两字节，UTF-8 下每个非法字节变为三字节的替换字符
+            raw.extend_from_slice(b"caf\xe9 \xff\xfe'\n");
+        }
+        fs::write(&path, &raw).unwrap();
+
+        for encoding in [InputEncoding::Latin1, InputEncoding::Utf8] {
+            for chunk_size in [1 << 20, 300] {
+                let pipeline = Pipeline::new()
+                    .set_chunk_size(chunk_size)
+                    .set_input_format(Compression::None, encoding)
+                    .set_ordered(true);
+                let mut offsets = Vec::new();
+                pipeline
+                    .run(
+                        vec![path.clone()],
+                        |_, rec| Some(rec.offset),
+                        |o| offsets.push(o),
+                    )
+                    .unwrap();
+                let mut scanned = Vec::new();
+                pipeline
+                    .scan(vec![path.clone()], |_, rec| {
+                        scanned.push(rec.offset);
+                        ControlFlow::Continue(())
+                    })
+                    .unwrap();
+                assert_eq!(offsets.len(), 20);
+                assert_eq!(offsets, scanned);
+                for &o in &offsets {
+                    let o = o as usize;
+                    assert!(o == 0 || raw[o - 1] == b'\n');
+                    assert!(raw[o..].starts_with(b"2025-08-12 10:57:"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_named_files_from_different_hosts_get_distinct_ids() {
+        let dir = tempdir().unwrap();
+        let mut files = Vec::new();
+        for (host, user) in [("node1", "A"), ("node2", "B")] {
+            let host_dir = dir.path().join(host);
+            fs::create_dir(&host_dir).unwrap();
+            let path = host_dir.join("dmsql_DM1_20250812_105700.log");
+            fs::write(
+                &path,
+                format!(
+                    "2025-08-12 10:57:00.000 (EP[0] sess:0x1 thrd:1 user:{user} trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n"
+                ),
+            )
+            .unwrap();
+            files.push(path);
+        }
+
+        let mut ids = Vec::new();
+        Pipeline::new()
+            .run(
+                files,
+                |src, rec| Some(src.record_id(&rec)),
+                |id| ids.push(id),
+            )
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn run_reuses_buffers_across_files() {
+        let dir = tempdir().unwrap();
+        let mut files = write_logs(dir.path());
+        for _ in 0..4 {
+            files.extend(write_logs(dir.path()));
+        }
+
+        let mut count = 0;
+        let summary = Pipeline::new()
+            .set_parse_workers(1)
+            .set_read_ahead(1)
+            .run(files, |_, _| Some(()), |_| count += 1)
+            .unwrap();
+        assert_eq!(count, 250);
+        assert!(summary.reused_buffers > 0);
+    }
+
+    #[test]
+    fn run_filters_records() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+
+        let mut count = 0;
+        let summary = Pipeline::new()
+            .run(
+                files,
+                |_, rec| (rec.execute_time_ms? >= 20).then_some(()),
+                |_| count += 1,
+            )
+            .unwrap();
+        assert_eq!(count, 10);
+        assert_eq!(summary.outputs, 10);
+    }
+
+    #[test]
+    fn scan_stops_early_in_file_order() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+
+        let mut got = Vec::new();
+        let summary = Pipeline::new()
+            .set_chunk_size(300)
+            .scan(files, |src, rec| {
+                got.push((src.instance.clone(), rec.execute_time_ms.unwrap()));
+                if got.len() == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            got,
+            vec![("DM1".into(), 0), ("DM1".into(), 1), ("DM1".into(), 2)]
+        );
+        assert_eq!(summary.outputs, 3);
+        // 只读取了第一个文件的开头几块
+        assert!(summary.bytes < 1000);
+    }
+
+    #[test]
+    fn run_reports_progress() {
+        let dir = tempdir().unwrap();
+        let files = write_logs(dir.path());
+        let total: u64 = files.iter().map(|f| fs::metadata(f).unwrap().len()).sum();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let summary = Pipeline::new()
+            .set_chunk_size(300)
+            .set_progress(ProgressReporter::new(move |p| {
+                recorded.lock().unwrap().push(p.clone())
+            }))
+            .run(files, |_, _| Some(()), |_| {})
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        let last = events.last().unwrap();
+        assert!(last.done);
+        assert_eq!(last.bytes_done, total);
+        assert_eq!(last.bytes_total, total);
+        assert_eq!(last.records_done, summary.records);
+        assert_eq!(last.eta_secs, Some(0.0));
+    }
+
+    #[test]
+    fn run_respects_memory_limit() {
+        let dir = tempdir().unwrap();
+        // 每个文件处理两遍，共 4 个等长的输入
+        let files = write_logs(dir.path());
+        let files = [files.clone(), files].concat();
+        let file_size = fs::metadata(&files[0]).unwrap().len();
+
+        let mut count = 0;
+        let summary = Pipeline::new()
+            .set_memory_limit(file_size as usize)
+            .run(files, |_, _| Some(()), |_| count += 1)
+            .unwrap();
+        assert_eq!(count, 100);
+        assert_eq!(summary.peak_buffered_bytes, file_size);
+
+        let unlimited = Pipeline::new()
+            .run(write_logs(dir.path()), |_, _| Some(()), |_| {})
+            .unwrap();
+        assert!(unlimited.peak_buffered_bytes >= file_size);
+    }
+
+    fn write_bad_log(dir: &std::path::Path) -> PathBuf {
+        let path = dir.join("dmsql_BAD_20250812_105700.log");
+        fs::write(
+            &path,
+            "garbage line\n\
+             2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app) [SEL] select 1 EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-08-12 10:57:09.562 truncated record\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn run_applies_on_error_policy() {
+        let dir = tempdir().unwrap();
+        let path = write_bad_log(dir.path());
+
+        let mut count = 0;
+        let summary = Pipeline::new()
+            .run(vec![path.clone()], |_, _| Some(()), |_| count += 1)
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(summary.bad_records, 2);
+        assert_eq!(summary.bad_lines, 2);
+
+        let err = Pipeline::new()
+            .set_on_error(OnError::Abort)
+            .run(vec![path.clone()], |_, _| Some(()), |_| {})
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let error_log = dir.path().join("out").join("error.log");
+        let summary = Pipeline::new()
+            .set_on_error(OnError::Collect)
+            .set_error_exporter(
+                ErrorExporterConfig::new().set_error_log_path(error_log.to_str().unwrap()),
+            )
+            .run(vec![path], |_, _| Some(()), |_| {})
+            .unwrap();
+        assert_eq!(summary.bad_records, 2);
+        let exported = fs::read_to_string(error_log).unwrap();
+        assert!(exported.contains("garbage line"));
+        assert!(exported.contains("truncated record"));
+    }
+
+    #[test]
+    fn fails_when_error_rate_exceeded() {
+        let dir = tempdir().unwrap();
+        let path = write_bad_log(dir.path());
+
+        // 前导垃圾文本与截断的记录共 2 条错误记录，多于拆分出的 2 条记录的一半
+        let err = Pipeline::new()
+            .set_max_error_rate(0.5)
+            .run(vec![path.clone()], |_, _| Some(()), |_| {})
+            .unwrap_err();
+        assert!(err.get_ref().unwrap().is::<ErrorRateExceeded>());
+        Pipeline::new()
+            .set_max_error_rate(1.0)
+            .run(vec![path.clone()], |_, _| Some(()), |_| {})
+            .unwrap();
+
+        let err = Pipeline::new()
+            .set_fail_fast(true)
+            .run(
+                vec![dir.path().join("missing.log")],
+                |_, _| Some(()),
+                |_| {},
+            )
+            .unwrap_err();
+        assert!(err.get_ref().unwrap().is::<InputError>());
+    }
+
+    #[test]
+    fn run_strict_mode_rejects_lookalike_records() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dmsql_X_20250812_105700.log");
+        fs::write(
+            &path,
+            "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x2 appname:app) [SEL] select 1\n\
+             2025-08-12 10:57:09.562 (EP[0] user:U sess:0x1 thrd:1 trxid:1 stmt:0x2 appname:app) [SEL] select 2\n",
+        )
+        .unwrap();
+
+        let lenient = Pipeline::new()
+            .run(vec![path.clone()], |_, _| Some(()), |_| {})
+            .unwrap();
+        assert_eq!((lenient.outputs, lenient.bad_records), (2, 0));
+
+        let strict = Pipeline::new()
+            .set_parse_mode(ParseMode::Strict)
+            .run(vec![path], |_, _| Some(()), |_| {})
+            .unwrap();
+        assert_eq!((strict.outputs, strict.bad_records), (1, 1));
+    }
+
+    #[test]
+    fn run_reports_missing_file() {
+        let dir = tempdir().unwrap();
+        let mut files = write_logs(dir.path());
+        files.insert(1, PathBuf::from("/nonexistent/dmsql_X.log"));
+
+        let mut n = 0;
+        let summary = Pipeline::new()
+            .run(files.clone(), |_, _| Some(()), |_| n += 1)
+            .unwrap();
+        assert_eq!(n, 50);
+        assert_eq!(summary.failed_files.len(), 1);
+        assert_eq!(summary.failed_files[0].path, files[1]);
+
+        let scanned = Pipeline::new()
+            .scan(files.clone(), |_, _| ControlFlow::Continue(()))
+            .unwrap();
+        assert_eq!(scanned.outputs, 50);
+        assert_eq!(scanned.failed_files, summary.failed_files);
+
+        let result = Pipeline::new()
+            .set_fail_fast(true)
+            .run(files, |_, _| Some(()), |_| {});
+        assert!(result.is_err());
+    }
+}
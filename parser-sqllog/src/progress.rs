@@ -0,0 +1,183 @@
+//! 处理进度事件：流水线按固定间隔报告已处理的字节数、记录数、吞吐率与预计剩余时间
+
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tracing::info;
+
+/// 未配置时进度事件的最小间隔
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 一次进度事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Progress {
+    pub bytes_done: u64,
+    /// 所有输入文件的总字节数
+    pub bytes_total: u64,
+    pub records_done: u64,
+    pub elapsed_secs: f64,
+    /// 吞吐率（字节/秒）
+    pub rate: f64,
+    /// 预计剩余时间（秒），尚无法估算时为 None
+    pub eta_secs: Option<f64>,
+    /// 是否为处理结束时的最后一次事件
+    pub done: bool,
+}
+
+impl Progress {
+    fn new(bytes_done: u64, bytes_total: u64, records_done: u64, elapsed: Duration) -> Self {
+        let elapsed_secs = elapsed.as_secs_f64();
+        let rate = if elapsed_secs > 0.0 {
+            bytes_done as f64 / elapsed_secs
+        } else {
+            0.0
+        };
+        let eta_secs = (rate > 0.0).then(|| bytes_total.saturating_sub(bytes_done) as f64 / rate);
+        Self {
+            bytes_done,
+            bytes_total,
+            records_done,
+            elapsed_secs,
+            rate,
+            eta_secs,
+            done: false,
+        }
+    }
+}
+
+/// 进度事件的接收者，在流水线的调用线程中被调用
+#[derive(Clone)]
+pub struct ProgressReporter(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressReporter {
+    pub fn new<F: Fn(&Progress) + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// 以日志形式输出进度，结束时输出吞吐率汇总
+    pub fn text() -> Self {
+        const MB: f64 = 1024.0 * 1024.0;
+        Self::new(|p| {
+            if p.done {
+                info!(
+                    "处理完成: {:.1} MB, {} 条记录, 用时 {:.1} 秒, 吞吐 {:.1} MB/s",
+                    p.bytes_done as f64 / MB,
+                    p.records_done,
+                    p.elapsed_secs,
+                    p.rate / MB
+                );
+            } else {
+                let pct = p.bytes_done as f64 * 100.0 / p.bytes_total.max(1) as f64;
+                let eta = p.eta_secs.map_or("-".to_string(), |s| format!("{s:.0} 秒"));
+                info!(
+                    "进度: {:.1}% ({:.1}/{:.1} MB), {} 条记录, {:.1} MB/s, 预计剩余 {}",
+                    pct,
+                    p.bytes_done as f64 / MB,
+                    p.bytes_total as f64 / MB,
+                    p.records_done,
+                    p.rate / MB,
+                    eta
+                );
+            }
+        })
+    }
+
+    /// 以 NDJSON（每行一个 JSON 对象）形式把进度写到标准错误，便于调度系统解析
+    pub fn json() -> Self {
+        Self::new(|p| {
+            if let Ok(line) = serde_json::to_string(p) {
+                let _ = writeln!(io::stderr().lock(), "{line}");
+            }
+        })
+    }
+
+    pub fn report(&self, p: &Progress) {
+        (self.0)(p)
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressReporter(..)")
+    }
+}
+
+/// 按间隔节流进度事件
+pub(crate) struct ProgressTracker<'a> {
+    reporter: Option<&'a ProgressReporter>,
+    interval: Duration,
+    bytes_total: u64,
+    start: Instant,
+    last: Instant,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        reporter: Option<&'a ProgressReporter>,
+        interval: Duration,
+        bytes_total: u64,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            reporter,
+            interval,
+            bytes_total,
+            start: now,
+            last: now,
+        }
+    }
+
+    /// 距上次事件超过间隔时发出一次进度事件
+    pub(crate) fn tick(&mut self, bytes_done: u64, records_done: u64) {
+        let Some(reporter) = self.reporter else {
+            return;
+        };
+        let now = Instant::now();
+        if now.duration_since(self.last) < self.interval {
+            return;
+        }
+        self.last = now;
+        reporter.report(&Progress::new(
+            bytes_done,
+            self.bytes_total,
+            records_done,
+            now.duration_since(self.start),
+        ));
+    }
+
+    /// 发出结束事件
+    pub(crate) fn finish(&mut self, bytes_done: u64, records_done: u64) {
+        if let Some(reporter) = self.reporter {
+            let mut p = Progress::new(
+                bytes_done,
+                self.bytes_total,
+                records_done,
+                self.start.elapsed(),
+            );
+            p.done = true;
+            p.eta_secs = Some(0.0);
+            reporter.report(&p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_computes_rate_and_eta() {
+        let p = Progress::new(50, 150, 10, Duration::from_secs(2));
+        assert_eq!(p.rate, 25.0);
+        assert_eq!(p.eta_secs, Some(4.0));
+
+        let json = serde_json::to_string(&p).unwrap();
+        assert!(json.contains("\"bytes_done\":50"));
+        assert!(json.contains("\"done\":false"));
+    }
+}
@@ -0,0 +1,210 @@
+//! 资源使用统计：峰值 RSS、内存分配次数与字节数（`alloc-stats` 特性）以及流水线各阶段耗时，
+//! 用于为定时任务选择合适的机器规格
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// 流水线的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Read,
+    Split,
+    Parse,
+    Sink,
+}
+
+/// 各阶段累计耗时的计数器，可在多个工作线程间共享
+#[derive(Debug, Default)]
+pub struct StageClock {
+    nanos: [AtomicU64; 4],
+}
+
+impl StageClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 把从 `since` 到现在的耗时计入 `stage`
+    pub fn add(&self, stage: Stage, since: Instant) {
+        let nanos = since.elapsed().as_nanos() as u64;
+        self.nanos[stage as usize].fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    pub fn times(&self) -> StageTimes {
+        let at =
+            |stage: Stage| Duration::from_nanos(self.nanos[stage as usize].load(Ordering::Relaxed));
+        StageTimes {
+            read: at(Stage::Read),
+            split: at(Stage::Split),
+            parse: at(Stage::Parse),
+            sink: at(Stage::Sink),
+        }
+    }
+}
+
+/// 各阶段的累计耗时；多个工作线程的阶段为各线程耗时之和，不含等待队列的时间
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StageTimes {
+    pub read: Duration,
+    pub split: Duration,
+    pub parse: Duration,
+    pub sink: Duration,
+}
+
+/// 内存分配统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    /// 分配次数
+    pub count: u64,
+    /// 累计分配的字节数
+    pub bytes: u64,
+}
+
+/// 运行结束时的资源使用汇总
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// 进程的峰值常驻内存（字节），不支持的平台为 None
+    pub peak_rss: Option<u64>,
+    /// 未启用 `alloc-stats` 特性时为 None
+    pub allocations: Option<AllocStats>,
+    pub stages: StageTimes,
+}
+
+impl ResourceUsage {
+    pub fn collect(stages: StageTimes) -> Self {
+        Self {
+            peak_rss: peak_rss(),
+            allocations: allocations(),
+            stages,
+        }
+    }
+}
+
+impl fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MB: f64 = 1024.0 * 1024.0;
+        match self.peak_rss {
+            Some(rss) => write!(f, "峰值 RSS {:.1} MB", rss as f64 / MB)?,
+            None => f.write_str("峰值 RSS 未知")?,
+        }
+        if let Some(a) = self.allocations {
+            write!(f, ", 分配 {} 次共 {:.1} MB", a.count, a.bytes as f64 / MB)?;
+        }
+        let s = &self.stages;
+        write!(
+            f,
+            ", 阶段耗时: 读取 {:.2} s, 拆分 {:.2} s, 解析 {:.2} s, 输出 {:.2} s",
+            s.read.as_secs_f64(),
+            s.split.as_secs_f64(),
+            s.parse.as_secs_f64(),
+            s.sink.as_secs_f64()
+        )
+    }
+}
+
+/// 进程的峰值常驻内存（字节），读取 `/proc/self/status` 中的 `VmHWM`
+#[cfg(target_os = "linux")]
+pub fn peak_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn peak_rss() -> Option<u64> {
+    None
+}
+
+/// 启动以来的内存分配统计，需要启用 `alloc-stats` 特性
+#[cfg(feature = "alloc-stats")]
+pub fn allocations() -> Option<AllocStats> {
+    Some(counting::stats())
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+pub fn allocations() -> Option<AllocStats> {
+    None
+}
+
+#[cfg(feature = "alloc-stats")]
+pub use counting::CountingAlloc;
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use super::AllocStats;
+
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    static BYTES: AtomicU64 = AtomicU64::new(0);
+
+    /// 统计分配次数与字节数的全局分配器，由可执行文件通过 `#[global_allocator]` 启用
+    pub struct CountingAlloc;
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            COUNT.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            COUNT.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    pub fn stats() -> AllocStats {
+        AllocStats {
+            count: COUNT.load(Ordering::Relaxed),
+            bytes: BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_stage_times_and_formats_summary() {
+        let clock = StageClock::new();
+        let start = Instant::now() - Duration::from_millis(20);
+        clock.add(Stage::Parse, start);
+        clock.add(Stage::Parse, start);
+        let times = clock.times();
+        assert!(times.parse >= Duration::from_millis(40));
+        assert_eq!(times.read, Duration::ZERO);
+
+        let usage = ResourceUsage {
+            peak_rss: Some(512 * 1024 * 1024),
+            allocations: Some(AllocStats {
+                count: 10,
+                bytes: 1024 * 1024,
+            }),
+            stages: StageTimes {
+                parse: Duration::from_millis(1500),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            usage.to_string(),
+            "峰值 RSS 512.0 MB, 分配 10 次共 1.0 MB, \
+             阶段耗时: 读取 0.00 s, 拆分 0.00 s, 解析 1.50 s, 输出 0.00 s"
+        );
+        #[cfg(target_os = "linux")]
+        assert!(peak_rss().is_some_and(|rss| rss > 0));
+    }
+}
@@ -0,0 +1,550 @@
+//! 标签规则（`[[rules.rule]]`）的条件表达式：编译后对每条记录求值，命中的规则把标签写入记录。
+//!
+//! 表达式语法接近 SQL 的 WHERE 子句，关键字不区分大小写：
+//!
+//! - 比较：`字段 = 值`，运算符为 `=`、`!=`（或 `<>`）、`<`、`<=`、`>`、`>=`；
+//! - 模式匹配：`字段 [NOT] LIKE 'ETL%'`，`%` 匹配任意个字符，`_` 匹配一个字符，区分大小写；
+//! - 列表：`字段 [NOT] IN ('a', 'b')`；
+//! - 组合：`AND`、`OR`、`NOT` 与括号。
+//!
+//! 字符串字段为 `ts`、`ep`、`sess`、`thrd`、`user`、`trxid`、`stmt`、`appname`、`ip`、
+//! `tag`（语句标记）、`category`、`sql`，缺失时视为空串；数值字段为 `exec_time_ms`、
+//! `row_count`、`exec_id`，缺失时任何比较都不成立。值为单引号字符串（`''` 转义）或数字。
+
+use std::cmp::Ordering;
+
+use dm_database_parser::{parser::ParsedRecord, sql};
+
+use crate::{
+    config::rules::RulesConfig,
+    error::{ConfigIssue, ConfigParseError},
+};
+
+/// 条件中可引用的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleField {
+    Ts,
+    Ep,
+    Sess,
+    Thrd,
+    User,
+    Trxid,
+    Stmt,
+    Appname,
+    Ip,
+    Tag,
+    Category,
+    Sql,
+    ExecTimeMs,
+    RowCount,
+    ExecId,
+}
+
+/// 字段取值
+enum FieldValue<'a> {
+    Str(&'a str),
+    Num(Option<f64>),
+}
+
+impl RuleField {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "ts" => RuleField::Ts,
+            "ep" => RuleField::Ep,
+            "sess" => RuleField::Sess,
+            "thrd" => RuleField::Thrd,
+            "user" => RuleField::User,
+            "trxid" => RuleField::Trxid,
+            "stmt" => RuleField::Stmt,
+            "appname" => RuleField::Appname,
+            "ip" => RuleField::Ip,
+            "tag" => RuleField::Tag,
+            "category" => RuleField::Category,
+            "sql" => RuleField::Sql,
+            "exec_time_ms" => RuleField::ExecTimeMs,
+            "row_count" => RuleField::RowCount,
+            "exec_id" => RuleField::ExecId,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            RuleField::ExecTimeMs | RuleField::RowCount | RuleField::ExecId
+        )
+    }
+
+    fn value<'a>(self, rec: &'a ParsedRecord<'_>) -> FieldValue<'a> {
+        let s = |v: Option<&'a str>| FieldValue::Str(v.unwrap_or_default());
+        let n = |v: Option<u64>| FieldValue::Num(v.map(|v| v as f64));
+        match self {
+            RuleField::Ts => FieldValue::Str(rec.ts),
+            RuleField::Ep => s(rec.ep),
+            RuleField::Sess => s(rec.sess),
+            RuleField::Thrd => s(rec.thrd),
+            RuleField::User => s(rec.user),
+            RuleField::Trxid => s(rec.trxid),
+            RuleField::Stmt => s(rec.stmt),
+            RuleField::Appname => s(rec.appname),
+            RuleField::Ip => s(rec.ip),
+            RuleField::Tag => s(sql::split_tag(&rec.body).0),
+            RuleField::Category => FieldValue::Str(sql::categorize(&rec.body).as_str()),
+            RuleField::Sql => FieldValue::Str(sql::sql_text(&rec.body)),
+            RuleField::ExecTimeMs => n(rec.execute_time_ms),
+            RuleField::RowCount => n(rec.row_count),
+            RuleField::ExecId => n(rec.execute_id),
+        }
+    }
+}
+
+/// 字面量：字符串，或数字（同时保留原文，与字符串字段比较时使用）
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Str(String),
+    Num(f64, String),
+}
+
+impl Literal {
+    fn text(&self) -> &str {
+        match self {
+            Literal::Str(s) | Literal::Num(_, s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn holds(self, ord: Ordering) -> bool {
+        match self {
+            CmpOp::Eq => ord == Ordering::Equal,
+            CmpOp::Ne => ord != Ordering::Equal,
+            CmpOp::Lt => ord == Ordering::Less,
+            CmpOp::Le => ord != Ordering::Greater,
+            CmpOp::Gt => ord == Ordering::Greater,
+            CmpOp::Ge => ord != Ordering::Less,
+        }
+    }
+}
+
+/// 编译后的条件
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp(RuleField, CmpOp, Literal),
+    Like(RuleField, Vec<char>),
+    In(RuleField, Vec<Literal>),
+}
+
+impl Expr {
+    fn eval(&self, rec: &ParsedRecord<'_>) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(rec) && b.eval(rec),
+            Expr::Or(a, b) => a.eval(rec) || b.eval(rec),
+            Expr::Not(e) => !e.eval(rec),
+            Expr::Cmp(field, op, lit) => {
+                compare(&field.value(rec), lit).is_some_and(|o| op.holds(o))
+            }
+            Expr::Like(field, pattern) => match field.value(rec) {
+                FieldValue::Str(s) => like(s, pattern),
+                FieldValue::Num(_) => false,
+            },
+            Expr::In(field, list) => {
+                let value = field.value(rec);
+                list.iter()
+                    .any(|lit| compare(&value, lit) == Some(Ordering::Equal))
+            }
+        }
+    }
+}
+
+/// 字段值与字面量比较；数值字段缺失或与字符串比较时返回 None
+fn compare(value: &FieldValue<'_>, lit: &Literal) -> Option<Ordering> {
+    match (value, lit) {
+        (FieldValue::Str(s), lit) => Some((*s).cmp(lit.text())),
+        (FieldValue::Num(v), Literal::Num(n, _)) => (*v)?.partial_cmp(n),
+        (FieldValue::Num(_), Literal::Str(_)) => None,
+    }
+}
+
+/// SQL LIKE 匹配：`%` 匹配任意个字符，`_` 匹配一个字符
+fn like(text: &str, pattern: &[char]) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    // 最近一个 `%` 在模式中的位置，以及当时对应的文本位置
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        // `%` 先于字面量判断：文本中的 `%` 不能把模式中的通配符当作字面量消耗掉
+        if p < pattern.len() && pattern[p] == '%' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '_' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '%')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Num(f64, String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\'')) if chars.peek().is_some_and(|&(_, c)| c == '\'') => {
+                        chars.next();
+                        text.push('\'');
+                    }
+                    Some((_, '\'')) => break,
+                    Some((_, c)) => text.push(c),
+                    None => return Err(format!("字符串未闭合（位置 {i}）")),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else if c.is_ascii_digit()
+            || (c == '-' && s[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            chars.next();
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = j + 1;
+                chars.next();
+            }
+            let text = &s[i..end];
+            let n = text.parse().map_err(|_| format!("无效的数字: {text}"))?;
+            tokens.push(Token::Num(n, text.to_string()));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = i;
+            while let Some(&(j, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = j + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Word(s[i..end].to_string()));
+        } else {
+            chars.next();
+            let next = chars.peek().map(|&(_, c)| c);
+            let token = match (c, next) {
+                ('(', _) => Token::LParen,
+                (')', _) => Token::RParen,
+                (',', _) => Token::Comma,
+                ('!', Some('=')) | ('<', Some('>')) => Token::Op("!="),
+                ('<', Some('=')) => Token::Op("<="),
+                ('>', Some('=')) => Token::Op(">="),
+                ('=', _) => Token::Op("="),
+                ('<', _) => Token::Op("<"),
+                ('>', _) => Token::Op(">"),
+                _ => return Err(format!("无法识别的字符 '{c}'（位置 {i}）")),
+            };
+            if matches!(token, Token::Op(op) if op.len() == 2) {
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析：or := and (OR and)*，and := not (AND not)*，not := NOT not | primary
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    /// 下一个单词为关键字 `kw` 时消耗它
+    fn keyword(&mut self, kw: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(kw)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            _ => Err(format!("缺少 {what}")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut e = self.and()?;
+        while self.keyword("or") {
+            e = Expr::Or(Box::new(e), Box::new(self.and()?));
+        }
+        Ok(e)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut e = self.not()?;
+        while self.keyword("and") {
+            e = Expr::And(Box::new(e), Box::new(self.not()?));
+        }
+        Ok(e)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let name = match self.next() {
+            Some(Token::LParen) => {
+                let e = self.or()?;
+                self.expect(Token::RParen, "右括号")?;
+                return Ok(e);
+            }
+            Some(Token::Word(w)) => w,
+            _ => return Err("应为字段名或左括号".to_string()),
+        };
+        let field = RuleField::from_name(&name).ok_or_else(|| format!("未知的字段: {name}"))?;
+        let negated = self.keyword("not");
+        let e = if self.keyword("like") {
+            if field.is_numeric() {
+                return Err(format!("数值字段 {name} 不能使用 LIKE"));
+            }
+            match self.next() {
+                Some(Token::Str(p)) => Expr::Like(field, p.chars().collect()),
+                _ => return Err("LIKE 之后应为字符串".to_string()),
+            }
+        } else if self.keyword("in") {
+            self.expect(Token::LParen, "IN 之后的左括号")?;
+            let mut list = vec![self.literal(field)?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                list.push(self.literal(field)?);
+            }
+            self.expect(Token::RParen, "IN 列表的右括号")?;
+            Expr::In(field, list)
+        } else if negated {
+            return Err("NOT 之后应为 LIKE 或 IN".to_string());
+        } else {
+            let op = match self.next() {
+                Some(Token::Op("=")) => CmpOp::Eq,
+                Some(Token::Op("!=")) => CmpOp::Ne,
+                Some(Token::Op("<")) => CmpOp::Lt,
+                Some(Token::Op("<=")) => CmpOp::Le,
+                Some(Token::Op(">")) => CmpOp::Gt,
+                Some(Token::Op(">=")) => CmpOp::Ge,
+                _ => return Err(format!("字段 {name} 之后应为比较运算符、LIKE 或 IN")),
+            };
+            Expr::Cmp(field, op, self.literal(field)?)
+        };
+        Ok(if negated { Expr::Not(Box::new(e)) } else { e })
+    }
+
+    fn literal(&mut self, field: RuleField) -> Result<Literal, String> {
+        match self.next() {
+            Some(Token::Num(n, text)) => Ok(Literal::Num(n, text)),
+            Some(Token::Str(_)) if field.is_numeric() => Err("数值字段只能与数字比较".to_string()),
+            Some(Token::Str(s)) => Ok(Literal::Str(s)),
+            _ => Err("应为字符串或数字".to_string()),
+        }
+    }
+}
+
+/// 编译条件表达式
+fn compile(condition: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(condition)?,
+        pos: 0,
+    };
+    let e = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err("表达式末尾有多余的内容".to_string());
+    }
+    Ok(e)
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    tag: String,
+    condition: Expr,
+}
+
+/// 编译后的全部标签规则
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// 编译 `[[rules.rule]]`，一次返回所有出错的规则
+    pub fn compile(cfg: &RulesConfig) -> Result<Self, ConfigParseError> {
+        let mut rules = Vec::new();
+        let issues = Self::check(cfg);
+        if !issues.is_empty() {
+            return Err(ConfigParseError::Invalid(issues));
+        }
+        for rule in &cfg.rules {
+            if let Ok(condition) = compile(&rule.condition) {
+                rules.push(Rule {
+                    tag: rule.tag.clone(),
+                    condition,
+                });
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// 校验各条规则，返回出错的规则及原因
+    pub fn check(cfg: &RulesConfig) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        for (i, rule) in cfg.rules.iter().enumerate() {
+            let key = format!("rules.rule[{i}]");
+            let label = match rule.name.as_str() {
+                "" => String::new(),
+                name => format!("规则 {name}: "),
+            };
+            if rule.tag.trim().is_empty() || rule.tag.contains(',') {
+                issues.push(ConfigIssue::new(
+                    &format!("{key}.tag"),
+                    format!("{label}标签不能为空，也不能包含逗号"),
+                ));
+            }
+            if let Err(e) = compile(&rule.condition) {
+                issues.push(ConfigIssue::new(
+                    &format!("{key}.condition"),
+                    format!("{label}{e}"),
+                ));
+            }
+        }
+        issues
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 记录命中的规则的标签，按规则顺序去重后以逗号连接；没有命中时为空串
+    pub fn tags(&self, rec: &ParsedRecord<'_>) -> String {
+        let mut tags: Vec<&str> = Vec::new();
+        for rule in &self.rules {
+            if !tags.contains(&rule.tag.as_str()) && rule.condition.eval(rec) {
+                tags.push(&rule.tag);
+            }
+        }
+        tags.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::rules::RuleConfig;
+    use dm_database_parser::parser::parse_record;
+
+    fn rule(condition: &str, tag: &str) -> RuleConfig {
+        RuleConfig {
+            name: String::new(),
+            condition: condition.to_string(),
+            tag: tag.to_string(),
+        }
+    }
+
+    #[test]
+    fn tags_records_matching_conditions() {
+        let cfg = RulesConfig::new().set_rules(vec![
+            rule("appname LIKE 'ETL%' or user in ('BATCH', 'JOB')", "batch"),
+            rule(
+                "exec_time_ms >= 1000 AND NOT (tag = 'SEL' and row_count > 10000)",
+                "slow",
+            ),
+            rule(
+                "sql not like '%where%' and category = 'statement'",
+                "full_scan",
+            ),
+            rule("ep != 'EP[0]'", "remote"),
+        ]);
+        let rules = RuleSet::compile(&cfg).unwrap();
+
+        let rec = parse_record(
+            "2025-08-12 10:57:09.561 (EP[0] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:ETL_nightly) [UPD] update t set a = 1 EXECTIME: 1500(ms) ROWCOUNT: 3(rows) EXEC_ID: 1.",
+        );
+        assert_eq!(rules.tags(&rec), "batch,slow,full_scan");
+
+        let rec = parse_record(
+            "2025-08-12 10:57:09.561 (EP[1] sess:0x1 thrd:1 user:A trxid:1 stmt:0x2 appname:web) [SEL] select * from t where id = 1",
+        );
+        assert_eq!(rules.tags(&rec), "remote");
+    }
+
+    #[test]
+    fn reports_invalid_rules() {
+        let cfg = RulesConfig::new().set_rules(vec![
+            rule("appname LIKE 'ETL%'", "ok"),
+            rule("exec_time_ms > 'x'", "bad"),
+            rule("nope = 1", "bad"),
+            rule("user = 'a' and", "a,b"),
+        ]);
+        let issues = RuleSet::check(&cfg);
+        let keys: Vec<_> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "rules.rule[1].condition",
+                "rules.rule[2].condition",
+                "rules.rule[3].tag",
+                "rules.rule[3].condition",
+            ]
+        );
+        assert!(RuleSet::compile(&cfg).is_err());
+        assert!(like("ETL_nightly", &"E_L%".chars().collect::<Vec<_>>()));
+        assert!(!like("ETL", &"ETL_%".chars().collect::<Vec<_>>()));
+        assert!(like("%b a", &"%a%".chars().collect::<Vec<_>>()));
+    }
+}
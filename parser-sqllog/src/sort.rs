@@ -0,0 +1,230 @@
+//! 外部归并排序：按键（时间戳）对跨文件的记录排序。内存中的记录达到上限时排好序溢写到临时文件，
+//! 结束时多路归并读出；临时文件过多时先分轮合并，避免同时打开的文件数过多
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// 归并时同时打开的临时文件数上限
+const MAX_FAN_IN: usize = 64;
+
+/// 本进程内临时文件的编号
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+/// 排序的一项：键、进入排序的顺序（键相同时保持输入顺序）与记录
+type Entry<T> = (String, u64, T);
+
+/// 一个已排序的临时文件，丢弃时删除
+#[derive(Debug)]
+struct Run {
+    path: PathBuf,
+    len: u64,
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 外部排序器：`push` 记录，`finish` 后按键的字典序（键相同时按输入顺序）读出
+#[derive(Debug)]
+pub struct ExternalSorter<T> {
+    run_size: usize,
+    dir: PathBuf,
+    buf: Vec<Entry<T>>,
+    runs: Vec<Run>,
+    seq: u64,
+}
+
+impl<T: Serialize + DeserializeOwned> ExternalSorter<T> {
+    /// `run_size` 为内存中最多保留的记录数（至少为 1），溢写的临时文件放在 `dir` 下
+    pub fn new(run_size: usize, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            run_size: run_size.max(1),
+            dir: dir.into(),
+            buf: Vec::new(),
+            runs: Vec::new(),
+            seq: 0,
+        }
+    }
+
+    pub fn push(&mut self, key: String, item: T) -> io::Result<()> {
+        self.buf.push((key, self.seq, item));
+        self.seq += 1;
+        if self.buf.len() >= self.run_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// 已溢写的临时文件数
+    pub fn spilled(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// 结束写入，返回按序读出记录的迭代器；没有溢写时直接在内存中排序
+    pub fn finish(mut self) -> io::Result<Sorted<T>> {
+        if self.runs.is_empty() {
+            self.buf
+                .sort_unstable_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+            return Ok(Sorted::Memory(self.buf.into_iter()));
+        }
+        if !self.buf.is_empty() {
+            self.spill()?;
+        }
+        let mut runs = std::mem::take(&mut self.runs);
+        while runs.len() > MAX_FAN_IN {
+            let rest = runs.split_off(MAX_FAN_IN);
+            let mut merge = Merge::<T>::open(runs)?;
+            let mut out = new_run(&self.dir)?;
+            let mut w = BufWriter::new(File::create(&out.path)?);
+            while let Some(entry) = merge.next_entry()? {
+                write_entry(&mut w, &entry)?;
+                out.len += 1;
+            }
+            w.flush()?;
+            runs = rest;
+            runs.push(out);
+        }
+        Ok(Sorted::Merge(Merge::open(runs)?))
+    }
+
+    /// 排序内存中的记录并写出到一个临时文件
+    fn spill(&mut self) -> io::Result<()> {
+        self.buf
+            .sort_unstable_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+        let mut run = new_run(&self.dir)?;
+        let mut w = BufWriter::new(File::create(&run.path)?);
+        for entry in &self.buf {
+            write_entry(&mut w, entry)?;
+        }
+        w.flush()?;
+        run.len = self.buf.len() as u64;
+        self.buf.clear();
+        self.runs.push(run);
+        Ok(())
+    }
+}
+
+fn new_run(dir: &Path) -> io::Result<Run> {
+    fs::create_dir_all(dir)?;
+    let n = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+    Ok(Run {
+        path: dir.join(format!(".sqllog-sort-{}-{n}.run", process::id())),
+        len: 0,
+    })
+}
+
+fn write_entry<T: Serialize>(w: &mut impl Write, entry: &Entry<T>) -> io::Result<()> {
+    rmp_serde::encode::write_named(w, entry).map_err(io::Error::other)
+}
+
+/// 排序结果
+pub enum Sorted<T> {
+    Memory(std::vec::IntoIter<Entry<T>>),
+    Merge(Merge<T>),
+}
+
+impl<T: DeserializeOwned> Iterator for Sorted<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Memory(it) => it.next().map(|(_, _, item)| Ok(item)),
+            Self::Merge(m) => m
+                .next_entry()
+                .transpose()
+                .map(|e| e.map(|(_, _, item)| item)),
+        }
+    }
+}
+
+/// 对多个临时文件的多路归并
+pub struct Merge<T> {
+    readers: Vec<(Run, BufReader<File>)>,
+    /// 各临时文件当前的首条记录，堆中保存其键与下标
+    heads: Vec<Option<T>>,
+    heap: BinaryHeap<Reverse<(String, u64, usize)>>,
+}
+
+impl<T: DeserializeOwned> Merge<T> {
+    fn open(runs: Vec<Run>) -> io::Result<Self> {
+        let mut merge = Self {
+            readers: Vec::with_capacity(runs.len()),
+            heads: Vec::with_capacity(runs.len()),
+            heap: BinaryHeap::with_capacity(runs.len()),
+        };
+        for run in runs {
+            let reader = BufReader::new(File::open(&run.path)?);
+            merge.readers.push((run, reader));
+            merge.heads.push(None);
+            merge.advance(merge.readers.len() - 1)?;
+        }
+        Ok(merge)
+    }
+
+    /// 读出第 `i` 个临时文件的下一条记录放入堆中
+    fn advance(&mut self, i: usize) -> io::Result<()> {
+        let (run, reader) = &mut self.readers[i];
+        if run.len == 0 {
+            return Ok(());
+        }
+        run.len -= 1;
+        let (key, seq, item): Entry<T> =
+            rmp_serde::decode::from_read(reader).map_err(io::Error::other)?;
+        self.heads[i] = Some(item);
+        self.heap.push(Reverse((key, seq, i)));
+        Ok(())
+    }
+
+    fn next_entry(&mut self) -> io::Result<Option<Entry<T>>> {
+        let Some(Reverse((key, seq, i))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        let item = self.heads[i].take().expect("堆中的下标必有首条记录");
+        self.advance(i)?;
+        Ok(Some((key, seq, item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sorts_in_memory_and_across_spilled_runs() {
+        let dir = tempdir().unwrap();
+        let keys: Vec<u32> = (0..500).map(|i| (i * 7919) % 211).collect();
+        let mut expected: Vec<(String, u32)> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (format!("{k:04}"), i as u32))
+            .collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // 3 条一个临时文件，超过归并上限时需要分轮合并
+        for run_size in [1000, 3] {
+            let mut sorter = ExternalSorter::new(run_size, dir.path());
+            for (i, k) in keys.iter().enumerate() {
+                sorter
+                    .push(format!("{k:04}"), (format!("{k:04}"), i as u32))
+                    .unwrap();
+            }
+            let got: Vec<(String, u32)> =
+                sorter.finish().unwrap().collect::<io::Result<_>>().unwrap();
+            assert_eq!(got, expected, "run_size = {run_size}");
+        }
+        // 临时文件在读完后删除
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}